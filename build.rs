@@ -1,5 +1,10 @@
 use std::{env, error::Error, fs};
 
+// The ship hulls are defined once, in Rust, and rendered into the shaders.
+#[allow(dead_code)]
+#[path = "src/ship_geometry.rs"]
+mod ship_geometry;
+
 // All shaders reside in the 'src/shaders' directory
 fn generate_shaders() -> std::result::Result<(), Box<dyn Error>> {
     let tera = tera::Tera::new("src/shaders/*")?;
@@ -11,6 +16,13 @@ fn generate_shaders() -> std::result::Result<(), Box<dyn Error>> {
     const PARTICLE_WORKGROUP_SIZE: u32 = 256;
     context.insert("particle_workgroup_size", &PARTICLE_WORKGROUP_SIZE);
 
+    // Must match MAX_HIT_TARGETS in entity_hits.rs.
+    const MAX_HIT_TARGETS: u32 = 32;
+    context.insert("max_hit_targets", &MAX_HIT_TARGETS);
+
+    insert_hulls(&mut context);
+    println!("cargo:rerun-if-changed=src/ship_geometry.rs");
+
     let output_path = env::var("OUT_DIR")?;
     fs::create_dir_all(format!("{}/shaders/", output_path))?;
     for file in fs::read_dir("src/shaders")? {
@@ -27,6 +39,29 @@ fn generate_shaders() -> std::result::Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// WGSL has no ragged arrays, so every hull's vertices and edge steps go in one
+// flat table, indexed from each hull's first vertex.
+fn insert_hulls(context: &mut tera::Context) {
+    let hulls = &ship_geometry::HULLS;
+    let mut first_vertex = Vec::new();
+    let mut vertices = Vec::new();
+    let mut edge_steps = Vec::new();
+    for hull in hulls {
+        first_vertex.push(vertices.len() as u32);
+        vertices.extend_from_slice(hull.vertices);
+        edge_steps.extend(hull.edge_steps());
+    }
+    let num_vertices: Vec<u32> = hulls.iter().map(|h| h.vertices.len() as u32).collect();
+    let num_samples: Vec<u32> = hulls.iter().map(|h| h.num_collision_samples()).collect();
+
+    context.insert("num_hulls", &hulls.len());
+    context.insert("hull_vertices", &vertices);
+    context.insert("hull_first_vertex", &first_vertex);
+    context.insert("hull_num_vertices", &num_vertices);
+    context.insert("hull_edge_steps", &edge_steps);
+    context.insert("hull_num_samples", &num_samples);
+}
+
 fn main() {
     if let Err(err) = generate_shaders() {
         // panic here for a nicer error message, otherwise it will
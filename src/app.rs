@@ -216,6 +216,9 @@ impl ApplicationHandler for App {
                 ..
             }
             | WindowEvent::CloseRequested => {
+                // A session still in play has no game over to write it.
+                let gpu = self.gpu.as_mut().unwrap(); // safe: checked above
+                gpu.spout.finish_recording();
                 event_loop.exit();
             }
             WindowEvent::Resized(size) => {
@@ -0,0 +1,432 @@
+//! Hand-authored levels painted as PNG images and placed at fixed level
+//! indices by `level_params.authored_levels`.
+//!
+//! Each pixel is one terrain cell, with the image's top row at the top of the
+//! level. Transparent pixels (alpha below half) are empty; any other pixel is
+//! solid, with the material whose `fill_color` is nearest to the pixel's color,
+//! at that material's starting health. Painting with the in-game fill colors
+//! therefore previews roughly how the level will look.
+//!
+//! Native builds read images from `assets/levels/` under the working directory
+//! when they are there, so new levels need no rebuild; images missing on disk,
+//! and every image on wasm, come from the copies baked into the binary.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::level_gen::LevelGenerator;
+use crate::material::Material;
+
+/// Level images baked into the binary, by file name under `assets/levels/`.
+const LEVEL_IMAGES: &[(&str, &[u8])] = &[(
+    "serpent.png",
+    include_bytes!("../assets/levels/serpent.png"),
+)];
+
+/// Where native builds look for level images, relative to the working
+/// directory.
+#[cfg(not(target_arch = "wasm32"))]
+const LEVEL_DIR: &str = "assets/levels";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthoredLevelError {
+    UnknownImage(String),
+    Read {
+        image: String,
+        message: String,
+    },
+    Decode {
+        image: String,
+        message: String,
+    },
+    Dimensions {
+        image: String,
+        width: u32,
+        height: u32,
+        level_width: u32,
+        level_height: u32,
+    },
+}
+
+impl fmt::Display for AuthoredLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthoredLevelError::UnknownImage(image) => write!(
+                f,
+                "unknown level image \"{image}\" (known images: {})",
+                known_images().join(", ")
+            ),
+            AuthoredLevelError::Read { image, message } => {
+                write!(f, "level image \"{image}\" could not be read: {message}")
+            }
+            AuthoredLevelError::Decode { image, message } => {
+                write!(f, "level image \"{image}\" failed to decode: {message}")
+            }
+            AuthoredLevelError::Dimensions {
+                image,
+                width,
+                height,
+                level_width,
+                level_height,
+            } => write!(
+                f,
+                "level image \"{image}\" is {width}x{height} but levels are \
+                 {level_width}x{level_height} (level_width x level_height)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AuthoredLevelError {}
+
+/// The material a painted pixel color stands for.
+pub fn material_for_color(rgb: [u8; 3]) -> Material {
+    let distance = |material: &Material| -> f32 {
+        let fill = material.properties().fill_color;
+        (0..3)
+            .map(|i| (rgb[i] as f32 - fill[i] * 255.0).powi(2))
+            .sum()
+    };
+    Material::ALL
+        .into_iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .expect("at least one material")
+}
+
+/// A decoded level, ready to hand to `LevelMaker` like a finished generator.
+#[derive(Clone)]
+pub struct AuthoredLevel {
+    data: Vec<i32>,
+    materials: Vec<u32>,
+}
+
+impl AuthoredLevel {
+    /// Load a level image by file name under `assets/levels/`: from disk
+    /// where it exists there, otherwise from the embedded copies.
+    pub fn load(
+        image: &str,
+        level_width: u32,
+        level_height: u32,
+        starting_terrain_health: i32,
+    ) -> Result<Self, AuthoredLevelError> {
+        let bytes = image_bytes(image)?;
+        Self::decode(
+            image,
+            &bytes,
+            level_width,
+            level_height,
+            starting_terrain_health,
+        )
+    }
+
+    /// Check that a level image exists and is `level_width` x `level_height`
+    /// from its header alone, without decoding the pixels.
+    pub fn check(
+        image: &str,
+        level_width: u32,
+        level_height: u32,
+    ) -> Result<(), AuthoredLevelError> {
+        let bytes = image_bytes(image)?;
+        let decode_error = |message: String| AuthoredLevelError::Decode {
+            image: image.to_string(),
+            message,
+        };
+        let dimensions = image::ImageReader::new(std::io::Cursor::new(&*bytes))
+            .with_guessed_format()
+            .map_err(|err| decode_error(err.to_string()))?
+            .into_dimensions()
+            .map_err(|err| decode_error(err.to_string()))?;
+        check_dimensions(image, dimensions, level_width, level_height)
+    }
+
+    /// Decode encoded image `bytes`; `image` names it in errors.
+    pub fn decode(
+        image: &str,
+        bytes: &[u8],
+        level_width: u32,
+        level_height: u32,
+        starting_terrain_health: i32,
+    ) -> Result<Self, AuthoredLevelError> {
+        let pixels = image::load_from_memory(bytes)
+            .map_err(|err| AuthoredLevelError::Decode {
+                image: image.to_string(),
+                message: err.to_string(),
+            })?
+            .to_rgba8();
+        let (width, height) = pixels.dimensions();
+        check_dimensions(image, (width, height), level_width, level_height)?;
+
+        let num_cells = (width * height) as usize;
+        let mut data = vec![0; num_cells];
+        let mut materials = vec![Material::Rock.id(); num_cells];
+        for (x, row, pixel) in pixels.enumerate_pixels() {
+            let [r, g, b, a] = pixel.0;
+            if a < 128 {
+                continue;
+            }
+            // Image rows run top-down; level rows run bottom-up.
+            let index = ((height - 1 - row) * width + x) as usize;
+            let material = material_for_color([r, g, b]);
+            data[index] = material.starting_health(starting_terrain_health);
+            materials[index] = material.id();
+        }
+        Ok(AuthoredLevel { data, materials })
+    }
+}
+
+fn check_dimensions(
+    image: &str,
+    (width, height): (u32, u32),
+    level_width: u32,
+    level_height: u32,
+) -> Result<(), AuthoredLevelError> {
+    if (width, height) != (level_width, level_height) {
+        return Err(AuthoredLevelError::Dimensions {
+            image: image.to_string(),
+            width,
+            height,
+            level_width,
+            level_height,
+        });
+    }
+    Ok(())
+}
+
+/// The encoded bytes of level image `image`, from wherever `AuthoredLevel::load`
+/// would read them.
+pub fn image_bytes(image: &str) -> Result<Cow<'static, [u8]>, AuthoredLevelError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(bytes) = read_level_image(std::path::Path::new(LEVEL_DIR), image)? {
+        return Ok(Cow::Owned(bytes));
+    }
+    LEVEL_IMAGES
+        .iter()
+        .find(|(name, _)| *name == image)
+        .map(|(_, bytes)| Cow::Borrowed(*bytes))
+        .ok_or_else(|| AuthoredLevelError::UnknownImage(image.to_string()))
+}
+
+/// Read `image` from `dir`, or `None` when there is no such file. Only bare
+/// file names are looked up, so a config cannot reach outside `dir`.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_level_image(
+    dir: &std::path::Path,
+    image: &str,
+) -> Result<Option<Vec<u8>>, AuthoredLevelError> {
+    if std::path::Path::new(image).file_name() != Some(std::ffi::OsStr::new(image)) {
+        return Err(AuthoredLevelError::UnknownImage(image.to_string()));
+    }
+    match std::fs::read(dir.join(image)) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(AuthoredLevelError::Read {
+            image: image.to_string(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// File names `AuthoredLevel::load` can find, sorted.
+fn known_images() -> Vec<String> {
+    let mut known: Vec<String> = LEVEL_IMAGES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(entries) = std::fs::read_dir(LEVEL_DIR) {
+        known.extend(
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| name.ends_with(".png")),
+        );
+    }
+    known.sort();
+    known.dedup();
+    known
+}
+
+/// Encode a level as a PNG that decodes back to the same materials and
+/// solidity: solid cells take their material's fill color, empty cells are
+/// transparent.
+pub fn encode_png(data: &[i32], materials: &[u32], width: u32, height: u32) -> Vec<u8> {
+    let pixels = image::RgbaImage::from_fn(width, height, |x, row| {
+        let index = ((height - 1 - row) * width + x) as usize;
+        if data[index] <= 0 {
+            return image::Rgba([0, 0, 0, 0]);
+        }
+        let material = Material::from_id(materials[index]).unwrap_or_default();
+        let [r, g, b] = material
+            .properties()
+            .fill_color
+            .map(|c| (c * 255.0).round() as u8);
+        image::Rgba([r, g, b, 255])
+    });
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    pixels
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .expect("PNG encoding into memory cannot fail");
+    bytes.into_inner()
+}
+
+impl LevelGenerator for AuthoredLevel {
+    fn done(&self) -> bool {
+        true
+    }
+
+    fn step(&mut self) {}
+
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>) {
+        (
+            std::mem::take(&mut self.data),
+            std::mem::take(&mut self.materials),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEALTH: i32 = 500;
+
+    fn fill_rgb(material: Material) -> [u8; 3] {
+        material
+            .properties()
+            .fill_color
+            .map(|c| (c * 255.0).round() as u8)
+    }
+
+    fn png_from_fn(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+        let img = image::RgbaImage::from_fn(width, height, |x, y| image::Rgba(pixel(x, y)));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageFormat::Png)
+            .expect("encode png");
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn fill_colors_map_back_to_their_materials() {
+        for material in Material::ALL {
+            assert_eq!(material_for_color(fill_rgb(material)), material);
+        }
+    }
+
+    #[test]
+    fn pixels_become_cells_with_the_top_row_on_top() {
+        // 2x2 image: top row crystal + transparent, bottom row dirt + rock.
+        let png = png_from_fn(2, 2, |x, y| {
+            let [r, g, b] = fill_rgb(match (x, y) {
+                (0, 0) => Material::Crystal,
+                (0, 1) => Material::Dirt,
+                _ => Material::Rock,
+            });
+            let alpha = if (x, y) == (1, 0) { 0 } else { 255 };
+            [r, g, b, alpha]
+        });
+        let mut level = AuthoredLevel::decode("test.png", &png, 2, 2, HEALTH).unwrap();
+        assert!(level.done());
+        let (data, materials) = level.take_level();
+        // Level row 0 is the bottom image row.
+        assert_eq!(
+            materials,
+            [
+                Material::Dirt.id(),
+                Material::Rock.id(),
+                Material::Crystal.id(),
+                Material::Rock.id()
+            ]
+        );
+        assert_eq!(
+            data,
+            [
+                Material::Dirt.starting_health(HEALTH),
+                HEALTH,
+                Material::Crystal.starting_health(HEALTH),
+                0
+            ]
+        );
+    }
+
+    #[test]
+    fn encoded_levels_decode_to_the_same_cells() {
+        let (width, height) = (5, 3);
+        let materials: Vec<u32> = (0..15).map(|i| i % 4).collect();
+        let data: Vec<i32> = materials
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| {
+                let material = Material::from_id(id).unwrap();
+                if i % 3 == 0 {
+                    0
+                } else {
+                    material.starting_health(HEALTH)
+                }
+            })
+            .collect();
+        let png = encode_png(&data, &materials, width, height);
+        let (decoded, decoded_materials) =
+            AuthoredLevel::decode("round_trip.png", &png, width, height, HEALTH)
+                .unwrap()
+                .take_level();
+        assert_eq!(decoded, data);
+        for ((&health, &id), &decoded_id) in data.iter().zip(&materials).zip(&decoded_materials) {
+            if health > 0 {
+                assert_eq!(decoded_id, id);
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_dimensions_are_reported() {
+        let png = png_from_fn(3, 2, |_, _| [0, 0, 0, 255]);
+        let err = AuthoredLevel::decode("small.png", &png, 4, 2, HEALTH)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "level image \"small.png\" is 3x2 but levels are 4x2 (level_width x level_height)"
+        );
+    }
+
+    #[test]
+    fn check_reads_only_the_header() {
+        assert_eq!(AuthoredLevel::check("serpent.png", 261, 480), Ok(()));
+        assert!(matches!(
+            AuthoredLevel::check("serpent.png", 261, 479),
+            Err(AuthoredLevelError::Dimensions { .. })
+        ));
+        assert_eq!(
+            AuthoredLevel::check("nope.png", 200, 200),
+            Err(AuthoredLevelError::UnknownImage("nope.png".into()))
+        );
+    }
+
+    #[test]
+    fn unknown_and_corrupt_images_are_reported() {
+        let err = AuthoredLevel::load("nope.png", 4, 2, HEALTH).err().unwrap();
+        assert_eq!(err, AuthoredLevelError::UnknownImage("nope.png".into()));
+        assert!(err.to_string().contains("serpent.png"));
+        assert!(matches!(
+            AuthoredLevel::decode("junk.png", b"not a png", 4, 2, HEALTH),
+            Err(AuthoredLevelError::Decode { .. })
+        ));
+    }
+
+    #[test]
+    fn level_images_are_read_from_disk_by_bare_file_name() {
+        let dir = std::env::temp_dir().join(format!("spout-levels-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = png_from_fn(2, 1, |_, _| [0, 0, 0, 255]);
+        std::fs::write(dir.join("fresh.png"), &png).unwrap();
+
+        assert_eq!(read_level_image(&dir, "fresh.png"), Ok(Some(png)));
+        assert_eq!(read_level_image(&dir, "missing.png"), Ok(None));
+        for escape in ["../fresh.png", "sub/fresh.png", ""] {
+            assert_eq!(
+                read_level_image(&dir, escape),
+                Err(AuthoredLevelError::UnknownImage(escape.into()))
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
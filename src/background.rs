@@ -15,6 +15,8 @@ struct BackgroundUniforms {
     viewport_height: f32,
     viewport_offset: f32,
     tile_size: f32,
+    viewport_offset_x: f32,
+    _padding: [f32; 3],
 }
 
 pub struct BackgroundRenderer {
@@ -77,6 +79,8 @@ impl BackgroundRenderer {
             viewport_height: 0.0,
             viewport_offset: 0.0,
             tile_size: tile_w as f32,
+            viewport_offset_x: 0.0,
+            _padding: [0.0; 3],
         };
         let uniform_buf =
             crate::buffer_util::make_uniform_buffer(device, "Background Uniforms", &uniforms);
@@ -182,15 +186,17 @@ impl BackgroundRenderer {
     pub fn update_state(
         &self,
         game_params: &crate::game_params::GameParams,
-        viewport_offset: i32,
+        viewport_offset: [i32; 2],
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
     ) {
         let uniforms = BackgroundUniforms {
             viewport_width: game_params.viewport_width as f32,
             viewport_height: game_params.viewport_height as f32,
-            viewport_offset: viewport_offset as f32,
+            viewport_offset: viewport_offset[1] as f32,
             tile_size: 65.0, // bgp1.gif is 65x65
+            viewport_offset_x: viewport_offset[0] as f32,
+            _padding: [0.0; 3],
         };
         belt.write_buffer(
             encoder,
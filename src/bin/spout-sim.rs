@@ -0,0 +1,175 @@
+//! Headless batch runner: plays a session of Spout without a window or GPU and
+//! prints how it ended. Reads `game_config.toml` like the game does.
+//!
+//! ```text
+//! spout-sim [--frames N] [--seed N] [--ship N] [--script PATH | --replay PATH]
+//! ```
+//!
+//! `--script` loops a `<frames> <thrust> <rotate>` script (see `spout::sim`);
+//! `--replay` plays a `--record` file once, using its recorded seed and ship.
+//! With neither, the ship holds full thrust. `--ship` picks a ship from the
+//! config's roster by index, the first by default.
+
+use std::path::{Path, PathBuf};
+
+use spout::game_params;
+use spout::play::fresh_run_seed;
+use spout::replay::{self, InputRecording, RecordedFrame};
+use spout::sim;
+
+const USAGE: &str =
+    "usage: spout-sim [--frames N] [--seed N] [--ship N] [--script PATH | --replay PATH]";
+
+/// One minute at the default 60 fps.
+const DEFAULT_FRAMES: u32 = 3600;
+
+#[derive(Default)]
+struct Options {
+    frames: Option<u32>,
+    seed: Option<u32>,
+    ship: Option<usize>,
+    script: Option<PathBuf>,
+    replay: Option<PathBuf>,
+}
+
+fn parse_options(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_owned(), Some(value.to_owned())),
+            None => (arg, None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{flag} requires a value"))
+        };
+        match flag.as_str() {
+            "--frames" => {
+                let value = value()?;
+                options.frames = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("--frames must be an integer, got {value}"))?,
+                );
+            }
+            "--seed" => {
+                let value = value()?;
+                options.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("--seed must be an integer, got {value}"))?,
+                );
+            }
+            "--ship" => {
+                let value = value()?;
+                options.ship = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("--ship must be an integer, got {value}"))?,
+                );
+            }
+            "--script" => options.script = Some(PathBuf::from(value()?)),
+            "--replay" => options.replay = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("unrecognized argument: {flag}")),
+        }
+    }
+    if options.script.is_some() && options.replay.is_some() {
+        return Err("--script and --replay are mutually exclusive".to_owned());
+    }
+    if options.ship.is_some() && options.replay.is_some() {
+        return Err("--ship and --replay are mutually exclusive".to_owned());
+    }
+    Ok(options)
+}
+
+fn main() {
+    env_logger::Builder::from_default_env().init();
+
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(err) = run(options) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut params = game_params::get_game_config_from_default_file();
+    if let Some(seed) = options.seed {
+        params.level_params.seed = Some(seed);
+    }
+    let max_frames = options.frames.unwrap_or(DEFAULT_FRAMES);
+
+    let num_ships = params.ships().len();
+    if let Some(ship) = options.ship.filter(|&ship| ship >= num_ships) {
+        return Err(format!(
+            "--ship {ship} is out of range: the roster has {num_ships} ships"
+        ));
+    }
+
+    let (seed, ship, inputs): (u32, usize, Box<dyn Iterator<Item = RecordedFrame>>) =
+        if let Some(path) = &options.replay {
+            let recording = load_recording(path, &params)?;
+            (
+                recording.seed,
+                recording.ship as usize,
+                Box::new(recording.frames.into_iter()),
+            )
+        } else {
+            let script = match &options.script {
+                Some(path) => std::fs::read_to_string(path)
+                    .map_err(|err| format!("failed to read {}: {err}", path.display()))?,
+                None => "1 1.0 0.0".to_owned(),
+            };
+            let frames = sim::parse_input_script(&script, &params)
+                .map_err(|err| format!("bad script: {err}"))?;
+            if frames.is_empty() {
+                return Err("script has no frames".to_owned());
+            }
+            let ship = options.ship.unwrap_or(0);
+            let seed = params.level_params.seed.unwrap_or_else(fresh_run_seed);
+            (seed, ship, Box::new(frames.into_iter().cycle()))
+        };
+
+    let outcome = sim::run(&params, seed, ship, inputs, max_frames);
+    println!("seed: {}", outcome.seed);
+    println!("frames: {}", outcome.frames);
+    println!("time: {:.2}s", outcome.game_time.as_secs_f32());
+    println!("score: {}", outcome.score);
+    println!("level: {}", outcome.level);
+    match outcome.death {
+        Some(cause) => println!("death: {cause:?}"),
+        None => println!("death: none"),
+    }
+    Ok(())
+}
+
+/// Load the `--replay` recording, rejecting one that flies a ship the roster
+/// doesn't have and warning if it was made with a different config.
+fn load_recording(path: &Path, params: &game_params::GameParams) -> Result<InputRecording, String> {
+    let recording = InputRecording::load(path)
+        .map_err(|err| format!("failed to load {}: {err}", path.display()))?;
+    let num_ships = params.ships().len();
+    if recording.ship as usize >= num_ships {
+        return Err(format!(
+            "{} flies roster ship {}, but the roster has {num_ships} ships",
+            path.display(),
+            recording.ship
+        ));
+    }
+    if recording.params_hash != replay::params_hash(params) {
+        eprintln!(
+            "warning: {} was recorded with a different game_config.toml; replay may diverge",
+            path.display()
+        );
+    }
+    Ok(recording)
+}
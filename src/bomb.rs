@@ -0,0 +1,221 @@
+//! Bombs: the ship's secondary weapon. A bomb leaves the ship with its
+//! velocity and speed cap, falls under gravity with the same `ShipState`
+//! physics the ship uses, and detonates on the first solid cell it reaches.
+//!
+//! Pure CPU state carried in `Play`, like `enemy`. Terrain comes from the
+//! `TerrainSnapshot`s handed to `Bombs::observe_terrain`; a bomb outside the
+//! latest snapshot keeps falling until one covers it. The game turns each
+//! detonation into a `crater::CraterPass` and a particle burst.
+
+use crate::entity_renderer::EntityBox;
+use crate::game_params::GameParams;
+use crate::ship::ShipState;
+use crate::terrain_query::TerrainSnapshot;
+
+/// Half the side of a bomb's drawn square.
+const BOMB_HALF_SIZE: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bomb {
+    pub body: ShipState,
+}
+
+#[derive(Debug, Default)]
+pub struct Bombs {
+    pub bombs: Vec<Bomb>,
+    /// Bombs left to drop.
+    pub ammo: u32,
+    /// Seconds until the next bomb can drop.
+    cooldown: f32,
+    /// Where bombs went off since the last `take_detonations`.
+    detonations: Vec<[f32; 2]>,
+    /// Latest terrain around the ship, for impacts.
+    terrain: Option<TerrainSnapshot>,
+}
+
+impl Bombs {
+    pub fn new(params: &GameParams) -> Self {
+        Bombs {
+            ammo: params.bomb_params.ammo,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bombs.is_empty()
+    }
+
+    /// Take in current terrain health around the ship.
+    pub fn observe_terrain(&mut self, snapshot: TerrainSnapshot) {
+        self.terrain = Some(snapshot);
+    }
+
+    /// Drop a bomb from `ship` while `drop_held`, ammo remains and the
+    /// cooldown has run out; then move every bomb. Bombs that reach terrain
+    /// in the latest snapshot detonate, and ones that leave the level or fall
+    /// below the snapshot are lost.
+    pub fn update(&mut self, params: &GameParams, drop_held: bool, ship: &ShipState, dt: f32) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        if drop_held && self.ammo > 0 && self.cooldown == 0.0 {
+            self.ammo -= 1;
+            self.cooldown = params.bomb_params.cooldown;
+            self.bombs.push(Bomb { body: *ship });
+        }
+
+        let gravity = params.particle_system_params.gravity;
+        let level_width = params.level_width as f32;
+        let terrain = self.terrain.as_ref();
+        let detonations = &mut self.detonations;
+        self.bombs.retain_mut(|bomb| {
+            let start = bomb.body.position;
+            bomb.body.update(dt, 0.0, 0.0, gravity);
+            let end = bomb.body.position;
+            let Some(terrain) = terrain else {
+                return true;
+            };
+            if let Some(impact) = first_solid_point(terrain, start, end) {
+                log::info!("Bomb detonated at ({:.0}, {:.0})", impact[0], impact[1]);
+                detonations.push(impact);
+                return false;
+            }
+            let lost =
+                end[0] < 0.0 || end[0] >= level_width || (end[1].floor() as i32) < terrain.region.y;
+            !lost
+        });
+    }
+
+    /// Detonation points since the last call, oldest first.
+    pub fn take_detonations(&mut self) -> Vec<[f32; 2]> {
+        std::mem::take(&mut self.detonations)
+    }
+
+    /// Boxes to draw with `EntityRenderer`: a square per bomb, turned with
+    /// the ship it fell from.
+    pub fn render_boxes(&self) -> Vec<EntityBox> {
+        self.bombs
+            .iter()
+            .map(|bomb| {
+                EntityBox::new(
+                    bomb.body.position,
+                    [BOMB_HALF_SIZE; 2],
+                    bomb.body.orientation,
+                    [1.6, 1.3, 0.9, 1.0],
+                )
+            })
+            .collect()
+    }
+}
+
+/// First point on the segment `start..end`, walked a cell at a time, that is
+/// in a solid cell of `terrain`.
+fn first_solid_point(
+    terrain: &TerrainSnapshot,
+    start: [f32; 2],
+    end: [f32; 2],
+) -> Option<[f32; 2]> {
+    let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+    let steps = (dx.hypot(dy).ceil() as u32).max(1);
+    (1..=steps)
+        .map(|step| {
+            let t = step as f32 / steps as f32;
+            [start[0] + dx * t, start[1] + dy * t]
+        })
+        .find(|&[x, y]| terrain.is_solid(x.floor() as i32, y.floor() as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_query::Region;
+
+    /// Open terrain over the first 200x200 cells, solid below row `floor`.
+    fn ground(floor: i32) -> TerrainSnapshot {
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 200,
+            height: 200,
+        };
+        let health = (0..200)
+            .flat_map(|y| (0..200).map(move |_| if y < floor { 100 } else { 0 }))
+            .collect();
+        TerrainSnapshot { region, health }
+    }
+
+    fn ship_at(position: [f32; 2]) -> ShipState {
+        ShipState {
+            position,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dropped_bomb_falls_and_detonates_on_terrain() {
+        let params = GameParams::default();
+        let mut bombs = Bombs::new(&params);
+        bombs.observe_terrain(ground(50));
+        let ship = ship_at([100.5, 120.5]);
+        let dt = 1.0 / 60.0;
+
+        bombs.update(&params, true, &ship, dt);
+        assert_eq!(bombs.bombs.len(), 1);
+        assert_eq!(bombs.ammo, params.bomb_params.ammo - 1);
+
+        let mut detonations = Vec::new();
+        for _ in 0..2000 {
+            bombs.update(&params, false, &ship, dt);
+            detonations.extend(bombs.take_detonations());
+            if bombs.is_empty() {
+                break;
+            }
+        }
+        assert_eq!(detonations.len(), 1);
+        let [x, y] = detonations[0];
+        assert!((x - 100.5).abs() < 1e-3);
+        assert!((49.0..50.0).contains(&y), "detonated at the surface: {y}");
+    }
+
+    #[test]
+    fn drops_respect_cooldown_and_ammo() {
+        let mut params = GameParams::default();
+        params.bomb_params.ammo = 2;
+        params.bomb_params.cooldown = 0.5;
+        let mut bombs = Bombs::new(&params);
+        let ship = ship_at([100.5, 120.5]);
+        let dt = 0.125;
+
+        // Held for two and a half seconds: one drop, then another once the
+        // cooldown runs out, then nothing with the ammo gone.
+        let mut drops = Vec::new();
+        for frame in 0..20 {
+            let before = bombs.ammo;
+            bombs.update(&params, true, &ship, dt);
+            if bombs.ammo < before {
+                drops.push(frame);
+            }
+        }
+        assert_eq!(drops, [0, 4]);
+        assert_eq!(bombs.ammo, 0);
+    }
+
+    #[test]
+    fn fast_bombs_do_not_tunnel_through_thin_terrain() {
+        let params = GameParams::default();
+        let mut bombs = Bombs::new(&params);
+        // One solid row at y = 60.
+        let mut terrain = ground(0);
+        for x in 0..200 {
+            terrain.health[(60 * 200 + x) as usize] = 100;
+        }
+        bombs.observe_terrain(terrain);
+        let mut ship = ship_at([100.5, 80.5]);
+        ship.max_speed = 3000.0;
+        ship.velocity = [0.0, -2000.0];
+
+        // Over 30 cells in one frame, straight through the row.
+        bombs.update(&params, true, &ship, 1.0 / 60.0);
+        let detonations = bombs.take_detonations();
+        assert_eq!(detonations.len(), 1);
+        assert_eq!(detonations[0][1].floor(), 60.0);
+    }
+}
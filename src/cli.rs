@@ -0,0 +1,128 @@
+//! Command-line options for native builds.
+//!
+//! Deliberately tiny: a handful of `--flag <value>` options, no dependency on
+//! an argument-parsing crate. WASM builds never see a command line.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::game_params::GameParams;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CommandLine {
+    /// `--seed <n>`: run seed for level generation; overrides `game_config.toml`.
+    pub seed: Option<u32>,
+    /// `--record <path>`: write each play session's input to `path`.
+    pub record: Option<PathBuf>,
+    /// `--replay <path>`: play back a recorded session instead of live input.
+    pub replay: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLineError(String);
+
+impl fmt::Display for CommandLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CommandLineError {}
+
+impl CommandLine {
+    /// Parse options from `args`, which excludes the program name. Accepts
+    /// both `--flag value` and `--flag=value`.
+    pub fn parse<I>(args: I) -> Result<Self, CommandLineError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = CommandLine::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_owned(), Some(value.to_owned())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CommandLineError(format!("{flag} requires a value")))
+            };
+            match flag.as_str() {
+                "--seed" => {
+                    let value = value()?;
+                    let seed = value.parse::<u32>().map_err(|_| {
+                        CommandLineError(format!(
+                            "--seed must be an integer in 0..=4294967295, got {value}"
+                        ))
+                    })?;
+                    parsed.seed = Some(seed);
+                }
+                "--record" => parsed.record = Some(PathBuf::from(value()?)),
+                "--replay" => parsed.replay = Some(PathBuf::from(value()?)),
+                _ => {
+                    return Err(CommandLineError(format!("unrecognized argument: {flag}")));
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Apply the options that override loaded config values.
+    pub fn apply_to(&self, params: &mut GameParams) {
+        if let Some(seed) = self.seed {
+            params.level_params.seed = Some(seed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn empty_command_line_changes_nothing() {
+        let cli = CommandLine::parse(args(&[])).unwrap();
+        assert_eq!(cli, CommandLine::default());
+
+        let mut params = GameParams::default();
+        params.level_params.seed = Some(3);
+        cli.apply_to(&mut params);
+        assert_eq!(params.level_params.seed, Some(3));
+    }
+
+    #[test]
+    fn seed_overrides_config() {
+        let mut params = GameParams::default();
+        CommandLine::parse(args(&["--seed", "42"]))
+            .unwrap()
+            .apply_to(&mut params);
+        assert_eq!(params.level_params.seed, Some(42));
+
+        CommandLine::parse(args(&["--seed=7"]))
+            .unwrap()
+            .apply_to(&mut params);
+        assert_eq!(params.level_params.seed, Some(7));
+    }
+
+    #[test]
+    fn record_and_replay_paths_parse() {
+        let cli = CommandLine::parse(args(&["--record", "a.rec", "--replay=b.rec"])).unwrap();
+        assert_eq!(cli.record, Some(PathBuf::from("a.rec")));
+        assert_eq!(cli.replay, Some(PathBuf::from("b.rec")));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(CommandLine::parse(args(&["--seed"])).is_err());
+        assert!(CommandLine::parse(args(&["--seed", "-1"])).is_err());
+        assert!(CommandLine::parse(args(&["--seedling"])).is_err());
+        assert!(CommandLine::parse(args(&["--speed", "3"])).is_err());
+        assert!(CommandLine::parse(args(&["--record"])).is_err());
+    }
+}
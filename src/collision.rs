@@ -1,10 +1,18 @@
 //! GPU-based ship-terrain collision detection with contact normal.
 //!
-//! Runs a compute shader that Bresenham-walks each hull vertex from its
+//! Runs a compute shader that Bresenham-walks each hull sample from its
 //! previous to current position, checking the GPU terrain buffer. Returns
 //! a hit flag, axis-aligned contact normal, and impact time along the swept
-//! movement segment.
+//! movement segment. The sweep follows the ship's turn in sub-steps short
+//! enough that no hull point skips over a cell, however fast the ship moves.
+//!
+//! Every frame's motion is its own `CollisionSegment`. `CollisionTracker`
+//! queues them in frame order, and `CollisionDetector` keeps a ring of
+//! readback slots so several segments can be in flight at once; results come
+//! back in the order they were dispatched, so each `impact_t` maps onto the
+//! frame, and pose, it was swept from.
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -24,7 +32,10 @@ struct CollisionUniforms {
     terrain_buffer_offset: i32,
     terrain_width: u32,
     terrain_buffer_height: u32,
-    _pad: u32,
+    /// Pieces the segment is swept in; see `CollisionSegment::num_substeps`.
+    num_substeps: u32,
+    /// Index into the hull tables; see `ship_geometry::HULLS`.
+    hull: u32,
 }
 
 /// Collision result read back from the GPU.
@@ -49,9 +60,144 @@ impl Default for CollisionResult {
     }
 }
 
-pub struct CollisionDetector {
-    pipeline: wgpu::ComputePipeline,
-    bind_group_layout: wgpu::BindGroupLayout,
+/// One frame's ship motion, swept for terrain collision.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollisionSegment {
+    pub prev_ship: crate::ship::ShipState,
+    pub next_ship: crate::ship::ShipState,
+    /// The controls that flew the ship from `prev_ship` to `next_ship`.
+    pub step: crate::ship::ShipStep,
+}
+
+/// Longest travel, in cells, one collision sub-step may cover.
+const MAX_SUBSTEP_TRAVEL: f32 = 1.0;
+
+/// Bounds the work for a pathological segment, such as a respawn jump.
+const MAX_SUBSTEPS: u32 = 256;
+
+impl CollisionSegment {
+    /// Angle turned over the segment, the shorter way round.
+    fn turn(&self) -> f32 {
+        crate::play::angle_diff(self.next_ship.orientation, self.prev_ship.orientation)
+    }
+
+    /// Equal pieces the segment is swept in, so that no piece moves the ship
+    /// more than `MAX_SUBSTEP_TRAVEL`, or turns the hull point farthest from
+    /// its center through more than that.
+    pub fn num_substeps(&self) -> u32 {
+        let dx = self.next_ship.position[0] - self.prev_ship.position[0];
+        let dy = self.next_ship.position[1] - self.prev_ship.position[1];
+        let travel = (dx.hypot(dy) / MAX_SUBSTEP_TRAVEL).ceil();
+        let max_angle = MAX_SUBSTEP_TRAVEL / self.next_ship.hull_shape().radius();
+        let turn = (self.turn().abs() / max_angle).ceil();
+        (travel.max(turn) as u32).clamp(1, MAX_SUBSTEPS)
+    }
+
+    /// CPU sweep: test the pose at the end of every sub-step with `collides`
+    /// and return the `t` of the first one that collides. The segment start
+    /// is not tested; it was the previous segment's end.
+    pub fn first_impact(
+        &self,
+        mut collides: impl FnMut(&crate::ship::ShipState) -> bool,
+    ) -> Option<f32> {
+        let steps = self.num_substeps();
+        (1..=steps)
+            .map(|step| step as f32 / steps as f32)
+            .find(|&t| collides(&self.ship_at(t)))
+    }
+
+    /// The ship's pose `t` of the way along the segment. Position moves in a
+    /// straight line and orientation turns the shorter way round; everything
+    /// else is taken from `next_ship`.
+    pub fn ship_at(&self, t: f32) -> crate::ship::ShipState {
+        let t = t.clamp(0.0, 1.0);
+        let mut ship = self.next_ship;
+        ship.position = [
+            self.prev_ship.position[0]
+                + (self.next_ship.position[0] - self.prev_ship.position[0]) * t,
+            self.prev_ship.position[1]
+                + (self.next_ship.position[1] - self.prev_ship.position[1]) * t,
+        ];
+        ship.orientation = self.prev_ship.orientation + self.turn() * t;
+        ship
+    }
+}
+
+/// Per-frame collision segments in frame order: those waiting to be swept,
+/// then those swept whose results have not come back yet.
+#[derive(Debug, Default)]
+pub struct CollisionTracker {
+    queued: VecDeque<CollisionSegment>,
+    in_flight: VecDeque<CollisionSegment>,
+    /// A `clear` dropped segments whose readbacks are still on their way.
+    dropped_in_flight: bool,
+}
+
+impl CollisionTracker {
+    /// Queue one frame's motion from `prev_ship` to `next_ship`, flown with
+    /// `step`.
+    pub fn record(
+        &mut self,
+        prev_ship: crate::ship::ShipState,
+        next_ship: crate::ship::ShipState,
+        step: crate::ship::ShipStep,
+    ) {
+        self.queued.push_back(CollisionSegment {
+            prev_ship,
+            next_ship,
+            step,
+        });
+    }
+
+    /// The oldest segment not yet swept.
+    pub fn next_queued(&self) -> Option<&CollisionSegment> {
+        self.queued.front()
+    }
+
+    /// Mark the oldest queued segment as swept, awaiting its result.
+    pub fn dispatch_next(&mut self) -> Option<CollisionSegment> {
+        let segment = self.queued.pop_front()?;
+        self.in_flight.push_back(segment);
+        Some(segment)
+    }
+
+    /// Take the segment the next result belongs to: the oldest in flight.
+    pub fn resolve_next(&mut self) -> Option<CollisionSegment> {
+        self.in_flight.pop_front()
+    }
+
+    /// Segments swept and awaiting results.
+    pub fn num_in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Drop every segment, e.g. once the ship has crashed.
+    pub fn clear(&mut self) {
+        self.dropped_in_flight |= !self.in_flight.is_empty();
+        self.queued.clear();
+        self.in_flight.clear();
+    }
+
+    /// Drop every segment like `clear`, returning them in frame order.
+    pub fn drain(&mut self) -> Vec<CollisionSegment> {
+        let segments = self.in_flight.iter().chain(&self.queued).copied().collect();
+        self.clear();
+        segments
+    }
+
+    /// Whether a `clear` since the last call dropped segments that were
+    /// already swept. Their results no longer belong to any segment, so the
+    /// detector must throw them away with `CollisionDetector::reset`.
+    pub fn take_dropped_in_flight(&mut self) -> bool {
+        std::mem::take(&mut self.dropped_in_flight)
+    }
+}
+
+/// Collision readbacks that can be in flight at once.
+const NUM_READBACK_SLOTS: usize = 4;
+
+/// Buffers for one dispatched segment, from uniforms through readback.
+struct ReadbackSlot {
     uniform_buffer: SizedBuffer,
     /// GPU-side result buffer (4 x u32). Written by the compute shader.
     result_buffer: wgpu::Buffer,
@@ -63,6 +209,62 @@ pub struct CollisionDetector {
     mapping_started: bool,
     /// Set to `true` by the `map_async` callback when the mapping completes.
     map_ready: Arc<AtomicBool>,
+    /// Dispatched before a `reset`; read back and thrown away.
+    stale: bool,
+}
+
+impl ReadbackSlot {
+    fn new(device: &wgpu::Device) -> Self {
+        let uniforms = CollisionUniforms {
+            ship_x: 0.0,
+            ship_y: 0.0,
+            prev_ship_x: 0.0,
+            prev_ship_y: 0.0,
+            ship_orientation: 0.0,
+            prev_ship_orientation: 0.0,
+            terrain_buffer_offset: 0,
+            terrain_width: 0,
+            terrain_buffer_height: 0,
+            num_substeps: 1,
+            hull: 0,
+        };
+        let uniform_buffer =
+            crate::buffer_util::make_uniform_buffer(device, "Collision Uniform Buffer", &uniforms);
+
+        // 4 x u32: [hit, normal_x_bits, normal_y_bits, impact_t_bits]
+        let result_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Collision Result"),
+            contents: bytemuck::cast_slice(&[0u32; 4]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Collision Staging"),
+            size: 16, // 4 x u32
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        ReadbackSlot {
+            uniform_buffer,
+            result_buffer,
+            staging_buffer,
+            pending_readback: false,
+            mapping_started: false,
+            map_ready: Arc::new(AtomicBool::new(false)),
+            stale: false,
+        }
+    }
+}
+
+pub struct CollisionDetector {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// Ring of readbacks. Segments are dispatched into `next_dispatch` and
+    /// read back from `next_result`, so results come out in dispatch order.
+    slots: Vec<ReadbackSlot>,
+    next_dispatch: usize,
+    next_result: usize,
     /// Last collision result read from the GPU.
     pub result: CollisionResult,
 }
@@ -71,7 +273,7 @@ impl CollisionDetector {
     pub fn init(device: &wgpu::Device) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Collision Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/collision.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(crate::include_shader!("collision.wgsl")),
         });
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -125,84 +327,71 @@ impl CollisionDetector {
             cache: None,
         });
 
-        let uniforms = CollisionUniforms {
-            ship_x: 0.0,
-            ship_y: 0.0,
-            prev_ship_x: 0.0,
-            prev_ship_y: 0.0,
-            ship_orientation: 0.0,
-            prev_ship_orientation: 0.0,
-            terrain_buffer_offset: 0,
-            terrain_width: 0,
-            terrain_buffer_height: 0,
-            _pad: 0,
-        };
-        let uniform_buffer =
-            crate::buffer_util::make_uniform_buffer(device, "Collision Uniform Buffer", &uniforms);
-
-        // 4 x u32: [hit, normal_x_bits, normal_y_bits, impact_t_bits]
-        let result_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Collision Result"),
-            contents: bytemuck::cast_slice(&[0u32; 4]),
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-        });
-
-        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Collision Staging"),
-            size: 16, // 4 x u32
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
         CollisionDetector {
             pipeline,
             bind_group_layout,
-            uniform_buffer,
-            result_buffer,
-            staging_buffer,
-            pending_readback: false,
-            mapping_started: false,
-            map_ready: Arc::new(AtomicBool::new(false)),
+            slots: (0..NUM_READBACK_SLOTS)
+                .map(|_| ReadbackSlot::new(device))
+                .collect(),
+            next_dispatch: 0,
+            next_result: 0,
             result: CollisionResult::default(),
         }
     }
 
-    /// Dispatch the collision compute shader.
-    /// Skips if a previous readback is still in flight (the staging buffer
-    /// can't be used as a copy destination while a map is pending).
-    #[allow(clippy::too_many_arguments)]
+    /// Throw away every readback in flight, e.g. when a new session starts.
+    /// Their slots are freed once the results arrive.
+    pub fn reset(&mut self) {
+        for slot in &mut self.slots {
+            if slot.pending_readback {
+                slot.stale = true;
+            }
+        }
+        self.result = CollisionResult::default();
+    }
+
+    /// Dispatch the collision compute shader for one segment into the next
+    /// free readback slot. Skips if every slot is still in flight (a staging
+    /// buffer can't be used as a copy destination while a map is pending).
     pub fn dispatch(
         &mut self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
-        ship: &crate::ship::ShipState,
-        prev_ship: &crate::ship::ShipState,
+        segment: &CollisionSegment,
         terrain: &crate::level_manager::TerrainTile,
         terrain_width: u32,
     ) -> bool {
-        if self.pending_readback {
+        let slot = &mut self.slots[self.next_dispatch];
+        if slot.pending_readback {
             return false;
         }
+        let CollisionSegment {
+            prev_ship,
+            next_ship: ship,
+            ..
+        } = segment;
         let uniforms = CollisionUniforms {
             ship_x: ship.position[0],
             ship_y: ship.position[1],
             prev_ship_x: prev_ship.position[0],
             prev_ship_y: prev_ship.position[1],
-            ship_orientation: ship.orientation,
+            // Unwrapped so the shader can interpolate the turn linearly.
+            ship_orientation: prev_ship.orientation + segment.turn(),
             prev_ship_orientation: prev_ship.orientation,
             terrain_buffer_offset: terrain.shape.start,
             terrain_width,
             terrain_buffer_height: terrain.shape.size() as u32,
-            _pad: 0,
+            num_substeps: segment.num_substeps(),
+            hull: ship.hull as u32,
         };
 
         // safe: uniform_buffer.size is always > 0 (set at GPU buffer creation)
         belt.write_buffer(
             encoder,
-            &self.uniform_buffer.buffer,
+            &slot.uniform_buffer.buffer,
             0,
-            wgpu::BufferSize::new(self.uniform_buffer.size as _).unwrap(),
+            wgpu::BufferSize::new(slot.uniform_buffer.size as _).unwrap(),
         )
         .copy_from_slice(bytemuck::bytes_of(&uniforms));
 
@@ -212,7 +401,7 @@ impl CollisionDetector {
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: self.uniform_buffer.buffer.as_entire_binding(),
+                    resource: slot.uniform_buffer.buffer.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
@@ -220,7 +409,7 @@ impl CollisionDetector {
                 },
                 wgpu::BindGroupEntry {
                     binding: 2,
-                    resource: self.result_buffer.as_entire_binding(),
+                    resource: slot.result_buffer.as_entire_binding(),
                 },
             ],
         });
@@ -235,60 +424,74 @@ impl CollisionDetector {
             cpass.dispatch_workgroups(1, 1, 1);
         }
 
-        encoder.copy_buffer_to_buffer(&self.result_buffer, 0, &self.staging_buffer, 0, 16);
-        self.pending_readback = true;
+        encoder.copy_buffer_to_buffer(&slot.result_buffer, 0, &slot.staging_buffer, 0, 16);
+        slot.pending_readback = true;
+        self.next_dispatch = (self.next_dispatch + 1) % NUM_READBACK_SLOTS;
         true
     }
 
-    /// Initiate async mapping of the staging buffer. Call after `queue.submit()`
-    /// so the GPU copy has been submitted. On native, the callback fires during
-    /// the next `device.poll()`; on WASM it fires on the next microtask.
+    /// Initiate async mapping of every staging buffer dispatched since the
+    /// last call. Call after `queue.submit()` so the GPU copies have been
+    /// submitted. On native, the callbacks fire during the next
+    /// `device.poll()`; on WASM they fire on the next microtask.
     pub fn start_readback(&mut self) {
-        if !self.pending_readback || self.mapping_started {
-            return;
+        for slot in &mut self.slots {
+            if !slot.pending_readback || slot.mapping_started {
+                continue;
+            }
+            let slice = slot.staging_buffer.slice(..);
+            let ready = Arc::clone(&slot.map_ready);
+            ready.store(false, Ordering::Release);
+            slice.map_async(wgpu::MapMode::Read, move |_| {
+                ready.store(true, Ordering::Release);
+            });
+            slot.mapping_started = true;
         }
-        let slice = self.staging_buffer.slice(..);
-        let ready = Arc::clone(&self.map_ready);
-        ready.store(false, Ordering::Release);
-        slice.map_async(wgpu::MapMode::Read, move |_| {
-            ready.store(true, Ordering::Release);
-        });
-        self.mapping_started = true;
     }
 
-    /// Poll for the async readback result. Updates `self.result`.
-    /// Returns immediately if the mapping hasn't completed yet (WASM-safe).
+    /// Poll for the oldest async readback result. Updates `self.result`.
+    /// Call until it returns `None` to take every result that has arrived, in
+    /// dispatch order. Returns immediately if the oldest mapping hasn't
+    /// completed yet (WASM-safe).
     /// Caller is responsible for driving wgpu callbacks (device.poll) before
     /// calling this — see render() in main.rs.
     pub fn poll_result(&mut self) -> Option<CollisionResult> {
-        if !self.pending_readback || !self.mapping_started {
-            return None;
-        }
+        loop {
+            let slot = &mut self.slots[self.next_result];
+            if !slot.pending_readback || !slot.mapping_started {
+                return None;
+            }
 
-        if !self.map_ready.load(Ordering::Acquire) {
-            return None; // Not ready yet — will check again next frame.
-        }
+            if !slot.map_ready.load(Ordering::Acquire) {
+                return None; // Not ready yet — will check again next frame.
+            }
 
-        let data = self.staging_buffer.slice(..).get_mapped_range();
-        let values: &[u32] = bytemuck::cast_slice(&data[..16]);
-        self.result = CollisionResult {
-            hit: values[0] != 0,
-            normal: [f32::from_bits(values[1]), f32::from_bits(values[2])],
-            impact_t: f32::from_bits(values[3]).clamp(0.0, 1.0),
-        };
-        let result = self.result;
-        drop(data);
-        self.staging_buffer.unmap();
+            let data = slot.staging_buffer.slice(..).get_mapped_range();
+            let values: &[u32] = bytemuck::cast_slice(&data[..16]);
+            let result = CollisionResult {
+                hit: values[0] != 0,
+                normal: [f32::from_bits(values[1]), f32::from_bits(values[2])],
+                impact_t: f32::from_bits(values[3]).clamp(0.0, 1.0),
+            };
+            drop(data);
+            slot.staging_buffer.unmap();
 
-        self.pending_readback = false;
-        self.mapping_started = false;
-        Some(result)
+            slot.pending_readback = false;
+            slot.mapping_started = false;
+            let stale = std::mem::take(&mut slot.stale);
+            self.next_result = (self.next_result + 1) % NUM_READBACK_SLOTS;
+            if !stale {
+                self.result = result;
+                return Some(result);
+            }
+        }
     }
 }
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
+    use crate::game_params::ShipHull;
     use crate::gpu_test_utils as gpu;
 
     fn make_terrain_tile(
@@ -317,18 +520,23 @@ mod tests {
                 end: terrain_height as i32,
             },
             buffer: terrain_buffer,
+            materials: crate::buffer_util::make_buffer(
+                device,
+                terrain_width as usize,
+                terrain_height as usize,
+                "Test materials",
+            ),
         }
     }
 
     fn run_collision(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        ship: &crate::ship::ShipState,
-        prev_ship: &crate::ship::ShipState,
+        detector: &mut CollisionDetector,
+        segment: &CollisionSegment,
         terrain_tile: &crate::level_manager::TerrainTile,
         terrain_width: u32,
     ) -> CollisionResult {
-        let mut detector = CollisionDetector::init(device);
         let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
@@ -336,8 +544,7 @@ mod tests {
             device,
             &mut encoder,
             &mut belt,
-            ship,
-            prev_ship,
+            segment,
             terrain_tile,
             terrain_width,
         ));
@@ -381,8 +588,11 @@ mod tests {
             &device,
             &mut encoder,
             &mut belt,
-            &ship,
-            &ship,
+            &CollisionSegment {
+                prev_ship: ship,
+                next_ship: ship,
+                ..Default::default()
+            },
             &terrain_tile,
             terrain_width,
         );
@@ -395,7 +605,7 @@ mod tests {
 
         // Phase 3: start_readback (initiates map_async with callback).
         detector.start_readback();
-        assert!(detector.mapping_started);
+        assert!(detector.slots[0].mapping_started);
 
         // Phase 4: poll_result (waits for callback, reads data).
         // This is the step that panicked on WASM before the fix.
@@ -403,7 +613,10 @@ mod tests {
         // device.poll(Poll) call in the render loop after queue.submit().
         device.poll(wgpu::PollType::wait_indefinitely()).ok();
         let result = detector.poll_result();
-        assert!(!detector.pending_readback, "Readback should have completed");
+        assert!(
+            !detector.slots[0].pending_readback,
+            "Readback should have completed"
+        );
 
         // Ship in empty terrain → no collision.
         assert!(result.is_some());
@@ -442,8 +655,12 @@ mod tests {
         let result = run_collision(
             &device,
             &queue,
-            &ship,
-            &prev_ship,
+            &mut CollisionDetector::init(&device),
+            &CollisionSegment {
+                prev_ship,
+                next_ship: ship,
+                ..Default::default()
+            },
             &terrain_tile,
             terrain_width,
         );
@@ -456,4 +673,380 @@ mod tests {
         );
         assert_eq!(result.normal, [0.0, -1.0]);
     }
+
+    fn ship_at(position: [f32; 2], orientation: f32) -> crate::ship::ShipState {
+        crate::ship::ShipState {
+            position,
+            orientation,
+            ..Default::default()
+        }
+    }
+
+    fn segment(
+        prev_ship: crate::ship::ShipState,
+        next_ship: crate::ship::ShipState,
+    ) -> CollisionSegment {
+        CollisionSegment {
+            prev_ship,
+            next_ship,
+            ..Default::default()
+        }
+    }
+
+    /// Several frames dispatched before any readback each get their own slot,
+    /// and their results come back in dispatch order.
+    #[test]
+    fn segments_in_flight_resolve_in_dispatch_order() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping segments_in_flight_resolve_in_dispatch_order"
+            );
+            return;
+        };
+
+        let terrain_width = 64u32;
+        let terrain_tile = make_terrain_tile(&device, terrain_width, 64, &[(44, 35)]);
+        let frames = [
+            // Clear of the wall, then through it, then clear again.
+            segment(ship_at([32.0, 10.0], 0.0), ship_at([32.0, 20.0], 0.0)),
+            segment(ship_at([32.0, 20.0], 0.0), ship_at([32.0, 50.0], 0.0)),
+            segment(ship_at([10.0, 10.0], 0.0), ship_at([10.0, 20.0], 0.0)),
+        ];
+
+        let mut detector = CollisionDetector::init(&device);
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        for frame in &frames {
+            assert!(detector.dispatch(
+                &device,
+                &mut encoder,
+                &mut belt,
+                frame,
+                &terrain_tile,
+                terrain_width,
+            ));
+        }
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        detector.start_readback();
+        device.poll(wgpu::PollType::wait_indefinitely()).ok();
+        let results: Vec<_> = std::iter::from_fn(|| detector.poll_result()).collect();
+        assert_eq!(
+            results.iter().map(|r| r.hit).collect::<Vec<_>>(),
+            [false, true, false]
+        );
+        assert!((results[1].impact_t - 0.5).abs() < 0.001);
+    }
+
+    /// Once every slot is in flight further dispatches wait, and a reset
+    /// throws away results from before it.
+    #[test]
+    fn full_ring_defers_dispatch_and_reset_drops_stale_results() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping full_ring_defers_dispatch_and_reset_drops_stale_results"
+            );
+            return;
+        };
+
+        let terrain_width = 64u32;
+        let terrain_tile = make_terrain_tile(&device, terrain_width, 64, &[(44, 35)]);
+        let crash = segment(ship_at([32.0, 20.0], 0.0), ship_at([32.0, 50.0], 0.0));
+
+        let mut detector = CollisionDetector::init(&device);
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let dispatched = (0..=NUM_READBACK_SLOTS)
+            .filter(|_| {
+                detector.dispatch(
+                    &device,
+                    &mut encoder,
+                    &mut belt,
+                    &crash,
+                    &terrain_tile,
+                    terrain_width,
+                )
+            })
+            .count();
+        assert_eq!(dispatched, NUM_READBACK_SLOTS);
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        detector.reset();
+        detector.start_readback();
+        device.poll(wgpu::PollType::wait_indefinitely()).ok();
+        assert!(detector.poll_result().is_none());
+        assert!(!detector.result.hit);
+        assert!(detector.slots.iter().all(|slot| !slot.pending_readback));
+    }
+
+    /// Turning in place sweeps the nose along its arc rather than the chord
+    /// between its start and end, so a lone cell on the arc is hit.
+    #[test]
+    fn turning_hull_hits_a_cell_on_its_arc() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping turning_hull_hits_a_cell_on_its_arc");
+            return;
+        };
+
+        let terrain_width = 64u32;
+        // The nose, 12 cells out, passes (40.99, 40.99) at 45 degrees.
+        let terrain_tile = make_terrain_tile(&device, terrain_width, 64, &[(40, 40)]);
+        let turn = segment(
+            ship_at([32.5, 32.5], 0.0),
+            ship_at([32.5, 32.5], std::f32::consts::FRAC_PI_2),
+        );
+        let result = run_collision(
+            &device,
+            &queue,
+            &mut CollisionDetector::init(&device),
+            &turn,
+            &terrain_tile,
+            terrain_width,
+        );
+
+        assert!(result.hit, "{result:?}");
+        assert!((result.impact_t - 0.5).abs() < 0.1, "{result:?}");
+    }
+
+    /// Each hull sweeps its own outline: a cell just past the dart's wingtip
+    /// is clear for the dart but in the hauler's path.
+    #[test]
+    fn wider_hull_hits_what_the_dart_clears() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping wider_hull_hits_what_the_dart_clears");
+            return;
+        };
+
+        let terrain_width = 64u32;
+        // Climbing nose-right, the hauler's left wingtip (local (-9, 13))
+        // runs up x = 23.5; the dart's wings stop at x = 24.5.
+        let terrain_tile = make_terrain_tile(&device, terrain_width, 64, &[(23, 40)]);
+        let climb = |hull| {
+            let ship = |y| crate::ship::ShipState {
+                hull,
+                ..ship_at([32.5, y], 0.0)
+            };
+            segment(ship(20.5), ship(30.5))
+        };
+        let mut detector = CollisionDetector::init(&device);
+        let mut sweep = |hull| {
+            run_collision(
+                &device,
+                &queue,
+                &mut detector,
+                &climb(hull),
+                &terrain_tile,
+                terrain_width,
+            )
+        };
+
+        assert!(!sweep(ShipHull::Dart).hit);
+        let result = sweep(ShipHull::Hauler);
+        assert!(result.hit, "{result:?}");
+        assert!((result.impact_t - 0.65).abs() < 0.1, "{result:?}");
+    }
+
+    /// Side of the square test terrain holding a 1-cell ring of wall.
+    const THIN_WALL_SIZE: u32 = 128;
+    /// How many cells out from the middle the ring of wall is.
+    const THIN_WALL_RING: i32 = 20;
+
+    /// How many rings out from the middle of the terrain a cell is.
+    fn thin_wall_ring(x: i32, y: i32) -> i32 {
+        let middle = THIN_WALL_SIZE as i32 / 2;
+        (x - middle).abs().max((y - middle).abs())
+    }
+
+    fn thin_wall_cells() -> Vec<(u32, u32)> {
+        (0..THIN_WALL_SIZE)
+            .flat_map(|y| (0..THIN_WALL_SIZE).map(move |x| (x, y)))
+            .filter(|&(x, y)| thin_wall_ring(x as i32, y as i32) == THIN_WALL_RING)
+            .collect()
+    }
+
+    /// Every roster ship fired from the middle of the ring, at every heading,
+    /// for a half-second frame at the fastest roster ship's top speed and
+    /// turning as it goes: far enough to carry the whole hull past the wall.
+    fn thin_wall_frames() -> Vec<(String, CollisionSegment)> {
+        let params = crate::game_params::parse_embedded_config().unwrap();
+        let ships = params.ships();
+        let top_speed = ships
+            .iter()
+            .map(|ship| ship.ship_params.max_speed)
+            .fold(0.0, f32::max);
+        let travel = top_speed * 0.5;
+        let center = [THIN_WALL_SIZE as f32 / 2.0 + 0.5; 2];
+        let mut frames = Vec::new();
+        for ship in &ships {
+            for degrees in (0..360).step_by(3) {
+                let heading = (degrees as f32).to_radians();
+                let mut frame = segment(
+                    ship_at(center, heading),
+                    ship_at(
+                        [
+                            center[0] + travel * heading.cos(),
+                            center[1] + travel * heading.sin(),
+                        ],
+                        heading + 1.0,
+                    ),
+                );
+                frame.prev_ship.hull = ship.ship_params.hull;
+                frame.next_ship.hull = ship.ship_params.hull;
+                frames.push((format!("{} at {degrees} degrees", ship.name), frame));
+            }
+        }
+        frames
+    }
+
+    /// How far the ship's center got `t` of the way along `frame`, in rings
+    /// out from the middle.
+    fn thin_wall_reached(frame: &CollisionSegment, t: f32) -> i32 {
+        let [x, y] = frame.ship_at(t).position;
+        thin_wall_ring(x.floor() as i32, y.floor() as i32)
+    }
+
+    /// The GPU sweep stops every roster hull at a 1-cell wall, whatever its
+    /// heading and turn.
+    #[test]
+    fn max_speed_hull_cannot_tunnel_through_thin_walls() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping max_speed_hull_cannot_tunnel_through_thin_walls"
+            );
+            return;
+        };
+
+        let terrain_tile =
+            make_terrain_tile(&device, THIN_WALL_SIZE, THIN_WALL_SIZE, &thin_wall_cells());
+        let mut detector = CollisionDetector::init(&device);
+        for (name, frame) in thin_wall_frames() {
+            let gpu = run_collision(
+                &device,
+                &queue,
+                &mut detector,
+                &frame,
+                &terrain_tile,
+                THIN_WALL_SIZE,
+            );
+            assert!(gpu.hit, "GPU sweep tunneled: {name}");
+            assert!(
+                thin_wall_reached(&frame, gpu.impact_t) <= THIN_WALL_RING,
+                "GPU: {name}"
+            );
+        }
+    }
+
+    /// The CPU sweep over `check_collision`, which the headless sim flies
+    /// with, stops every roster hull at a 1-cell wall too. Needs no GPU.
+    #[test]
+    fn max_speed_hull_cannot_tunnel_through_thin_walls_on_the_cpu() {
+        let mut level = vec![0; (THIN_WALL_SIZE * THIN_WALL_SIZE) as usize];
+        for (x, y) in thin_wall_cells() {
+            level[(y * THIN_WALL_SIZE + x) as usize] = 1000;
+        }
+        let levels = [level];
+        for (name, frame) in thin_wall_frames() {
+            let cpu = frame.first_impact(|ship| {
+                crate::level_manager::check_collision(&levels, THIN_WALL_SIZE, THIN_WALL_SIZE, ship)
+            });
+            let cpu = cpu.unwrap_or_else(|| panic!("CPU sweep tunneled: {name}"));
+            assert!(
+                thin_wall_reached(&frame, cpu) <= THIN_WALL_RING + 1,
+                "CPU: {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn substeps_bound_travel_and_turn() {
+        let still = ship_at([10.0, 10.0], 0.0);
+        assert_eq!(segment(still, still).num_substeps(), 1);
+        assert_eq!(segment(still, ship_at([13.0, 14.0], 0.0)).num_substeps(), 5);
+        // A quarter turn moves the nose 12 cells out about 19 cells of arc.
+        let quarter_turn = ship_at([10.0, 10.0], std::f32::consts::FRAC_PI_2);
+        assert_eq!(segment(still, quarter_turn).num_substeps(), 19);
+        assert_eq!(
+            segment(still, ship_at([1e6, 0.0], 0.0)).num_substeps(),
+            MAX_SUBSTEPS
+        );
+
+        // The CPU sweep tests the end of every piece, in order.
+        let mut tested = Vec::new();
+        let hit = segment(still, ship_at([13.0, 14.0], 0.0)).first_impact(|ship| {
+            tested.push(ship.position);
+            ship.position[1] >= 12.0
+        });
+        assert_eq!(hit, Some(0.6));
+        let expected = [[10.6, 10.8], [11.2, 11.6], [11.8, 12.4]];
+        assert_eq!(tested.len(), expected.len());
+        for (tested, expected) in tested.iter().zip(expected) {
+            assert!((tested[0] - expected[0]).abs() < 1e-5);
+            assert!((tested[1] - expected[1]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn segment_interpolates_position_and_orientation() {
+        use std::f32::consts::PI;
+        let segment = CollisionSegment {
+            prev_ship: ship_at([10.0, 20.0], 0.9 * PI),
+            next_ship: ship_at([18.0, 36.0], -0.9 * PI),
+            ..Default::default()
+        };
+        let ship = segment.ship_at(0.25);
+
+        assert!((ship.position[0] - 12.0).abs() < 1e-5);
+        assert!((ship.position[1] - 24.0).abs() < 1e-5);
+        // Turns the short way, through PI, rather than back through 0.
+        assert!((ship.orientation - 0.95 * PI).abs() < 1e-5);
+        assert!((segment.ship_at(-1.0).position[0] - 10.0).abs() < 1e-5);
+        assert!((segment.ship_at(2.0).position[1] - 36.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn tracker_keeps_every_frame_in_order() {
+        let a = ship_at([0.0, 0.0], 0.0);
+        let b = ship_at([1.0, 1.0], 0.5);
+        let c = ship_at([2.0, 3.0], 1.0);
+        let d = ship_at([4.0, 8.0], 1.5);
+
+        let mut tracker = CollisionTracker::default();
+        let step = crate::ship::ShipStep::default();
+        tracker.record(a, b, step);
+        tracker.record(b, c, step);
+        tracker.record(c, d, step);
+
+        // Each frame stays its own segment rather than one chord from a to d.
+        let first = tracker.dispatch_next().expect("first segment");
+        assert_eq!(first.next_ship.position, [1.0, 1.0]);
+        let second = tracker.dispatch_next().expect("second segment");
+        assert_eq!(second.prev_ship.position, [1.0, 1.0]);
+        assert_eq!(second.next_ship.orientation, 1.0);
+        assert_eq!(tracker.num_in_flight(), 2);
+        assert_eq!(
+            tracker.next_queued().map(|s| s.next_ship.position),
+            Some([4.0, 8.0])
+        );
+
+        assert_eq!(
+            tracker.resolve_next().map(|s| s.prev_ship.position),
+            Some([0.0, 0.0])
+        );
+        assert_eq!(
+            tracker.resolve_next().map(|s| s.prev_ship.position),
+            Some([1.0, 1.0])
+        );
+        assert!(tracker.resolve_next().is_none());
+
+        tracker.clear();
+        assert!(tracker.next_queued().is_none());
+        // Only queued segments were dropped: no readbacks to throw away.
+        assert!(!tracker.take_dropped_in_flight());
+    }
 }
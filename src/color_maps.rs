@@ -1,5 +1,7 @@
-//! Particle color map palettes (Viridis, Magma, Inferno, Plasma) backed by
-//! the `scarlet` crate. Generates 1D GPU textures for the particle shader.
+//! Particle color map palettes (Viridis, Magma, Inferno, Plasma, Parula,
+//! Smoke) backed by
+//! the `scarlet` crate. Generates GPU textures for the particle shader, one
+//! row per color map.
 
 use wgpu::util::DeviceExt;
 
@@ -13,6 +15,7 @@ pub enum ColorMap {
     Inferno = 2,
     Plasma = 3,
     Parula = 4,
+    Smoke = 5,
 }
 
 use std::sync::OnceLock;
@@ -59,6 +62,13 @@ fn parula() -> scarlet::colormap::ListedColorMap {
     scarlet::colormap::ListedColorMap::new(PARULA_DATA.iter().copied())
 }
 
+/// Dark to pale gray, for smoke.
+const SMOKE_DATA: [[f64; 3]; 3] = [[0.08, 0.08, 0.09], [0.35, 0.35, 0.37], [0.75, 0.74, 0.72]];
+
+fn smoke() -> scarlet::colormap::ListedColorMap {
+    scarlet::colormap::ListedColorMap::new(SMOKE_DATA.iter().copied())
+}
+
 static COLOR_MAPS: OnceLock<Vec<scarlet::colormap::ListedColorMap>> = OnceLock::new();
 
 fn color_maps() -> &'static [scarlet::colormap::ListedColorMap] {
@@ -69,6 +79,7 @@ fn color_maps() -> &'static [scarlet::colormap::ListedColorMap] {
             scarlet::colormap::ListedColorMap::inferno(),
             scarlet::colormap::ListedColorMap::plasma(),
             parula(),
+            smoke(),
         ]
     })
 }
@@ -81,18 +92,20 @@ pub fn get_color_map_from_index(i: usize) -> &'static scarlet::colormap::ListedC
     &color_maps()[i]
 }
 
-// Create a particle density color map rgba
+// Create a particle density color map rgba, `size` wide with row `i` from
+// `cms[i]`.
 // Rust image defaults to row major.
 pub fn create_color_map(
     size: u32,
     device: &wgpu::Device,
-    cm: &scarlet::colormap::ListedColorMap,
+    cms: &[&scarlet::colormap::ListedColorMap],
     encoder: &mut wgpu::CommandEncoder,
 ) -> wgpu::Texture {
-    let im = image::ImageBuffer::<image::Rgba<u8>, Vec<u8>>::from_fn(size, 1, |x, _y| {
+    let rows = cms.len() as u32;
+    let im = image::ImageBuffer::<image::Rgba<u8>, Vec<u8>>::from_fn(size, rows, |x, y| {
         let parameter = x as f64 / (size - 1) as f64;
         let color_point: scarlet::color::RGBColor =
-            scarlet::colormap::ColorMap::transform_single(cm, parameter);
+            scarlet::colormap::ColorMap::transform_single(cms[y as usize], parameter);
         image::Rgba([
             color_point.int_r(),
             color_point.int_g(),
@@ -103,7 +116,7 @@ pub fn create_color_map(
     let data = im.into_raw();
     let texture_extent = wgpu::Extent3d {
         width: size,
-        height: 1,
+        height: rows,
         depth_or_array_layers: 1,
     };
     let texture = device.create_texture(&wgpu::TextureDescriptor {
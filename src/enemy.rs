@@ -0,0 +1,470 @@
+//! Enemy ships: placement in open space, steering on top of the player's
+//! `ShipState` physics, and destruction by exhaust particles.
+//!
+//! Pure CPU state carried in `Play`, like `turret`. Enemies fly with the same
+//! thrust and rotate controls as the player: steering picks a velocity to
+//! aim for (patrol sideways around home, chase a ship in sight, push away
+//! from nearby walls) and a small controller turns that into thrust and
+//! rotation. Terrain comes from the `TerrainSnapshot`s handed to
+//! `Enemies::observe_terrain`; an enemy outside the latest snapshot holds
+//! still until one covers it. Exhaust hits come from `EntityHitDetector`
+//! readbacks through `Enemies::apply_hits`.
+
+use glam::Vec2;
+
+use crate::entity_hits::{EntityHits, HitTarget};
+use crate::entity_renderer::EntityBox;
+use crate::game_params::{EnemyParams, GameParams};
+use crate::ship::ShipState;
+use crate::terrain_query::TerrainSnapshot;
+
+/// Hull radius: exhaust particles inside it hit the enemy, and the player's
+/// hull touching it dies.
+const ENEMY_RADIUS: f32 = 5.0;
+/// Empty cells a spawn site needs on every side.
+const SPAWN_CLEARANCE: u32 = 12;
+/// Spawn sites are tested on a grid this many cells apart.
+const SITE_STRIDE: usize = 4;
+/// Minimum distance between enemies in one level, in cells.
+const MIN_ENEMY_SPACING: f32 = 48.0;
+/// Patrols sweep this far to either side of home.
+const PATROL_RANGE: f32 = 40.0;
+/// Walls are felt for this far out, in steps of `FEELER_STEP`.
+const FEELER_LENGTH: f32 = 16.0;
+const FEELER_STEP: f32 = 4.0;
+/// Seconds the controller aims to take closing a velocity error.
+const RESPONSE_TIME: f32 = 0.4;
+/// Salt so enemy placement draws from a different stream than the level's
+/// generator and turret placement, which share the per-level seed.
+const PLACEMENT_SALT: u64 = 0x656e_656d_7973_6869;
+
+/// Pick up to `count` enemy spawn sites in the open space of a finished
+/// level, spread apart. Sites are `[x, y]` in level cells. Depends only on
+/// the level and `seed`.
+pub fn place_enemies(
+    level: &[i32],
+    width: u32,
+    height: u32,
+    count: u32,
+    seed: u64,
+) -> Vec<[u32; 2]> {
+    let c = SPAWN_CLEARANCE;
+    if width <= 2 * c || height <= 2 * c {
+        return Vec::new();
+    }
+    let open = |x: u32, y: u32| {
+        (y - c..=y + c).all(|y| (x - c..=x + c).all(|x| level[(y * width + x) as usize] <= 0))
+    };
+    let candidates: Vec<[u32; 2]> = (c..height - c)
+        .step_by(SITE_STRIDE)
+        .flat_map(|y| (c..width - c).step_by(SITE_STRIDE).map(move |x| [x, y]))
+        .filter(|&[x, y]| open(x, y))
+        .collect();
+    crate::turret::pick_spread_out(candidates, count, MIN_ENEMY_SPACING, seed ^ PLACEMENT_SALT)
+}
+
+/// Distance to the first solid cell along `direction` from `position`,
+/// probing every `FEELER_STEP` out to `FEELER_LENGTH`.
+fn wall_distance(terrain: &TerrainSnapshot, position: Vec2, direction: Vec2) -> Option<f32> {
+    (1..=(FEELER_LENGTH / FEELER_STEP) as u32)
+        .map(|step| step as f32 * FEELER_STEP)
+        .find(|&distance| {
+            let probe = position + direction * distance;
+            terrain.is_solid(probe.x.floor() as i32, probe.y.floor() as i32)
+        })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnemyShip {
+    /// Stable across other enemies spawning and dying; tags GPU hit targets.
+    pub id: u32,
+    pub ship: ShipState,
+    /// Spawn point. Patrols sweep sideways around it.
+    pub home: [f32; 2],
+    /// Sideways patrol direction, 1.0 or -1.0.
+    pub patrol_direction: f32,
+    /// Exhaust particle hits taken so far.
+    pub hits: u32,
+}
+
+impl EnemyShip {
+    /// Velocity to aim for this step: toward the ship when it is in sight,
+    /// otherwise sweeping around home, plus a push away from close walls.
+    fn steer(&mut self, params: &EnemyParams, target: Vec2, terrain: &TerrainSnapshot) -> Vec2 {
+        let position = Vec2::from(self.ship.position);
+        let to_target = target - position;
+        let mut desired = if to_target.length() <= params.sight_range {
+            to_target.normalize_or_zero() * params.chase_speed
+        } else {
+            let from_home = position.x - self.home[0];
+            if from_home > PATROL_RANGE {
+                self.patrol_direction = -1.0;
+            } else if from_home < -PATROL_RANGE {
+                self.patrol_direction = 1.0;
+            }
+            if wall_distance(terrain, position, Vec2::new(self.patrol_direction, 0.0)).is_some() {
+                self.patrol_direction = -self.patrol_direction;
+            }
+            let climb =
+                (self.home[1] - position.y).clamp(-params.patrol_speed, params.patrol_speed);
+            Vec2::new(self.patrol_direction * params.patrol_speed, climb)
+        };
+
+        for k in 0..8 {
+            let direction = Vec2::from_angle(k as f32 * std::f32::consts::FRAC_PI_4);
+            if let Some(distance) = wall_distance(terrain, position, direction) {
+                let closeness = 1.0 - (distance - FEELER_STEP) / FEELER_LENGTH;
+                desired -= direction * params.chase_speed * closeness;
+            }
+        }
+        desired
+    }
+
+    /// Thrust and turn toward `desired` velocity, holding altitude against
+    /// gravity, then step the ship's physics.
+    fn fly_toward(&mut self, desired: Vec2, gravity: f32, dt: f32) {
+        let velocity = Vec2::from(self.ship.velocity);
+        let needed = (desired - velocity) / RESPONSE_TIME - Vec2::new(0.0, gravity);
+        let heading = needed.y.atan2(needed.x);
+        // Same bang-bang turning as the player's touch steering.
+        let error = crate::play::angle_diff(heading, self.ship.orientation);
+        let rotate = if error.abs() <= self.ship.rotation_rate * dt {
+            0.0
+        } else {
+            error.signum()
+        };
+        let thrust = (needed.length() / self.ship.acceleration).min(1.0) * error.cos().max(0.0);
+        self.ship.update(dt, thrust, rotate, gravity);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Enemies {
+    pub ships: Vec<EnemyShip>,
+    /// Levels below this index have had their enemies spawned.
+    spawned_levels: u32,
+    next_id: u32,
+    /// Latest terrain around the ship, for steering and crashes.
+    terrain: Option<TerrainSnapshot>,
+}
+
+impl Enemies {
+    /// The next level whose enemies should spawn, up to
+    /// `play::spawn_horizon_level`.
+    pub fn next_level_to_spawn(&self, params: &GameParams, viewport_offset: i32) -> Option<u32> {
+        if params.enemy_params.ships_per_level == 0 {
+            return None;
+        }
+        let reached = crate::play::spawn_horizon_level(params, viewport_offset);
+        (self.spawned_levels <= reached).then_some(self.spawned_levels)
+    }
+
+    /// Add enemies at `sites` (level cells, see `place_enemies`) for
+    /// `level_index`.
+    pub fn spawn_level(&mut self, params: &GameParams, level_index: u32, sites: &[[u32; 2]]) {
+        let base_y = (level_index * params.level_height) as f32;
+        for &[x, y] in sites {
+            let home = [x as f32 + 0.5, base_y + y as f32 + 0.5];
+            let ship = ShipState {
+                position: home,
+                acceleration: params.enemy_params.acceleration,
+                rotation_rate: params.enemy_params.rotation_rate,
+                max_speed: params.enemy_params.chase_speed,
+                ..Default::default()
+            };
+            self.ships.push(EnemyShip {
+                id: self.next_id,
+                ship,
+                home,
+                patrol_direction: if self.next_id.is_multiple_of(2) {
+                    1.0
+                } else {
+                    -1.0
+                },
+                hits: 0,
+            });
+            self.next_id += 1;
+        }
+        self.spawned_levels = level_index + 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ships.is_empty()
+    }
+
+    /// Take in current terrain health around the ship.
+    pub fn observe_terrain(&mut self, snapshot: TerrainSnapshot) {
+        self.terrain = Some(snapshot);
+    }
+
+    /// Steer and move every enemy the latest terrain snapshot covers;
+    /// enemies that fly into terrain crash. Returns whether one touched
+    /// `ship`'s hull.
+    pub fn update(&mut self, params: &GameParams, ship: &ShipState, dt: f32) -> bool {
+        let gravity = params.particle_system_params.gravity;
+        let target = Vec2::from(ship.position);
+        if let Some(terrain) = &self.terrain {
+            let covers = |enemy: &EnemyShip| {
+                let [x, y] = enemy.ship.position;
+                terrain.region.contains(x.floor() as i32, y.floor() as i32)
+            };
+            for enemy in self.ships.iter_mut().filter(|enemy| covers(enemy)) {
+                let desired = enemy.steer(&params.enemy_params, target, terrain);
+                enemy.fly_toward(desired, gravity, dt);
+            }
+            self.ships.retain(|enemy| {
+                let [x, y] = enemy.ship.position;
+                let crashed = terrain.is_solid(x.floor() as i32, y.floor() as i32);
+                if crashed {
+                    log::info!("Enemy ship crashed at ({x:.0}, {y:.0})");
+                }
+                !crashed
+            });
+        }
+        self.touching(ship)
+    }
+
+    /// Whether any enemy touches `ship`'s hull.
+    pub fn touching(&self, ship: &ShipState) -> bool {
+        self.ships
+            .iter()
+            .any(|enemy| ship.hull_overlaps_circle(enemy.ship.position, ENEMY_RADIUS))
+    }
+
+    /// Circles to count exhaust particles in: the enemies the latest terrain
+    /// snapshot covers, which are the only ones near the ship.
+    pub fn hit_targets(&self) -> Vec<HitTarget> {
+        let Some(terrain) = &self.terrain else {
+            return Vec::new();
+        };
+        self.ships
+            .iter()
+            .filter(|enemy| {
+                let [x, y] = enemy.ship.position;
+                terrain.region.contains(x.floor() as i32, y.floor() as i32)
+            })
+            .map(|enemy| HitTarget {
+                center: enemy.ship.position,
+                radius: ENEMY_RADIUS,
+                id: enemy.id,
+            })
+            .collect()
+    }
+
+    /// Add particle hits from a GPU readback. Enemies reaching
+    /// `hit_points` are destroyed; returns how many were.
+    pub fn apply_hits(&mut self, params: &EnemyParams, hits: &[EntityHits]) -> usize {
+        for hit in hits {
+            if let Some(enemy) = self.ships.iter_mut().find(|enemy| enemy.id == hit.id) {
+                enemy.hits = enemy.hits.saturating_add(hit.count);
+            }
+        }
+        let before = self.ships.len();
+        self.ships.retain(|enemy| {
+            let destroyed = enemy.hits >= params.hit_points;
+            if destroyed {
+                let [x, y] = enemy.ship.position;
+                log::info!("Enemy ship destroyed at ({x:.0}, {y:.0})");
+            }
+            !destroyed
+        });
+        before - self.ships.len()
+    }
+
+    /// Boxes to draw with `EntityRenderer`: a hull and tail fin per enemy,
+    /// glowing hotter as it takes hits.
+    pub fn render_boxes(&self, params: &EnemyParams) -> Vec<EntityBox> {
+        let cool = Vec2::new(0.9, 0.2);
+        let mut boxes = Vec::with_capacity(self.ships.len() * 2);
+        for enemy in &self.ships {
+            let damage = (enemy.hits as f32 / params.hit_points as f32).min(1.0);
+            let [r, g] = cool.lerp(Vec2::new(2.0, 1.6), damage).to_array();
+            let color = [r, g, 1.1, 1.0];
+            let angle = enemy.ship.orientation;
+            let position = Vec2::from(enemy.ship.position);
+            let tail = position - Vec2::from_angle(angle) * 3.0;
+            boxes.push(EntityBox::new(
+                enemy.ship.position,
+                [4.5, 2.0],
+                angle,
+                color,
+            ));
+            boxes.push(EntityBox::new(tail.to_array(), [1.0, 3.5], angle, color));
+        }
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_query::Region;
+
+    fn params() -> GameParams {
+        let mut params = GameParams {
+            viewport_height: 100,
+            level_width: 200,
+            level_height: 300,
+            ..Default::default()
+        };
+        params.particle_system_params.gravity = -40.0;
+        params
+    }
+
+    /// Open terrain over the first 200x200 cells, with `solid` cells filled.
+    fn snapshot(solid: impl Fn(i32, i32) -> bool) -> TerrainSnapshot {
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 200,
+            height: 200,
+        };
+        let health = (0..200)
+            .flat_map(|y| (0..200).map(move |x| (x, y)))
+            .map(|(x, y)| if solid(x, y) { 100 } else { 0 })
+            .collect();
+        TerrainSnapshot { region, health }
+    }
+
+    fn ship_at(position: [f32; 2]) -> ShipState {
+        ShipState {
+            position,
+            ..Default::default()
+        }
+    }
+
+    fn enemy_at(params: &GameParams, home: [u32; 2]) -> Enemies {
+        let mut enemies = Enemies::default();
+        enemies.spawn_level(params, 0, &[home]);
+        enemies
+    }
+
+    #[test]
+    fn enemies_spawn_in_open_space() {
+        let (width, height) = (120, 80);
+        // Solid ground below row 40; only the air above can host enemies.
+        let level: Vec<i32> = (0..width * height)
+            .map(|i| if i / width < 40 { 100 } else { 0 })
+            .collect();
+        let sites = place_enemies(&level, width, height, 10, 3);
+        assert!(!sites.is_empty());
+        for &[x, y] in &sites {
+            assert!(
+                y >= 40 + SPAWN_CLEARANCE,
+                "site {x},{y} is too close to the ground"
+            );
+            assert!(y < height - SPAWN_CLEARANCE);
+        }
+        assert_eq!(sites, place_enemies(&level, width, height, 10, 3));
+        assert!(place_enemies(&vec![100; 120 * 80], width, height, 10, 3).is_empty());
+    }
+
+    #[test]
+    fn enemy_physics_come_from_enemy_params_alone() {
+        let mut params = params();
+        params.ship_params.acceleration = 1.0;
+        params.ship_params.rotation_rate = 1.0;
+        params.ship_params.max_speed = 1.0;
+        let enemies = enemy_at(&params, [100, 100]);
+        let ship = &enemies.ships[0].ship;
+        assert_eq!(ship.acceleration, params.enemy_params.acceleration);
+        assert_eq!(ship.rotation_rate, params.enemy_params.rotation_rate);
+        assert_eq!(ship.max_speed, params.enemy_params.chase_speed);
+    }
+
+    #[test]
+    fn enemies_hold_still_until_terrain_covers_them() {
+        let params = params();
+        let mut enemies = enemy_at(&params, [100, 100]);
+        for _ in 0..60 {
+            enemies.update(&params, &ship_at([0.0, 0.0]), 1.0 / 60.0);
+        }
+        assert_eq!(enemies.ships[0].ship.position, [100.5, 100.5]);
+        assert!(enemies.hit_targets().is_empty());
+    }
+
+    #[test]
+    fn patrolling_enemies_stay_near_home() {
+        let params = params();
+        let mut enemies = enemy_at(&params, [100, 100]);
+        enemies.observe_terrain(snapshot(|_, _| false));
+        let (mut min_x, mut max_x) = (f32::MAX, f32::MIN);
+        for _ in 0..1200 {
+            assert!(!enemies.update(&params, &ship_at([0.5, 0.5]), 1.0 / 60.0));
+            let [x, y] = enemies.ships[0].ship.position;
+            assert!((y - 100.5).abs() < 20.0, "drifted to y = {y}");
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+        }
+        assert!(
+            max_x - min_x > PATROL_RANGE,
+            "barely moved: {min_x}..{max_x}"
+        );
+        assert!(min_x > 100.5 - 2.0 * PATROL_RANGE && max_x < 100.5 + 2.0 * PATROL_RANGE);
+    }
+
+    #[test]
+    fn enemies_chase_a_ship_in_sight() {
+        let params = params();
+        let mut enemies = enemy_at(&params, [100, 100]);
+        enemies.observe_terrain(snapshot(|_, _| false));
+        let ship = [160.5, 130.5];
+        let rammed = (0..600).any(|_| enemies.update(&params, &ship_at(ship), 1.0 / 60.0));
+        assert!(rammed);
+    }
+
+    #[test]
+    fn contact_follows_the_player_hull() {
+        use crate::game_params::ShipHull;
+
+        let params = params();
+        // No terrain yet, so the enemy holds still at (100.5, 100.5).
+        let mut enemies = enemy_at(&params, [100, 100]);
+        // Facing right, 17 cells below the enemy: the hauler's wingtip
+        // reaches within the enemy's radius, the scout's doesn't.
+        let ship = |hull| ShipState {
+            position: [109.5, 83.5],
+            orientation: 0.0,
+            hull,
+            ..Default::default()
+        };
+        assert!(enemies.update(&params, &ship(ShipHull::Hauler), 1.0 / 60.0));
+        assert!(!enemies.update(&params, &ship(ShipHull::Scout), 1.0 / 60.0));
+    }
+
+    #[test]
+    fn enemies_steer_clear_of_walls() {
+        let params = params();
+        // A box 30 cells either side of home: tighter than the patrol range.
+        let mut enemies = enemy_at(&params, [100, 100]);
+        enemies.observe_terrain(snapshot(|x, y| {
+            (x - 100).abs() >= 30 || (y - 100).abs() >= 30
+        }));
+        for _ in 0..1200 {
+            enemies.update(&params, &ship_at([0.5, 0.5]), 1.0 / 60.0);
+        }
+        assert_eq!(enemies.ships.len(), 1, "enemy crashed into a wall");
+    }
+
+    #[test]
+    fn particle_hits_destroy_enemies() {
+        let params = params();
+        let mut enemies = Enemies::default();
+        enemies.spawn_level(&params, 0, &[[40, 100], [140, 100]]);
+        enemies.observe_terrain(snapshot(|_, _| false));
+        let ids: Vec<u32> = enemies.hit_targets().iter().map(|t| t.id).collect();
+        assert_eq!(ids, [0, 1]);
+
+        let hit_points = params.enemy_params.hit_points;
+        let half = [EntityHits {
+            id: 1,
+            count: hit_points / 2,
+        }];
+        assert_eq!(enemies.apply_hits(&params.enemy_params, &half), 0);
+        assert_eq!(enemies.apply_hits(&params.enemy_params, &half), 1);
+        assert_eq!(enemies.ships.len(), 1);
+        assert_eq!(enemies.ships[0].id, 0);
+        // Stale hits for a destroyed enemy are ignored.
+        assert_eq!(enemies.apply_hits(&params.enemy_params, &half), 0);
+    }
+}
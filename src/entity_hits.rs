@@ -0,0 +1,357 @@
+//! GPU particle-vs-entity hit counting.
+//!
+//! Counts the live particles inside each of up to `MAX_HIT_TARGETS` circles,
+//! after the frame's particle update. Dispatch and readback follow a
+//! `CollisionDetector` readback slot: one query in flight, mapped
+//! asynchronously and picked up a frame or so later with `poll_result`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::buffer_util::SizedBuffer;
+use crate::particles::PARTICLE_WORKGROUP_SIZE;
+
+/// Most circles one dispatch can test. Must match `max_hit_targets` in build.rs.
+pub const MAX_HIT_TARGETS: usize = 32;
+
+/// A circle to count particles in, in world coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct HitTarget {
+    pub center: [f32; 2],
+    pub radius: f32,
+    /// Caller's tag for the target, handed back with its count.
+    pub id: u32,
+}
+
+/// Particles found inside the target tagged `id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityHits {
+    pub id: u32,
+    pub count: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct EntityHitUniforms {
+    num_particles: u32,
+    num_targets: u32,
+}
+
+const COUNTS_SIZE: wgpu::BufferAddress = (MAX_HIT_TARGETS * std::mem::size_of::<u32>()) as _;
+const TARGETS_SIZE: wgpu::BufferAddress = (MAX_HIT_TARGETS * std::mem::size_of::<HitTarget>()) as _;
+
+pub struct EntityHitDetector {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: SizedBuffer,
+    target_buffer: wgpu::Buffer,
+    /// GPU-side hit counts, one u32 per target slot.
+    count_buffer: wgpu::Buffer,
+    /// CPU-readable staging buffer for async readback.
+    staging_buffer: wgpu::Buffer,
+    /// Targets of the dispatch whose counts are being read back.
+    in_flight: Vec<HitTarget>,
+    /// Whether a readback copy has been queued (dispatch called).
+    pending_readback: bool,
+    /// Whether `map_async` has been initiated for the current readback.
+    mapping_started: bool,
+    /// Set to `true` by the `map_async` callback when the mapping completes.
+    map_ready: Arc<AtomicBool>,
+}
+
+impl EntityHitDetector {
+    pub fn init(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Entity Hits Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::include_shader!("entity_hits.wgsl")),
+        });
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Entity Hits BGL"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, true),
+                storage_entry(3, false),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Entity Hits Pipeline Layout"),
+            bind_group_layouts: &[Some(&bind_group_layout)],
+            immediate_size: 0,
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Entity Hits Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let uniform_buffer = crate::buffer_util::make_default_uniform_buffer::<EntityHitUniforms>(
+            device,
+            "Entity Hits Uniform Buffer",
+        );
+
+        let target_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Entity Hit Targets"),
+            size: TARGETS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let count_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Entity Hit Counts"),
+            size: COUNTS_SIZE,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Entity Hits Staging"),
+            size: COUNTS_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        EntityHitDetector {
+            pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            target_buffer,
+            count_buffer,
+            staging_buffer,
+            in_flight: Vec::new(),
+            pending_readback: false,
+            mapping_started: false,
+            map_ready: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Count `particles`' live particles inside each of `targets` (only the
+    /// first `MAX_HIT_TARGETS` are tested). Skips, returning false, if there
+    /// is nothing to test or the previous readback is still in flight.
+    pub fn dispatch(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+        particles: &crate::particles::ParticleSystem,
+        targets: &[HitTarget],
+    ) -> bool {
+        if self.pending_readback || targets.is_empty() {
+            return false;
+        }
+        let targets = &targets[..targets.len().min(MAX_HIT_TARGETS)];
+        let num_particles = particles.num_particles();
+        let uniforms = EntityHitUniforms {
+            num_particles,
+            num_targets: targets.len() as u32,
+        };
+
+        // safe: uniform_buffer.size is always > 0 (set at GPU buffer creation)
+        belt.write_buffer(
+            encoder,
+            &self.uniform_buffer.buffer,
+            0,
+            wgpu::BufferSize::new(self.uniform_buffer.size as _).unwrap(),
+        )
+        .copy_from_slice(bytemuck::bytes_of(&uniforms));
+        let target_bytes: &[u8] = bytemuck::cast_slice(targets);
+        // safe: targets is non-empty
+        belt.write_buffer(
+            encoder,
+            &self.target_buffer,
+            0,
+            wgpu::BufferSize::new(target_bytes.len() as _).unwrap(),
+        )
+        .copy_from_slice(target_bytes);
+        // safe: COUNTS_SIZE > 0
+        belt.write_buffer(
+            encoder,
+            &self.count_buffer,
+            0,
+            wgpu::BufferSize::new(COUNTS_SIZE).unwrap(),
+        )
+        .copy_from_slice(&[0; COUNTS_SIZE as usize]);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Entity Hits BG"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: particles.particle_buffer().buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.target_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.count_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Entity Hits"),
+                ..Default::default()
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(num_particles.div_ceil(PARTICLE_WORKGROUP_SIZE), 1, 1);
+        }
+
+        encoder.copy_buffer_to_buffer(&self.count_buffer, 0, &self.staging_buffer, 0, COUNTS_SIZE);
+        self.in_flight = targets.to_vec();
+        self.pending_readback = true;
+        true
+    }
+
+    /// Initiate async mapping of the staging buffer. Call after `queue.submit()`;
+    /// see `CollisionDetector::start_readback`.
+    pub fn start_readback(&mut self) {
+        if !self.pending_readback || self.mapping_started {
+            return;
+        }
+        let slice = self.staging_buffer.slice(..);
+        let ready = Arc::clone(&self.map_ready);
+        ready.store(false, Ordering::Release);
+        slice.map_async(wgpu::MapMode::Read, move |_| {
+            ready.store(true, Ordering::Release);
+        });
+        self.mapping_started = true;
+    }
+
+    /// Hit counts for the last dispatch's targets, once the readback lands.
+    /// Targets no particle reached are left out.
+    pub fn poll_result(&mut self) -> Option<Vec<EntityHits>> {
+        if !self.pending_readback
+            || !self.mapping_started
+            || !self.map_ready.load(Ordering::Acquire)
+        {
+            return None;
+        }
+
+        let data = self.staging_buffer.slice(..).get_mapped_range();
+        let counts: &[u32] = bytemuck::cast_slice(&data);
+        let hits = self
+            .in_flight
+            .iter()
+            .zip(counts)
+            .filter(|(_, &count)| count > 0)
+            .map(|(target, &count)| EntityHits {
+                id: target.id,
+                count,
+            })
+            .collect();
+        drop(data);
+        self.staging_buffer.unmap();
+
+        self.in_flight.clear();
+        self.pending_readback = false;
+        self.mapping_started = false;
+        Some(hits)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_particles_inside_targets() {
+        let Some((device, queue)) = crate::gpu_test_utils::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping counts_particles_inside_targets");
+            return;
+        };
+        let mut params = crate::game_params::GameParams::default();
+        params.viewport_width = 64;
+        params.viewport_height = 32;
+        params.level_width = 64;
+        params.level_height = 64;
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let level_manager = crate::level_manager::LevelManager::init(
+            &device,
+            &params,
+            1,
+            0,
+            &mut encoder,
+            &mut belt,
+        );
+        let mut particles =
+            crate::particles::ParticleSystem::new(&device, &params, &mut encoder, &level_manager);
+
+        // A burst is written straight into the particle buffer, all at the
+        // burst center before any update moves them.
+        particles.emit_burst(
+            &mut encoder,
+            &mut belt,
+            crate::particles::ParticleType::Exhaust,
+            [50.0, 50.0],
+            [0.0, 0.0],
+            100,
+            1.0,
+            1.0,
+        );
+
+        let mut detector = EntityHitDetector::init(&device);
+        let targets = [
+            HitTarget {
+                center: [51.0, 50.0],
+                radius: 2.0,
+                id: 7,
+            },
+            HitTarget {
+                center: [80.0, 80.0],
+                radius: 5.0,
+                id: 9,
+            },
+        ];
+        assert!(detector.dispatch(&device, &mut encoder, &mut belt, &particles, &targets));
+        assert!(!detector.dispatch(&device, &mut encoder, &mut belt, &particles, &targets));
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        detector.start_readback();
+        device.poll(wgpu::PollType::wait_indefinitely()).ok();
+        let hits = detector
+            .poll_result()
+            .expect("hit readback should complete after polling");
+        assert_eq!(hits, vec![EntityHits { id: 7, count: 100 }]);
+    }
+}
@@ -0,0 +1,218 @@
+//! GPU rendering for small world entities (turrets, projectiles) as instanced
+//! oriented boxes, drawn into the game view alongside the ship.
+
+use wgpu::util::DeviceExt;
+
+use crate::{buffer_util::SizedBuffer, game_params};
+
+/// One filled rectangle in world coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EntityBox {
+    pub center: [f32; 2],
+    pub half_size: [f32; 2],
+    /// Rotation about `center`, in radians.
+    pub angle: f32,
+    /// HDR color; channels above 1.0 bloom.
+    pub color: [f32; 4],
+}
+
+impl EntityBox {
+    pub fn new(center: [f32; 2], half_size: [f32; 2], angle: f32, color: [f32; 4]) -> Self {
+        EntityBox {
+            center,
+            half_size,
+            angle,
+            color,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct EntityRendererUniforms {
+    viewport_width: u32,
+    viewport_height: u32,
+    viewport_offset: i32,
+    viewport_offset_x: i32,
+}
+
+pub struct EntityRenderer {
+    uniform_buffer: SizedBuffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl EntityRenderer {
+    pub fn init(device: &wgpu::Device) -> Self {
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Entity Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::include_shader!("entity.wgsl")),
+        });
+
+        let uniform_buffer = crate::buffer_util::make_default_uniform_buffer::<
+            EntityRendererUniforms,
+        >(device, "Entity Renderer Uniform Buffer");
+
+        let bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("EntityRendererBGL"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(uniform_buffer.size as _),
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bgl,
+            label: Some("EntityRendererBG"),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(
+                    uniform_buffer.buffer.as_entire_buffer_binding(),
+                ),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Entity render pipeline layout"),
+            bind_group_layouts: &[Some(&bgl)],
+            immediate_size: 0,
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Entity render pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<EntityBox>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &[
+                        wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 8,
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 16,
+                            shader_location: 2,
+                            format: wgpu::VertexFormat::Float32,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 20,
+                            shader_location: 3,
+                            format: wgpu::VertexFormat::Float32x4,
+                        },
+                    ],
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: crate::bloom::GAME_VIEW_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::all(),
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            cache: None,
+            multiview_mask: None,
+        });
+
+        EntityRenderer {
+            uniform_buffer,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        boxes: &[EntityBox],
+        game_params: &game_params::GameParams,
+        viewport_offset: [i32; 2],
+        output_texture_view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+    ) {
+        if boxes.is_empty() {
+            return;
+        }
+
+        let uniform_values = EntityRendererUniforms {
+            viewport_width: game_params.viewport_width,
+            viewport_height: game_params.viewport_height,
+            viewport_offset: viewport_offset[1],
+            viewport_offset_x: viewport_offset[0],
+        };
+        belt.write_buffer(
+            encoder,
+            &self.uniform_buffer.buffer,
+            0,
+            // safe: uniform_buffer.size is always > 0 (set at GPU buffer creation)
+            wgpu::BufferSize::new(self.uniform_buffer.size as _).unwrap(),
+        )
+        .copy_from_slice(bytemuck::bytes_of(&uniform_values));
+
+        let instance_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Entity Instances"),
+            contents: bytemuck::cast_slice(boxes),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Entity Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output_texture_view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            ..Default::default()
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, instance_buf.slice(..));
+        rpass.draw(0..4, 0..boxes.len() as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_renderer_constructs_headless() {
+        let Some((device, _queue)) = crate::gpu_test_utils::try_create_headless_device() else {
+            eprintln!("No headless GPU adapter available; skipping entity_renderer_constructs");
+            return;
+        };
+        let _renderer = EntityRenderer::init(&device);
+    }
+}
@@ -4,6 +4,8 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::ship_geometry;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum TouchControlScheme {
@@ -48,7 +50,7 @@ impl From<toml::de::Error> for GameParamsError {
 const MAX_EXACT_PARTICLE_COUNT_F32: f32 = (1_u32 << 24) as f32;
 
 // Parameters that define the game. These don't change at runtime.
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct GameParams {
     pub viewport_width: u32,
@@ -74,6 +76,26 @@ pub struct GameParams {
 
     #[serde(default)]
     pub visual_params: VisualParams,
+
+    #[serde(default)]
+    pub turret_params: TurretParams,
+
+    #[serde(default)]
+    pub enemy_params: EnemyParams,
+
+    #[serde(default)]
+    pub pickup_params: PickupParams,
+
+    #[serde(default)]
+    pub bomb_params: BombParams,
+
+    #[serde(default)]
+    pub gun_params: GunParams,
+
+    /// Ships offered on the title screen's ship select. Empty flies
+    /// `ship_params` with the base nozzle and skips ship select.
+    #[serde(default)]
+    pub ship_roster: Vec<RosterShip>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -156,6 +178,11 @@ pub struct ParticleSystemParams {
     pub damage_rate: f32,
     pub gravity: f32,
     pub elasticity: f32,
+    /// Terrain health a particle adds to the cell it is in when its life runs
+    /// out, turning an empty cell into loose dirt. Exhaust piles up over time
+    /// and can be eroded again. 0 disables deposition.
+    #[serde(default)]
+    pub deposition_amount: i32,
 }
 
 impl Default for ParticleSystemParams {
@@ -167,16 +194,87 @@ impl Default for ParticleSystemParams {
             damage_rate: 0.00001,
             gravity: -5.0,
             elasticity: 0.5,
+            deposition_amount: 0,
         }
     }
 }
 
+/// Which hull shape the ship flies. Variants are in `ship_geometry::HULLS`
+/// order.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShipHull {
+    /// The original chevron.
+    #[default]
+    Dart,
+    /// Broad wings; a bigger target.
+    Hauler,
+    /// Small enough for tighter gaps.
+    Scout,
+}
+
+impl ShipHull {
+    pub const ALL: [ShipHull; ship_geometry::HULLS.len()] =
+        [ShipHull::Dart, ShipHull::Hauler, ShipHull::Scout];
+
+    pub fn shape(self) -> &'static ship_geometry::HullShape {
+        &ship_geometry::HULLS[self as usize]
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(deny_unknown_fields)]
 pub struct ShipParams {
     pub acceleration: f32,
     pub rotation_rate: f32,
     pub max_speed: f32,
+    /// Fuel in a full tank; each run starts full.
+    #[serde(default = "default_fuel_capacity")]
+    pub fuel_capacity: f32,
+    /// Fuel burned per second at full thrust. 0 makes thrust free.
+    #[serde(default = "default_fuel_burn_rate")]
+    pub fuel_burn_rate: f32,
+    /// Hull points each run starts with. Terrain impacts no faster than
+    /// `max_safe_impact_speed` bounce the ship off and cost hull points;
+    /// the ship dies when they run out. 0 makes every impact fatal.
+    #[serde(default = "default_hull_points")]
+    pub hull_points: f32,
+    /// Fastest impact, along the contact normal, the hull survives.
+    #[serde(default = "default_max_safe_impact_speed")]
+    pub max_safe_impact_speed: f32,
+    /// Fraction of the impact speed kept when bouncing off terrain, in [0, 1].
+    #[serde(default = "default_bounce_restitution")]
+    pub bounce_restitution: f32,
+    /// Hull points lost per unit of impact speed.
+    #[serde(default = "default_hull_damage_per_speed")]
+    pub hull_damage_per_speed: f32,
+    /// Shape drawn and swept for terrain collision.
+    #[serde(default)]
+    pub hull: ShipHull,
+}
+
+fn default_fuel_capacity() -> f32 {
+    100.0
+}
+
+fn default_fuel_burn_rate() -> f32 {
+    5.0
+}
+
+fn default_hull_points() -> f32 {
+    100.0
+}
+
+fn default_max_safe_impact_speed() -> f32 {
+    60.0
+}
+
+fn default_bounce_restitution() -> f32 {
+    0.5
+}
+
+fn default_hull_damage_per_speed() -> f32 {
+    0.5
 }
 
 impl Default for ShipParams {
@@ -185,16 +283,333 @@ impl Default for ShipParams {
             acceleration: 50.0,
             rotation_rate: 15.0,
             max_speed: 100.0,
+            fuel_capacity: default_fuel_capacity(),
+            fuel_burn_rate: default_fuel_burn_rate(),
+            hull_points: default_hull_points(),
+            max_safe_impact_speed: default_max_safe_impact_speed(),
+            bounce_restitution: default_bounce_restitution(),
+            hull_damage_per_speed: default_hull_damage_per_speed(),
+            hull: ShipHull::default(),
         }
     }
 }
 
+/// A roster ship's exhaust nozzle, as multipliers on the base exhaust in
+/// `particle_system_params`. Nozzle pickups scale it further.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NozzleParams {
+    /// Multiplier on `particle_system_params.emission_speed`.
+    #[serde(default = "default_nozzle_scale")]
+    pub speed_scale: f32,
+    /// Multiplier on `particle_system_params.max_particle_life`.
+    #[serde(default = "default_nozzle_scale")]
+    pub life_scale: f32,
+    /// Multiplier on the exhaust cone width.
+    #[serde(default = "default_nozzle_scale")]
+    pub spread_scale: f32,
+}
+
+fn default_nozzle_scale() -> f32 {
+    1.0
+}
+
+impl Default for NozzleParams {
+    fn default() -> Self {
+        NozzleParams {
+            speed_scale: default_nozzle_scale(),
+            life_scale: default_nozzle_scale(),
+            spread_scale: default_nozzle_scale(),
+        }
+    }
+}
+
+/// One ship on the ship-select roster: its handling, hull and exhaust.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RosterShip {
+    /// Shown on the ship select.
+    pub name: String,
+    pub ship_params: ShipParams,
+    #[serde(default)]
+    pub nozzle_params: NozzleParams,
+}
+
+/// Enemy turrets: placed on terrain surfaces as levels are generated, and
+/// firing at the ship when it comes within range.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct TurretParams {
+    /// Turrets placed per level, where the level has room. 0 disables turrets.
+    pub turrets_per_level: u32,
+    /// First level index with turrets, keeping the launch area quiet.
+    pub first_level: u32,
+    /// Turrets track and fire at a ship within this distance.
+    pub range: f32,
+    /// Seconds between shots from one turret.
+    pub fire_interval: f32,
+    pub projectile_speed: f32,
+    /// Seconds before a projectile that hits nothing fizzles out.
+    pub projectile_life: f32,
+}
+
+impl Default for TurretParams {
+    fn default() -> Self {
+        TurretParams {
+            turrets_per_level: 3,
+            first_level: 1,
+            range: 120.0,
+            fire_interval: 2.0,
+            projectile_speed: 60.0,
+            projectile_life: 3.0,
+        }
+    }
+}
+
+/// Enemy ships: placed in open space as levels are generated. They patrol
+/// until the ship comes into sight, then give chase; enough exhaust
+/// particles through their hull destroys them.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyParams {
+    /// Ships placed per level, where the level has room. 0 disables them.
+    pub ships_per_level: u32,
+    /// First level index with enemy ships.
+    pub first_level: u32,
+    /// Enemies chase a ship within this distance.
+    pub sight_range: f32,
+    pub patrol_speed: f32,
+    /// Top speed while chasing; also caps the enemy's physics.
+    pub chase_speed: f32,
+    /// Enemy thrust and turn rate, as in `ShipParams`.
+    #[serde(default = "default_enemy_acceleration")]
+    pub acceleration: f32,
+    #[serde(default = "default_enemy_rotation_rate")]
+    pub rotation_rate: f32,
+    /// Exhaust particle hits, counted once per particle per frame, needed to
+    /// destroy one.
+    pub hit_points: u32,
+}
+
+impl Default for EnemyParams {
+    fn default() -> Self {
+        EnemyParams {
+            ships_per_level: 2,
+            first_level: 2,
+            sight_range: 90.0,
+            patrol_speed: 15.0,
+            chase_speed: 40.0,
+            acceleration: default_enemy_acceleration(),
+            rotation_rate: default_enemy_rotation_rate(),
+            hit_points: 400,
+        }
+    }
+}
+
+fn default_enemy_acceleration() -> f32 {
+    100.0
+}
+
+fn default_enemy_rotation_rate() -> f32 {
+    7.5
+}
+
+/// Collectible pickups placed in open space as levels are generated: time
+/// bonuses, fuel and exhaust nozzle upgrades.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(deny_unknown_fields)]
+pub struct PickupParams {
+    /// Pickups placed per level, where the level has room. 0 disables them.
+    pub pickups_per_level: u32,
+    pub first_level: u32,
+    /// Seconds a time pickup adds to the countdown.
+    pub time_bonus_seconds: f32,
+    /// Fuel a fuel pickup adds, up to `ship_params.fuel_capacity`.
+    pub fuel_refill: f32,
+    /// Fraction of the base exhaust speed each speed upgrade adds.
+    pub speed_step: f32,
+    /// Fraction of the base particle life each range upgrade adds.
+    pub life_step: f32,
+    /// Fraction of the base nozzle spread each spread upgrade adds.
+    pub spread_step: f32,
+    /// Upgrades of each kind beyond which further pickups do nothing.
+    pub max_upgrades: u32,
+}
+
+impl PickupParams {
+    /// Particle life multiplier with every range upgrade collected.
+    pub fn max_life_scale(&self) -> f32 {
+        1.0 + self.life_step * self.max_upgrades as f32
+    }
+}
+
+impl Default for PickupParams {
+    fn default() -> Self {
+        PickupParams {
+            pickups_per_level: 3,
+            first_level: 0,
+            time_bonus_seconds: 10.0,
+            fuel_refill: 40.0,
+            speed_step: 0.15,
+            life_step: 0.2,
+            spread_step: 0.25,
+            max_upgrades: 4,
+        }
+    }
+}
+
+/// The ship's secondary weapon: bombs that fall under gravity and blast a
+/// crater where they hit terrain.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct BombParams {
+    /// Bombs carried at the start of a run. 0 disables bombs.
+    pub ammo: u32,
+    /// Seconds between drops while the bomb button is held.
+    pub cooldown: f32,
+    /// Crater radius in cells.
+    pub crater_radius: f32,
+    /// Terrain health removed at the crater's center, falling off linearly
+    /// to nothing at `crater_radius`. Metal is untouched.
+    pub crater_damage: i32,
+    /// Particles in the detonation burst.
+    pub burst_particles: u32,
+}
+
+impl Default for BombParams {
+    fn default() -> Self {
+        BombParams {
+            ammo: 5,
+            cooldown: 0.75,
+            crater_radius: 14.0,
+            crater_damage: 1500,
+            burst_particles: 20000,
+        }
+    }
+}
+
+/// The forward gun: a second particle emitter that fires a narrow, fast
+/// stream from the ship's nose while the fire button is held.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct GunParams {
+    /// Particles fired per second. 0 disables the gun.
+    pub emission_rate: f32,
+    pub emission_speed: f32,
+    /// Seconds each gun particle lives.
+    pub particle_life: f32,
+    /// Width of the stream's cone in radians.
+    pub spread: f32,
+    /// Terrain damage per particle relative to exhaust at the same speed.
+    pub damage_scale: f32,
+    /// Index into the particle color map palette (see color_maps.rs).
+    pub color_map: i32,
+}
+
+impl Default for GunParams {
+    fn default() -> Self {
+        GunParams {
+            emission_rate: 2000.0,
+            emission_speed: 600.0,
+            particle_life: 0.6,
+            spread: 0.08,
+            damage_scale: 4.0,
+            color_map: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct LevelParams {
     pub starting_terrain_health: i32,
     #[serde(default = "default_level_time_limit_seconds")]
     pub level_time_limit_seconds: f32,
+    /// Run seed for level generation. When set, every run generates the same
+    /// terrain; when absent, each run picks a fresh random seed. The seed in
+    /// use is shown on the game-over screen and can be passed back via
+    /// `--seed <n>` on the command line.
+    #[serde(default)]
+    pub seed: Option<u32>,
+    /// Let loose terrain (dirt) fall and slide into empty cells below it each
+    /// frame, so undercut dirt collapses instead of floating.
+    #[serde(default)]
+    pub falling_sand: bool,
+    /// Terrain generator per band of levels, sorted by `first_level`. Each
+    /// band runs until the next one starts; levels before the first band (or
+    /// all levels, if empty) use rectangles.
+    #[serde(default)]
+    pub generators: Vec<GeneratorBand>,
+    /// Hand-painted levels that replace generated ones at fixed indices. When
+    /// several placements claim a level, the first listed wins.
+    #[serde(default)]
+    pub authored_levels: Vec<AuthoredLevelPlacement>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelGeneratorKind {
+    /// Solid rock with randomly placed rectangular vacancies. Original generator.
+    #[default]
+    Rectangles,
+    /// Cellular-automaton caves: smoothed random noise, so open space forms
+    /// rounded, connected pockets.
+    Caves,
+    /// Caverns where fractal gradient noise crosses a threshold: long winding
+    /// tunnels and blobs.
+    Noise,
+    /// Maze corridors carved by a depth-first search, open at the top and
+    /// bottom edges.
+    Maze,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorBand {
+    pub first_level: u32,
+    pub generator: LevelGeneratorKind,
+}
+
+/// Puts a hand-authored level image (see `authored_level`) at `level`, and
+/// optionally every `repeat_every` levels after it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AuthoredLevelPlacement {
+    /// File name under `assets/levels/`.
+    pub image: String,
+    pub level: u32,
+    #[serde(default)]
+    pub repeat_every: Option<u32>,
+}
+
+impl AuthoredLevelPlacement {
+    pub fn covers(&self, level_index: u32) -> bool {
+        match self.repeat_every {
+            Some(period) if period > 0 && level_index >= self.level => {
+                (level_index - self.level).is_multiple_of(period)
+            }
+            _ => level_index == self.level,
+        }
+    }
+}
+
+impl LevelParams {
+    /// The authored level image placed at `level_index`, if any.
+    pub fn authored_level_for(&self, level_index: u32) -> Option<&str> {
+        self.authored_levels
+            .iter()
+            .find(|placement| placement.covers(level_index))
+            .map(|placement| placement.image.as_str())
+    }
+
+    /// The generator for `level_index` according to `generators`.
+    pub fn generator_for_level(&self, level_index: u32) -> LevelGeneratorKind {
+        self.generators
+            .iter()
+            .rev()
+            .find(|band| band.first_level <= level_index)
+            .map_or(LevelGeneratorKind::Rectangles, |band| band.generator)
+    }
 }
 
 impl Default for LevelParams {
@@ -202,6 +617,10 @@ impl Default for LevelParams {
         LevelParams {
             starting_terrain_health: 100000,
             level_time_limit_seconds: default_level_time_limit_seconds(),
+            seed: None,
+            falling_sand: false,
+            generators: vec![],
+            authored_levels: vec![],
         }
     }
 }
@@ -216,17 +635,43 @@ impl std::str::FromStr for GameParams {
 }
 
 impl GameParams {
+    /// The ships a run can fly: `ship_roster`, or a lone ship flying
+    /// `ship_params` when the roster is empty.
+    pub fn ships(&self) -> Vec<RosterShip> {
+        if self.ship_roster.is_empty() {
+            vec![RosterShip {
+                name: self.ship_params.hull.shape().name.to_uppercase(),
+                ship_params: self.ship_params,
+                nozzle_params: NozzleParams::default(),
+            }]
+        } else {
+            self.ship_roster.clone()
+        }
+    }
+
+    /// Longest exhaust particle life: the base life scaled by the longest
+    /// roster nozzle and every range upgrade.
+    pub fn max_exhaust_life(&self) -> f32 {
+        let ship_life_scale = self
+            .ships()
+            .iter()
+            .map(|ship| ship.nozzle_params.life_scale)
+            .fold(0.0, f32::max);
+        self.particle_system_params.max_particle_life
+            * ship_life_scale
+            * self.pickup_params.max_life_scale()
+    }
+
     pub fn validate(&self) -> Result<(), GameParamsError> {
         ensure_positive_u32("viewport_width", self.viewport_width)?;
         ensure_positive_u32("viewport_height", self.viewport_height)?;
         ensure_positive_u32("level_width", self.level_width)?;
         ensure_positive_u32("level_height", self.level_height)?;
-        // Width is currently fixed to the viewport because particle/terrain
-        // shaders still conflate terrain stride, view width, and density width.
-        // Height may exceed the viewport because levels scroll vertically.
+        // Levels scroll in both directions, so they may exceed the viewport
+        // but not fall short of it.
         ensure(
-            self.level_width == self.viewport_width,
-            "level_width must equal viewport_width until terrain/view/density widths are split",
+            self.level_width >= self.viewport_width,
+            "level_width must be at least viewport_width",
         )?;
         ensure(
             self.level_height >= self.viewport_height,
@@ -234,13 +679,15 @@ impl GameParams {
         )?;
         ensure_positive_f64("fps", self.fps)?;
 
-        let particle_count = self.particle_system_params.emission_rate
-            * self.particle_system_params.max_particle_life;
+        // The particle buffer holds exhaust at its longest upgraded life,
+        // followed by the gun's particles.
+        let particle_count = self.particle_system_params.emission_rate * self.max_exhaust_life()
+            + self.gun_params.emission_rate * self.gun_params.particle_life;
         ensure(
             particle_count.is_finite()
                 && particle_count > 0.0
                 && particle_count <= MAX_EXACT_PARTICLE_COUNT_F32,
-            "particle_system_params.emission_rate * max_particle_life must be finite, positive, and no more than 16,777,216",
+            "particle_system_params.emission_rate * max_particle_life, at the longest upgraded life of any roster ship, plus gun_params.emission_rate * particle_life, must be finite, positive, and no more than 16,777,216",
         )?;
         ensure_positive_f32(
             "particle_system_params.emission_rate",
@@ -266,10 +713,17 @@ impl GameParams {
             "particle_system_params.elasticity",
             self.particle_system_params.elasticity,
         )?;
+        ensure(
+            self.particle_system_params.deposition_amount >= 0,
+            "particle_system_params.deposition_amount must be non-negative",
+        )?;
 
-        ensure_non_negative_f32("ship_params.acceleration", self.ship_params.acceleration)?;
-        ensure_non_negative_f32("ship_params.rotation_rate", self.ship_params.rotation_rate)?;
-        ensure_positive_f32("ship_params.max_speed", self.ship_params.max_speed)?;
+        validate_ship_params(&self.ship_params)?;
+        for (i, ship) in self.ship_roster.iter().enumerate() {
+            validate_roster_ship(ship).map_err(|err| {
+                GameParamsError::Invalid(format!("ship_roster[{i}] ({}): {err}", ship.name))
+            })?;
+        }
 
         ensure(
             self.level_params.starting_terrain_health > 0,
@@ -279,6 +733,80 @@ impl GameParams {
             "level_params.level_time_limit_seconds",
             self.level_params.level_time_limit_seconds,
         )?;
+        ensure(
+            self.level_params
+                .generators
+                .windows(2)
+                .all(|bands| bands[0].first_level < bands[1].first_level),
+            "level_params.generators must be sorted by first_level without duplicates",
+        )?;
+        for placement in &self.level_params.authored_levels {
+            ensure(
+                placement.repeat_every != Some(0),
+                "level_params.authored_levels repeat_every must be positive",
+            )?;
+            crate::authored_level::AuthoredLevel::check(
+                &placement.image,
+                self.level_width,
+                self.level_height,
+            )
+            .map_err(|err| {
+                GameParamsError::Invalid(format!("level_params.authored_levels: {err}"))
+            })?;
+        }
+
+        ensure_positive_f32("turret_params.range", self.turret_params.range)?;
+        ensure_positive_f32(
+            "turret_params.fire_interval",
+            self.turret_params.fire_interval,
+        )?;
+        ensure_positive_f32(
+            "turret_params.projectile_speed",
+            self.turret_params.projectile_speed,
+        )?;
+        ensure_positive_f32(
+            "turret_params.projectile_life",
+            self.turret_params.projectile_life,
+        )?;
+        ensure_positive_f32("enemy_params.sight_range", self.enemy_params.sight_range)?;
+        ensure_positive_f32("enemy_params.patrol_speed", self.enemy_params.patrol_speed)?;
+        ensure_positive_f32("enemy_params.chase_speed", self.enemy_params.chase_speed)?;
+        ensure_positive_f32("enemy_params.acceleration", self.enemy_params.acceleration)?;
+        ensure_non_negative_f32(
+            "enemy_params.rotation_rate",
+            self.enemy_params.rotation_rate,
+        )?;
+        ensure_positive_u32("enemy_params.hit_points", self.enemy_params.hit_points)?;
+        ensure_positive_f32(
+            "pickup_params.time_bonus_seconds",
+            self.pickup_params.time_bonus_seconds,
+        )?;
+        ensure_positive_f32("pickup_params.fuel_refill", self.pickup_params.fuel_refill)?;
+        ensure_non_negative_f32("pickup_params.speed_step", self.pickup_params.speed_step)?;
+        ensure_non_negative_f32("pickup_params.life_step", self.pickup_params.life_step)?;
+        ensure_non_negative_f32("pickup_params.spread_step", self.pickup_params.spread_step)?;
+
+        ensure_non_negative_f32("bomb_params.cooldown", self.bomb_params.cooldown)?;
+        ensure_positive_f32("bomb_params.crater_radius", self.bomb_params.crater_radius)?;
+        ensure(
+            self.bomb_params.crater_damage >= 0,
+            "bomb_params.crater_damage must be non-negative",
+        )?;
+        ensure_positive_u32(
+            "bomb_params.burst_particles",
+            self.bomb_params.burst_particles,
+        )?;
+
+        ensure_non_negative_f32("gun_params.emission_rate", self.gun_params.emission_rate)?;
+        ensure_positive_f32("gun_params.emission_speed", self.gun_params.emission_speed)?;
+        ensure_positive_f32("gun_params.particle_life", self.gun_params.particle_life)?;
+        ensure_non_negative_f32("gun_params.spread", self.gun_params.spread)?;
+        ensure_non_negative_f32("gun_params.damage_scale", self.gun_params.damage_scale)?;
+        ensure(
+            self.gun_params.color_map >= 0
+                && crate::color_maps::has_color_map_index(self.gun_params.color_map as usize),
+            "gun_params.color_map must name an existing color map",
+        )?;
 
         ensure(
             self.visual_params.color_map >= 0
@@ -314,6 +842,37 @@ impl GameParams {
     }
 }
 
+fn validate_ship_params(ship: &ShipParams) -> Result<(), GameParamsError> {
+    ensure_non_negative_f32("ship_params.acceleration", ship.acceleration)?;
+    ensure_non_negative_f32("ship_params.rotation_rate", ship.rotation_rate)?;
+    ensure_positive_f32("ship_params.max_speed", ship.max_speed)?;
+    ensure_positive_f32("ship_params.fuel_capacity", ship.fuel_capacity)?;
+    ensure_non_negative_f32("ship_params.fuel_burn_rate", ship.fuel_burn_rate)?;
+    ensure_non_negative_f32("ship_params.hull_points", ship.hull_points)?;
+    ensure_non_negative_f32(
+        "ship_params.max_safe_impact_speed",
+        ship.max_safe_impact_speed,
+    )?;
+    ensure(
+        (0.0..=1.0).contains(&ship.bounce_restitution),
+        "ship_params.bounce_restitution must be in [0, 1]",
+    )?;
+    ensure_non_negative_f32(
+        "ship_params.hull_damage_per_speed",
+        ship.hull_damage_per_speed,
+    )?;
+    Ok(())
+}
+
+fn validate_roster_ship(ship: &RosterShip) -> Result<(), GameParamsError> {
+    ensure(!ship.name.is_empty(), "name must not be empty")?;
+    validate_ship_params(&ship.ship_params)?;
+    let nozzle = &ship.nozzle_params;
+    ensure_positive_f32("nozzle_params.speed_scale", nozzle.speed_scale)?;
+    ensure_positive_f32("nozzle_params.life_scale", nozzle.life_scale)?;
+    ensure_positive_f32("nozzle_params.spread_scale", nozzle.spread_scale)
+}
+
 fn ensure(condition: bool, message: &'static str) -> Result<(), GameParamsError> {
     if condition {
         Ok(())
@@ -385,6 +944,12 @@ impl Default for GameParams {
             ship_params: ShipParams::default(),
             level_params: LevelParams::default(),
             visual_params: VisualParams::default(),
+            turret_params: TurretParams::default(),
+            enemy_params: EnemyParams::default(),
+            pickup_params: PickupParams::default(),
+            bomb_params: BombParams::default(),
+            gun_params: GunParams::default(),
+            ship_roster: Vec::new(),
         }
     }
 }
@@ -453,6 +1018,12 @@ mod tests {
             ship_params: ShipParams::default(),
             level_params: LevelParams::default(),
             visual_params: VisualParams::default(),
+            turret_params: TurretParams::default(),
+            enemy_params: EnemyParams::default(),
+            pickup_params: PickupParams::default(),
+            bomb_params: BombParams::default(),
+            gun_params: GunParams::default(),
+            ship_roster: Vec::new(),
         };
         let serialized = toml::to_string(&params).unwrap();
         let deserialized: GameParams = toml::from_str(&serialized).unwrap();
@@ -534,9 +1105,11 @@ mod tests {
     }
 
     #[test]
-    fn width_mismatch_is_invalid_until_wider_levels_are_split() {
+    fn levels_may_be_wider_but_not_narrower_than_the_viewport() {
         let mut params = GameParams::default();
-        params.level_width = params.viewport_width + 1;
+        params.level_width = params.viewport_width * 4;
+        assert!(params.validate().is_ok());
+        params.level_width = params.viewport_width - 1;
         assert!(params.validate().is_err());
     }
 
@@ -570,4 +1143,362 @@ mod tests {
         params.particle_system_params.max_particle_life = 1.0;
         assert!(params.validate().is_err());
     }
+
+    #[test]
+    fn invalid_turret_params_are_rejected() {
+        let mut params = GameParams::default();
+        params.turret_params.fire_interval = 0.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.turret_params.projectile_speed = f32::NAN;
+        assert!(params.validate().is_err());
+
+        // No turrets at all is fine.
+        params = GameParams::default();
+        params.turret_params.turrets_per_level = 0;
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_enemy_params_are_rejected() {
+        let mut params = GameParams::default();
+        params.enemy_params.chase_speed = -1.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.enemy_params.hit_points = 0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.enemy_params.acceleration = 0.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.enemy_params.ships_per_level = 0;
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn fuel_params_default_when_absent_and_are_validated() {
+        let params: GameParams = r#"
+            viewport_width = 100
+            viewport_height = 50
+            level_width = 100
+            level_height = 200
+            fps = 30.0
+            music_starts_on = true
+            render_ship = false
+
+            [ship_params]
+            acceleration = 10.0
+            rotation_rate = 1.0
+            max_speed = 20.0
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(params.ship_params.fuel_capacity, default_fuel_capacity());
+        assert_eq!(params.ship_params.fuel_burn_rate, default_fuel_burn_rate());
+
+        let mut params = GameParams::default();
+        params.ship_params.fuel_burn_rate = 0.0;
+        assert!(
+            params.validate().is_ok(),
+            "a zero burn rate makes thrust free"
+        );
+        params.ship_params.fuel_capacity = 0.0;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn hull_params_default_when_absent_and_are_validated() {
+        let params: GameParams = r#"
+            viewport_width = 100
+            viewport_height = 50
+            level_width = 100
+            level_height = 200
+            fps = 30.0
+            music_starts_on = true
+            render_ship = false
+
+            [ship_params]
+            acceleration = 10.0
+            rotation_rate = 1.0
+            max_speed = 20.0
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(params.ship_params.hull_points, default_hull_points());
+        assert_eq!(
+            params.ship_params.bounce_restitution,
+            default_bounce_restitution()
+        );
+
+        let mut params = GameParams::default();
+        params.ship_params.hull_points = 0.0;
+        assert!(
+            params.validate().is_ok(),
+            "no hull makes every impact fatal"
+        );
+
+        params = GameParams::default();
+        params.ship_params.bounce_restitution = 1.5;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.ship_params.hull_damage_per_speed = -1.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.ship_params.max_safe_impact_speed = f32::NAN;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn ship_hulls_are_named_as_in_ship_geometry() {
+        let ship_params = |hull: &str| {
+            toml::from_str::<ShipParams>(&format!(
+                "acceleration = 1.0\nrotation_rate = 1.0\nmax_speed = 1.0\nhull = {hull:?}"
+            ))
+        };
+        for (hull, shape) in ShipHull::ALL.into_iter().zip(&ship_geometry::HULLS) {
+            assert_eq!(ship_params(shape.name).unwrap().hull, hull);
+            assert_eq!(hull.shape(), shape);
+        }
+        assert!(ship_params("zeppelin").is_err());
+    }
+
+    #[test]
+    fn empty_roster_flies_the_configured_ship() {
+        let params = GameParams::default();
+        let ships = params.ships();
+        assert_eq!(ships.len(), 1);
+        assert_eq!(ships[0].name, "DART");
+        assert_eq!(
+            ships[0].ship_params.acceleration,
+            params.ship_params.acceleration
+        );
+        assert_eq!(ships[0].nozzle_params.life_scale, 1.0);
+    }
+
+    #[test]
+    fn invalid_roster_ships_are_rejected() {
+        let roster_ship = RosterShip {
+            name: "BRICK".to_string(),
+            ship_params: GameParams::default().ship_params,
+            nozzle_params: NozzleParams::default(),
+        };
+        let mut params = GameParams::default();
+        params.ship_roster = vec![roster_ship.clone()];
+        assert!(params.validate().is_ok());
+
+        params.ship_roster[0].name.clear();
+        assert!(params.validate().is_err());
+
+        params.ship_roster = vec![roster_ship.clone()];
+        params.ship_roster[0].ship_params.max_speed = 0.0;
+        assert!(params.validate().is_err());
+
+        params.ship_roster = vec![roster_ship];
+        params.ship_roster[0].nozzle_params.spread_scale = 0.0;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn invalid_pickup_params_are_rejected() {
+        let mut params = GameParams::default();
+        params.pickup_params.life_step = -0.5;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.pickup_params.time_bonus_seconds = 0.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.pickup_params.fuel_refill = 0.0;
+        assert!(params.validate().is_err());
+
+        // Range upgrades grow the particle buffer, so they count toward
+        // the particle limit.
+        params = GameParams::default();
+        params.particle_system_params.emission_rate = MAX_EXACT_PARTICLE_COUNT_F32 / 2.0;
+        params.particle_system_params.max_particle_life = 1.0;
+        assert!(params.validate().is_ok());
+        params.pickup_params.life_step = 1.0;
+        params.pickup_params.max_upgrades = 2;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn invalid_bomb_params_are_rejected() {
+        let mut params = GameParams::default();
+        params.bomb_params.crater_radius = 0.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.bomb_params.crater_damage = -1;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.bomb_params.burst_particles = 0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.bomb_params.ammo = 0;
+        assert!(params.validate().is_ok(), "no ammo just disables bombs");
+    }
+
+    #[test]
+    fn invalid_gun_params_are_rejected() {
+        let mut params = GameParams::default();
+        params.gun_params.particle_life = 0.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.gun_params.damage_scale = -1.0;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.gun_params.color_map = 99;
+        assert!(params.validate().is_err());
+
+        params = GameParams::default();
+        params.gun_params.emission_rate = 0.0;
+        assert!(
+            params.validate().is_ok(),
+            "no emission just disables the gun"
+        );
+    }
+
+    #[test]
+    fn negative_deposition_is_rejected() {
+        let mut params = GameParams::default();
+        params.particle_system_params.deposition_amount = -1;
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn seed_parses_from_level_params() {
+        let config = r#"
+            viewport_width = 100
+            viewport_height = 50
+            level_width = 100
+            level_height = 200
+            fps = 30.0
+            music_starts_on = true
+            render_ship = false
+
+            [level_params]
+            starting_terrain_health = 500
+            seed = 1234
+        "#;
+        let params: GameParams = config.parse().unwrap();
+        assert_eq!(params.level_params.seed, Some(1234));
+        assert_eq!(GameParams::default().level_params.seed, None);
+    }
+
+    #[test]
+    fn generator_bands_parse_and_select_by_level() {
+        let config = r#"
+            viewport_width = 100
+            viewport_height = 50
+            level_width = 100
+            level_height = 200
+            fps = 30.0
+            music_starts_on = true
+            render_ship = false
+
+            [level_params]
+            starting_terrain_health = 500
+
+            [[level_params.generators]]
+            first_level = 2
+            generator = "caves"
+
+            [[level_params.generators]]
+            first_level = 5
+            generator = "maze"
+        "#;
+        let params: GameParams = config.parse().unwrap();
+        let level_params = &params.level_params;
+        assert_eq!(
+            level_params.generator_for_level(0),
+            LevelGeneratorKind::Rectangles
+        );
+        assert_eq!(
+            level_params.generator_for_level(2),
+            LevelGeneratorKind::Caves
+        );
+        assert_eq!(
+            level_params.generator_for_level(4),
+            LevelGeneratorKind::Caves
+        );
+        assert_eq!(
+            level_params.generator_for_level(5),
+            LevelGeneratorKind::Maze
+        );
+        assert_eq!(
+            level_params.generator_for_level(500),
+            LevelGeneratorKind::Maze
+        );
+    }
+
+    #[test]
+    fn unsorted_generator_bands_are_rejected() {
+        let mut params = GameParams::default();
+        params.level_params.generators = vec![
+            GeneratorBand {
+                first_level: 3,
+                generator: LevelGeneratorKind::Noise,
+            },
+            GeneratorBand {
+                first_level: 3,
+                generator: LevelGeneratorKind::Caves,
+            },
+        ];
+        assert!(params.validate().is_err());
+        params.level_params.generators[1].first_level = 4;
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn authored_levels_repeat_and_first_placement_wins() {
+        let level_params = LevelParams {
+            authored_levels: vec![
+                AuthoredLevelPlacement {
+                    image: "intro.png".into(),
+                    level: 0,
+                    repeat_every: None,
+                },
+                AuthoredLevelPlacement {
+                    image: "boss.png".into(),
+                    level: 0,
+                    repeat_every: Some(5),
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(level_params.authored_level_for(0), Some("intro.png"));
+        assert_eq!(level_params.authored_level_for(1), None);
+        assert_eq!(level_params.authored_level_for(5), Some("boss.png"));
+        assert_eq!(level_params.authored_level_for(10), Some("boss.png"));
+        assert_eq!(level_params.authored_level_for(11), None);
+    }
+
+    #[test]
+    fn authored_levels_must_match_level_dimensions() {
+        let mut params = GameParams::default();
+        params.level_params.authored_levels = vec![AuthoredLevelPlacement {
+            image: "serpent.png".into(),
+            level: 4,
+            repeat_every: None,
+        }];
+        let err = params.validate().unwrap_err().to_string();
+        assert!(err.contains("serpent.png"), "{err}");
+        assert!(err.contains("but levels are 640x960"), "{err}");
+
+        params.level_params.authored_levels[0].image = "missing.png".into();
+        let err = params.validate().unwrap_err().to_string();
+        assert!(err.contains("unknown level image \"missing.png\""), "{err}");
+    }
 }
@@ -1,5 +1,6 @@
 use spout::background;
 use spout::bloom;
+use spout::entity_renderer;
 use spout::game_params;
 use spout::render;
 use spout::ship;
@@ -16,6 +17,7 @@ pub(crate) struct Graphics {
     pub(crate) renderer: render::Render,
     pub(crate) title_overlay: title_overlay::TitleOverlay,
     pub(crate) ship_renderer: ship::ShipRenderer,
+    pub(crate) entity_renderer: entity_renderer::EntityRenderer,
     pub(crate) background: background::BackgroundRenderer,
     /// Renders into the game view (240x135) — pixel-perfect with terrain/particles.
     pub(crate) game_text: text::TextRenderer,
@@ -74,6 +76,7 @@ impl Graphics {
             game_params.viewport_height,
         );
         let ship_renderer = ship::ShipRenderer::init(device);
+        let entity_renderer = entity_renderer::EntityRenderer::init(device);
         let background = background::BackgroundRenderer::init(device, queue);
         let game_text = text::TextRenderer::init(
             device,
@@ -123,6 +126,7 @@ impl Graphics {
             renderer,
             title_overlay,
             ship_renderer,
+            entity_renderer,
             background,
             game_text,
             ui,
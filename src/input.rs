@@ -1,6 +1,7 @@
-//! Input abstraction: keyboard and touch → unified `InputState`.
+//! Input abstraction: keyboard, touch and gamepad → unified `InputState`.
 //! Supports desktop/mobile native input through winit and mobile web touch through
-//! DOM listeners, with touch drag producing an absolute-angle heading.
+//! DOM listeners, with touch drag producing an absolute-angle heading. Gamepads
+//! are read through gilrs on native builds.
 
 use crate::game_params::TouchControlScheme;
 
@@ -289,6 +290,59 @@ mod tests {
         assert_eq!(state.rotate, 1.0);
     }
 
+    #[test]
+    fn keyboard_bomb_keys() {
+        let mut c = InputCollector::default();
+        assert!(!c.current_state().bomb);
+        c.held_bomb = true;
+        assert!(c.current_state().bomb);
+        c.held_bomb = false;
+        c.held_arrow_bomb = true;
+        assert!(c.current_state().bomb);
+    }
+
+    #[test]
+    fn fire_from_keyboard_or_gamepad() {
+        let mut c = InputCollector::default();
+        assert!(!c.current_state().fire);
+        c.held_fire = true;
+        assert!(c.current_state().fire);
+        c.held_fire = false;
+        c.gamepad.fire = true;
+        assert!(c.current_state().fire);
+    }
+
+    #[test]
+    fn gamepad_drives_flight_and_menus() {
+        let mut c = InputCollector::default();
+        c.gamepad = GamepadInput {
+            thrust: 0.5,
+            rotate: -1.0,
+            bomb: true,
+            menu_confirm: true,
+            ..Default::default()
+        };
+        let state = c.current_state();
+        assert_eq!(state.thrust, 0.5);
+        assert_eq!(state.rotate, -1.0);
+        assert!(state.bomb);
+        assert!(state.menu_confirm);
+
+        // Keyboard wins over the pad when both are held.
+        c.held_thrust = true;
+        c.held_left = true;
+        let state = c.current_state();
+        assert_eq!(state.thrust, 1.0);
+        assert_eq!(state.rotate, 1.0);
+    }
+
+    #[test]
+    fn gamepad_stick_has_a_deadzone() {
+        assert_eq!(GamepadInput::stick_rotate(0.1), 0.0);
+        assert_eq!(GamepadInput::stick_rotate(-0.6), 0.6);
+        assert_eq!(GamepadInput::stick_rotate(1.0), -1.0);
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     mod touch_tests {
         use super::*;
@@ -328,6 +382,19 @@ mod tests {
             assert!(second.pointer_released.is_none());
         }
 
+        #[test]
+        fn first_touch_is_held_pointer_until_it_ends() {
+            let mut c = touch_collector(200.0, 100.0);
+            c.touch.started(1, 150.0, 50.0);
+            c.touch.started(2, 10.0, 50.0);
+            c.touch.moved(1, 160.0, 40.0);
+            let held = c.current_state().pointer_held.expect("touch is down");
+            assert_eq!((held.x, held.y), (160.0, 40.0));
+
+            c.touch.ended(1, 160.0, 40.0);
+            assert!(c.current_state().pointer_held.is_none());
+        }
+
         #[test]
         fn touch_rotate_at_anchor_heading_none() {
             // Finger down, not moved → within deadzone → no target heading.
@@ -358,6 +425,37 @@ mod tests {
             assert!((h - (-FRAC_PI_2)).abs() < 1e-4, "got {h}");
         }
 
+        #[test]
+        fn touch_second_finger_in_thrust_zone_drops_bombs() {
+            let mut c = touch_collector(200.0, 100.0);
+            c.touch.started(1, 10.0, 50.0);
+            assert!(!c.current_state().bomb);
+            c.touch.started(2, 40.0, 50.0);
+            let state = c.current_state();
+            assert_eq!(state.thrust, 1.0);
+            assert!(state.bomb);
+            c.touch.ended(2, 40.0, 50.0);
+            let state = c.current_state();
+            assert_eq!(state.thrust, 1.0);
+            assert!(!state.bomb);
+        }
+
+        #[test]
+        fn touch_second_finger_in_rotate_zone_fires() {
+            let mut c = touch_collector(200.0, 100.0);
+            c.touch.started(1, 150.0, 50.0);
+            c.touch.moved(1, 150.0, 20.0);
+            assert!(!c.current_state().fire);
+            c.touch.started(2, 180.0, 80.0);
+            let state = c.current_state();
+            assert!(state.fire);
+            assert!(state.target_heading.is_some());
+            c.touch.ended(2, 180.0, 80.0);
+            let state = c.current_state();
+            assert!(!state.fire);
+            assert!(state.target_heading.is_some());
+        }
+
         #[test]
         fn touch_both_zones_independent() {
             // Thrust + target heading simultaneously.
@@ -524,6 +622,10 @@ pub struct PointerPress {
 pub struct InputState {
     pub thrust: f32, // [0.0, 1.0]
     pub rotate: f32, // [-1.0, 1.0]; positive = CCW/left, negative = CW/right (keyboard only)
+    /// Bomb button held; bombs drop at the cooldown rate while it is.
+    pub bomb: bool,
+    /// Fire button held; the forward gun streams particles while it is.
+    pub fire: bool,
     /// Absolute target heading in radians from touch input (standard math convention:
     /// 0=right, π/2=up). `None` when touch is not controlling rotation.
     /// When `Some`, the caller should use a bang-bang controller instead of `rotate`.
@@ -533,6 +635,8 @@ pub struct InputState {
     pub touch_started: bool,
     pub pointer_pressed: Option<PointerPress>,
     pub pointer_released: Option<PointerPress>,
+    /// Where the left mouse button or first touch is held down, if anywhere.
+    pub pointer_held: Option<PointerPress>,
     pub help: bool,
     pub audio_next_track: bool,
     pub audio_toggle: bool,
@@ -635,6 +739,10 @@ impl InputFrame {
         self.current.pointer_released
     }
 
+    pub fn pointer_held(&self) -> Option<PointerPress> {
+        self.current.pointer_held
+    }
+
     pub fn thrust_started(&self) -> bool {
         self.current.thrust > 0.0 && self.previous.thrust == 0.0
     }
@@ -647,8 +755,10 @@ impl InputFrame {
 // --- Touch layout -------------------------------------------------------
 //
 // Screen is split vertically at center (landscape orientation assumed):
-//   Left half  → thrust zone: any touch here fires the thruster.
-//   Right half → rotate zone (scheme-dependent, see TouchControlScheme):
+//   Left half  → thrust zone: any touch here fires the thruster. A second
+//                touch here while the first is held drops bombs.
+//   Right half → rotate zone (scheme-dependent, see TouchControlScheme). A
+//                second touch here while the first is held fires the gun:
 //     Drag:     drag from anchor sets an absolute target heading.
 //     Triangle: diagonal from (W/2,0)→(W,H) splits CW (upper-right) from
 //               CCW (lower-left); direction follows current touch position.
@@ -672,6 +782,8 @@ enum TouchRotation {
 #[derive(Debug, Copy, Clone, Default)]
 struct TouchInput {
     thrust: bool,
+    bomb: bool,
+    fire: bool,
     rotation: TouchRotation,
 }
 
@@ -680,7 +792,9 @@ struct TouchTracker {
     surface_width: f32,
     surface_height: f32,
     thrust_id: Option<TouchId>,
+    bomb_id: Option<TouchId>,
     rotate_id: Option<TouchId>,
+    fire_id: Option<TouchId>,
     rotate_anchor_x: f32,
     rotate_anchor_y: f32,
     rotate_x: f32,
@@ -691,6 +805,11 @@ struct TouchTracker {
     touch_ended: bool,
     touch_ended_x: f32,
     touch_ended_y: f32,
+    /// The first touch still down, tracked regardless of zone for pointer
+    /// input (`InputState::pointer_held`).
+    pointer_id: Option<TouchId>,
+    pointer_x: f32,
+    pointer_y: f32,
     /// Sticky "any touch event has occurred this session" flag — used to
     /// detect that the player is on a touch device so we can show the
     /// touch-zone hint. Never resets after the first touch.
@@ -711,6 +830,11 @@ impl TouchTracker {
         self.touch_started_x = x;
         self.touch_started_y = y;
         self.ever_touched = true;
+        if self.pointer_id.is_none() {
+            self.pointer_id = Some(id);
+            self.pointer_x = x;
+            self.pointer_y = y;
+        }
         if self.surface_width <= 0.0 || self.surface_height <= 0.0 {
             return;
         }
@@ -719,6 +843,8 @@ impl TouchTracker {
         if x < center {
             if self.thrust_id.is_none() {
                 self.thrust_id = Some(id);
+            } else if self.bomb_id.is_none() {
+                self.bomb_id = Some(id);
             }
         } else if self.rotate_id.is_none() {
             self.rotate_id = Some(id);
@@ -726,10 +852,16 @@ impl TouchTracker {
             self.rotate_anchor_y = y;
             self.rotate_x = x;
             self.rotate_y = y;
+        } else if self.fire_id.is_none() {
+            self.fire_id = Some(id);
         }
     }
 
     fn moved(&mut self, id: TouchId, x: f32, y: f32) {
+        if Some(id) == self.pointer_id {
+            self.pointer_x = x;
+            self.pointer_y = y;
+        }
         if Some(id) == self.rotate_id {
             self.rotate_x = x;
             self.rotate_y = y;
@@ -740,9 +872,18 @@ impl TouchTracker {
         self.touch_ended = true;
         self.touch_ended_x = x;
         self.touch_ended_y = y;
+        if Some(id) == self.pointer_id {
+            self.pointer_id = None;
+        }
         if Some(id) == self.thrust_id {
             self.thrust_id = None;
         }
+        if Some(id) == self.bomb_id {
+            self.bomb_id = None;
+        }
+        if Some(id) == self.fire_id {
+            self.fire_id = None;
+        }
         if Some(id) == self.rotate_id {
             self.rotate_id = None;
             self.rotate_anchor_x = 0.0;
@@ -778,6 +919,13 @@ impl TouchTracker {
         }
     }
 
+    fn pointer_held(&self) -> Option<PointerPress> {
+        self.pointer_id.map(|_| PointerPress {
+            x: self.pointer_x,
+            y: self.pointer_y,
+        })
+    }
+
     fn current_input(&self, scheme: TouchControlScheme) -> TouchInput {
         let thrust = self.thrust_id.is_some();
         let rotation = match scheme {
@@ -810,7 +958,103 @@ impl TouchTracker {
             }
         };
 
-        TouchInput { thrust, rotation }
+        TouchInput {
+            thrust,
+            bomb: self.bomb_id.is_some(),
+            fire: self.fire_id.is_some(),
+            rotation,
+        }
+    }
+}
+
+/// Stick deflection below this is ignored.
+const GAMEPAD_STICK_DEADZONE: f32 = 0.25;
+
+/// Gamepad controls held this frame, merged across connected pads.
+///
+/// Right trigger or A thrusts; the left stick or d-pad rotates; X, the right
+/// bumper or the left trigger drops bombs; Y or the left bumper fires the
+/// gun; Start pauses. The d-pad, A and B also drive menus.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct GamepadInput {
+    thrust: f32,
+    rotate: f32,
+    bomb: bool,
+    fire: bool,
+    pause: bool,
+    menu_up: bool,
+    menu_down: bool,
+    menu_left: bool,
+    menu_right: bool,
+    menu_confirm: bool,
+    menu_cancel: bool,
+}
+
+impl GamepadInput {
+    /// Rotation for a stick at `x` in [-1, 1]: stick left turns CCW, like
+    /// the left arrow key. Zero inside the deadzone.
+    fn stick_rotate(x: f32) -> f32 {
+        if x.abs() < GAMEPAD_STICK_DEADZONE {
+            0.0
+        } else {
+            -x.clamp(-1.0, 1.0)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(pad: &gilrs::Gamepad) -> Self {
+        use gilrs::{Axis, Button};
+        let pressed = |button| pad.is_pressed(button);
+        let trigger = pad
+            .button_data(Button::RightTrigger2)
+            .map_or(0.0, |data| data.value());
+        let dpad_rotate = match (pressed(Button::DPadLeft), pressed(Button::DPadRight)) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        let stick_rotate = Self::stick_rotate(pad.value(Axis::LeftStickX));
+        GamepadInput {
+            thrust: if pressed(Button::South) { 1.0 } else { trigger },
+            rotate: if dpad_rotate != 0.0 {
+                dpad_rotate
+            } else {
+                stick_rotate
+            },
+            bomb: pressed(Button::West)
+                || pressed(Button::RightTrigger)
+                || pressed(Button::LeftTrigger2),
+            fire: pressed(Button::North) || pressed(Button::LeftTrigger),
+            pause: pressed(Button::Start),
+            menu_up: pressed(Button::DPadUp),
+            menu_down: pressed(Button::DPadDown),
+            menu_left: pressed(Button::DPadLeft),
+            menu_right: pressed(Button::DPadRight),
+            menu_confirm: pressed(Button::South),
+            menu_cancel: pressed(Button::East),
+        }
+    }
+
+    /// Combine two pads: the stronger analog input wins and any held
+    /// button counts.
+    fn merge(self, other: Self) -> Self {
+        GamepadInput {
+            thrust: self.thrust.max(other.thrust),
+            rotate: if self.rotate != 0.0 {
+                self.rotate
+            } else {
+                other.rotate
+            },
+            bomb: self.bomb || other.bomb,
+            fire: self.fire || other.fire,
+            pause: self.pause || other.pause,
+            menu_up: self.menu_up || other.menu_up,
+            menu_down: self.menu_down || other.menu_down,
+            menu_left: self.menu_left || other.menu_left,
+            menu_right: self.menu_right || other.menu_right,
+            menu_confirm: self.menu_confirm || other.menu_confirm,
+            menu_cancel: self.menu_cancel || other.menu_cancel,
+        }
     }
 }
 
@@ -819,9 +1063,14 @@ impl TouchTracker {
 /// Keyboard is handled via `handle_winit_event` on all platforms.
 /// Touch is handled via `handle_winit_event` on native (winit relays
 /// `WindowEvent::Touch`) and via DOM listeners registered in `init_touch` on WASM.
+/// Gamepads are polled in `current_state` once `init_gamepads` has run (native
+/// only).
 pub struct InputCollector {
     // Keyboard held-key state
     held_thrust: bool,
+    held_bomb: bool,
+    held_arrow_bomb: bool,
+    held_fire: bool,
     held_left: bool,
     held_right: bool,
     held_arrow_thrust: bool,
@@ -849,6 +1098,7 @@ pub struct InputCollector {
     held_menu_cancel: bool,
     pointer_press: Option<PointerPress>,
     pointer_release: Option<PointerPress>,
+    held_pointer: bool,
     cursor_x: f32,
     cursor_y: f32,
 
@@ -857,6 +1107,11 @@ pub struct InputCollector {
     #[cfg(not(target_arch = "wasm32"))]
     touch: TouchTracker,
 
+    /// Gamepad state as of the last `current_state`.
+    gamepad: GamepadInput,
+    #[cfg(not(target_arch = "wasm32"))]
+    gilrs: Option<gilrs::Gilrs>,
+
     // WASM touch (shared with JS closures via Rc; closures are forgotten and kept
     // alive by the DOM for the lifetime of the page).
     #[cfg(target_arch = "wasm32")]
@@ -867,6 +1122,9 @@ impl Default for InputCollector {
     fn default() -> Self {
         InputCollector {
             held_thrust: false,
+            held_bomb: false,
+            held_arrow_bomb: false,
+            held_fire: false,
             held_left: false,
             held_right: false,
             held_arrow_thrust: false,
@@ -894,11 +1152,15 @@ impl Default for InputCollector {
             held_menu_cancel: false,
             pointer_press: None,
             pointer_release: None,
+            held_pointer: false,
             cursor_x: 0.0,
             cursor_y: 0.0,
             touch_scheme: TouchControlScheme::Drag,
             #[cfg(not(target_arch = "wasm32"))]
             touch: TouchTracker::default(),
+            gamepad: GamepadInput::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            gilrs: None,
             #[cfg(target_arch = "wasm32")]
             wasm_touch: std::rc::Rc::new(std::cell::RefCell::new(TouchTracker::default())),
         }
@@ -924,6 +1186,29 @@ impl InputCollector {
         self.touch_scheme = scheme;
     }
 
+    /// Start reading gamepads (native only). Without this, or if the platform
+    /// has no gamepad support, input comes from keyboard and touch alone.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn init_gamepads(&mut self) {
+        match gilrs::Gilrs::new() {
+            Ok(gilrs) => self.gilrs = Some(gilrs),
+            Err(err) => log::warn!("Gamepad input unavailable: {err}"),
+        }
+    }
+
+    /// Drain gamepad events and read every connected pad's controls.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_gamepads(&mut self) {
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+        while gilrs.next_event().is_some() {}
+        self.gamepad = gilrs
+            .gamepads()
+            .map(|(_, pad)| GamepadInput::read(&pad))
+            .fold(GamepadInput::default(), GamepadInput::merge);
+    }
+
     /// True once any touch event has been observed this session. Used by the
     /// renderer to gate touch-only HUD elements so they don't appear on
     /// keyboard-driven desktop or web sessions.
@@ -1043,6 +1328,7 @@ impl InputCollector {
             match key {
                 // Ship motion
                 KeyCode::KeyW => self.held_thrust = pressed,
+                KeyCode::KeyS => self.held_bomb = pressed,
                 KeyCode::KeyA => self.held_left = pressed,
                 KeyCode::KeyD => self.held_right = pressed,
                 KeyCode::KeyP => self.held_pause = pressed,
@@ -1069,7 +1355,10 @@ impl InputCollector {
                     self.held_arrow_thrust = pressed;
                     self.held_menu_up = pressed;
                 }
-                KeyCode::ArrowDown => self.held_menu_down = pressed,
+                KeyCode::ArrowDown => {
+                    self.held_arrow_bomb = pressed;
+                    self.held_menu_down = pressed;
+                }
                 KeyCode::ArrowLeft => {
                     self.held_arrow_left = pressed;
                     self.held_menu_left = pressed;
@@ -1078,7 +1367,11 @@ impl InputCollector {
                     self.held_arrow_right = pressed;
                     self.held_menu_right = pressed;
                 }
-                KeyCode::Enter | KeyCode::Space => self.held_menu_confirm = pressed,
+                KeyCode::Enter => self.held_menu_confirm = pressed,
+                KeyCode::Space => {
+                    self.held_fire = pressed;
+                    self.held_menu_confirm = pressed;
+                }
                 KeyCode::Escape => self.held_menu_cancel = pressed,
 
                 _ => {}
@@ -1094,6 +1387,7 @@ impl InputCollector {
                 if *state == winit::event::ElementState::Pressed
                     && *button == winit::event::MouseButton::Left =>
             {
+                self.held_pointer = true;
                 self.pointer_press = Some(PointerPress {
                     x: self.cursor_x,
                     y: self.cursor_y,
@@ -1103,6 +1397,7 @@ impl InputCollector {
                 if *state == winit::event::ElementState::Released
                     && *button == winit::event::MouseButton::Left =>
             {
+                self.held_pointer = false;
                 self.pointer_release = Some(PointerPress {
                     x: self.cursor_x,
                     y: self.cursor_y,
@@ -1131,6 +1426,9 @@ impl InputCollector {
     }
 
     pub fn current_state(&mut self) -> InputState {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.poll_gamepads();
+        let gamepad = self.gamepad;
         let restart = self.restart_requested;
         self.restart_requested = false;
         let help = self.help_requested;
@@ -1145,18 +1443,24 @@ impl InputCollector {
         let keyboard_thrust = if self.held_thrust || self.held_arrow_thrust {
             1.0
         } else {
-            0.0
+            gamepad.thrust
         };
         let keyboard_left = self.held_left || self.held_arrow_left;
         let keyboard_right = self.held_right || self.held_arrow_right;
         let keyboard_rotate = match (keyboard_left, keyboard_right) {
             (true, false) => 1.0,
             (false, true) => -1.0,
-            _ => 0.0,
+            (false, false) => gamepad.rotate,
+            (true, true) => 0.0,
         };
 
+        let mouse_held = self.held_pointer.then_some(PointerPress {
+            x: self.cursor_x,
+            y: self.cursor_y,
+        });
+
         #[cfg(not(target_arch = "wasm32"))]
-        let (touch_started, touch_input) = {
+        let (touch_started, touch_input, touch_held) = {
             let touch_press = self.touch.consume_touch_started();
             if pointer_pressed.is_none() {
                 pointer_pressed = touch_press;
@@ -1167,11 +1471,11 @@ impl InputCollector {
             }
             let touch_started = touch_press.is_some();
             let touch_input = self.touch.current_input(self.touch_scheme);
-            (touch_started, touch_input)
+            (touch_started, touch_input, self.touch.pointer_held())
         };
 
         #[cfg(target_arch = "wasm32")]
-        let (touch_started, touch_input) = {
+        let (touch_started, touch_input, touch_held) = {
             let mut touch = self.wasm_touch.borrow_mut();
             let touch_press = touch.consume_touch_started();
             if pointer_pressed.is_none() {
@@ -1183,7 +1487,7 @@ impl InputCollector {
             }
             let touch_started = touch_press.is_some();
             let touch_input = touch.current_input(self.touch_scheme);
-            (touch_started, touch_input)
+            (touch_started, touch_input, touch.pointer_held())
         };
 
         // Touch owns its axis entirely; keyboard fills the other.
@@ -1202,22 +1506,25 @@ impl InputCollector {
         InputState {
             thrust,
             rotate,
+            bomb: self.held_bomb || self.held_arrow_bomb || touch_input.bomb || gamepad.bomb,
+            fire: self.held_fire || touch_input.fire || gamepad.fire,
             target_heading,
             restart,
             touch_started,
             pointer_pressed,
             pointer_released,
+            pointer_held: mouse_held.or(touch_held),
             help,
             audio_next_track,
             audio_toggle,
-            pause: self.held_pause,
+            pause: self.held_pause || gamepad.pause,
             fullscreen: self.held_fullscreen,
-            menu_up: self.held_menu_up,
-            menu_down: self.held_menu_down,
-            menu_left: self.held_menu_left,
-            menu_right: self.held_menu_right,
-            menu_confirm: self.held_menu_confirm,
-            menu_cancel: self.held_menu_cancel,
+            menu_up: self.held_menu_up || gamepad.menu_up,
+            menu_down: self.held_menu_down || gamepad.menu_down,
+            menu_left: self.held_menu_left || gamepad.menu_left,
+            menu_right: self.held_menu_right || gamepad.menu_right,
+            menu_confirm: self.held_menu_confirm || gamepad.menu_confirm,
+            menu_cancel: self.held_menu_cancel || gamepad.menu_cancel,
             cam_in: self.held_cam_in,
             cam_out: self.held_cam_out,
             cam_up: self.held_cam_up,
@@ -0,0 +1,197 @@
+//! Editable copy of one level for the in-game editor: brush painting, plus
+//! import and export through the authored level image format (see
+//! `authored_level`).
+
+use crate::authored_level::{self, AuthoredLevel, AuthoredLevelError};
+use crate::level_gen::LevelGenerator;
+use crate::material::Material;
+
+/// Brush radii the editor cycles through, in cells.
+pub const BRUSH_RADII: [u32; 4] = [2, 4, 8, 16];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Brush {
+    Paint(Material),
+    Erase,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditorLevel {
+    width: u32,
+    height: u32,
+    starting_terrain_health: i32,
+    data: Vec<i32>,
+    // Material ID per cell, same layout as `data`.
+    materials: Vec<u32>,
+}
+
+impl EditorLevel {
+    /// An empty `width` x `height` level.
+    pub fn empty(width: u32, height: u32, starting_terrain_health: i32) -> Self {
+        let num_cells = (width * height) as usize;
+        EditorLevel {
+            width,
+            height,
+            starting_terrain_health,
+            data: vec![0; num_cells],
+            materials: vec![Material::Rock.id(); num_cells],
+        }
+    }
+
+    /// Import a level image, with the same rules and errors as authored
+    /// levels.
+    pub fn from_png(
+        bytes: &[u8],
+        width: u32,
+        height: u32,
+        starting_terrain_health: i32,
+    ) -> Result<Self, AuthoredLevelError> {
+        let (data, materials) = AuthoredLevel::decode(
+            "imported level",
+            bytes,
+            width,
+            height,
+            starting_terrain_health,
+        )?
+        .take_level();
+        Ok(EditorLevel {
+            width,
+            height,
+            starting_terrain_health,
+            data,
+            materials,
+        })
+    }
+
+    /// Export as a level image, ready to drop into `assets/levels/`.
+    pub fn to_png(&self) -> Vec<u8> {
+        authored_level::encode_png(&self.data, &self.materials, self.width, self.height)
+    }
+
+    /// A copy of the level as (health, material ID) per cell.
+    pub fn to_level(&self) -> (Vec<i32>, Vec<u32>) {
+        (self.data.clone(), self.materials.clone())
+    }
+
+    /// The level as (health, material ID) per cell, without copying.
+    pub fn cells(&self) -> (&[i32], &[u32]) {
+        (&self.data, &self.materials)
+    }
+
+    pub fn health_at(&self, x: u32, y: u32) -> i32 {
+        self.data[(y * self.width + x) as usize]
+    }
+
+    pub fn material_at(&self, x: u32, y: u32) -> u32 {
+        self.materials[(y * self.width + x) as usize]
+    }
+
+    /// Apply `brush` to the disc of `radius` cells around world point
+    /// `center`. Returns whether any cell changed.
+    pub fn stamp(&mut self, center: [f32; 2], radius: u32, brush: Brush) -> bool {
+        let (health, material) = match brush {
+            Brush::Paint(material) => (
+                material.starting_health(self.starting_terrain_health),
+                material,
+            ),
+            Brush::Erase => (0, Material::Rock),
+        };
+        let r = radius as f32;
+        let x_range = (center[0] - r).floor().max(0.0) as u32
+            ..((center[0] + r).ceil().max(0.0) as u32).min(self.width);
+        let y_range = (center[1] - r).floor().max(0.0) as u32
+            ..((center[1] + r).ceil().max(0.0) as u32).min(self.height);
+        let mut changed = false;
+        for y in y_range {
+            for x in x_range.clone() {
+                let (dx, dy) = (x as f32 + 0.5 - center[0], y as f32 + 0.5 - center[1]);
+                if dx * dx + dy * dy > r * r {
+                    continue;
+                }
+                let index = (y * self.width + x) as usize;
+                if self.data[index] != health || self.materials[index] != material.id() {
+                    self.data[index] = health;
+                    self.materials[index] = material.id();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Stamp along the segment `from` → `to`, closely enough that fast drags
+    /// leave a continuous line. Returns whether any cell changed.
+    pub fn stroke(&mut self, from: [f32; 2], to: [f32; 2], radius: u32, brush: Brush) -> bool {
+        let length = (to[0] - from[0]).hypot(to[1] - from[1]);
+        let spacing = (radius as f32 / 2.0).max(0.5);
+        let steps = (length / spacing).ceil().max(1.0) as u32;
+        let mut changed = false;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let point = [
+                from[0] + (to[0] - from[0]) * t,
+                from[1] + (to[1] - from[1]) * t,
+            ];
+            changed |= self.stamp(point, radius, brush);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEALTH: i32 = 500;
+
+    #[test]
+    fn stamp_paints_a_disc_and_erase_clears_it() {
+        let mut level = EditorLevel::empty(20, 20, HEALTH);
+        assert!(level.stamp([10.0, 10.0], 3, Brush::Paint(Material::Dirt)));
+        assert_eq!(
+            level.health_at(10, 10),
+            Material::Dirt.starting_health(HEALTH)
+        );
+        assert_eq!(level.material_at(10, 10), Material::Dirt.id());
+        assert_eq!(level.health_at(12, 10), level.health_at(10, 10));
+        // Corners of the bounding box stay outside the disc.
+        assert_eq!(level.health_at(7, 7), 0);
+        assert_eq!(level.health_at(14, 10), 0);
+        // Repainting the same disc changes nothing.
+        assert!(!level.stamp([10.0, 10.0], 3, Brush::Paint(Material::Dirt)));
+
+        assert!(level.stamp([10.0, 10.0], 3, Brush::Erase));
+        assert!(level.to_level().0.iter().all(|&h| h == 0));
+    }
+
+    #[test]
+    fn stamps_clip_to_the_level() {
+        let mut level = EditorLevel::empty(8, 8, HEALTH);
+        assert!(level.stamp([0.0, 7.5], 4, Brush::Paint(Material::Rock)));
+        assert_eq!(level.health_at(0, 7), HEALTH);
+        assert!(!level.stamp([-20.0, -20.0], 4, Brush::Paint(Material::Rock)));
+    }
+
+    #[test]
+    fn strokes_leave_no_gaps() {
+        let mut level = EditorLevel::empty(64, 8, HEALTH);
+        level.stroke([2.0, 4.0], [60.0, 4.0], 1, Brush::Paint(Material::Metal));
+        for x in 2..60 {
+            assert!(level.health_at(x, 4) > 0, "gap at x={x}");
+        }
+    }
+
+    #[test]
+    fn exported_levels_import_unchanged() {
+        let mut level = EditorLevel::empty(16, 12, HEALTH);
+        level.stamp([4.0, 3.0], 3, Brush::Paint(Material::Crystal));
+        level.stamp([11.0, 9.0], 2, Brush::Paint(Material::Metal));
+        let imported = EditorLevel::from_png(&level.to_png(), 16, 12, HEALTH).unwrap();
+        assert_eq!(imported.to_level(), level.to_level());
+
+        let err = EditorLevel::from_png(&level.to_png(), 12, 16, HEALTH)
+            .err()
+            .unwrap();
+        assert!(matches!(err, AuthoredLevelError::Dimensions { .. }));
+    }
+}
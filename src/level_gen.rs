@@ -0,0 +1,778 @@
+//! Terrain generators for play levels. Every generator starts from solid rock,
+//! paints veins of other materials, then carves open space in its own style.
+//! Work is split into small `step`s so `LevelMaker` can spread generation over
+//! frames with `work_until(deadline)`.
+
+use crate::game_params::LevelGeneratorKind;
+use crate::material::Material;
+use web_time::Instant;
+
+/// Incremental level generator. `step` does a small, bounded amount of work;
+/// once `done`, `take_level` hands over the finished terrain.
+pub trait LevelGenerator {
+    fn done(&self) -> bool;
+
+    fn step(&mut self);
+
+    /// The finished level as (health, material ID) per cell, row-major from
+    /// the bottom row. Leaves the generator empty.
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>);
+
+    fn finish(&mut self) {
+        while !self.done() {
+            self.step();
+        }
+    }
+
+    /// Step until done or past `deadline`. Returns whether the level is done.
+    fn work_until(&mut self, deadline: Instant) -> bool {
+        while !self.done() && Instant::now() < deadline {
+            self.step();
+        }
+        self.done()
+    }
+}
+
+/// Create the generator of `kind` for one level.
+pub fn make_generator(
+    kind: LevelGeneratorKind,
+    level_index: u32,
+    level_width: u32,
+    level_height: u32,
+    starting_terrain_health: i32,
+    seed: u64,
+) -> Box<dyn LevelGenerator> {
+    let canvas = Canvas::new(
+        level_index,
+        level_width,
+        level_height,
+        starting_terrain_health,
+        seed,
+    );
+    match kind {
+        LevelGeneratorKind::Rectangles => Box::new(WIPRectangleLevel::init(level_index, canvas)),
+        LevelGeneratorKind::Caves => Box::new(WIPCaveLevel::init(level_index, canvas)),
+        LevelGeneratorKind::Noise => Box::new(WIPNoiseLevel::init(level_index, canvas)),
+        LevelGeneratorKind::Maze => Box::new(WIPMazeLevel::init(level_index, canvas)),
+    }
+}
+
+// Rows of cells (or of coarse cave cells) processed per step by the
+// row-at-a-time generators.
+const ROWS_PER_STEP: u32 = 4;
+
+// Difficulty curve shared by all generators: 0 at the first level, rising
+// towards 1 as levels increase.
+fn difficulty(level_index: u32) -> f32 {
+    1.0 - 1.0 / (level_index as f32 + 1.0)
+}
+
+// Largest rectangle side for a level: starts large (half the level width),
+// shrinks as levels increase. Sizes both vacancies and veins.
+fn max_rect_dimension(level_index: u32, level_width: u32, level_height: u32) -> u32 {
+    let level_num = level_index + 1;
+    let max_dimension = std::cmp::max(level_width / (level_num + 1) / 2, 2);
+    std::cmp::min(max_dimension, std::cmp::min(level_width, level_height))
+}
+
+/// A level under construction, plus the RNG that generates it.
+struct Canvas {
+    width: u32,
+    height: u32,
+    data: Vec<i32>,
+    // Material ID per cell, same layout as `data`.
+    materials: Vec<u32>,
+    starting_terrain_health: i32,
+    // Metal can wall off the whole width, so it stays out of the launch level.
+    allow_metal: bool,
+    rng: fastrand::Rng,
+}
+
+impl Canvas {
+    // Solid rock.
+    fn new(
+        level_index: u32,
+        width: u32,
+        height: u32,
+        starting_terrain_health: i32,
+        seed: u64,
+    ) -> Self {
+        let num_cells = (width * height) as usize;
+        Canvas {
+            width,
+            height,
+            data: vec![starting_terrain_health; num_cells],
+            materials: vec![Material::Rock.id(); num_cells],
+            starting_terrain_health,
+            allow_metal: level_index > 0,
+            rng: fastrand::Rng::with_seed(seed),
+        }
+    }
+
+    fn paint_vein(&mut self, max_dimension: u32) {
+        let material = match self.rng.u32(0..10) {
+            0..=4 => Material::Dirt,
+            5..=7 => Material::Crystal,
+            _ if self.allow_metal => Material::Metal,
+            _ => Material::Dirt,
+        };
+        // Keep metal small enough to route around.
+        let max_dimension = match material {
+            Material::Metal => (max_dimension / 3).max(2),
+            _ => max_dimension,
+        };
+        let width = self.rng.u32(0..max_dimension);
+        let height = self.rng.u32(0..max_dimension);
+        let health = material.starting_health(self.starting_terrain_health);
+        self.fill_random_rect(width, height, health, Some(material));
+    }
+
+    // Fill a randomly placed `width` x `height` rectangle with `health`, and
+    // with `material` if given.
+    fn fill_random_rect(
+        &mut self,
+        width: u32,
+        height: u32,
+        health: i32,
+        material: Option<Material>,
+    ) {
+        let left = self.rng.u32(0..(self.width - width));
+        let bot = self.rng.u32(0..(self.height - height));
+        self.fill_rect(left, bot, width, height, health, material);
+    }
+
+    fn fill_rect(
+        &mut self,
+        left: u32,
+        bot: u32,
+        width: u32,
+        height: u32,
+        health: i32,
+        material: Option<Material>,
+    ) {
+        let (left, width) = (left as usize, width as usize);
+        for y in bot as usize..(bot + height) as usize {
+            // Inner loop over rows...
+            let offset = y * (self.width as usize) + left;
+            self.data[offset..offset + width].fill(health);
+            if let Some(material) = material {
+                self.materials[offset..offset + width].fill(material.id());
+            }
+        }
+    }
+
+    // Clear a rectangle, keeping its materials.
+    fn carve_rect(&mut self, left: u32, bot: u32, width: u32, height: u32) {
+        self.fill_rect(left, bot, width, height, 0, None);
+    }
+
+    fn take(&mut self) -> (Vec<i32>, Vec<u32>) {
+        (
+            std::mem::take(&mut self.data),
+            std::mem::take(&mut self.materials),
+        )
+    }
+}
+
+/// Veins of other materials, painted into the rock before any carving so
+/// the open space cuts through them too.
+struct Veins {
+    num_veins: u32,
+    completed_veins: u32,
+    max_dimension: u32,
+}
+
+impl Veins {
+    fn new(level_index: u32, canvas: &Canvas) -> Self {
+        Veins {
+            num_veins: canvas.height / 16,
+            completed_veins: 0,
+            max_dimension: max_rect_dimension(level_index, canvas.width, canvas.height),
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.completed_veins >= self.num_veins
+    }
+
+    fn step(&mut self, canvas: &mut Canvas) {
+        canvas.paint_vein(self.max_dimension);
+        self.completed_veins += 1;
+    }
+}
+
+/// Solid rock with randomly placed rectangular vacancies.
+pub struct WIPRectangleLevel {
+    canvas: Canvas,
+    veins: Veins,
+    max_dimension: u32,
+    num_vacancies: u32,
+    completed_vacancies: u32,
+}
+
+impl WIPRectangleLevel {
+    fn init(level_index: u32, canvas: Canvas) -> Self {
+        // Difficulty curve: early levels have large, numerous vacancies (sparse
+        // terrain). Later levels have smaller, fewer vacancies (dense terrain).
+        // level_num ramps from 1 upward.
+        let level_num = level_index + 1;
+
+        // Number of vacancies: many at the start, fewer later.
+        let num_vacancies =
+            (canvas.height as f64 * (2.0 + 10.0 / (level_num as f64 + 1.0))).ceil() as u32;
+
+        WIPRectangleLevel {
+            veins: Veins::new(level_index, &canvas),
+            max_dimension: max_rect_dimension(level_index, canvas.width, canvas.height),
+            num_vacancies,
+            completed_vacancies: 0,
+            canvas,
+        }
+    }
+}
+
+impl LevelGenerator for WIPRectangleLevel {
+    fn done(&self) -> bool {
+        self.veins.done() && self.completed_vacancies >= self.num_vacancies
+    }
+
+    fn step(&mut self) {
+        if !self.veins.done() {
+            self.veins.step(&mut self.canvas);
+        } else {
+            let width = self.canvas.rng.u32(0..self.max_dimension);
+            let height = self.canvas.rng.u32(0..self.max_dimension);
+            self.canvas.fill_random_rect(width, height, 0, None);
+            self.completed_vacancies += 1;
+        }
+    }
+
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>) {
+        self.canvas.take()
+    }
+}
+
+// Side of one cave automaton cell, in level cells. Running the automaton on a
+// coarse grid makes caves big enough to fly through.
+const CAVE_CELL_SIZE: u32 = 4;
+const CAVE_SMOOTHING_PASSES: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CavePhase {
+    Veins,
+    Smooth { pass: u32, row: u32 },
+    Carve { row: u32 },
+    Done,
+}
+
+/// Cellular-automaton caves: random coarse cells are smoothed with the
+/// classic 4-5 rule, then the open cells are carved out of the rock.
+pub struct WIPCaveLevel {
+    canvas: Canvas,
+    veins: Veins,
+    grid_width: u32,
+    grid_height: u32,
+    // Coarse cells, `true` = open. `next` is the smoothing pass being built.
+    open: Vec<bool>,
+    next: Vec<bool>,
+    phase: CavePhase,
+}
+
+impl WIPCaveLevel {
+    fn init(level_index: u32, mut canvas: Canvas) -> Self {
+        let grid_width = canvas.width.div_ceil(CAVE_CELL_SIZE);
+        let grid_height = canvas.height.div_ceil(CAVE_CELL_SIZE);
+        // Later levels start with more rock, so caves get tighter.
+        let solid_fraction = 0.40 + 0.08 * difficulty(level_index);
+        let open: Vec<bool> = (0..grid_width * grid_height)
+            .map(|_| canvas.rng.f32() >= solid_fraction)
+            .collect();
+        WIPCaveLevel {
+            veins: Veins::new(level_index, &canvas),
+            grid_width,
+            grid_height,
+            next: open.clone(),
+            open,
+            phase: CavePhase::Veins,
+            canvas,
+        }
+    }
+
+    // Solid neighbours of a coarse cell, out of 8. The side walls count as
+    // rock; above and below count as open so levels aren't sealed off.
+    fn solid_neighbours(&self, x: u32, y: u32) -> u32 {
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                let outside_sides = nx < 0 || nx >= self.grid_width as i32;
+                let inside_rows = ny >= 0 && ny < self.grid_height as i32;
+                if outside_sides
+                    || (inside_rows
+                        && !self.open[(ny as u32 * self.grid_width + nx as u32) as usize])
+                {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn smooth_row(&mut self, y: u32) {
+        for x in 0..self.grid_width {
+            let index = (y * self.grid_width + x) as usize;
+            self.next[index] = match self.solid_neighbours(x, y) {
+                0..=3 => true,
+                4 => self.open[index],
+                _ => false,
+            };
+        }
+    }
+
+    fn carve_row(&mut self, y: u32) {
+        let grid_row = (y / CAVE_CELL_SIZE * self.grid_width) as usize;
+        for x in 0..self.canvas.width {
+            if self.open[grid_row + (x / CAVE_CELL_SIZE) as usize] {
+                self.canvas.data[(y * self.canvas.width + x) as usize] = 0;
+            }
+        }
+    }
+}
+
+impl LevelGenerator for WIPCaveLevel {
+    fn done(&self) -> bool {
+        self.phase == CavePhase::Done
+    }
+
+    fn step(&mut self) {
+        self.phase = match self.phase {
+            CavePhase::Veins => {
+                if !self.veins.done() {
+                    self.veins.step(&mut self.canvas);
+                    CavePhase::Veins
+                } else {
+                    CavePhase::Smooth { pass: 0, row: 0 }
+                }
+            }
+            CavePhase::Smooth { pass, row } => {
+                let end = (row + ROWS_PER_STEP).min(self.grid_height);
+                for y in row..end {
+                    self.smooth_row(y);
+                }
+                if end < self.grid_height {
+                    CavePhase::Smooth { pass, row: end }
+                } else {
+                    std::mem::swap(&mut self.open, &mut self.next);
+                    if pass + 1 < CAVE_SMOOTHING_PASSES {
+                        CavePhase::Smooth {
+                            pass: pass + 1,
+                            row: 0,
+                        }
+                    } else {
+                        CavePhase::Carve { row: 0 }
+                    }
+                }
+            }
+            CavePhase::Carve { row } => {
+                let end = (row + ROWS_PER_STEP).min(self.canvas.height);
+                for y in row..end {
+                    self.carve_row(y);
+                }
+                if end < self.canvas.height {
+                    CavePhase::Carve { row: end }
+                } else {
+                    CavePhase::Done
+                }
+            }
+            CavePhase::Done => CavePhase::Done,
+        };
+    }
+
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>) {
+        self.canvas.take()
+    }
+}
+
+/// 2D gradient (Perlin) noise over a seeded permutation table.
+struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    fn new(rng: &mut fastrand::Rng) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        rng.shuffle(&mut table);
+        let mut permutation = [0; 512];
+        for (i, p) in permutation.iter_mut().enumerate() {
+            *p = table[i % 256];
+        }
+        Perlin { permutation }
+    }
+
+    fn hash(&self, x: i32, y: i32) -> u8 {
+        let px = self.permutation[(x & 255) as usize] as usize;
+        self.permutation[px + (y & 255) as usize]
+    }
+
+    // Dot product of the lattice gradient picked by `hash` with (x, y).
+    fn gradient(hash: u8, x: f32, y: f32) -> f32 {
+        match hash & 7 {
+            0 => x + y,
+            1 => x - y,
+            2 => -x + y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    fn noise(&self, x: f32, y: f32) -> f32 {
+        fn fade(t: f32) -> f32 {
+            t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+        }
+        fn lerp(a: f32, b: f32, t: f32) -> f32 {
+            a + (b - a) * t
+        }
+        let (x0, y0) = (x.floor(), y.floor());
+        let (xi, yi) = (x0 as i32, y0 as i32);
+        let (fx, fy) = (x - x0, y - y0);
+        let (u, v) = (fade(fx), fade(fy));
+        let bottom = lerp(
+            Self::gradient(self.hash(xi, yi), fx, fy),
+            Self::gradient(self.hash(xi + 1, yi), fx - 1.0, fy),
+            u,
+        );
+        let top = lerp(
+            Self::gradient(self.hash(xi, yi + 1), fx, fy - 1.0),
+            Self::gradient(self.hash(xi + 1, yi + 1), fx - 1.0, fy - 1.0),
+            u,
+        );
+        lerp(bottom, top, v)
+    }
+
+    // Fractal sum of `octaves` octaves, normalized by total amplitude.
+    fn fbm(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves {
+            sum += amplitude * self.noise(x * frequency, y * frequency);
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / total
+    }
+}
+
+// Wavelength of the base noise octave, in cells.
+const NOISE_FEATURE_SIZE: f32 = 64.0;
+const NOISE_OCTAVES: u32 = 4;
+
+/// Caverns wherever fractal gradient noise rises above a threshold.
+pub struct WIPNoiseLevel {
+    canvas: Canvas,
+    veins: Veins,
+    noise: Perlin,
+    // Cells with noise above this are carved out.
+    threshold: f32,
+    // Next row to carve.
+    row: u32,
+}
+
+impl WIPNoiseLevel {
+    fn init(level_index: u32, mut canvas: Canvas) -> Self {
+        let noise = Perlin::new(&mut canvas.rng);
+        WIPNoiseLevel {
+            veins: Veins::new(level_index, &canvas),
+            noise,
+            // Raising the threshold leaves less open space in later levels.
+            threshold: -0.05 + 0.15 * difficulty(level_index),
+            row: 0,
+            canvas,
+        }
+    }
+}
+
+impl LevelGenerator for WIPNoiseLevel {
+    fn done(&self) -> bool {
+        self.veins.done() && self.row >= self.canvas.height
+    }
+
+    fn step(&mut self) {
+        if !self.veins.done() {
+            self.veins.step(&mut self.canvas);
+            return;
+        }
+        let end = (self.row + ROWS_PER_STEP).min(self.canvas.height);
+        for y in self.row..end {
+            for x in 0..self.canvas.width {
+                let value = self.noise.fbm(
+                    x as f32 / NOISE_FEATURE_SIZE,
+                    y as f32 / NOISE_FEATURE_SIZE,
+                    NOISE_OCTAVES,
+                );
+                if value > self.threshold {
+                    self.canvas.data[(y * self.canvas.width + x) as usize] = 0;
+                }
+            }
+        }
+        self.row = end;
+    }
+
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>) {
+        self.canvas.take()
+    }
+}
+
+// Rock left between neighbouring maze corridors.
+const MAZE_WALL: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MazePhase {
+    Veins,
+    Carve,
+    Done,
+}
+
+/// Maze corridors carved by a randomized depth-first search over a grid of
+/// corridor junctions, one move per step. A corridor runs out of the bottom
+/// and top edges so the maze joins the levels around it.
+pub struct WIPMazeLevel {
+    canvas: Canvas,
+    veins: Veins,
+    corridor: u32,
+    // Junction grid and the cell position of junction (0, 0).
+    nodes_x: u32,
+    nodes_y: u32,
+    origin: [u32; 2],
+    visited: Vec<bool>,
+    stack: Vec<[u32; 2]>,
+    phase: MazePhase,
+}
+
+impl WIPMazeLevel {
+    fn init(level_index: u32, canvas: Canvas) -> Self {
+        // Corridors narrow in later levels, down to roughly ship width.
+        let corridor = (24.0 - 12.0 * difficulty(level_index)).round() as u32;
+        let corridor = corridor.min(canvas.width.min(canvas.height));
+        let pitch = corridor + MAZE_WALL;
+        let nodes_x = (canvas.width.saturating_sub(corridor) / pitch + 1).max(1);
+        let nodes_y = (canvas.height.saturating_sub(corridor) / pitch + 1).max(1);
+        // Center the junction grid.
+        let used_width = (nodes_x - 1) * pitch + corridor;
+        let used_height = (nodes_y - 1) * pitch + corridor;
+        WIPMazeLevel {
+            veins: Veins::new(level_index, &canvas),
+            corridor,
+            nodes_x,
+            nodes_y,
+            origin: [
+                (canvas.width - used_width) / 2,
+                (canvas.height - used_height) / 2,
+            ],
+            visited: vec![false; (nodes_x * nodes_y) as usize],
+            stack: vec![],
+            phase: MazePhase::Veins,
+            canvas,
+        }
+    }
+
+    fn node_corner(&self, node: [u32; 2]) -> [u32; 2] {
+        let pitch = self.corridor + MAZE_WALL;
+        [
+            self.origin[0] + node[0] * pitch,
+            self.origin[1] + node[1] * pitch,
+        ]
+    }
+
+    fn visit(&mut self, node: [u32; 2]) {
+        self.visited[(node[1] * self.nodes_x + node[0]) as usize] = true;
+        self.stack.push(node);
+    }
+
+    // Carve the corridor spanning junctions `a` and `b` (which may be equal).
+    fn carve_between(&mut self, a: [u32; 2], b: [u32; 2]) {
+        let (a, b) = (self.node_corner(a), self.node_corner(b));
+        let (left, bot) = (a[0].min(b[0]), a[1].min(b[1]));
+        let width = a[0].max(b[0]) - left + self.corridor;
+        let height = a[1].max(b[1]) - bot + self.corridor;
+        self.canvas.carve_rect(left, bot, width, height);
+    }
+
+    // Run a corridor from a random junction on the bottom row down to the
+    // level's bottom edge, and likewise at the top.
+    fn carve_exits(&mut self) {
+        let bottom = [self.canvas.rng.u32(0..self.nodes_x), 0];
+        let [left, bot] = self.node_corner(bottom);
+        self.canvas
+            .carve_rect(left, 0, self.corridor, bot + self.corridor);
+        let top = [self.canvas.rng.u32(0..self.nodes_x), self.nodes_y - 1];
+        let [left, bot] = self.node_corner(top);
+        self.canvas
+            .carve_rect(left, bot, self.corridor, self.canvas.height - bot);
+    }
+
+    fn unvisited_neighbours(&self, node: [u32; 2]) -> Vec<[u32; 2]> {
+        let [x, y] = node;
+        let mut neighbours = Vec::with_capacity(4);
+        if x > 0 {
+            neighbours.push([x - 1, y]);
+        }
+        if x + 1 < self.nodes_x {
+            neighbours.push([x + 1, y]);
+        }
+        if y > 0 {
+            neighbours.push([x, y - 1]);
+        }
+        if y + 1 < self.nodes_y {
+            neighbours.push([x, y + 1]);
+        }
+        neighbours.retain(|n| !self.visited[(n[1] * self.nodes_x + n[0]) as usize]);
+        neighbours
+    }
+}
+
+impl LevelGenerator for WIPMazeLevel {
+    fn done(&self) -> bool {
+        self.phase == MazePhase::Done
+    }
+
+    fn step(&mut self) {
+        match self.phase {
+            MazePhase::Veins => {
+                if !self.veins.done() {
+                    self.veins.step(&mut self.canvas);
+                } else {
+                    let start = [
+                        self.canvas.rng.u32(0..self.nodes_x),
+                        self.canvas.rng.u32(0..self.nodes_y),
+                    ];
+                    self.carve_between(start, start);
+                    self.visit(start);
+                    self.phase = MazePhase::Carve;
+                }
+            }
+            MazePhase::Carve => match self.stack.last().copied() {
+                Some(node) => {
+                    let neighbours = self.unvisited_neighbours(node);
+                    if neighbours.is_empty() {
+                        self.stack.pop();
+                    } else {
+                        let next = neighbours[self.canvas.rng.usize(0..neighbours.len())];
+                        self.carve_between(node, next);
+                        self.visit(next);
+                    }
+                }
+                None => {
+                    self.carve_exits();
+                    self.phase = MazePhase::Done;
+                }
+            },
+            MazePhase::Done => {}
+        }
+    }
+
+    fn take_level(&mut self) -> (Vec<i32>, Vec<u32>) {
+        self.canvas.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: u32 = 96;
+    const H: u32 = 128;
+    const HEALTH: i32 = 500;
+
+    const ALL_KINDS: [LevelGeneratorKind; 4] = [
+        LevelGeneratorKind::Rectangles,
+        LevelGeneratorKind::Caves,
+        LevelGeneratorKind::Noise,
+        LevelGeneratorKind::Maze,
+    ];
+
+    fn generate(kind: LevelGeneratorKind, level_index: u32, seed: u64) -> (Vec<i32>, Vec<u32>) {
+        let mut generator = make_generator(kind, level_index, W, H, HEALTH, seed);
+        generator.finish();
+        generator.take_level()
+    }
+
+    fn open_fraction(level: &[i32]) -> f32 {
+        level.iter().filter(|&&h| h == 0).count() as f32 / level.len() as f32
+    }
+
+    #[test]
+    fn every_generator_is_deterministic_per_seed() {
+        for kind in ALL_KINDS {
+            assert_eq!(generate(kind, 2, 7), generate(kind, 2, 7), "{kind:?}");
+            assert_ne!(generate(kind, 2, 7), generate(kind, 2, 8), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn every_generator_leaves_open_space_and_rock() {
+        for kind in ALL_KINDS {
+            for level_index in [0, 1, 5, 20] {
+                let (level, materials) = generate(kind, level_index, 3);
+                assert_eq!(level.len(), (W * H) as usize);
+                assert_eq!(materials.len(), level.len());
+                let open = open_fraction(&level);
+                assert!(
+                    open > 0.0 && open < 1.0,
+                    "{kind:?} level {level_index} is {open} open"
+                );
+                for (&health, &id) in level.iter().zip(&materials) {
+                    let material = Material::from_id(id).expect("valid material ID");
+                    assert!(health == 0 || health == material.starting_health(HEALTH));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn later_levels_are_denser() {
+        for kind in [LevelGeneratorKind::Caves, LevelGeneratorKind::Noise] {
+            let early: f32 = (0..8)
+                .map(|seed| open_fraction(&generate(kind, 0, seed).0))
+                .sum();
+            let late: f32 = (0..8)
+                .map(|seed| open_fraction(&generate(kind, 30, seed).0))
+                .sum();
+            assert!(late < early, "{kind:?}: {late} vs {early}");
+        }
+    }
+
+    #[test]
+    fn work_until_matches_finish() {
+        for kind in ALL_KINDS {
+            let mut generator = make_generator(kind, 4, W, H, HEALTH, 11);
+            while !generator.work_until(Instant::now() + std::time::Duration::from_micros(50)) {}
+            assert_eq!(generator.take_level(), generate(kind, 4, 11), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn maze_opens_onto_the_bottom_and_top_edges() {
+        let (level, _) = generate(LevelGeneratorKind::Maze, 3, 5);
+        let row = |y: u32| &level[(y * W) as usize..((y + 1) * W) as usize];
+        assert!(row(0).contains(&0));
+        assert!(row(H - 1).contains(&0));
+    }
+
+    #[test]
+    fn perlin_noise_is_zero_on_lattice_points_and_bounded() {
+        let noise = Perlin::new(&mut fastrand::Rng::with_seed(1));
+        for i in -3..3 {
+            assert_eq!(noise.noise(i as f32, 2.0), 0.0);
+        }
+        for i in 0..1000 {
+            let value = noise.fbm(i as f32 * 0.137, i as f32 * 0.071, NOISE_OCTAVES);
+            assert!((-1.5..=1.5).contains(&value));
+        }
+    }
+}
@@ -2,81 +2,27 @@
 //! Manages a scrolling window of terrain tiles that are generated on a background
 //! budget and uploaded to the GPU as the viewport advances.
 
+use crate::authored_level::AuthoredLevel;
 use crate::buffer_util::{self, SizedBuffer};
+use crate::game_params::{EnemyParams, LevelParams, PickupParams, TurretParams};
+use crate::level_gen::{self, LevelGenerator};
+use crate::material::Material;
+use crate::terrain_query::{
+    Region, TerrainQueries, TerrainQueryError, TerrainQueryId, TerrainSnapshot,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use web_time::Instant;
 
-pub struct WIPRectangleLevel {
-    width: u32,
-    height: u32,
-    data: Vec<i32>,
-    max_dimension: u32,
-    // Creation state:
-    rng: fastrand::Rng,
-    num_vacancies: u32,
-    completed_vacancies: u32,
-}
-
-impl WIPRectangleLevel {
-    fn init(
-        level_index: u32,
-        level_width: u32,
-        level_height: u32,
-        starting_terrain_health: i32,
-    ) -> Self {
-        // Difficulty curve: early levels have large, numerous vacancies (sparse
-        // terrain). Later levels have smaller, fewer vacancies (dense terrain).
-        // level_num ramps from 1 upward.
-        let level_num = level_index + 1;
-
-        // Vacancy size: starts large (half the level width), shrinks as levels increase.
-        let max_dimension = std::cmp::max(level_width / (level_num + 1) / 2, 2);
-        let max_dimension = std::cmp::min(max_dimension, std::cmp::min(level_width, level_height));
-
-        // Number of vacancies: many at the start, fewer later.
-        let num_vacancies =
-            (level_height as f64 * (2.0 + 10.0 / (level_num as f64 + 1.0))).ceil() as u32;
-
-        // Start with a solid buffer
-        let data: Vec<i32> = vec![starting_terrain_health; (level_width * level_height) as usize];
-        WIPRectangleLevel {
-            width: level_width,
-            height: level_height,
-            data,
-            max_dimension,
-            rng: fastrand::Rng::new(),
-            num_vacancies,
-            completed_vacancies: 0,
-        }
-    }
-    fn done(&self) -> bool {
-        self.completed_vacancies >= self.num_vacancies
-    }
-
-    fn step(&mut self) {
-        let width = self.rng.u32(0..self.max_dimension) as usize;
-        let height = self.rng.u32(0..self.max_dimension) as usize;
-        let left = self.rng.u32(0..(self.width - width as u32)) as usize;
-        let bot = self.rng.u32(0..(self.height - height as u32)) as usize;
-        for y in bot..(bot + height) {
-            // Inner loop over rows...
-            let offset = y * (self.width as usize) + left;
-            self.data[offset..offset + width].fill(0);
-        }
-        self.completed_vacancies += 1;
-    }
-
-    fn finish(&mut self) {
-        while !self.done() {
-            self.step();
-        }
-    }
-
-    fn work_until(&mut self, deadline: Instant) -> bool {
-        while !self.done() && Instant::now() < deadline {
-            self.step();
-        }
-        self.done()
-    }
+/// Derive the generator seed for one level from the run seed. Each level gets
+/// its own stream so a level's bytes depend only on `(run_seed, level_index)`,
+/// not on how many levels were generated before it or in what order.
+pub fn level_seed(run_seed: u32, level_index: u32) -> u64 {
+    // SplitMix64 finalizer over the packed pair.
+    let mut z = ((run_seed as u64) << 32 | level_index as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 #[cfg(test)]
@@ -97,52 +43,106 @@ fn make_stripe_level(width: u32, height: u32) -> Vec<i32> {
 pub struct LevelMaker {
     level_width: u32,
     level_height: u32,
-    starting_terrain_health: i32,
+    // Health and per-band generator choice for new levels.
+    level_params: LevelParams,
+    // Run seed; each level's generator is seeded from this via `level_seed`.
+    seed: u32,
+    // Every image `level_params.authored_levels` places, decoded once up
+    // front by name. Images that failed to load are missing.
+    authored_levels: HashMap<String, AuthoredLevel>,
 
     // Finished levels, indexed by level index.
     levels: Vec<Vec<i32>>,
+    // Material IDs of each finished level, parallel to `levels`.
+    materials: Vec<Vec<u32>>,
     // WIP levels, indexed by level index.
-    wip_levels: std::collections::BTreeMap<u32, WIPRectangleLevel>,
+    wip_levels: std::collections::BTreeMap<u32, Box<dyn LevelGenerator>>,
 }
 
 impl LevelMaker {
-    fn init(level_width: u32, level_height: u32, starting_terrain_health: i32) -> Self {
+    /// Start generating play levels from `seed`. Level 0 is generated
+    /// immediately, with its bottom half cleared as the launch area.
+    pub fn init(
+        level_width: u32,
+        level_height: u32,
+        level_params: &LevelParams,
+        seed: u32,
+    ) -> Self {
         let mut maker = LevelMaker {
             level_width,
             level_height,
-            starting_terrain_health,
+            level_params: level_params.clone(),
+            seed,
+            authored_levels: load_authored_levels(level_params, level_width, level_height),
             levels: vec![],
+            materials: vec![],
             wip_levels: std::collections::BTreeMap::new(),
         };
         // Pre-generate level 0 and clear the bottom half so the ship has room.
+        // An authored level 0 is used as painted.
         maker.prefetch_up_to_level(0);
         maker.finish_through_level(0);
-        if let Some(level) = maker.levels.first_mut() {
+        if let Some(level) = maker
+            .levels
+            .first_mut()
+            .filter(|_| level_params.authored_level_for(0).is_none())
+        {
             let clear_rows = level_height / 2;
             level[..(clear_rows * level_width) as usize].fill(0);
         }
         maker
     }
 
+    /// Start with `first_level` (health, material ID per cell) as level 0,
+    /// generating the levels above it from `seed` as usual.
+    pub fn with_first_level(
+        level_width: u32,
+        level_height: u32,
+        level_params: &LevelParams,
+        seed: u32,
+        first_level: (Vec<i32>, Vec<u32>),
+    ) -> Self {
+        let (level, materials) = first_level;
+        LevelMaker {
+            level_width,
+            level_height,
+            level_params: level_params.clone(),
+            seed,
+            authored_levels: load_authored_levels(level_params, level_width, level_height),
+            levels: vec![level],
+            materials: vec![materials],
+            wip_levels: std::collections::BTreeMap::new(),
+        }
+    }
+
     /// Create a title-screen level: empty terrain with text blitted as solid
-    /// cells centered in the viewport.
+    /// cells centered in the viewport, which sits at the level origin.
     fn init_title(
         level_width: u32,
         level_height: u32,
         starting_terrain_health: i32,
+        viewport_width: u32,
         viewport_height: u32,
     ) -> Self {
         let mut maker = LevelMaker {
             level_width,
             level_height,
-            starting_terrain_health,
+            level_params: LevelParams {
+                starting_terrain_health,
+                ..Default::default()
+            },
+            // The ship never leaves level 0 on the title, so any fixed seed works.
+            seed: 0,
+            authored_levels: HashMap::new(),
             levels: vec![],
+            materials: vec![],
             wip_levels: std::collections::BTreeMap::new(),
         };
 
         // Create an empty level.
-        let level_data = vec![0i32; (level_width * level_height) as usize];
-        maker.levels.push(level_data);
+        let num_cells = (level_width * level_height) as usize;
+        maker.levels.push(vec![0i32; num_cells]);
+        maker.materials.push(vec![Material::Rock.id(); num_cells]);
 
         // Rasterize "SPOUT" at 3x scale into terrain cells.
         // Title text gets 20x normal health so it erodes visibly but not too fast.
@@ -156,7 +156,7 @@ impl LevelMaker {
 
         // Center the text in the viewport area.
         let level = maker.levels.first_mut().expect("level 0 exists");
-        let offset_x = (level_width.saturating_sub(tw)) / 2;
+        let offset_x = (viewport_width.saturating_sub(tw)) / 2;
         let offset_y = (viewport_height.saturating_sub(th)) / 2;
 
         for row in 0..th {
@@ -178,17 +178,35 @@ impl LevelMaker {
 
     pub fn prefetch_up_to_level(&mut self, i: i32) {
         for level_index in self.levels.len() as u32..(i + 1) as u32 {
-            self.wip_levels.entry(level_index).or_insert_with(|| {
-                WIPRectangleLevel::init(
-                    level_index,
-                    self.level_width,
-                    self.level_height,
-                    self.starting_terrain_health,
-                )
-            });
+            if !self.wip_levels.contains_key(&level_index) {
+                let generator = self.make_generator(level_index);
+                self.wip_levels.insert(level_index, generator);
+            }
         }
     }
 
+    // The authored level placed at `level_index`, or else the procedural
+    // generator for its band.
+    fn make_generator(&self, level_index: u32) -> Box<dyn LevelGenerator> {
+        let health = self.level_params.starting_terrain_health;
+        if let Some(image) = self.level_params.authored_level_for(level_index) {
+            match self.authored_levels.get(image) {
+                Some(level) => return Box::new(level.clone()),
+                None => log::error!(
+                    "Level image \"{image}\" did not load; generating level {level_index} instead"
+                ),
+            }
+        }
+        level_gen::make_generator(
+            self.level_params.generator_for_level(level_index),
+            level_index,
+            self.level_width,
+            self.level_height,
+            health,
+            level_seed(self.seed, level_index),
+        )
+    }
+
     pub fn work_until(&mut self, deadline: Instant) {
         while !self.wip_levels.is_empty() && Instant::now() < deadline {
             // Generate levels in order of level index. The break below means
@@ -199,7 +217,9 @@ impl LevelMaker {
                 if value.done() {
                     log::info!("Finished generating level: {}", key);
                     finished_key = Some(*key);
-                    self.levels.push(std::mem::take(&mut value.data));
+                    let (level, materials) = value.take_level();
+                    self.levels.push(level);
+                    self.materials.push(materials);
                 } else {
                     value.work_until(deadline);
                     break;
@@ -211,6 +231,91 @@ impl LevelMaker {
         }
     }
 
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Number of levels generated so far.
+    pub fn num_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Check a ship against CPU-side terrain (see [`LevelManager::terrain_health_at`]).
+    pub fn check_ship_collision(&self, ship: &crate::ship::ShipState) -> bool {
+        check_collision(&self.levels, self.level_width, self.level_height, ship)
+    }
+
+    /// Turret sites on level `level_index` (see `turret::place_turrets`),
+    /// finishing the level first if needed. Sites come from the level as
+    /// generated, so ask before the level erodes.
+    pub fn turret_sites(&mut self, level_index: u32, params: &TurretParams) -> Vec<[u32; 2]> {
+        if level_index < params.first_level || params.turrets_per_level == 0 {
+            return Vec::new();
+        }
+        self.finish_through_level(level_index as i32);
+        crate::turret::place_turrets(
+            &self.levels[level_index as usize],
+            self.level_width,
+            self.level_height,
+            params.turrets_per_level,
+            level_seed(self.seed, level_index),
+        )
+    }
+
+    /// Enemy ship spawn sites on level `level_index` (see
+    /// `enemy::place_enemies`), finishing the level first if needed.
+    pub fn enemy_sites(&mut self, level_index: u32, params: &EnemyParams) -> Vec<[u32; 2]> {
+        if level_index < params.first_level || params.ships_per_level == 0 {
+            return Vec::new();
+        }
+        self.finish_through_level(level_index as i32);
+        crate::enemy::place_enemies(
+            &self.levels[level_index as usize],
+            self.level_width,
+            self.level_height,
+            params.ships_per_level,
+            level_seed(self.seed, level_index),
+        )
+    }
+
+    /// Pickups on level `level_index` (see `pickup::place_pickups`),
+    /// finishing the level first if needed.
+    pub fn pickup_sites(
+        &mut self,
+        level_index: u32,
+        params: &PickupParams,
+    ) -> Vec<([u32; 2], crate::pickup::PickupKind)> {
+        if level_index < params.first_level || params.pickups_per_level == 0 {
+            return Vec::new();
+        }
+        self.finish_through_level(level_index as i32);
+        crate::pickup::place_pickups(
+            &self.levels[level_index as usize],
+            self.level_width,
+            self.level_height,
+            params.pickups_per_level,
+            level_seed(self.seed, level_index),
+        )
+    }
+
+    /// Terrain health over `region` from the CPU level data, in the shape of
+    /// a GPU terrain query result.
+    pub fn terrain_snapshot(&self, region: Region) -> TerrainSnapshot {
+        let health = (region.y..region.y + region.height as i32)
+            .flat_map(|y| (region.x..region.x + region.width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                terrain_health_at(
+                    &self.levels,
+                    self.level_width,
+                    self.level_height,
+                    x as f32,
+                    y as f32,
+                )
+            })
+            .collect();
+        TerrainSnapshot { region, health }
+    }
+
     pub fn finish_through_level(&mut self, i: i32) {
         // Resolve all futures up to the requested one.
         self.prefetch_up_to_level(i);
@@ -218,7 +323,9 @@ impl LevelMaker {
         for level_index in self.levels.len() as u32..(i + 1) as u32 {
             if let Some(wip) = self.wip_levels.get_mut(&level_index) {
                 wip.finish();
-                self.levels.push(std::mem::take(&mut wip.data));
+                let (level, materials) = wip.take_level();
+                self.levels.push(level);
+                self.materials.push(materials);
             }
             self.wip_levels.remove(&level_index);
         }
@@ -255,6 +362,8 @@ pub struct TerrainTile {
     // Shape of this `tile`: a 1d interval in units of rows from the start of the game.
     pub shape: Interval,
     pub buffer: SizedBuffer,
+    // Material ID per cell, same layout as `buffer`.
+    pub materials: SizedBuffer,
 }
 
 impl TerrainTile {
@@ -282,6 +391,13 @@ impl TerrainTile {
             dst_byte_offset,
             copy_byte_size,
         );
+        encoder.copy_buffer_to_buffer(
+            &self.materials.buffer,
+            src_byte_offset,
+            &other.materials.buffer,
+            dst_byte_offset,
+            copy_byte_size,
+        );
         true
     }
 }
@@ -305,21 +421,138 @@ pub struct LevelManager {
     // This tile is composed of the above tiles. Each iteration, it is composited, then used, and then the results are copied out.
     composite_tile: TerrainTile,
 
-    unused_buffers: Vec<SizedBuffer>,
+    // Recycled (terrain, material) buffer pairs for level tiles.
+    unused_buffers: Vec<(SizedBuffer, SizedBuffer)>,
+
+    // Tiles unloaded from the GPU whose contents are being read back into
+    // `level_maker`. Their buffers are recycled once the readback lands.
+    evictions: Vec<TileEviction>,
+
+    // Outstanding `query_terrain` requests against the composite tile.
+    terrain_queries: TerrainQueries,
 
     pub level_maker: LevelMaker,
 
     pub terrain_renderer: TerrainRenderer,
 }
 
+/// Levels within this many of the active levels stay loaded on the GPU, so a
+/// viewport hovering over a level boundary doesn't evict and reload a tile
+/// every frame.
+const EVICTION_MARGIN_LEVELS: i32 = 1;
+
+/// A level tile unloaded from the GPU. Its contents are copied into staging
+/// buffers and, once mapped, replace the level's CPU-side data, so erosion
+/// survives the tile being reloaded later.
+struct TileEviction {
+    level_index: i32,
+    tile: TerrainTile,
+    terrain_staging: wgpu::Buffer,
+    material_staging: wgpu::Buffer,
+    /// Whether `map_async` has been initiated on both staging buffers.
+    mapping_started: bool,
+    /// Set by each staging buffer's `map_async` callback when its mapping
+    /// completes or fails, in `[terrain, material]` order.
+    map_results: [Arc<OnceLock<Result<(), wgpu::BufferAsyncError>>>; 2],
+}
+
+impl TileEviction {
+    fn start(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        level_index: i32,
+        tile: TerrainTile,
+    ) -> Self {
+        let mut make_staging = |source: &SizedBuffer, label| {
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: source.size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            encoder.copy_buffer_to_buffer(&source.buffer, 0, &staging, 0, source.size);
+            staging
+        };
+        let terrain_staging = make_staging(&tile.buffer, "Terrain eviction staging");
+        let material_staging = make_staging(&tile.materials, "Material eviction staging");
+        TileEviction {
+            level_index,
+            tile,
+            terrain_staging,
+            material_staging,
+            mapping_started: false,
+            map_results: Default::default(),
+        }
+    }
+
+    fn start_readback(&mut self) {
+        if self.mapping_started {
+            return;
+        }
+        for (staging, map_result) in [&self.terrain_staging, &self.material_staging]
+            .into_iter()
+            .zip(&self.map_results)
+        {
+            let map_result = Arc::clone(map_result);
+            staging
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = map_result.set(result);
+                });
+        }
+        self.mapping_started = true;
+    }
+
+    /// How the readback went, or `None` while either mapping is in flight.
+    fn outcome(&self) -> Option<Result<(), wgpu::BufferAsyncError>> {
+        if !self.mapping_started {
+            return None;
+        }
+        let [terrain, material] = &self.map_results;
+        match (terrain.get()?, material.get()?) {
+            (Ok(()), Ok(())) => Some(Ok(())),
+            (Err(err), _) | (_, Err(err)) => Some(Err(err.clone())),
+        }
+    }
+}
+
+/// Decode every image `level_params.authored_levels` places, keyed by name.
+/// `GameParams::validate` checks names and sizes up front; images that still
+/// fail to load are logged and left out.
+fn load_authored_levels(
+    level_params: &LevelParams,
+    level_width: u32,
+    level_height: u32,
+) -> HashMap<String, AuthoredLevel> {
+    let mut levels = HashMap::new();
+    for placement in &level_params.authored_levels {
+        if levels.contains_key(&placement.image) {
+            continue;
+        }
+        match AuthoredLevel::load(
+            &placement.image,
+            level_width,
+            level_height,
+            level_params.starting_terrain_health,
+        ) {
+            Ok(level) => {
+                levels.insert(placement.image.clone(), level);
+            }
+            Err(err) => log::error!("{err}"),
+        }
+    }
+    levels
+}
+
 /// Query terrain health at a world position from CPU-side level data.
 ///
 /// Returns the terrain health value (> 0 means solid terrain) or 0 if the
 /// position is out of bounds or the level hasn't been generated yet.
 ///
-/// **Note:** This reads the initial (pre-erosion) terrain data. Terrain
-/// destroyed by particles on the GPU is not reflected here. For upward-
-/// scrolling gameplay this is accurate for terrain ahead of the player.
+/// **Note:** Levels are updated from the GPU only when their tile is evicted
+/// (see [`LevelManager::finish_evictions`]). Terrain eroded in a tile that is
+/// still loaded is not reflected here until then; use
+/// [`LevelManager::query_terrain`] for the current state near the viewport.
 fn terrain_health_at(
     levels: &[Vec<i32>],
     level_width: u32,
@@ -350,35 +583,23 @@ fn terrain_health_at(
     level_data[index]
 }
 
-/// Ship collision radius — approximate the triangular hull with a small circle.
-const SHIP_COLLISION_RADIUS: f32 = 4.0;
-
-/// Check if a ship position collides with terrain.
-fn check_collision(
+/// Check if a ship collides with terrain: whether any of the hull's
+/// collision samples, the points the GPU sweep tests, is in a solid cell.
+pub(crate) fn check_collision(
     levels: &[Vec<i32>],
     level_width: u32,
     level_height: u32,
     ship: &crate::ship::ShipState,
 ) -> bool {
-    let cx = ship.position[0];
-    let cy = ship.position[1];
-
-    // Test center + 4 cardinal points around the hull.
-    let test_points = [
-        (cx, cy),
-        (cx + SHIP_COLLISION_RADIUS, cy),
-        (cx - SHIP_COLLISION_RADIUS, cy),
-        (cx, cy + SHIP_COLLISION_RADIUS),
-        (cx, cy - SHIP_COLLISION_RADIUS),
-    ];
-
-    test_points
+    ship.hull_samples()
         .iter()
-        .any(|&(x, y)| terrain_health_at(levels, level_width, level_height, x, y) > 0)
+        .any(|&[x, y]| terrain_health_at(levels, level_width, level_height, x, y) > 0)
 }
 
 impl LevelManager {
-    /// Query terrain health at a world position.
+    /// Query terrain health at a world position. Reflects erosion in levels
+    /// whose tiles have been evicted and read back; see the free function's
+    /// note.
     pub fn terrain_health_at(&self, x: f32, y: f32) -> i32 {
         terrain_health_at(
             &self.level_maker.levels,
@@ -391,12 +612,7 @@ impl LevelManager {
 
     /// Check if the ship collides with terrain.
     pub fn check_ship_collision(&self, ship: &crate::ship::ShipState) -> bool {
-        check_collision(
-            &self.level_maker.levels,
-            self.level_width,
-            self.level_height,
-            ship,
-        )
+        self.level_maker.check_ship_collision(ship)
     }
 
     fn active_tiles(&self) -> impl Iterator<Item = &TerrainTile> {
@@ -452,64 +668,58 @@ impl LevelManager {
         &self.composite_tile
     }
 
+    /// Initialize for a play session. `seed` is the run seed: the same seed
+    /// and `game_params` always produce the same terrain.
     pub fn init(
         device: &wgpu::Device,
         game_params: &super::game_params::GameParams,
+        seed: u32,
         viewport_offset: i32,
         init_encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
     ) -> Self {
-        let level_width = game_params.level_width;
-        let level_height = game_params.level_height;
-
-        let active_interval_height = std::cmp::max(
-            level_height,
-            (game_params.viewport_height as f32 * 1.5) as u32,
+        let level_maker = LevelMaker::init(
+            game_params.level_width,
+            game_params.level_height,
+            &game_params.level_params,
+            seed,
         );
-        let active_extent_below_viewport = game_params.viewport_height / 4;
-        let active_interval = LevelManager::get_active_interval(
-            0,
-            active_interval_height as i32,
-            active_extent_below_viewport as i32,
-        );
-
-        let unused_buffers = vec![buffer_util::make_buffer(
+        LevelManager::with_level_maker(
             device,
-            level_width as usize,
-            level_height as usize,
-            "Terrain",
-        )];
-        let composite_tile_buffer = buffer_util::make_buffer(
-            device,
-            level_width as usize,
-            active_interval_height as usize,
-            "CompositeTerrainBuffer",
-        );
-        let renderer = TerrainRenderer::init(device, game_params, &composite_tile_buffer);
-
-        let mut lm = LevelManager {
-            level_width: game_params.level_width,
-            level_height: game_params.level_height,
-            active_interval_height,
-            active_extent_below_viewport,
-
-            loaded_tiles: std::collections::BTreeMap::new(),
-            composite_tile: TerrainTile {
-                shape: active_interval,
-                buffer: composite_tile_buffer,
-            },
-
-            unused_buffers,
-            level_maker: LevelMaker::init(
-                level_width,
-                level_height,
-                game_params.level_params.starting_terrain_health,
-            ),
-            terrain_renderer: renderer,
-        };
+            game_params,
+            level_maker,
+            viewport_offset,
+            init_encoder,
+            belt,
+        )
+    }
 
-        lm.sync_height(device, viewport_offset, init_encoder, game_params, belt);
-        lm
+    /// Like `init`, but with `first_level` (health, material ID per cell) as
+    /// level 0 instead of a generated one. Used to edit and test-fly a level.
+    pub fn init_with_first_level(
+        device: &wgpu::Device,
+        game_params: &super::game_params::GameParams,
+        seed: u32,
+        first_level: (Vec<i32>, Vec<u32>),
+        viewport_offset: i32,
+        init_encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+    ) -> Self {
+        let level_maker = LevelMaker::with_first_level(
+            game_params.level_width,
+            game_params.level_height,
+            &game_params.level_params,
+            seed,
+            first_level,
+        );
+        LevelManager::with_level_maker(
+            device,
+            game_params,
+            level_maker,
+            viewport_offset,
+            init_encoder,
+            belt,
+        )
     }
 
     /// Initialize for the title screen: empty terrain with "SPOUT" text blitted
@@ -520,6 +730,31 @@ impl LevelManager {
         viewport_offset: i32,
         init_encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
+    ) -> Self {
+        let level_maker = LevelMaker::init_title(
+            game_params.level_width,
+            game_params.level_height,
+            game_params.level_params.starting_terrain_health,
+            game_params.viewport_width,
+            game_params.viewport_height,
+        );
+        LevelManager::with_level_maker(
+            device,
+            game_params,
+            level_maker,
+            viewport_offset,
+            init_encoder,
+            belt,
+        )
+    }
+
+    fn with_level_maker(
+        device: &wgpu::Device,
+        game_params: &super::game_params::GameParams,
+        level_maker: LevelMaker,
+        viewport_offset: i32,
+        init_encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
     ) -> Self {
         let level_width = game_params.level_width;
         let level_height = game_params.level_height;
@@ -535,11 +770,10 @@ impl LevelManager {
             active_extent_below_viewport as i32,
         );
 
-        let unused_buffers = vec![buffer_util::make_buffer(
+        let unused_buffers = vec![LevelManager::make_tile_buffers(
             device,
-            level_width as usize,
-            level_height as usize,
-            "Terrain",
+            level_width,
+            level_height,
         )];
         let composite_tile_buffer = buffer_util::make_buffer(
             device,
@@ -547,7 +781,18 @@ impl LevelManager {
             active_interval_height as usize,
             "CompositeTerrainBuffer",
         );
-        let renderer = TerrainRenderer::init(device, game_params, &composite_tile_buffer);
+        let composite_material_buffer = buffer_util::make_buffer(
+            device,
+            level_width as usize,
+            active_interval_height as usize,
+            "CompositeMaterialBuffer",
+        );
+        let renderer = TerrainRenderer::init(
+            device,
+            game_params,
+            &composite_tile_buffer,
+            &composite_material_buffer,
+        );
 
         let mut lm = LevelManager {
             level_width: game_params.level_width,
@@ -559,22 +804,56 @@ impl LevelManager {
             composite_tile: TerrainTile {
                 shape: active_interval,
                 buffer: composite_tile_buffer,
+                materials: composite_material_buffer,
             },
 
             unused_buffers,
-            level_maker: LevelMaker::init_title(
-                level_width,
-                level_height,
-                game_params.level_params.starting_terrain_health,
-                game_params.viewport_height,
-            ),
+            evictions: vec![],
+            terrain_queries: TerrainQueries::default(),
+            level_maker,
             terrain_renderer: renderer,
         };
 
-        lm.sync_height(device, viewport_offset, init_encoder, game_params, belt);
+        lm.sync_height(
+            device,
+            [0, viewport_offset],
+            init_encoder,
+            game_params,
+            belt,
+        );
         lm
     }
 
+    /// Replace a generated level's terrain, including its GPU tile if loaded.
+    /// Call before `compose_tiles` so the frame sees the new cells.
+    pub fn replace_level(
+        &mut self,
+        level_index: usize,
+        data: &[i32],
+        materials: &[u32],
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+    ) {
+        self.level_maker.levels[level_index].copy_from_slice(data);
+        self.level_maker.materials[level_index].copy_from_slice(materials);
+        let Some(tile) = self.loaded_tiles.get(&(level_index as i32)) else {
+            return;
+        };
+        for (target, data) in [
+            (&tile.buffer, bytemuck::cast_slice(data)),
+            (&tile.materials, bytemuck::cast_slice(materials)),
+        ] {
+            belt.write_buffer(
+                encoder,
+                &target.buffer,
+                0,
+                // safe: buffer.size is always > 0 (set at GPU buffer creation)
+                wgpu::BufferSize::new(target.size as _).unwrap(),
+            )
+            .copy_from_slice(data);
+        }
+    }
+
     pub fn block_on_levels(&mut self, active_levels: Interval) {
         for check_level_index in active_levels.start..active_levels.end {
             if check_level_index >= self.level_maker.levels.len() as i32 {
@@ -585,18 +864,35 @@ impl LevelManager {
         }
     }
 
-    pub fn get_unused_tile_buffer(&mut self, device: &wgpu::Device) -> SizedBuffer {
-        if let Some(buffer) = self.unused_buffers.pop() {
-            return buffer;
-        }
-        buffer_util::make_buffer(
-            device,
-            self.level_width as usize,
-            self.level_height as usize,
-            "Terrain",
+    fn make_tile_buffers(
+        device: &wgpu::Device,
+        level_width: u32,
+        level_height: u32,
+    ) -> (SizedBuffer, SizedBuffer) {
+        (
+            buffer_util::make_buffer(
+                device,
+                level_width as usize,
+                level_height as usize,
+                "Terrain",
+            ),
+            buffer_util::make_buffer(
+                device,
+                level_width as usize,
+                level_height as usize,
+                "TerrainMaterials",
+            ),
         )
     }
 
+    /// Returns a (terrain, material) buffer pair for a level tile.
+    pub fn get_unused_tile_buffers(&mut self, device: &wgpu::Device) -> (SizedBuffer, SizedBuffer) {
+        if let Some(buffers) = self.unused_buffers.pop() {
+            return buffers;
+        }
+        LevelManager::make_tile_buffers(device, self.level_width, self.level_height)
+    }
+
     pub fn load_active_levels(
         &mut self,
         device: &wgpu::Device,
@@ -608,23 +904,41 @@ impl LevelManager {
             assert!(level_index < self.level_maker.levels.len() as i32);
             // Check if this level has a buffer, if not, find it one.
             // entry() API can't be used here: the block borrows self mutably
-            // (get_unused_tile_buffer) which conflicts with holding an Entry.
+            // (get_unused_tile_buffers) which conflicts with holding an Entry.
             #[allow(clippy::map_entry)]
             if !self.loaded_tiles.contains_key(&level_index) {
+                // Still on its way back to the CPU: the tile's buffers haven't
+                // been recycled, so keep using them and drop the readback.
+                if let Some(i) = self
+                    .evictions
+                    .iter()
+                    .position(|e| e.level_index == level_index)
+                {
+                    log::info!("Cancelling eviction of level {}", level_index);
+                    let eviction = self.evictions.swap_remove(i);
+                    self.loaded_tiles.insert(level_index, eviction.tile);
+                    continue;
+                }
                 // Tile isn't loaded, find a buffer.
                 log::info!("Loading level {} to gpu", level_index);
-                let buffer = self.get_unused_tile_buffer(device);
+                let (buffer, materials) = self.get_unused_tile_buffers(device);
                 let level_data = &self.level_maker.levels[level_index as usize];
+                let material_data = &self.level_maker.materials[level_index as usize];
 
                 // Request data copy.
-                belt.write_buffer(
-                    encoder,
-                    &buffer.buffer,
-                    0,
-                    // safe: buffer.size is always > 0 (set at GPU buffer creation)
-                    wgpu::BufferSize::new(buffer.size as _).unwrap(),
-                )
-                .copy_from_slice(bytemuck::cast_slice(level_data));
+                for (target, data) in [
+                    (&buffer, bytemuck::cast_slice(level_data)),
+                    (&materials, bytemuck::cast_slice(material_data)),
+                ] {
+                    belt.write_buffer(
+                        encoder,
+                        &target.buffer,
+                        0,
+                        // safe: buffer.size is always > 0 (set at GPU buffer creation)
+                        wgpu::BufferSize::new(target.size as _).unwrap(),
+                    )
+                    .copy_from_slice(data);
+                }
                 let level_start = level_index * self.level_height as i32;
                 self.loaded_tiles.insert(
                     level_index,
@@ -634,12 +948,130 @@ impl LevelManager {
                             end: level_start + self.level_height as i32,
                         },
                         buffer,
+                        materials,
                     },
                 );
             }
         }
     }
 
+    /// Unload tiles more than `EVICTION_MARGIN_LEVELS` outside
+    /// `active_levels`, queueing a copy of their contents for readback. Call
+    /// `start_readbacks` once `encoder` has been submitted.
+    pub fn evict_inactive_levels(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        active_levels: Interval,
+    ) {
+        let retained = Interval {
+            start: active_levels.start - EVICTION_MARGIN_LEVELS,
+            end: active_levels.end + EVICTION_MARGIN_LEVELS,
+        };
+        let evicted: Vec<i32> = self
+            .loaded_tiles
+            .keys()
+            .copied()
+            .filter(|&i| i < retained.start || i >= retained.end)
+            .collect();
+        for level_index in evicted {
+            log::info!("Evicting level {} from gpu", level_index);
+            // safe: `evicted` was collected from `loaded_tiles`
+            let tile = self.loaded_tiles.remove(&level_index).unwrap();
+            self.evictions
+                .push(TileEviction::start(device, encoder, level_index, tile));
+        }
+    }
+
+    /// Initiate async mapping of queued eviction and terrain query copies.
+    /// Call after `queue.submit()`, like `CollisionDetector::start_readback`.
+    pub fn start_readbacks(&mut self) {
+        for eviction in &mut self.evictions {
+            eviction.start_readback();
+        }
+        self.terrain_queries.start_readback();
+    }
+
+    /// Request the current (post-erosion) terrain health over `region`. Rows
+    /// inside the composite tile are read back from the GPU once
+    /// `dispatch_terrain_queries` and `start_readbacks` have run; the rest
+    /// come from the CPU level data. Collect the result with
+    /// `poll_terrain_query`.
+    pub fn query_terrain(&mut self, region: Region) -> TerrainQueryId {
+        self.terrain_queries.request(region)
+    }
+
+    /// Copy the composite rows new queries need. Call after the frame's last
+    /// pass that writes the composite.
+    pub fn dispatch_terrain_queries(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        self.terrain_queries
+            .dispatch(device, encoder, &self.composite_tile, self.level_width);
+    }
+
+    /// The result of a `query_terrain` request, if its readback has landed
+    /// or failed. Non-blocking; the caller drives wgpu callbacks with
+    /// `device.poll`.
+    pub fn poll_terrain_query(
+        &mut self,
+        id: TerrainQueryId,
+    ) -> Option<Result<TerrainSnapshot, TerrainQueryError>> {
+        let (levels, level_width, level_height) = (
+            &self.level_maker.levels,
+            self.level_width,
+            self.level_height,
+        );
+        self.terrain_queries.finish(level_width, |x, y| {
+            terrain_health_at(levels, level_width, level_height, x as f32, y as f32)
+        });
+        self.terrain_queries.take(id)
+    }
+
+    /// Store every completed eviction readback as its level's authoritative
+    /// CPU data and recycle the tile's buffers. A readback that failed to map
+    /// puts its tile back among the loaded ones, erosion intact, to be
+    /// evicted again later. Non-blocking; the caller drives wgpu callbacks
+    /// with `device.poll`.
+    pub fn finish_evictions(&mut self) {
+        let mut i = 0;
+        while i < self.evictions.len() {
+            let Some(outcome) = self.evictions[i].outcome() else {
+                i += 1;
+                continue;
+            };
+            let eviction = self.evictions.swap_remove(i);
+            if let Err(err) = outcome {
+                log::warn!(
+                    "Eviction readback of level {} failed to map: {err}; keeping its tile",
+                    eviction.level_index
+                );
+                self.loaded_tiles
+                    .insert(eviction.level_index, eviction.tile);
+                continue;
+            }
+            let level_index = eviction.level_index as usize;
+            log::info!("Stored evicted level {}", level_index);
+            for (staging, data) in [
+                (
+                    &eviction.terrain_staging,
+                    bytemuck::cast_slice_mut(&mut self.level_maker.levels[level_index]),
+                ),
+                (
+                    &eviction.material_staging,
+                    bytemuck::cast_slice_mut(&mut self.level_maker.materials[level_index]),
+                ),
+            ] {
+                data.copy_from_slice(&staging.slice(..).get_mapped_range());
+                staging.unmap();
+            }
+            self.unused_buffers
+                .push((eviction.tile.buffer, eviction.tile.materials));
+        }
+    }
+
     pub fn get_active_interval(
         viewport_offset: i32,
         active_interval_height: i32,
@@ -667,16 +1099,19 @@ impl LevelManager {
         self.composite_tile.shape = active_interval;
     }
 
+    /// Load and evict tiles for a viewport whose bottom-left world cell is
+    /// `viewport_offset`. Only the height decides which tiles are loaded;
+    /// every loaded level spans the full level width.
     pub fn sync_height(
         &mut self,
         device: &wgpu::Device,
-        viewport_offset: i32,
+        viewport_offset: [i32; 2],
         encoder: &mut wgpu::CommandEncoder,
         game_params: &crate::game_params::GameParams,
         belt: &mut wgpu::util::StagingBelt,
     ) {
-        log::debug!("Syncing to height: {}", viewport_offset);
-        self.update_active_interval(viewport_offset);
+        log::debug!("Syncing to height: {}", viewport_offset[1]);
+        self.update_active_interval(viewport_offset[1]);
 
         // Find all level indices corresponding to active interval.
         let active_levels = Interval {
@@ -705,8 +1140,8 @@ impl LevelManager {
         // Find levels we need, make sure they're done (blocking) and loaded into the gpu(blocking)
         self.block_on_levels(active_levels);
         self.load_active_levels(device, encoder, active_levels, belt);
-
-        // TODO when a level is no longer needed, recycle the buffer.
+        self.finish_evictions();
+        self.evict_inactive_levels(device, encoder, active_levels);
 
         self.terrain_renderer.update_render_state(
             game_params,
@@ -741,23 +1176,53 @@ struct FragmentUniforms {
 
     pub viewport_offset: i32,
     pub terrain_buffer_offset: i32,
+
+    pub viewport_offset_x: i32,
+    // Terrain buffer row stride.
+    pub level_width: u32,
+    pub _padding: [u32; 2],
+
+    // Per-material colors indexed by material ID; `w` is unused.
+    pub edge_colors: [[f32; 4]; crate::material::NUM_MATERIALS],
+    pub fill_colors: [[f32; 4]; crate::material::NUM_MATERIALS],
+}
+
+impl FragmentUniforms {
+    fn new(
+        game_params: &super::game_params::GameParams,
+        viewport_offset: [i32; 2],
+        terrain_buffer_offset: i32,
+    ) -> Self {
+        FragmentUniforms {
+            viewport_width: game_params.viewport_width,
+            viewport_height: game_params.viewport_height,
+            viewport_offset: viewport_offset[1],
+            terrain_buffer_offset,
+            viewport_offset_x: viewport_offset[0],
+            level_width: game_params.level_width,
+            _padding: [0; 2],
+            edge_colors: Material::ALL.map(|m| {
+                let [r, g, b] = m.properties().edge_color;
+                [r, g, b, 1.0]
+            }),
+            fill_colors: Material::ALL.map(|m| {
+                let [r, g, b] = m.properties().fill_color;
+                [r, g, b, 1.0]
+            }),
+        }
+    }
 }
 
 impl TerrainRenderer {
     pub fn update_render_state(
         &mut self,
         game_params: &super::game_params::GameParams,
-        viewport_offset: i32,
+        viewport_offset: [i32; 2],
         terrain_buffer_offset: i32,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
     ) {
-        let uniforms = FragmentUniforms {
-            viewport_width: game_params.level_width,
-            viewport_height: game_params.viewport_height,
-            viewport_offset,
-            terrain_buffer_offset,
-        };
+        let uniforms = FragmentUniforms::new(game_params, viewport_offset, terrain_buffer_offset);
 
         // Update uniforms
         belt.write_buffer(
@@ -774,18 +1239,14 @@ impl TerrainRenderer {
         device: &wgpu::Device,
         game_params: &super::game_params::GameParams,
         composite_terrain_buffer: &SizedBuffer,
+        composite_material_buffer: &SizedBuffer,
     ) -> Self {
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
             source: wgpu::ShaderSource::Wgsl(crate::include_shader!("terrain.wgsl")),
         });
 
-        let fragment_uniforms = FragmentUniforms {
-            viewport_width: game_params.viewport_width,
-            viewport_height: game_params.viewport_height,
-            viewport_offset: 0,
-            terrain_buffer_offset: 0,
-        };
+        let fragment_uniforms = FragmentUniforms::new(game_params, [0, 0], 0);
         let uniform_buf =
             crate::buffer_util::make_uniform_buffer(device, "Uniform buffer", &fragment_uniforms);
 
@@ -817,6 +1278,19 @@ impl TerrainRenderer {
                         },
                         count: None,
                     },
+                    // Material buffer
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: std::num::NonZeroU64::new(
+                                composite_material_buffer.size,
+                            ),
+                        },
+                        count: None,
+                    },
                 ],
                 label: None,
             });
@@ -836,6 +1310,12 @@ impl TerrainRenderer {
                         composite_terrain_buffer.buffer.as_entire_buffer_binding(),
                     ),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(
+                        composite_material_buffer.buffer.as_entire_buffer_binding(),
+                    ),
+                },
             ],
             label: None,
         });
@@ -978,6 +1458,32 @@ mod collision_tests {
         assert!(!check_collision(&empty_level(), W, H, &ship));
     }
 
+    #[test]
+    fn collision_follows_the_hull_shape_and_heading() {
+        use crate::game_params::ShipHull;
+
+        // A single solid cell 12 cells right of the ship's center.
+        let mut level = vec![0i32; (W * H) as usize];
+        level[(5 * W + 9) as usize] = 1000;
+        let levels = vec![level];
+        let ship = |hull, orientation| crate::ship::ShipState {
+            position: [-2.5, 5.5],
+            orientation,
+            hull,
+            ..Default::default()
+        };
+
+        // Only the dart's nose reaches it, and only pointing at it.
+        assert!(check_collision(&levels, W, H, &ship(ShipHull::Dart, 0.0)));
+        assert!(!check_collision(&levels, W, H, &ship(ShipHull::Scout, 0.0)));
+        assert!(!check_collision(
+            &levels,
+            W,
+            H,
+            &ship(ShipHull::Dart, std::f32::consts::FRAC_PI_2)
+        ));
+    }
+
     #[test]
     fn no_collision_beyond_generated_levels() {
         let ship = crate::ship::ShipState {
@@ -988,6 +1494,110 @@ mod collision_tests {
     }
 }
 
+#[cfg(test)]
+mod generation_tests {
+    use super::*;
+
+    const W: u32 = 64;
+    const H: u32 = 48;
+    const HEALTH: i32 = 500;
+
+    fn level_params() -> LevelParams {
+        LevelParams {
+            starting_terrain_health: HEALTH,
+            ..Default::default()
+        }
+    }
+
+    fn generate(seed: u32, through_level: i32) -> Vec<Vec<i32>> {
+        generate_with_materials(seed, through_level).0
+    }
+
+    fn generate_with_materials(seed: u32, through_level: i32) -> (Vec<Vec<i32>>, Vec<Vec<u32>>) {
+        let mut maker = LevelMaker::init(W, H, &level_params(), seed);
+        maker.finish_through_level(through_level);
+        (maker.levels, maker.materials)
+    }
+
+    #[test]
+    fn same_seed_generates_identical_levels() {
+        assert_eq!(generate(1234, 3), generate(1234, 3));
+    }
+
+    #[test]
+    fn different_seeds_generate_different_levels() {
+        assert_ne!(generate(1, 2)[1..], generate(2, 2)[1..]);
+    }
+
+    #[test]
+    fn level_bytes_do_not_depend_on_generation_order() {
+        // Level 2 alone (via the incremental path) matches level 2 generated
+        // as part of a batch.
+        let batch = generate(99, 2);
+        let mut maker = LevelMaker::init(W, H, &level_params(), 99);
+        maker.prefetch_up_to_level(2);
+        while maker.levels.len() < 3 {
+            maker.work_until(Instant::now() + std::time::Duration::from_millis(1));
+        }
+        assert_eq!(maker.levels, batch);
+        assert_eq!(maker.seed(), 99);
+    }
+
+    #[test]
+    fn authored_levels_replace_generated_ones_at_their_indices() {
+        // The embedded level images are sized for the shipped config.
+        let (width, height) = (261, 480);
+        let params = LevelParams {
+            starting_terrain_health: HEALTH,
+            authored_levels: vec![crate::game_params::AuthoredLevelPlacement {
+                image: "serpent.png".into(),
+                level: 1,
+                repeat_every: Some(2),
+            }],
+            ..Default::default()
+        };
+        let mut maker = LevelMaker::init(width, height, &params, 3);
+        maker.finish_through_level(3);
+        let (serpent, serpent_materials) =
+            AuthoredLevel::load("serpent.png", width, height, HEALTH)
+                .unwrap()
+                .take_level();
+        assert_eq!(maker.levels[1], serpent);
+        assert_eq!(maker.materials[1], serpent_materials);
+        assert_eq!(maker.levels[3], serpent);
+        assert_ne!(maker.levels[2], serpent);
+    }
+
+    #[test]
+    fn levels_place_materials_with_scaled_health() {
+        let (levels, materials) = generate_with_materials(5, 15);
+        assert_eq!(materials.len(), levels.len());
+        let mut seen = [false; crate::material::NUM_MATERIALS];
+        for (level, level_materials) in levels.iter().zip(&materials) {
+            assert_eq!(level.len(), level_materials.len());
+            for (&health, &id) in level.iter().zip(level_materials) {
+                let material = Material::from_id(id).expect("valid material ID");
+                seen[id as usize] = true;
+                assert!(
+                    health == 0 || health == material.starting_health(HEALTH),
+                    "{material:?} cell with health {health}"
+                );
+            }
+        }
+        assert_eq!(seen, [true; crate::material::NUM_MATERIALS]);
+        // The launch level never has metal.
+        assert!(!materials[0].contains(&Material::Metal.id()));
+        assert_eq!(generate_with_materials(5, 15), (levels, materials));
+    }
+
+    #[test]
+    fn level_seeds_are_distinct_per_level_and_run() {
+        assert_ne!(level_seed(7, 0), level_seed(7, 1));
+        assert_ne!(level_seed(7, 0), level_seed(8, 0));
+        assert_eq!(level_seed(7, 3), level_seed(7, 3));
+    }
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
@@ -1029,12 +1639,21 @@ mod tests {
             gpu::create_offscreen_target(&device, TEST_W, TEST_H, crate::bloom::GAME_VIEW_FORMAT);
         let staging_buffer = gpu::create_readback_buffer(&device, TEST_W, TEST_H, 8);
 
-        let mut renderer = TerrainRenderer::init(&device, &game_params, &terrain_buffer);
+        // All rock.
+        let material_buffer = crate::buffer_util::make_buffer(
+            &device,
+            TEST_W as usize,
+            TEST_H as usize,
+            "Test materials",
+        );
+
+        let mut renderer =
+            TerrainRenderer::init(&device, &game_params, &terrain_buffer, &material_buffer);
         let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
 
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        renderer.update_render_state(&game_params, 0, 0, &mut encoder, &mut belt);
+        renderer.update_render_state(&game_params, [0, 0], 0, &mut encoder, &mut belt);
         renderer.render(&target.view, &mut encoder);
         gpu::encode_texture_readback(
             &mut encoder,
@@ -1066,4 +1685,249 @@ mod tests {
         let rgba = gpu::rgba16f_to_rgba8(&raw, TEST_W, TEST_H);
         gpu::compare_or_generate_golden("terrain_render", &rgba, TEST_W, TEST_H);
     }
+
+    /// Erosion in a tile survives it being evicted: the readback replaces the
+    /// level's CPU data, which `terrain_health_at` and later reloads use.
+    #[test]
+    fn test_evicted_tiles_keep_erosion() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping test_evicted_tiles_keep_erosion");
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.viewport_width = 64;
+        game_params.viewport_height = 32;
+        game_params.level_width = 64;
+        game_params.level_height = 64;
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let submit = |encoder: wgpu::CommandEncoder, belt: &mut wgpu::util::StagingBelt| {
+            belt.finish();
+            queue.submit(Some(encoder.finish()));
+            belt.recall();
+        };
+        let new_encoder =
+            || device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let mut encoder = new_encoder();
+        let mut lm = LevelManager::init(&device, &game_params, 1, 0, &mut encoder, &mut belt);
+        submit(encoder, &mut belt);
+        // Stand in for the particle pass: fill level 0's tile with eroded dirt.
+        let tile = &lm.loaded_tiles[&0];
+        let eroded = vec![-7i32; (64 * 64) as usize];
+        let dirt = vec![Material::Dirt.id(); (64 * 64) as usize];
+        queue.write_buffer(&tile.buffer.buffer, 0, bytemuck::cast_slice(&eroded));
+        queue.write_buffer(&tile.materials.buffer, 0, bytemuck::cast_slice(&dirt));
+        let solid = lm.level_maker.levels[0]
+            .iter()
+            .position(|&h| h > 0)
+            .expect("level 0 has terrain");
+        let (x, y) = ((solid % 64) as f32, (solid / 64) as f32);
+
+        // Scrolling back before the readback starts keeps the tile as is.
+        let mut encoder = new_encoder();
+        lm.sync_height(&device, [0, 300], &mut encoder, &game_params, &mut belt);
+        assert!(!lm.loaded_tiles.contains_key(&0));
+        lm.sync_height(&device, [0, 0], &mut encoder, &game_params, &mut belt);
+        assert!(lm.loaded_tiles.contains_key(&0));
+        assert!(lm.evictions.iter().all(|e| e.level_index != 0));
+        submit(encoder, &mut belt);
+
+        // Scrolling away for good reads the tile back.
+        let mut encoder = new_encoder();
+        lm.sync_height(&device, [0, 300], &mut encoder, &game_params, &mut belt);
+        submit(encoder, &mut belt);
+        lm.start_readbacks();
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let pending = lm.evictions.len();
+        let unused_before = lm.unused_buffers.len();
+        lm.finish_evictions();
+        assert!(lm.evictions.is_empty());
+        assert_eq!(lm.unused_buffers.len(), unused_before + pending);
+        assert_eq!(lm.level_maker.levels[0], eroded);
+        assert_eq!(lm.level_maker.materials[0], dirt);
+        assert_eq!(lm.terrain_health_at(x, y), -7);
+    }
+
+    /// An eviction whose readback fails to map doesn't linger: its tile goes
+    /// back to the loaded ones, still holding the erosion.
+    #[test]
+    fn test_failed_eviction_keeps_the_tile() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping test_failed_eviction_keeps_the_tile");
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.viewport_width = 64;
+        game_params.viewport_height = 32;
+        game_params.level_width = 64;
+        game_params.level_height = 64;
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut lm = LevelManager::init(&device, &game_params, 1, 0, &mut encoder, &mut belt);
+        let generated = lm.level_maker.levels[0].clone();
+        lm.sync_height(&device, [0, 300], &mut encoder, &game_params, &mut belt);
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        let eviction = lm
+            .evictions
+            .iter()
+            .find(|e| e.level_index == 0)
+            .expect("level 0 evicted");
+        // Mapping a destroyed buffer fails.
+        eviction.terrain_staging.destroy();
+        let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        lm.start_readbacks();
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        assert!(pollster::block_on(scope.pop()).is_some());
+
+        lm.finish_evictions();
+        assert!(lm.evictions.iter().all(|e| e.level_index != 0));
+        assert!(lm.loaded_tiles.contains_key(&0));
+        assert_eq!(lm.level_maker.levels[0], generated);
+    }
+
+    /// Queries see erosion in the composite right away, and fall back to the
+    /// CPU level data for rows the composite doesn't cover.
+    #[test]
+    fn test_replaced_level_reaches_the_composite() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping test_replaced_level_reaches_the_composite"
+            );
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.viewport_width = 64;
+        game_params.viewport_height = 32;
+        game_params.level_width = 64;
+        game_params.level_height = 64;
+        let num_cells = 64 * 64;
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut lm = LevelManager::init_with_first_level(
+            &device,
+            &game_params,
+            1,
+            (vec![0; num_cells], vec![0; num_cells]),
+            0,
+            &mut encoder,
+            &mut belt,
+        );
+        assert_eq!(lm.terrain_health_at(3.0, 5.0), 0);
+
+        let mut data = vec![0; num_cells];
+        let mut materials = vec![0; num_cells];
+        data[5 * 64 + 3] = 77;
+        materials[5 * 64 + 3] = Material::Crystal.id();
+        lm.replace_level(0, &data, &materials, &mut encoder, &mut belt);
+        lm.compose_tiles(&mut encoder);
+        let painted = lm.query_terrain(Region {
+            x: 3,
+            y: 5,
+            width: 1,
+            height: 1,
+        });
+        lm.dispatch_terrain_queries(&device, &mut encoder);
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+        lm.start_readbacks();
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+
+        let snapshot = lm
+            .poll_terrain_query(painted)
+            .expect("query finished")
+            .expect("readback mapped");
+        assert_eq!(snapshot.health, vec![77]);
+        assert_eq!(lm.terrain_health_at(3.0, 5.0), 77);
+    }
+
+    #[test]
+    fn test_terrain_query_reads_current_composite() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping test_terrain_query_reads_current_composite"
+            );
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.viewport_width = 64;
+        game_params.viewport_height = 32;
+        game_params.level_width = 64;
+        game_params.level_height = 64;
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut lm = LevelManager::init(&device, &game_params, 1, 0, &mut encoder, &mut belt);
+        lm.level_maker.finish_through_level(1);
+        lm.compose_tiles(&mut encoder);
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        // Stand in for the particle pass: erode one cell in the composite.
+        let composite = lm.terrain_buffer();
+        assert_eq!(composite.shape.start, 0);
+        let top = composite.shape.end;
+        queue.write_buffer(
+            &composite.buffer.buffer,
+            (5 * 64 + 3) * 4,
+            bytemuck::bytes_of(&-9i32),
+        );
+
+        // Straddles the composite's top edge and the level's right edge.
+        let region = Region {
+            x: 60,
+            y: top - 2,
+            width: 8,
+            height: 4,
+        };
+        let eroded = lm.query_terrain(Region {
+            x: 3,
+            y: 5,
+            width: 1,
+            height: 1,
+        });
+        let straddling = lm.query_terrain(region);
+        assert_eq!(lm.poll_terrain_query(eroded), None);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        lm.dispatch_terrain_queries(&device, &mut encoder);
+        queue.submit(Some(encoder.finish()));
+        lm.start_readbacks();
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+
+        let snapshot = lm
+            .poll_terrain_query(eroded)
+            .expect("query finished")
+            .expect("readback mapped");
+        assert_eq!(snapshot.health, vec![-9]);
+        assert!(!snapshot.is_solid(3, 5));
+        assert_eq!(
+            lm.poll_terrain_query(eroded),
+            None,
+            "results are taken once"
+        );
+
+        let snapshot = lm
+            .poll_terrain_query(straddling)
+            .expect("query finished")
+            .expect("readback mapped");
+        for y in region.y..region.y + 4 {
+            for x in region.x..region.x + 8 {
+                // Nothing eroded here, so the composite matches the CPU data.
+                let expected = lm.terrain_health_at(x as f32, y as f32);
+                assert_eq!(snapshot.health_at(x, y), expected, "at ({x}, {y})");
+            }
+        }
+    }
 }
@@ -1,23 +1,42 @@
 //! Spout game library — GPU-accelerated particle terrain destruction game.
 
+pub mod authored_level;
 pub mod background;
 pub mod bloom;
+pub mod bomb;
 pub mod buffer_util;
 pub mod camera;
+pub mod cli;
 pub mod collision;
 pub mod color_maps;
+pub mod crater;
+pub mod enemy;
+pub mod entity_hits;
+pub mod entity_renderer;
 pub mod game_params;
 #[cfg(test)]
 pub(crate) mod gpu_test_utils;
 pub mod input;
+pub mod level_editor;
+pub mod level_gen;
 pub mod level_manager;
+pub mod material;
 pub mod particles;
+pub mod particles_cpu;
+pub mod pickup;
+pub mod play;
 pub mod render;
+pub mod replay;
+pub mod sand;
 pub mod scoring;
 pub mod shader_util;
 pub mod ship;
+pub mod ship_geometry;
+pub mod sim;
+pub mod terrain_query;
 pub mod text;
 pub mod textured_quad;
 pub mod title_overlay;
 pub mod touch_zone_indicator;
+pub mod turret;
 pub mod ui;
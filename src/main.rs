@@ -9,23 +9,26 @@ use std::time::Duration;
 
 use web_time::Instant;
 
+use spout::cli;
 use spout::collision;
+use spout::crater::{self, Crater};
+use spout::entity_hits;
 use spout::game_params;
 use spout::input::{InputCollector, InputFrame, InputState};
 use spout::level_manager;
-use spout::particles;
+use spout::particles::{self, EmitterKind, ParticleType};
+use spout::pickup::PickupKind;
+use spout::play::{DeathCause, Play};
+use spout::replay;
+use spout::sand;
 use spout::scoring;
 use spout::ship;
+use spout::ui::{RectStyle, UiRect};
 
 use graphics::Graphics;
+use screens::editor::{EditorAction, EditorScreen, EditorUiRenderContext};
 use screens::title::{TitleAction, TitleRenderFlags, TitleScreen, TitleUiRenderContext};
 
-/// Shortest signed angular distance from `current` to `target`, in [-π, π].
-fn angle_diff(target: f32, current: f32) -> f32 {
-    let d = glam::Vec2::from_angle(target - current);
-    d.y.atan2(d.x) // equivalent to wrapping (target-current) to [-π, π]
-}
-
 /// Time budget per frame for background level generation (≈ 1/300 s).
 const LEVEL_BUDGET: Duration = Duration::from_nanos(3_333_333);
 
@@ -33,6 +36,9 @@ const LEVEL_BUDGET: Duration = Duration::from_nanos(3_333_333);
 /// don't cause the ship and particles to simulate a huge time jump.
 const MAX_FRAME_DT: Duration = Duration::from_millis(50);
 
+/// How long the HUD shows what the last pickup did.
+const PICKUP_NOTICE_TIME: Duration = Duration::from_millis(1500);
+
 fn restart_prompt() -> &'static str {
     if tap_restart_prompt() {
         "TAP TO RESTART"
@@ -83,13 +89,44 @@ fn tap_restart_prompt() -> bool {
     false
 }
 
-/// How the player died — drives the GAME OVER / TIMES UP overlay text and
-/// makes future causes (hazards, etc.) easy to add.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum DeathCause {
-    Collided,
-    FellOff,
-    TimeExpired,
+/// Load the `--replay` recording, warning if it was made with a different
+/// config (the replay still runs, but will likely diverge). A recording
+/// flying a ship the roster doesn't have is not replayed.
+fn load_playback(
+    command_line: &cli::CommandLine,
+    params: &game_params::GameParams,
+) -> Option<replay::InputPlayback> {
+    let path = command_line.replay.as_ref()?;
+    match replay::InputRecording::load(path) {
+        Ok(recording) => {
+            let num_ships = params.ships().len();
+            if recording.ship as usize >= num_ships {
+                log::warn!(
+                    "{} flies roster ship {}, but the roster has {num_ships} ships; not replaying it",
+                    path.display(),
+                    recording.ship
+                );
+                return None;
+            }
+            if recording.params_hash != replay::params_hash(params) {
+                log::warn!(
+                    "{} was recorded with a different game_config.toml; replay may diverge",
+                    path.display()
+                );
+            }
+            log::info!(
+                "Replaying {} frames (seed {}) from {}",
+                recording.frames.len(),
+                recording.seed,
+                path.display()
+            );
+            Some(replay::InputPlayback::new(recording))
+        }
+        Err(err) => {
+            log::warn!("Failed to load recording {}: {err}", path.display());
+            None
+        }
+    }
 }
 
 /// GPU-bound state transitions queued up by `update_phase` to be applied
@@ -98,28 +135,15 @@ enum DeathCause {
 /// Lightweight transitions (`ToGameOver`, pause toggle, title-instructions)
 /// are applied inline during `update_phase` instead.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 enum PendingTransition {
     ToTitle,
-    ToPlay,
-}
-
-/// In-game session data, shared across Playing / Paused / GameOver.
-///
-/// Lives inside the relevant `AppState` variants. Methods on `Play` are pure
-/// game logic — they take `&GameParams`/`&InputState` rather than touching any
-/// GPU state, so the simulation step is testable without wgpu.
-#[derive(Debug, Default)]
-struct Play {
-    ship_state: ship::ShipState,
-    prev_ship_state: ship::ShipState,
-    viewport_offset: i32,
-    progress_height: i32,
-    score: i32,
-    current_level_index: i32,
-    timer_elapsed: Duration,
-    timer_budget: Duration,
-    pending_collision_segment: Option<PendingCollisionSegment>,
-    in_flight_collision_segment: Option<PendingCollisionSegment>,
+    /// Start a game flying roster ship `ship`.
+    ToPlay {
+        ship: usize,
+    },
+    ToEditor,
+    ToTestFlight,
 }
 
 /// Top-level state machine. Exhaustive — every screen / mode is a variant,
@@ -133,6 +157,7 @@ enum AppState {
     /// Placeholder for the upcoming leaderboard screen. Not yet constructed.
     #[allow(dead_code)]
     Leaderboard,
+    Editor(EditorScreen),
     Playing(Play),
     Paused(Play),
     GameOver {
@@ -148,13 +173,15 @@ impl Default for AppState {
 }
 
 impl AppState {
-    /// Camera offset for level/background rendering. Title/Settings/Leaderboard
-    /// have no notion of a viewport, so report 0 (level 0 origin).
-    fn viewport_offset(&self) -> i32 {
+    /// Camera offset `[x, y]` for level/background rendering.
+    /// Title/Settings/Leaderboard have no notion of a viewport, so report the
+    /// level 0 origin.
+    fn viewport_offset(&self) -> [i32; 2] {
         match self {
-            AppState::Playing(p) | AppState::Paused(p) => p.viewport_offset,
-            AppState::GameOver { play, .. } => play.viewport_offset,
-            AppState::Title(_) | AppState::Settings | AppState::Leaderboard => 0,
+            AppState::Playing(p) | AppState::Paused(p) => p.viewport_offset(),
+            AppState::GameOver { play, .. } => play.viewport_offset(),
+            AppState::Editor(editor) => editor.viewport_offset(),
+            AppState::Title(_) | AppState::Settings | AppState::Leaderboard => [0, 0],
         }
     }
 
@@ -162,6 +189,10 @@ impl AppState {
         matches!(self, AppState::Title(_))
     }
 
+    fn is_editor(&self) -> bool {
+        matches!(self, AppState::Editor(_))
+    }
+
     fn is_playing(&self) -> bool {
         matches!(self, AppState::Playing(_))
     }
@@ -178,46 +209,12 @@ impl AppState {
             AppState::Title(_)
             | AppState::Settings
             | AppState::Leaderboard
+            | AppState::Editor(_)
             | AppState::GameOver { .. } => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct PendingCollisionSegment {
-    prev_ship: ship::ShipState,
-    next_ship: ship::ShipState,
-}
-
-impl PendingCollisionSegment {
-    fn ship_at(&self, t: f32) -> ship::ShipState {
-        let t = t.clamp(0.0, 1.0);
-        let mut ship = self.next_ship;
-        ship.position = [
-            self.prev_ship.position[0]
-                + (self.next_ship.position[0] - self.prev_ship.position[0]) * t,
-            self.prev_ship.position[1]
-                + (self.next_ship.position[1] - self.prev_ship.position[1]) * t,
-        ];
-        ship
-    }
-}
-
-fn record_collision_motion(
-    pending_segment: &mut Option<PendingCollisionSegment>,
-    prev_ship: ship::ShipState,
-    next_ship: ship::ShipState,
-) {
-    if let Some(segment) = pending_segment {
-        segment.next_ship = next_ship;
-    } else {
-        *pending_segment = Some(PendingCollisionSegment {
-            prev_ship,
-            next_ship,
-        });
-    }
-}
-
 /// Position + velocity captured at the moment of death, used to spawn the
 /// explosion particle burst on the next render.
 #[derive(Debug, Clone, Copy)]
@@ -269,166 +266,161 @@ fn ship_emitter_motion(
     })
 }
 
-impl Play {
-    fn new(params: &game_params::GameParams) -> Self {
-        let ship_state = ship::ShipState::init(
-            &params.ship_params,
-            [
-                (params.viewport_width / 2) as f32 + 0.5,
-                (params.viewport_height / 2) as f32 + 0.5,
-            ],
-        );
-        Self {
-            ship_state,
-            prev_ship_state: ship_state,
-            timer_budget: scoring::level_time_limit_duration(params),
-            ..Default::default()
-        }
+/// Gun emitter motion while the fire button is held, from the muzzle at the
+/// ship's nose. Returns `None` otherwise.
+fn gun_emitter_motion(
+    prev_ship: &ship::ShipState,
+    cur_ship: &ship::ShipState,
+    fire: bool,
+) -> Option<particles::EmitterMotion> {
+    if !fire {
+        return None;
     }
+    let start = prev_ship.get_gun_state();
+    let end = cur_ship.get_gun_state();
+    Some(particles::EmitterMotion {
+        position_start: start.0,
+        position_end: end.0,
+        velocity_start: prev_ship.velocity,
+        velocity_end: cur_ship.velocity,
+        angle_start: start.1,
+        angle_end: end.1,
+        ..Default::default()
+    })
+}
 
-    fn level_timer_remaining(&self, params: &game_params::GameParams) -> Duration {
-        self.timer_budget
-            .max(scoring::level_time_limit_duration(params))
-            .saturating_sub(self.timer_elapsed)
-    }
+/// Set the exhaust nozzle to the base config scaled by the ship's nozzle and
+/// collected upgrades.
+fn apply_nozzle_upgrades(
+    particle_system: &mut particles::ParticleSystem,
+    play: &Play,
+    params: &game_params::GameParams,
+) {
+    let base = &params.particle_system_params;
+    let ship = &play.ship.nozzle_params;
+    let upgrades = &params.pickup_params;
+    let speed = base.emission_speed * ship.speed_scale * play.nozzle.speed_scale(upgrades);
+    let ttl = base.max_particle_life * ship.life_scale * play.nozzle.life_scale(upgrades);
+    particle_system.set_nozzle_speed(speed, speed);
+    particle_system.set_nozzle_ttl(ttl, ttl);
+    particle_system.set_nozzle_spread(
+        particles::DEFAULT_NOZZLE_SPREAD * ship.spread_scale * play.nozzle.spread_scale(upgrades),
+    );
+}
 
-    fn commit_progress_height(&mut self, params: &game_params::GameParams, height: f32) {
-        let confirmed_height = height.floor() as i32;
-        self.progress_height = std::cmp::max(confirmed_height, self.progress_height);
-
-        let next_level_index =
-            scoring::level_index_for_progress(self.progress_height, params.level_height);
-        if next_level_index > self.current_level_index {
-            let levels_crossed = next_level_index - self.current_level_index;
-            let time_award = scoring::level_time_award(params, levels_crossed);
-            self.timer_budget = self.timer_budget.saturating_add(time_award);
-            self.current_level_index = next_level_index;
-            log::info!(
-                "Entered level {} with {:.1}s added to time bank",
-                self.current_level_index + 1,
-                time_award.as_secs_f32()
-            );
-        }
+/// HUD bomb count right of the fuel gauge: a pip per bomb left.
+fn bomb_ammo(ammo: u32) -> Vec<(UiRect, RectStyle)> {
+    (0..ammo)
+        .map(|i| {
+            (
+                UiRect {
+                    x: 38.0 + 5.0 * i as f32,
+                    y: 12.0,
+                    w: 4.0,
+                    h: 4.0,
+                },
+                RectStyle {
+                    fill_color: [0.9, 0.75, 0.5, 1.0],
+                    outline_color: [0.45, 0.57, 0.58, 0.92],
+                    outline_px: 1.0,
+                },
+            )
+        })
+        .collect()
+}
 
-        self.score = scoring::height_score(self.progress_height);
-    }
+/// HUD fuel gauge under the score.
+fn fuel_gauge(fraction: f32) -> Vec<(UiRect, RectStyle)> {
+    hud_gauge(12.0, fraction, [0.9, 0.6, 0.2, 1.0])
+}
 
-    fn update_camera(&mut self, params: &game_params::GameParams) {
-        let live_height = self.ship_state.position[1].floor() as i32;
-        let camera_height = std::cmp::max(live_height, self.progress_height);
-        self.viewport_offset = camera_height - (params.viewport_height / 2) as i32;
-    }
+/// HUD hull integrity gauge under the fuel gauge.
+fn hull_gauge(fraction: f32) -> Vec<(UiRect, RectStyle)> {
+    hud_gauge(18.0, fraction, [0.5, 0.8, 0.9, 1.0])
+}
 
-    /// Advance ship physics one frame. Returns the death cause if the ship
-    /// flew off the playfield this step.
-    fn update_ship(
-        &mut self,
-        params: &game_params::GameParams,
-        input: &InputState,
-        dt: f32,
-    ) -> Option<DeathCause> {
-        let gravity = params.particle_system_params.gravity;
-
-        let rotate = if let Some(target) = input.target_heading {
-            // Bang-bang controller: rotate at full speed toward target heading,
-            // stop when within one frame's worth of rotation to avoid oscillation.
-            let current = self.ship_state.orientation;
-            let error = angle_diff(target, current);
-            let dead_zone = self.ship_state.rotation_rate * dt;
-            if error.abs() <= dead_zone {
-                0.0
-            } else {
-                error.signum()
-            }
+/// An outlined HUD bar at `y` that empties right to left and turns red when
+/// nearly empty.
+fn hud_gauge(y: f32, fraction: f32, fill_color: [f32; 4]) -> Vec<(UiRect, RectStyle)> {
+    let frame = UiRect {
+        x: 2.0,
+        y,
+        w: 32.0,
+        h: 4.0,
+    };
+    let mut rects = vec![(
+        frame,
+        RectStyle {
+            fill_color: [0.02, 0.05, 0.07, 0.68],
+            outline_color: [0.45, 0.57, 0.58, 0.92],
+            outline_px: 1.0,
+        },
+    )];
+    // Inside the outline. The renderer draws at least a pixel, so an empty
+    // bar gets no fill rect at all.
+    let fill_w = (frame.w - 2.0) * fraction;
+    if fill_w >= 0.5 {
+        let color = if fraction <= 0.25 {
+            [0.95, 0.3, 0.25, 1.0]
         } else {
-            input.rotate
+            fill_color
         };
-        self.ship_state.update(dt, input.thrust, rotate, gravity);
-
-        // Kill if the ship flies off the horizontal edges.
-        let x = self.ship_state.position[0];
-        if x < 0.0 || x >= params.level_width as f32 {
-            return Some(DeathCause::FellOff);
-        }
-
-        // Kill if the ship falls more than one viewport-height below the
-        // bottom of the visible window. At default gravity and viewport
-        // height, that is ~2.8 s of free-fall from rest from the viewport
-        // bottom — enough to recover from a stall, but not from a sustained
-        // fall.
-        let bottom = self.viewport_offset as f32;
-        let viewport_h = params.viewport_height as f32;
-        if self.ship_state.position[1] < bottom - viewport_h {
-            return Some(DeathCause::FellOff);
-        }
-        None
+        rects.push((
+            UiRect {
+                x: frame.x + 1.0,
+                y: frame.y + 1.0,
+                w: fill_w,
+                h: frame.h - 2.0,
+            },
+            RectStyle {
+                fill_color: color,
+                outline_color: color,
+                outline_px: 0.0,
+            },
+        ));
     }
+    rects
+}
 
-    /// Advance one frame of gameplay. Returns the death cause if any check
-    /// killed the ship this step (timer expiry, out-of-bounds).
-    fn update(
-        &mut self,
-        params: &game_params::GameParams,
-        input: &InputState,
-        game_dt: f32,
-        game_dt_duration: Duration,
-    ) -> Option<DeathCause> {
-        self.timer_elapsed = self.timer_elapsed.saturating_add(game_dt_duration);
-        let timer_budget = self
-            .timer_budget
-            .max(scoring::level_time_limit_duration(params));
-        if self.timer_elapsed >= timer_budget {
-            log::info!(
-                "Level timer expired on level {}",
-                self.current_level_index + 1,
-            );
-            return Some(DeathCause::TimeExpired);
-        }
-
-        if game_dt <= 0.0 {
-            return None;
-        }
-
-        let prev_ship = self.ship_state;
-        self.prev_ship_state = prev_ship;
-        if let Some(cause) = self.update_ship(params, input, game_dt) {
-            return Some(cause);
-        }
-        self.update_camera(params);
-        record_collision_motion(
-            &mut self.pending_collision_segment,
-            prev_ship,
-            self.ship_state,
-        );
-        None
+/// HUD text for a recently collected pickup.
+fn pickup_notice(play: &Play, params: &game_params::GameParams) -> Option<String> {
+    let (kind, collected_at) = play.last_pickup?;
+    if play.timer_elapsed.saturating_sub(collected_at) >= PICKUP_NOTICE_TIME {
+        return None;
     }
+    let max = params.pickup_params.max_upgrades;
+    let upgrade = |name: &str, level: u32| {
+        let suffix = if level >= max { "MAX" } else { "UP" };
+        format!("{name} {suffix}")
+    };
+    Some(match kind {
+        PickupKind::TimeBonus => {
+            format!("+{:.0} SEC", params.pickup_params.time_bonus_seconds)
+        }
+        PickupKind::Fuel => "FUEL".to_string(),
+        PickupKind::NozzleSpeed => upgrade("SPEED", play.nozzle.speed),
+        PickupKind::NozzleLife => upgrade("RANGE", play.nozzle.life),
+        PickupKind::NozzleSpread => upgrade("SPREAD", play.nozzle.spread),
+    })
+}
 
-    /// Apply a GPU collision-detection readback to current play state.
-    /// Returns `Some(DeathCause::Collided)` if the ship hit terrain.
-    fn resolve_collision_result(
-        &mut self,
-        params: &game_params::GameParams,
-        result: collision::CollisionResult,
-    ) -> Option<DeathCause> {
-        let segment = self.in_flight_collision_segment.take()?;
-        if result.hit {
-            let impact_ship = segment.ship_at(result.impact_t);
-            self.commit_progress_height(params, impact_ship.position[1]);
-            self.prev_ship_state = segment.prev_ship;
-            self.ship_state = impact_ship;
-            self.pending_collision_segment = None;
-            log::info!(
-                "Ship collided with terrain at ({:.0}, {:.0}) t={:.3}",
-                impact_ship.position[0],
-                impact_ship.position[1],
-                result.impact_t
-            );
-            Some(DeathCause::Collided)
-        } else {
-            self.commit_progress_height(params, segment.next_ship.position[1]);
-            None
+/// Keep one terrain query in flight over the enemies' surroundings, handing
+/// each result to them as it lands.
+fn watch_terrain(
+    play: &mut Play,
+    level_manager: &mut level_manager::LevelManager,
+    params: &game_params::GameParams,
+) {
+    if let Some(id) = play.terrain_query {
+        match level_manager.poll_terrain_query(id) {
+            None => return,
+            Some(Ok(snapshot)) => play.observe_terrain(snapshot),
+            // Nothing to observe this time; the next query asks again.
+            Some(Err(err)) => log::warn!("{err}"),
         }
     }
+    play.terrain_query = (play.needs_terrain() || play.can_bounce())
+        .then(|| level_manager.query_terrain(play.watch_region(params)));
 }
 
 struct Spout {
@@ -443,9 +435,28 @@ struct Spout {
     /// into `GameOver`; consumed by the render pass.
     pending_explosion: Option<ExplosionRequest>,
     collector: InputCollector,
+    /// `--record`: where to write each play session's input when it ends.
+    record_path: Option<std::path::PathBuf>,
+    /// Input captured for the current play session when recording.
+    recording: Option<replay::InputRecording>,
+    /// `--replay`: recorded session feeding `update_phase` in place of
+    /// `collector`. Dropped once exhausted, handing control back to live input.
+    playback: Option<replay::InputPlayback>,
+    /// Editor set aside while test-flying its level, or after leaving it, so
+    /// the edits survive until the next visit.
+    parked_editor: Option<EditorScreen>,
+    /// The current play session is an editor test flight; ending it returns
+    /// to the editor instead of the title.
+    test_flight: bool,
+    /// Roster index of the ship last picked on the title screen, which the
+    /// ship select returns to and editor test flights fly.
+    selected_ship: usize,
     level_manager: level_manager::LevelManager,
     particle_system: particles::ParticleSystem,
     collision_detector: collision::CollisionDetector,
+    entity_hit_detector: entity_hits::EntityHitDetector,
+    falling_sand: sand::FallingSand,
+    crater_pass: crater::CraterPass,
     audio: audio::AudioPlayer,
     game_time: Duration,
     iteration_start: Instant,
@@ -459,7 +470,11 @@ struct Spout {
 impl Spout {
     /// Reset to the title screen. Used at startup and after death/restart.
     fn transition_to_title(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
-        self.state = AppState::Title(TitleScreen::default());
+        self.finish_recording();
+        if let AppState::Editor(editor) = std::mem::take(&mut self.state) {
+            self.parked_editor = Some(editor);
+        }
+        self.state = AppState::Title(TitleScreen::new(self.selected_ship));
         self.game_time = Duration::default();
         self.iteration_start = Instant::now();
 
@@ -478,7 +493,7 @@ impl Spout {
             &mut init_encoder,
             &self.level_manager,
         );
-        self.collision_detector.result = collision::CollisionResult::default();
+        self.collision_detector.reset();
         self.particle_system.set_nozzle_speed(75.0, 75.0);
 
         self.graphics.staging_belt.finish();
@@ -488,18 +503,115 @@ impl Spout {
         log::info!("Entered title screen");
     }
 
-    /// Start a fresh game from the title (or after game-over).
-    fn transition_to_play(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
-        self.state = AppState::Playing(Play::new(&self.game_params));
+    /// Start a fresh game from the title (or after game-over) flying roster
+    /// ship `ship`. A replay flies the ship it was recorded with instead.
+    fn transition_to_play(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, ship: usize) {
+        self.finish_recording();
+        let ship = match &self.playback {
+            Some(playback) => playback.ship() as usize,
+            None => ship,
+        };
+        let mut play = Play::new(&self.game_params, ship);
+        if let Some(playback) = &self.playback {
+            play.seed = playback.seed();
+        }
+        if self.record_path.is_some() {
+            self.recording = Some(replay::InputRecording::new(
+                play.seed,
+                ship as u32,
+                &self.game_params,
+            ));
+        }
+        self.start_play(device, queue, play, None);
+    }
+
+    /// Fly the editor's level as level 0 of a fresh game. Not recorded: the
+    /// level only exists in the editor, so a replay could not rebuild it.
+    fn transition_to_test_flight(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let AppState::Editor(editor) = std::mem::take(&mut self.state) else {
+            return;
+        };
+        let first_level = editor.level().to_level();
+        self.parked_editor = Some(editor);
+        self.test_flight = true;
+        let play = Play::new(&self.game_params, self.selected_ship);
+        self.start_play(device, queue, play, Some(first_level));
+    }
+
+    /// Enter `Playing` with `play`, rebuilding the terrain (optionally with a
+    /// given level 0) and particles for its seed.
+    fn start_play(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        play: Play,
+        first_level: Option<(Vec<i32>, Vec<u32>)>,
+    ) {
+        let seed = play.seed;
+        self.state = AppState::Playing(play);
         self.game_time = Duration::default();
         self.iteration_start = Instant::now();
 
         let mut init_encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        self.level_manager = level_manager::LevelManager::init(
+        self.level_manager = match first_level {
+            Some(first_level) => level_manager::LevelManager::init_with_first_level(
+                device,
+                &self.game_params,
+                seed,
+                first_level,
+                0,
+                &mut init_encoder,
+                &mut self.graphics.staging_belt,
+            ),
+            None => level_manager::LevelManager::init(
+                device,
+                &self.game_params,
+                seed,
+                0,
+                &mut init_encoder,
+                &mut self.graphics.staging_belt,
+            ),
+        };
+        self.particle_system = particles::ParticleSystem::new(
+            device,
+            &self.game_params,
+            &mut init_encoder,
+            &self.level_manager,
+        );
+        self.collision_detector.reset();
+        let base_speed = self.game_params.particle_system_params.emission_speed;
+        self.particle_system
+            .set_nozzle_speed(base_speed, base_speed);
+
+        self.graphics.staging_belt.finish();
+        queue.submit(Some(init_encoder.finish()));
+        self.graphics.staging_belt.recall();
+
+        log::info!("Game started (seed {seed})");
+    }
+
+    /// Open the level editor, resuming the parked one if there is one.
+    fn transition_to_editor(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.finish_recording();
+        self.test_flight = false;
+        let editor = self
+            .parked_editor
+            .take()
+            .unwrap_or_else(|| EditorScreen::new(&self.game_params));
+        let first_level = editor.level().to_level();
+        self.state = AppState::Editor(editor);
+        self.game_time = Duration::default();
+        self.iteration_start = Instant::now();
+
+        let mut init_encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.level_manager = level_manager::LevelManager::init_with_first_level(
             device,
             &self.game_params,
             0,
+            first_level,
+            0,
             &mut init_encoder,
             &mut self.graphics.staging_belt,
         );
@@ -509,16 +621,13 @@ impl Spout {
             &mut init_encoder,
             &self.level_manager,
         );
-        self.collision_detector.result = collision::CollisionResult::default();
-        let base_speed = self.game_params.particle_system_params.emission_speed;
-        self.particle_system
-            .set_nozzle_speed(base_speed, base_speed);
+        self.collision_detector.reset();
 
         self.graphics.staging_belt.finish();
         queue.submit(Some(init_encoder.finish()));
         self.graphics.staging_belt.recall();
 
-        log::info!("Game started");
+        log::info!("Entered level editor");
     }
 
     /// Take the current `Play` session and move it into `AppState::GameOver`,
@@ -533,9 +642,9 @@ impl Spout {
                 return;
             }
         };
+        self.finish_recording();
         // Drop any in-flight collision pipelining; the result no longer matters.
-        play.pending_collision_segment = None;
-        play.in_flight_collision_segment = None;
+        play.collisions.clear();
         self.pending_explosion = Some(ExplosionRequest {
             position: play.ship_state.position,
             velocity: play.ship_state.velocity,
@@ -565,9 +674,47 @@ impl Spout {
         };
     }
 
+    /// End the current play session's recording, if any, and write it to the
+    /// `--record` path. Also called on quit, so closing the window mid-run
+    /// keeps the run.
+    fn finish_recording(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        let Some(path) = &self.record_path else {
+            return;
+        };
+        match recording.save(path) {
+            Ok(()) => log::info!(
+                "Wrote {} recorded frames (seed {}) to {}",
+                recording.frames.len(),
+                recording.seed,
+                path.display()
+            ),
+            Err(err) => log::warn!("Failed to write recording {}: {err}", path.display()),
+        }
+    }
+
+    /// Next replayed frame while a play session is running. Recordings cover
+    /// only `Playing` / `Paused` frames, so title and game-over frames keep
+    /// reading live input.
+    fn next_replay_frame(&mut self) -> Option<replay::RecordedFrame> {
+        if !matches!(self.state, AppState::Playing(_) | AppState::Paused(_)) {
+            return None;
+        }
+        let playback = self.playback.as_mut()?;
+        let frame = playback.next_frame();
+        if playback.finished() {
+            log::info!("Replay finished; returning to live input");
+            self.playback = None;
+        }
+        frame
+    }
+
     /// Compute the per-frame game / wall delta times. Wall time always
-    /// advances; game time freezes while paused.
-    fn tick_time(&mut self) -> (Duration, Duration) {
+    /// advances; game time freezes while paused. `replay_dt` replaces the
+    /// measured game dt when replaying a recording.
+    fn tick_time(&mut self, replay_dt: Option<Duration>) -> (Duration, Duration) {
         let now = Instant::now();
         let delta_t = (now - self.iteration_start).min(MAX_FRAME_DT);
         self.iteration_start = now;
@@ -576,24 +723,43 @@ impl Spout {
         if paused {
             (Duration::ZERO, delta_t)
         } else {
-            self.game_time += delta_t;
-            (delta_t, delta_t)
+            let game_dt = replay_dt.unwrap_or(delta_t);
+            self.game_time += game_dt;
+            (game_dt, delta_t)
         }
     }
 
-    /// Poll last frame's GPU collision readback. Results still resolve while
-    /// paused so in-flight collision segments do not block future dispatches
-    /// after unpausing.
+    /// Poll earlier frames' GPU collision readbacks, oldest first. Results
+    /// still resolve while paused so in-flight collision segments do not block
+    /// future dispatches after unpausing.
     fn resolve_pending_collision(&mut self) {
-        let Some(result) = self.collision_detector.poll_result() else {
+        while let Some(result) = self.collision_detector.poll_result() {
+            let collision_death = self
+                .state
+                .resolve_collision_result(&self.game_params, result);
+            if let Some(cause) = collision_death {
+                self.transition_to_game_over(cause);
+                return;
+            }
+            // A bounce drops the segments swept after it; their readbacks
+            // would otherwise be matched to the segments flown after it.
+            if let AppState::Playing(play) | AppState::Paused(play) = &mut self.state {
+                if play.collisions.take_dropped_in_flight() {
+                    self.collision_detector.reset();
+                }
+            }
+        }
+    }
+
+    /// Poll the last exhaust-vs-enemy hit count readback and damage the
+    /// enemies it hit.
+    fn resolve_enemy_hits(&mut self) {
+        let Some(hits) = self.entity_hit_detector.poll_result() else {
             return;
         };
-
-        let collision_death = self
-            .state
-            .resolve_collision_result(&self.game_params, result);
-        if let Some(cause) = collision_death {
-            self.transition_to_game_over(cause);
+        if let AppState::Playing(play) | AppState::Paused(play) = &mut self.state {
+            play.enemies
+                .apply_hits(&self.game_params.enemy_params, &hits);
         }
     }
 
@@ -639,12 +805,17 @@ impl Spout {
             AppState::Title(_) => {
                 self.particle_system.update_state(
                     game_dt,
-                    0,
-                    Some(title_emitter_motion(&self.game_params)),
+                    [0, 0],
+                    &[(
+                        EmitterKind::Exhaust,
+                        title_emitter_motion(&self.game_params),
+                    )],
                 );
                 None
             }
             AppState::Playing(play) => {
+                play.spawn_entities(&self.game_params, &mut self.level_manager.level_maker);
+                watch_terrain(play, &mut self.level_manager, &self.game_params);
                 let prev_ship = play.ship_state;
                 let cause = play.update(
                     &self.game_params,
@@ -652,23 +823,35 @@ impl Spout {
                     game_dt,
                     game_dt_duration,
                 );
-                let motion = if cause.is_none() {
-                    ship_emitter_motion(&prev_ship, &play.ship_state, self.input_state.thrust)
+                apply_nozzle_upgrades(&mut self.particle_system, play, &self.game_params);
+                let emissions: Vec<_> = if cause.is_none() {
+                    let exhaust = ship_emitter_motion(
+                        &prev_ship,
+                        &play.ship_state,
+                        play.available_thrust(self.input_state.thrust),
+                    );
+                    let gun =
+                        gun_emitter_motion(&prev_ship, &play.ship_state, self.input_state.fire);
+                    [(EmitterKind::Exhaust, exhaust), (EmitterKind::Gun, gun)]
+                        .into_iter()
+                        .filter_map(|(kind, motion)| Some((kind, motion?)))
+                        .collect()
                 } else {
-                    None
+                    Vec::new()
                 };
                 self.particle_system
-                    .update_state(game_dt, play.viewport_offset, motion);
+                    .update_state(game_dt, play.viewport_offset(), &emissions);
                 cause
             }
             AppState::Paused(_)
             | AppState::GameOver { .. }
+            | AppState::Editor(_)
             | AppState::Settings
             | AppState::Leaderboard => {
                 // No simulation step. Particles continue to animate without
                 // a new emitter motion (drifting from previous frame's state).
                 let offset = self.state.viewport_offset();
-                self.particle_system.update_state(game_dt, offset, None);
+                self.particle_system.update_state(game_dt, offset, &[]);
                 None
             }
         }
@@ -693,7 +876,11 @@ impl Spout {
             self.audio.is_playing(),
             (surface_w, surface_h),
         ) {
-            Some(TitleAction::StartGame) => Some(PendingTransition::ToPlay),
+            Some(TitleAction::StartGame { ship }) => {
+                self.selected_ship = ship;
+                Some(PendingTransition::ToPlay { ship })
+            }
+            Some(TitleAction::OpenEditor) => Some(PendingTransition::ToEditor),
             Some(TitleAction::ToggleMusic) => {
                 self.audio.toggle();
                 None
@@ -702,6 +889,30 @@ impl Spout {
         }
     }
 
+    /// Editor input. Painting and file import/export stay inside the screen;
+    /// test flights and leaving need new GPU resources.
+    fn process_editor_input(
+        &mut self,
+        input: InputFrame,
+        surface_size: (u32, u32),
+        wall_dt: f32,
+    ) -> Option<PendingTransition> {
+        let AppState::Editor(editor) = &mut self.state else {
+            return None;
+        };
+
+        match editor.update(
+            input,
+            &self.game_params,
+            &self.graphics.game_text,
+            surface_size,
+            wall_dt,
+        )? {
+            EditorAction::TestFly => Some(PendingTransition::ToTestFlight),
+            EditorAction::Exit => Some(PendingTransition::ToTitle),
+        }
+    }
+
     /// Pure-CPU update phase. Snapshots input, ticks time, drives simulation,
     /// applies non-GPU transitions inline (pause toggle, GameOver), and
     /// returns a GPU-bound transition intent (ToTitle / ToPlay) if any.
@@ -712,9 +923,14 @@ impl Spout {
     ) -> Option<PendingTransition> {
         self.audio.poll();
         self.resolve_pending_collision();
+        self.resolve_enemy_hits();
 
+        let replayed = self.next_replay_frame();
         self.prev_input_state = self.input_state;
-        self.input_state = self.collector.current_state();
+        self.input_state = match replayed {
+            Some(frame) => frame.input,
+            None => self.collector.current_state(),
+        };
         let input = InputFrame::new(self.input_state, self.prev_input_state);
 
         self.handle_global_input(window, input);
@@ -724,7 +940,13 @@ impl Spout {
             .level_maker
             .work_until(Instant::now() + LEVEL_BUDGET);
 
-        let (game_dt_duration, wall_dt_duration) = self.tick_time();
+        let (game_dt_duration, wall_dt_duration) =
+            self.tick_time(replayed.map(|frame| frame.game_dt));
+        if matches!(self.state, AppState::Playing(_) | AppState::Paused(_)) {
+            if let Some(recording) = &mut self.recording {
+                recording.push(&self.input_state, game_dt_duration);
+            }
+        }
         let game_dt = game_dt_duration.as_secs_f32();
         let wall_dt = wall_dt_duration.as_secs_f32();
         self.tick_wall_dt = wall_dt;
@@ -737,13 +959,17 @@ impl Spout {
             .renderer
             .update_state(wall_dt, &input.current, &input.previous);
 
-        // Restart from game-over: explicit restart key, or any tap.
+        // Restart from game-over: explicit restart key, or any tap. The editor
+        // paints with taps and leaves through its own button.
         let restart_pressed = input.restart_pressed()
             || (matches!(self.state, AppState::GameOver { .. }) && input.touch_started());
-        if restart_pressed {
+        if restart_pressed && !self.state.is_editor() {
             return Some(PendingTransition::ToTitle);
         }
 
+        if let Some(t) = self.process_editor_input(input, surface_size, wall_dt) {
+            return Some(t);
+        }
         self.process_title_input(input, surface_size.0, surface_size.1)
     }
 
@@ -754,8 +980,14 @@ impl Spout {
         queue: &wgpu::Queue,
     ) {
         match t {
+            // Ending a test flight goes back to the editor.
+            PendingTransition::ToTitle if self.test_flight => {
+                self.transition_to_editor(device, queue)
+            }
             PendingTransition::ToTitle => self.transition_to_title(device, queue),
-            PendingTransition::ToPlay => self.transition_to_play(device, queue),
+            PendingTransition::ToPlay { ship } => self.transition_to_play(device, queue, ship),
+            PendingTransition::ToEditor => self.transition_to_editor(device, queue),
+            PendingTransition::ToTestFlight => self.transition_to_test_flight(device, queue),
         }
     }
 }
@@ -769,7 +1001,20 @@ impl Spout {
         window: &winit::window::Window,
     ) -> Self {
         window.set_cursor_visible(false);
-        let game_params = game_params::get_game_config_from_default_file();
+        #[allow(unused_mut)]
+        let mut game_params = game_params::get_game_config_from_default_file();
+        #[cfg(not(target_arch = "wasm32"))]
+        let command_line = match cli::CommandLine::parse(std::env::args().skip(1)) {
+            Ok(command_line) => command_line,
+            Err(err) => {
+                log::warn!("Ignoring command line: {err}");
+                cli::CommandLine::default()
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        let command_line = cli::CommandLine::default();
+        command_line.apply_to(&mut game_params);
+        let playback = load_playback(&command_line, &game_params);
         let mut graphics = Graphics::new(config, adapter, device, queue, &game_params);
 
         let mut init_encoder =
@@ -781,6 +1026,7 @@ impl Spout {
             device,
             &game_params,
             0,
+            0,
             &mut init_encoder,
             &mut graphics.staging_belt,
         );
@@ -789,6 +1035,9 @@ impl Spout {
             particles::ParticleSystem::new(device, &game_params, &mut init_encoder, &level_manager);
 
         let collision_detector = collision::CollisionDetector::init(device);
+        let entity_hit_detector = entity_hits::EntityHitDetector::init(device);
+        let falling_sand = sand::FallingSand::init(device);
+        let crater_pass = crater::CraterPass::init(device);
 
         graphics.staging_belt.finish();
         queue.submit(Some(init_encoder.finish()));
@@ -805,6 +1054,7 @@ impl Spout {
 
         #[cfg(not(target_arch = "wasm32"))]
         {
+            collector.init_gamepads();
             collector.set_surface_width(config.width as f32);
             collector.set_surface_height(config.height as f32);
         }
@@ -824,11 +1074,20 @@ impl Spout {
             prev_input_state: InputState::default(),
             pending_explosion: None,
             collector,
+            record_path: command_line.record,
+            recording: None,
+            playback,
+            parked_editor: None,
+            test_flight: false,
+            selected_ship: 0,
             level_manager,
             game_time: Duration::default(),
             iteration_start: Instant::now(),
             particle_system,
             collision_detector,
+            entity_hit_detector,
+            falling_sand,
+            crater_pass,
             audio,
             graphics,
             frame_times: Vec::with_capacity(60),
@@ -837,6 +1096,9 @@ impl Spout {
             frame_log_count: 0,
         };
         spout.transition_to_title(device, queue);
+        if spout.playback.is_some() {
+            spout.transition_to_play(device, queue, 0);
+        }
         spout
     }
 
@@ -879,6 +1141,8 @@ impl Spout {
         let pending = self.update_phase(window, surface_size);
         if let Some(t) = pending {
             self.apply_transition(t, device, queue);
+            // The pointer is hidden everywhere except in the editor.
+            window.set_cursor_visible(self.state.is_editor());
         }
         self.draw_phase(view, device, queue, window);
         self.post_phase(cpu_start, window);
@@ -907,46 +1171,131 @@ impl Spout {
             &mut self.graphics.staging_belt,
         );
 
+        // Edited terrain goes up before composing so this frame shows it.
+        if let AppState::Editor(editor) = &mut self.state {
+            if let Some(level) = editor.take_changes() {
+                let (data, materials) = level.cells();
+                self.level_manager.replace_level(
+                    0,
+                    data,
+                    materials,
+                    &mut encoder,
+                    &mut self.graphics.staging_belt,
+                );
+            }
+        }
+
         // Ship explosion burst — write particles before compute runs.
+        let system_params = &self.game_params.particle_system_params;
         if let Some(explosion) = self.pending_explosion.take() {
             self.particle_system.emit_burst(
                 &mut encoder,
                 &mut self.graphics.staging_belt,
+                ParticleType::Debris,
+                explosion.position,
+                explosion.velocity,
+                40000, // burst count
+                system_params.emission_speed,
+                system_params.max_particle_life,
+            );
+            // A slower puff of smoke that lingers after the debris settles.
+            self.particle_system.emit_burst(
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                ParticleType::Smoke,
                 explosion.position,
                 explosion.velocity,
-                50000, // burst count
-                self.game_params.particle_system_params.emission_speed,
-                self.game_params.particle_system_params.max_particle_life,
+                10000,
+                system_params.emission_speed / 4.0,
+                system_params.max_particle_life,
+            );
+        }
+
+        // Bombs that went off this tick: a burst now, a crater once the
+        // terrain is composed.
+        let detonations = match &mut self.state {
+            AppState::Playing(play) => play.bombs.take_detonations(),
+            _ => Vec::new(),
+        };
+        let bomb_params = &self.game_params.bomb_params;
+        for &position in &detonations {
+            self.particle_system.emit_burst(
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                ParticleType::Debris,
+                position,
+                [0.0, 0.0],
+                bomb_params.burst_particles,
+                system_params.emission_speed,
+                system_params.max_particle_life,
+            );
+            self.particle_system.emit_burst(
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                ParticleType::Smoke,
+                position,
+                [0.0, 0.0],
+                bomb_params.burst_particles / 4,
+                system_params.emission_speed / 4.0,
+                system_params.max_particle_life,
             );
         }
 
         // Compute pipelines.
         self.level_manager.compose_tiles(&mut encoder);
+        for &center in &detonations {
+            self.crater_pass.run(
+                device,
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                self.level_manager.terrain_buffer(),
+                self.game_params.level_width,
+                &Crater {
+                    center,
+                    radius: bomb_params.crater_radius,
+                    damage: bomb_params.crater_damage,
+                },
+            );
+        }
         self.particle_system.run_compute(
             &self.level_manager,
             &mut encoder,
             &mut self.graphics.staging_belt,
         );
+        // Sand stays put while editing so the painted level is what flies.
+        if self.game_params.level_params.falling_sand && !self.state.is_editor() {
+            self.falling_sand.run(
+                device,
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                self.level_manager.terrain_buffer(),
+                self.game_params.level_width,
+            );
+        }
 
         // Collision detection — only during active gameplay.
         if let AppState::Playing(play) = &mut self.state {
-            if play.in_flight_collision_segment.is_none() {
-                if let Some(segment) = play.pending_collision_segment.take() {
-                    let dispatched = self.collision_detector.dispatch(
-                        device,
-                        &mut encoder,
-                        &mut self.graphics.staging_belt,
-                        &segment.next_ship,
-                        &segment.prev_ship,
-                        self.level_manager.terrain_buffer(),
-                        self.game_params.level_width,
-                    );
-                    if dispatched {
-                        play.in_flight_collision_segment = Some(segment);
-                    } else {
-                        play.pending_collision_segment = Some(segment);
-                    }
+            self.entity_hit_detector.dispatch(
+                device,
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+                &self.particle_system,
+                &play.enemies.hit_targets(),
+            );
+            // One dispatch per frame segment, until the readback ring fills.
+            while let Some(segment) = play.collisions.next_queued() {
+                let dispatched = self.collision_detector.dispatch(
+                    device,
+                    &mut encoder,
+                    &mut self.graphics.staging_belt,
+                    segment,
+                    self.level_manager.terrain_buffer(),
+                    self.game_params.level_width,
+                );
+                if !dispatched {
+                    break;
                 }
+                play.collisions.dispatch_next();
             }
         }
 
@@ -980,6 +1329,38 @@ impl Spout {
                 },
             });
         }
+        if let AppState::Editor(editor) = &self.state {
+            editor.prepare_ui(EditorUiRenderContext {
+                device,
+                encoder: &mut encoder,
+                title_ui_view: &self.graphics.title_ui_view,
+                ui: &self.graphics.ui,
+                params: &self.game_params,
+                text: &self.graphics.game_text,
+            });
+        }
+
+        // Enemies, projectiles and pickups stay in the world after death.
+        let world_play = match &self.state {
+            AppState::Playing(p) | AppState::Paused(p) => Some(p),
+            AppState::GameOver { play, .. } => Some(play),
+            _ => None,
+        };
+        if let Some(play) = world_play {
+            let mut boxes = play.turrets.render_boxes();
+            boxes.extend(play.enemies.render_boxes(&self.game_params.enemy_params));
+            boxes.extend(play.pickups.render_boxes());
+            boxes.extend(play.bombs.render_boxes());
+            self.graphics.entity_renderer.render(
+                device,
+                &boxes,
+                &self.game_params,
+                play.viewport_offset(),
+                &self.graphics.game_view_texture,
+                &mut encoder,
+                &mut self.graphics.staging_belt,
+            );
+        }
 
         // Ship — only during active gameplay or pause.
         if self.game_params.render_ship {
@@ -991,7 +1372,7 @@ impl Spout {
                 self.graphics.ship_renderer.render(
                     &play.ship_state,
                     &self.game_params,
-                    play.viewport_offset,
+                    play.viewport_offset(),
                     &self.graphics.game_view_texture,
                     &mut encoder,
                     &mut self.graphics.staging_belt,
@@ -1010,7 +1391,7 @@ impl Spout {
         self.graphics.bloom.render(&mut encoder);
         self.graphics.renderer.render(view, &mut encoder);
 
-        if self.state.is_title() {
+        if self.state.is_title() || self.state.is_editor() {
             self.graphics.title_overlay.render(view, &mut encoder);
         }
 
@@ -1028,6 +1409,8 @@ impl Spout {
         }
 
         self.level_manager.decompose_tiles(&mut encoder);
+        self.level_manager
+            .dispatch_terrain_queries(device, &mut encoder);
 
         // FPS overlay (debug builds). Computes avg_dt now so the on-screen
         // text uses up-to-date numbers; the same avg_dt is reused for the
@@ -1069,6 +1452,8 @@ impl Spout {
         // submitted. On native the callback fires during the next poll(); on
         // WASM it fires asynchronously before the next frame.
         self.collision_detector.start_readback();
+        self.entity_hit_detector.start_readback();
+        self.level_manager.start_readbacks();
     }
 
     /// Score / level / timer text + game-over overlay. Drawn whenever a `Play`
@@ -1113,12 +1498,37 @@ impl Spout {
             ],
         );
 
+        let mut gauges = [fuel_gauge(play.fuel_fraction()), bomb_ammo(play.bombs.ammo)].concat();
+        // Without hull integrity every hit is fatal, so there is nothing to show.
+        if play.ship.ship_params.hull_points > 0.0 {
+            gauges.extend(hull_gauge(play.hull_fraction()));
+        }
+        self.graphics
+            .ui
+            .draw_rects(device, encoder, &self.graphics.game_view_texture, &gauges);
+
+        if let Some(notice) = pickup_notice(play, &self.game_params) {
+            let notice_x = (self.graphics.game_text.surface_width
+                - self.graphics.game_text.text_width(&notice, 1.0))
+                / 2.0;
+            self.graphics.game_text.draw(
+                device,
+                encoder,
+                &self.graphics.game_view_texture,
+                &[(&notice, notice_x, 26.0, 1.0, [0.6, 0.95, 0.7, 1.0])],
+            );
+        }
+
         if let Some(cause) = cause {
             let w = self.graphics.game_text.surface_width;
             let h = self.graphics.game_text.surface_height;
             let status = match cause {
                 DeathCause::TimeExpired => "TIMES UP",
-                DeathCause::Collided | DeathCause::FellOff => "GAME OVER",
+                DeathCause::OutOfFuel => "OUT OF FUEL",
+                DeathCause::Collided
+                | DeathCause::FellOff
+                | DeathCause::Shot
+                | DeathCause::Rammed => "GAME OVER",
             };
             let status_x = (w - self.graphics.game_text.text_width(status, 1.0)) / 2.0;
             let status_y = h * 0.28;
@@ -1127,6 +1537,10 @@ impl Spout {
             let score_x = (w - self.graphics.game_text.text_width(&score, 1.0)) / 2.0;
             let score_y = h * 0.5;
 
+            let seed = format!("SEED: {}", play.seed);
+            let seed_x = (w - self.graphics.game_text.text_width(&seed, 1.0)) / 2.0;
+            let seed_y = h * 0.59;
+
             let restart = restart_prompt();
             let restart_x = (w - self.graphics.game_text.text_width(restart, 1.0)) / 2.0;
             let restart_y = h * 0.68;
@@ -1138,6 +1552,7 @@ impl Spout {
                 &[
                     (status, status_x, status_y, 1.0, text_color),
                     (&score, score_x, score_y, 1.0, text_color),
+                    (&seed, seed_x, seed_y, 1.0, text_color),
                     (restart, restart_x, restart_y, 1.0, text_color),
                 ],
             );
@@ -1182,158 +1597,7 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
-    use super::{angle_diff, record_collision_motion, AppState, PendingCollisionSegment, Play};
-    use std::{f32::consts::PI, time::Duration};
-
-    fn approx(a: f32, b: f32) -> bool {
-        (a - b).abs() < 1e-5
-    }
-
-    #[test]
-    fn same_angle_is_zero() {
-        assert!(approx(angle_diff(1.0, 1.0), 0.0));
-    }
-
-    #[test]
-    fn small_positive_difference() {
-        assert!(approx(angle_diff(1.0, 0.5), 0.5));
-    }
-
-    #[test]
-    fn small_negative_difference() {
-        assert!(approx(angle_diff(0.5, 1.0), -0.5));
-    }
-
-    #[test]
-    fn wraps_across_pi_boundary() {
-        // From just below π to just above π should be a small positive step,
-        // not a large negative one.
-        let diff = angle_diff(PI - 0.1, -(PI - 0.1));
-        assert!(diff.abs() < 0.3, "should wrap short way, got {}", diff);
-    }
-
-    #[test]
-    fn wraps_negative_direction() {
-        // From just above -π to just below -π.
-        let diff = angle_diff(-(PI - 0.1), PI - 0.1);
-        assert!(diff.abs() < 0.3, "should wrap short way, got {}", diff);
-    }
-
-    #[test]
-    fn opposite_directions_magnitude_is_pi() {
-        let diff = angle_diff(0.0, PI);
-        assert!(
-            approx(diff.abs(), PI),
-            "opposite angles should differ by π, got {}",
-            diff
-        );
-    }
-
-    #[test]
-    fn result_always_in_minus_pi_to_pi() {
-        for i in 0..100 {
-            let target = (i as f32) * 0.13 - 3.0;
-            let current = (i as f32) * 0.07 - 5.0;
-            let diff = angle_diff(target, current);
-            assert!(
-                diff >= -PI - 1e-5 && diff <= PI + 1e-5,
-                "angle_diff({}, {}) = {} out of range",
-                target,
-                current,
-                diff
-            );
-        }
-    }
-
-    #[test]
-    fn level_transition_banks_time_and_keeps_score_height_based() {
-        let mut params = spout::game_params::GameParams::default();
-        params.level_height = 100;
-        params.level_params.level_time_limit_seconds = 60.0;
-
-        let mut play = Play::new(&params);
-        play.timer_elapsed = Duration::from_secs(40);
-        play.commit_progress_height(&params, 100.0);
-
-        assert_eq!(play.current_level_index, 1);
-        assert_eq!(play.level_timer_remaining(&params), Duration::from_secs(80));
-        assert_eq!(play.score, 100);
-    }
-
-    #[test]
-    fn crossing_multiple_levels_adds_one_time_award_per_level() {
-        let mut params = spout::game_params::GameParams::default();
-        params.level_height = 100;
-        params.level_params.level_time_limit_seconds = 60.0;
-
-        let mut play = Play::new(&params);
-        play.timer_elapsed = Duration::from_secs(40);
-        play.commit_progress_height(&params, 250.0);
-
-        assert_eq!(play.current_level_index, 2);
-        assert_eq!(
-            play.level_timer_remaining(&params),
-            Duration::from_secs(140)
-        );
-        assert_eq!(play.score, 250);
-    }
-
-    #[test]
-    fn collision_segment_interpolates_position() {
-        let mut prev_ship = spout::ship::ShipState::default();
-        prev_ship.position = [10.0, 20.0];
-        let mut next_ship = prev_ship;
-        next_ship.position = [18.0, 36.0];
-
-        let segment = PendingCollisionSegment {
-            prev_ship,
-            next_ship,
-        };
-        let ship = segment.ship_at(0.25);
-
-        assert!(approx(ship.position[0], 12.0));
-        assert!(approx(ship.position[1], 24.0));
-    }
-
-    #[test]
-    fn collision_segment_clamps_impact_time() {
-        let mut prev_ship = spout::ship::ShipState::default();
-        prev_ship.position = [1.0, 2.0];
-        let mut next_ship = prev_ship;
-        next_ship.position = [3.0, 4.0];
-
-        let segment = PendingCollisionSegment {
-            prev_ship,
-            next_ship,
-        };
-
-        assert!(approx(segment.ship_at(-1.0).position[0], 1.0));
-        assert!(approx(segment.ship_at(2.0).position[1], 4.0));
-    }
-
-    #[test]
-    fn pending_collision_segment_extends_to_latest_motion() {
-        let mut a = spout::ship::ShipState::default();
-        a.position = [0.0, 0.0];
-        let mut b = a;
-        b.position = [1.0, 1.0];
-        let mut c = b;
-        c.position = [2.0, 3.0];
-        let mut d = c;
-        d.position = [4.0, 8.0];
-
-        let mut pending = None;
-        record_collision_motion(&mut pending, a, b);
-        record_collision_motion(&mut pending, b, c);
-        record_collision_motion(&mut pending, c, d);
-
-        let segment = pending.expect("segment");
-
-        assert!(approx(segment.prev_ship.position[0], 0.0));
-        assert!(approx(segment.prev_ship.position[1], 0.0));
-        assert!(approx(segment.next_ship.position[0], 4.0));
-        assert!(approx(segment.next_ship.position[1], 8.0));
-    }
+    use super::{AppState, Play};
 
     #[test]
     fn paused_state_resolves_in_flight_collision_result() {
@@ -1343,15 +1607,15 @@ mod tests {
         let mut next_ship = prev_ship;
         next_ship.position = [10.0, 42.0];
 
-        let mut state = AppState::Paused(Play {
+        let mut play = Play {
             ship_state: next_ship,
             prev_ship_state: prev_ship,
-            in_flight_collision_segment: Some(PendingCollisionSegment {
-                prev_ship,
-                next_ship,
-            }),
             ..Default::default()
-        });
+        };
+        play.collisions
+            .record(prev_ship, next_ship, Default::default());
+        play.collisions.dispatch_next();
+        let mut state = AppState::Paused(play);
 
         let death = state.resolve_collision_result(
             &params,
@@ -1366,7 +1630,7 @@ mod tests {
         let AppState::Paused(play) = state else {
             panic!("state should remain paused after a clean collision result");
         };
-        assert!(play.in_flight_collision_segment.is_none());
+        assert_eq!(play.collisions.num_in_flight(), 0);
         assert_eq!(play.progress_height, 42);
     }
 }
@@ -0,0 +1,118 @@
+//! Terrain materials. Every terrain cell carries a material ID alongside its
+//! health; the material decides how much health the cell starts with, how
+//! particles bounce off it, and how the terrain shader draws it.
+
+/// Terrain material IDs as stored in the material buffers. The discriminant is
+/// the value the shaders see, so it must match `terrain_material.wgsl.include`.
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Material {
+    #[default]
+    Rock = 0,
+    /// Soft: erodes quickly and soaks up bounces. The only loose material:
+    /// with `level_params.falling_sand` on, it falls into empty cells.
+    Dirt = 1,
+    /// Indestructible: particles bounce off without doing damage.
+    Metal = 2,
+    /// Brittle and springy. A hit that breaks a crystal cell also breaks any
+    /// crystal directly next to it.
+    Crystal = 3,
+}
+
+pub const NUM_MATERIALS: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MaterialProperties {
+    /// Multiplier on `level_params.starting_terrain_health`.
+    pub health_scale: f32,
+    /// Multiplier on `particle_system_params.elasticity`.
+    pub elasticity_scale: f32,
+    /// HDR color of exposed (edge) cells; values > 1.0 drive the bloom pass.
+    pub edge_color: [f32; 3],
+    /// Color of interior cells.
+    pub fill_color: [f32; 3],
+}
+
+impl Material {
+    pub const ALL: [Material; NUM_MATERIALS] = [
+        Material::Rock,
+        Material::Dirt,
+        Material::Metal,
+        Material::Crystal,
+    ];
+
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_id(id: u32) -> Option<Material> {
+        Material::ALL.get(id as usize).copied()
+    }
+
+    pub fn properties(self) -> MaterialProperties {
+        match self {
+            Material::Rock => MaterialProperties {
+                health_scale: 1.0,
+                elasticity_scale: 1.0,
+                edge_color: [1.2, 0.15, 0.08],
+                fill_color: [0.196, 0.196, 0.196],
+            },
+            Material::Dirt => MaterialProperties {
+                health_scale: 0.25,
+                elasticity_scale: 0.3,
+                edge_color: [0.95, 0.55, 0.2],
+                fill_color: [0.24, 0.16, 0.08],
+            },
+            Material::Metal => MaterialProperties {
+                health_scale: 1.0,
+                elasticity_scale: 2.0,
+                edge_color: [0.75, 0.85, 1.0],
+                fill_color: [0.32, 0.35, 0.4],
+            },
+            Material::Crystal => MaterialProperties {
+                health_scale: 0.5,
+                elasticity_scale: 2.5,
+                edge_color: [0.35, 1.0, 1.3],
+                fill_color: [0.08, 0.25, 0.35],
+            },
+        }
+    }
+
+    /// Health of a freshly generated cell of this material.
+    pub fn starting_health(self, starting_terrain_health: i32) -> i32 {
+        ((starting_terrain_health as f32 * self.properties().health_scale) as i32).max(1)
+    }
+}
+
+/// Per-material particle elasticity, indexed by material ID.
+pub fn elasticities(base_elasticity: f32) -> [f32; NUM_MATERIALS] {
+    Material::ALL.map(|m| base_elasticity * m.properties().elasticity_scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        for material in Material::ALL {
+            assert_eq!(Material::from_id(material.id()), Some(material));
+        }
+        assert_eq!(Material::from_id(NUM_MATERIALS as u32), None);
+    }
+
+    #[test]
+    fn starting_health_scales_and_stays_solid() {
+        assert_eq!(Material::Rock.starting_health(500), 500);
+        assert_eq!(Material::Dirt.starting_health(500), 125);
+        assert_eq!(Material::Dirt.starting_health(1), 1);
+    }
+
+    #[test]
+    fn rock_keeps_the_configured_elasticity() {
+        let e = elasticities(0.3);
+        assert_eq!(e[Material::Rock as usize], 0.3);
+        assert!(e[Material::Dirt as usize] < e[Material::Rock as usize]);
+        assert!(e[Material::Crystal as usize] > e[Material::Rock as usize]);
+    }
+}
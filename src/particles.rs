@@ -1,28 +1,247 @@
 //! GPU particle system: emission, physics simulation, terrain erosion, and
 //! rendering. All particle state lives in GPU buffers; compute shaders handle
 //! the simulation each frame.
+//!
+//! One particle buffer is shared by several emitters, each owning a region of
+//! it that it fills as a ring buffer. Every particle carries a `ParticleType`
+//! that picks its gravity, drag, damage, bounce and layer of the density
+//! buffer, so exhaust, gun fire, debris and smoke move, erode and glow
+//! differently within the same update pass.
 
 use crate::buffer_util::{self, SizedBuffer};
 
-/// Must match `@workgroup_size` in particles.wgsl, emitter.wgsl, and
-/// clear_density_buffer.wgsl (injected at compile time via build.rs).
-const PARTICLE_WORKGROUP_SIZE: u32 = 256;
+/// Must match `@workgroup_size` in particles.wgsl, emitter.wgsl,
+/// clear_density_buffer.wgsl and entity_hits.wgsl (injected at compile time
+/// via build.rs).
+pub(crate) const PARTICLE_WORKGROUP_SIZE: u32 = 256;
+
+pub const NUM_EMITTERS: usize = 2;
+
+/// The emitters a `ParticleSystem` owns, in the order of their regions in
+/// the particle buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmitterKind {
+    /// The ship's exhaust; also takes explosion bursts.
+    Exhaust = 0,
+    /// The forward gun.
+    Gun = 1,
+}
+
+/// Must match `NUM_PARTICLE_TYPES` in particle.wgsl.include.
+pub const NUM_PARTICLE_TYPES: usize = 4;
+
+/// What a particle is, which sets how it moves, erodes and looks. The IDs
+/// must match the `PARTICLE_*` constants in particle.wgsl.include.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ParticleType {
+    /// Ship exhaust.
+    Exhaust = 0,
+    /// Gun fire: flies straight and hits hard.
+    Gun = 1,
+    /// Heavy, hot fragments thrown out by explosions.
+    Debris = 2,
+    /// Harmless smoke that drifts upward and slows quickly.
+    Smoke = 3,
+}
+
+impl ParticleType {
+    pub const ALL: [ParticleType; NUM_PARTICLE_TYPES] = [
+        ParticleType::Exhaust,
+        ParticleType::Gun,
+        ParticleType::Debris,
+        ParticleType::Smoke,
+    ];
+
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    /// How particles of this type move, erode and glow.
+    pub fn params(self, game_params: &crate::game_params::GameParams) -> ParticleTypeParams {
+        let neutral = ParticleTypeParams::default();
+        match self {
+            ParticleType::Exhaust => neutral,
+            ParticleType::Gun => ParticleTypeParams {
+                gravity_scale: 0.0,
+                damage_scale: game_params.gun_params.damage_scale,
+                // Gun particles glow as hot as exhaust over their shorter life.
+                heat_scale: game_params.particle_system_params.max_particle_life
+                    / game_params.gun_params.particle_life,
+                ..neutral
+            },
+            ParticleType::Debris => ParticleTypeParams {
+                gravity_scale: 2.5,
+                drag: 0.5,
+                damage_scale: 0.5,
+                elasticity_scale: 0.5,
+                ..neutral
+            },
+            ParticleType::Smoke => ParticleTypeParams {
+                gravity_scale: -0.25,
+                drag: 1.5,
+                damage_scale: 0.0,
+                elasticity_scale: 0.2,
+                heat_scale: 0.4,
+                ..neutral
+            },
+        }
+    }
+
+    /// Index into `color_maps` of this type's palette.
+    fn color_map(self, game_params: &crate::game_params::GameParams) -> usize {
+        match self {
+            ParticleType::Exhaust => game_params.visual_params.color_map as usize,
+            ParticleType::Gun => game_params.gun_params.color_map as usize,
+            ParticleType::Debris => crate::color_maps::ColorMap::Inferno as usize,
+            ParticleType::Smoke => crate::color_maps::ColorMap::Smoke as usize,
+        }
+    }
+}
 
 // This should match the struct defined in the relevant compute shader.
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Particle {
-    position: [f32; 2],
-    velocity: [f32; 2],
-    ttl: f32,
-    // One-frame offset from the uniform particle update dt. 0.0 is neutral.
-    subframe_dt_offset: f32,
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub ttl: f32,
+    /// The one-frame offset from the uniform particle update dt as an f16 in
+    /// the high 16 bits (0.0 is neutral), and the `ParticleType` ID in the
+    /// low 16. See `pack_subframe_and_kind`.
+    pub subframe_and_kind: u32,
+}
+
+// Every particle in the ring is read and written each frame; keep it small.
+const _: () = assert!(std::mem::size_of::<Particle>() == 24);
+
+/// Pack a sub-frame dt offset and a `ParticleType` ID the way
+/// `particle.wgsl.include` unpacks them.
+pub fn pack_subframe_and_kind(subframe_dt_offset: f32, kind: u32) -> u32 {
+    ((f32_to_f16_bits(subframe_dt_offset) as u32) << 16) | (kind & 0xffff)
+}
+
+impl Particle {
+    /// A particle of type `kind` with the neutral sub-frame offset.
+    pub fn new(position: [f32; 2], velocity: [f32; 2], ttl: f32, kind: ParticleType) -> Self {
+        Particle {
+            position,
+            velocity,
+            ttl,
+            subframe_and_kind: pack_subframe_and_kind(0.0, kind.id()),
+        }
+    }
+
+    /// The `ParticleType` ID.
+    pub fn kind(&self) -> u32 {
+        self.subframe_and_kind & 0xffff
+    }
+
+    /// One-frame offset from the uniform particle update dt. 0.0 is neutral.
+    pub fn subframe_dt_offset(&self) -> f32 {
+        f16_bits_to_f32((self.subframe_and_kind >> 16) as u16)
+    }
+
+    pub fn set_subframe_dt_offset(&mut self, subframe_dt_offset: f32) {
+        self.subframe_and_kind = pack_subframe_and_kind(subframe_dt_offset, self.kind());
+    }
+}
+
+/// Nearest f16 to `value`, ties to even, as WGSL's `pack2x16float` stores
+/// it. Out-of-range values become infinities.
+fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let magnitude = value.abs();
+    if magnitude.is_nan() {
+        return sign | 0x7e00;
+    }
+    if magnitude >= 65520.0 {
+        return sign | 0x7c00;
+    }
+    if magnitude < 2f32.powi(-14) {
+        // Zero or subnormal: a count of 2^-24 steps. Rounding up to 0x400
+        // lands on the smallest normal, as it should.
+        return sign | (magnitude * 2f32.powi(24)).round_ties_even() as u16;
+    }
+    // Rebias from f32's 127 to f16's 15; at least 1 in this range.
+    let exponent = ((bits >> 23) & 0xff) - 112;
+    let mantissa = bits & 0x7f_ffff;
+    let mut half = (exponent << 10) | (mantissa >> 13);
+    let dropped = mantissa & 0x1fff;
+    // A carry out of the mantissa correctly bumps the exponent.
+    if dropped > 0x1000 || (dropped == 0x1000 && half & 1 == 1) {
+        half += 1;
+    }
+    sign | half as u16
 }
 
+/// The f32 an f16 holds exactly, as WGSL's `unpack2x16float` reads it.
+fn f16_bits_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// One emitter's region of the particle buffer and how it fills it.
 struct EmitterParams {
+    region_start: u32,
     num_particles: u32,
     emit_period: f32,
     nozzle: NozzleParams,
+    particle_type: ParticleType,
+}
+
+impl EmitterParams {
+    /// The ship's exhaust, with room for particles at their longest life on
+    /// any roster ship's nozzle, fully upgraded.
+    fn exhaust(game_params: &crate::game_params::GameParams, region_start: u32) -> Self {
+        let system_params = &game_params.particle_system_params;
+        EmitterParams {
+            region_start,
+            num_particles: (system_params.emission_rate * game_params.max_exhaust_life()).ceil()
+                as u32,
+            emit_period: 1.0 / system_params.emission_rate,
+            nozzle: NozzleParams {
+                speed_min: system_params.emission_speed,
+                speed_max: system_params.emission_speed,
+                ttl_min: system_params.max_particle_life,
+                ttl_max: system_params.max_particle_life,
+                ..Default::default()
+            },
+            particle_type: ParticleType::Exhaust,
+        }
+    }
+
+    /// The forward gun: a narrow stream from a single point.
+    fn gun(game_params: &crate::game_params::GameParams, region_start: u32) -> Self {
+        let gun = &game_params.gun_params;
+        EmitterParams {
+            region_start,
+            num_particles: (gun.emission_rate * gun.particle_life).ceil() as u32,
+            emit_period: 1.0 / gun.emission_rate,
+            nozzle: NozzleParams {
+                speed_min: gun.emission_speed,
+                speed_max: gun.emission_speed,
+                ttl_min: gun.particle_life,
+                ttl_max: gun.particle_life,
+                spread: gun.spread,
+                width: 1.0,
+                ..Default::default()
+            },
+            particle_type: ParticleType::Gun,
+        }
+    }
+
+    fn region_end(&self) -> u32 {
+        self.region_start + self.num_particles
+    }
 }
 
 pub struct Emitter {
@@ -41,7 +260,8 @@ pub struct Emitter {
     compute_work_groups: u32,
     compute_bind_group: wgpu::BindGroup,
     uniform_buffer: SizedBuffer,
-    pub particle_buffer: SizedBuffer,
+    /// The shared particle buffer; this emitter only writes its region.
+    particle_buffer: wgpu::Buffer,
     compute_pipeline: wgpu::ComputePipeline,
 }
 
@@ -81,6 +301,12 @@ pub struct NozzleParams {
     pub speed_max: f32,
     pub ttl_min: f32,
     pub ttl_max: f32,
+    /// Width of the exhaust cone in radians.
+    pub spread: f32,
+    /// Width of the nozzle mouth particles leave from, in cells.
+    pub width: f32,
+    pub _p1: u32,
+    pub _p2: u32,
 }
 impl Default for NozzleParams {
     fn default() -> Self {
@@ -89,10 +315,17 @@ impl Default for NozzleParams {
             speed_max: 300.0,
             ttl_min: 0.0,
             ttl_max: 0.0,
+            spread: DEFAULT_NOZZLE_SPREAD,
+            width: 8.0,
+            _p1: 0,
+            _p2: 0,
         }
     }
 }
 
+/// Exhaust cone width of an un-upgraded nozzle, in radians.
+pub const DEFAULT_NOZZLE_SPREAD: f32 = 1.0;
+
 // Params for emitting particles in one iteration
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
@@ -105,6 +338,9 @@ pub struct EmitParams {
     pub motion: EmitterMotion,
     pub nozzle: NozzleParams,
     pub num_particles: u32,
+    pub region_start: u32,
+    /// A `ParticleType` ID, given to every emitted particle.
+    pub particle_type: u32,
     pub _pad: u32,
 }
 
@@ -118,16 +354,14 @@ impl Default for EmitParams {
             motion: EmitterMotion::default(),
             nozzle: NozzleParams::default(),
             num_particles: 0,
+            region_start: 0,
+            particle_type: ParticleType::Exhaust.id(),
             _pad: 0,
         }
     }
 }
 
 impl Emitter {
-    fn num_particles(&self) -> u32 {
-        self.params.num_particles
-    }
-
     fn create_particle_buffer(device: &wgpu::Device, num_particles: u32) -> SizedBuffer {
         let buf_size =
             (num_particles * std::mem::size_of::<Particle>() as u32) as wgpu::BufferAddress;
@@ -144,13 +378,13 @@ impl Emitter {
         }
     }
 
-    pub fn new(device: &wgpu::Device, game_params: &crate::game_params::GameParams) -> Self {
-        let emission_frequency = game_params.particle_system_params.emission_rate;
-        let max_particle_life = game_params.particle_system_params.max_particle_life;
-
-        let max_num_particles = (emission_frequency * max_particle_life).ceil() as u32;
-        log::info!("Num particles: {}", max_num_particles);
-        let particle_buffer = Emitter::create_particle_buffer(device, max_num_particles);
+    fn new(device: &wgpu::Device, particle_buffer: &SizedBuffer, params: EmitterParams) -> Self {
+        let max_num_particles = params.num_particles;
+        log::info!(
+            "Num particles: {} from {}",
+            max_num_particles,
+            params.region_start
+        );
         // Initialize the uniform buffer.
         let uniform_buffer = crate::buffer_util::make_default_uniform_buffer::<EmitParams>(
             device,
@@ -236,16 +470,7 @@ impl Emitter {
         });
 
         Emitter {
-            params: EmitterParams {
-                num_particles: max_num_particles,
-                emit_period: 1.0 / emission_frequency,
-                nozzle: NozzleParams {
-                    speed_min: game_params.particle_system_params.emission_speed,
-                    speed_max: game_params.particle_system_params.emission_speed,
-                    ttl_min: game_params.particle_system_params.max_particle_life,
-                    ttl_max: game_params.particle_system_params.max_particle_life,
-                },
-            },
+            params,
             time: 0.0,
             dt: 0.0,
             emit_progress: 0.0,
@@ -255,7 +480,7 @@ impl Emitter {
             compute_work_groups,
             compute_bind_group,
             uniform_buffer,
-            particle_buffer,
+            particle_buffer: particle_buffer.buffer.clone(),
             compute_pipeline,
         }
     }
@@ -265,6 +490,15 @@ impl Emitter {
         self.params.nozzle.speed_max = speed_max;
     }
 
+    pub fn set_nozzle_ttl(&mut self, ttl_min: f32, ttl_max: f32) {
+        self.params.nozzle.ttl_min = ttl_min;
+        self.params.nozzle.ttl_max = ttl_max;
+    }
+
+    pub fn set_nozzle_spread(&mut self, spread: f32) {
+        self.params.nozzle.spread = spread;
+    }
+
     pub fn emit_for_period(&mut self, dt: f32, emitter_motion: EmitterMotion) {
         // Update the emitter state and prepare all the necessary inputs to run compute, but don't actually run the compute yet.
         let start_time = self.time;
@@ -283,6 +517,8 @@ impl Emitter {
                 motion: emitter_motion,
                 nozzle: self.params.nozzle,
                 num_particles: self.params.num_particles,
+                region_start: self.params.region_start,
+                particle_type: self.params.particle_type.id(),
                 _pad: 0,
             });
 
@@ -332,13 +568,15 @@ impl Emitter {
         }
     }
 
-    /// Write a radial burst of particles directly into the particle buffer.
-    /// Particles radiate outward from `center` with random speed variation.
+    /// Write a radial burst of `particle_type` particles directly into the
+    /// particle buffer. Particles radiate outward from `center` with random
+    /// speed variation.
     #[allow(clippy::too_many_arguments)]
     pub fn emit_burst(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
+        particle_type: ParticleType,
         center: [f32; 2],
         base_velocity: [f32; 2],
         burst_count: u32,
@@ -357,20 +595,21 @@ impl Emitter {
             let angle = 2.0 * std::f32::consts::PI * (i as f32) / (count as f32);
             // Random speed variation: 50%–150% of base speed.
             let speed_vary = speed * (0.5 + rng.f32());
-            particles.push(Particle {
-                position: center,
-                velocity: [
+            particles.push(Particle::new(
+                center,
+                [
                     base_velocity[0] + speed_vary * angle.cos(),
                     base_velocity[1] + speed_vary * angle.sin(),
                 ],
                 ttl,
-                subframe_dt_offset: 0.0,
-            });
+                particle_type,
+            ));
         }
 
         // Write particles into the circular buffer at write_index.
         let buf_len = self.params.num_particles;
         let start = self.write_index;
+        let region_offset = self.params.region_start as u64 * particle_size;
         let data = bytemuck::cast_slice::<Particle, u8>(&particles);
 
         // Handle wrap-around: may need two copies.
@@ -379,8 +618,8 @@ impl Emitter {
             // Single contiguous write.
             belt.write_buffer(
                 encoder,
-                &self.particle_buffer.buffer,
-                start as u64 * particle_size,
+                &self.particle_buffer,
+                region_offset + start as u64 * particle_size,
                 // safe: count > 0
                 wgpu::BufferSize::new(count as u64 * particle_size).unwrap(),
             )
@@ -391,8 +630,8 @@ impl Emitter {
             let first_bytes = first_chunk * std::mem::size_of::<Particle>();
             belt.write_buffer(
                 encoder,
-                &self.particle_buffer.buffer,
-                start as u64 * particle_size,
+                &self.particle_buffer,
+                region_offset + start as u64 * particle_size,
                 // safe: first_chunk > 0 (start < buf_len)
                 wgpu::BufferSize::new(first_bytes as u64).unwrap(),
             )
@@ -401,8 +640,8 @@ impl Emitter {
             let second_bytes = data.len() - first_bytes;
             belt.write_buffer(
                 encoder,
-                &self.particle_buffer.buffer,
-                0,
+                &self.particle_buffer,
+                region_offset,
                 // safe: second_bytes > 0 (end > buf_len)
                 wgpu::BufferSize::new(second_bytes as u64).unwrap(),
             )
@@ -414,7 +653,9 @@ impl Emitter {
 }
 
 pub struct ParticleSystem {
-    emitter: Emitter,
+    particle_buffer: SizedBuffer,
+    /// Indexed by `EmitterKind`.
+    emitters: [Emitter; NUM_EMITTERS],
     uniform_values: ParticleSystemUniforms,
 
     // GPU interface cruft
@@ -431,23 +672,69 @@ pub struct ParticleSystem {
     renderer: ParticleRenderer,
 }
 
+/// How one `ParticleType` moves, erodes and glows, as `particles.wgsl` sees
+/// it. The default leaves the system-wide parameters unscaled.
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
-struct ParticleSystemUniforms {
-    dt: f32,
-    viewport_width: u32,
-    viewport_height: u32,
-    viewport_offset: i32,
-    level_width: u32,
-    level_height: u32,
-    terrain_buffer_offset: i32,
-    terrain_buffer_height: u32,
-
-    damage_rate: f32,
-    gravity: f32,
-    elasticity: f32,
-    max_particle_life: f32,
-    num_particles: u32,
+pub struct ParticleTypeParams {
+    /// Scales `gravity`; negative values make the particle rise.
+    pub gravity_scale: f32,
+    /// Fraction of velocity lost per second.
+    pub drag: f32,
+    /// Scales `damage_rate`.
+    pub damage_scale: f32,
+    /// Scales the terrain's bounce elasticity.
+    pub elasticity_scale: f32,
+    /// Scales the heat the particle adds to the density buffer.
+    pub heat_scale: f32,
+    pub _padding: [u32; 3],
+}
+impl Default for ParticleTypeParams {
+    fn default() -> Self {
+        ParticleTypeParams {
+            gravity_scale: 1.0,
+            drag: 0.0,
+            damage_scale: 1.0,
+            elasticity_scale: 1.0,
+            heat_scale: 1.0,
+            _padding: [0; 3],
+        }
+    }
+}
+
+/// Uniforms for `particles.wgsl`; also the parameters of the CPU reference
+/// step in `particles_cpu`.
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ParticleSystemUniforms {
+    pub dt: f32,
+    /// Shape of the density buffer, which covers the viewport.
+    pub viewport_width: u32,
+    pub viewport_height: u32,
+    /// World cell at the bottom-left of the viewport.
+    pub viewport_offset: i32,
+    pub viewport_offset_x: i32,
+    /// Terrain buffer row stride.
+    pub level_width: u32,
+    pub level_height: u32,
+    pub terrain_buffer_offset: i32,
+    pub terrain_buffer_height: u32,
+
+    pub damage_rate: f32,
+    pub gravity: f32,
+    pub elasticity: f32,
+    pub max_particle_life: f32,
+    pub num_particles: u32,
+    /// Terrain health a particle adds to its cell when its ttl runs out.
+    pub deposition_amount: i32,
+    // Aligns `material_elasticity` to 16 bytes, as WGSL lays out `vec4<f32>`.
+    pub _padding: u32,
+    /// Bounce elasticity per terrain material, indexed by material ID.
+    /// `elasticity` applies to bounces off the edge of the terrain buffer.
+    pub material_elasticity: [f32; crate::material::NUM_MATERIALS],
+    /// Indexed by `ParticleType`. Each type also draws to its own layer of
+    /// the density buffer.
+    pub types: [ParticleTypeParams; NUM_PARTICLE_TYPES],
 }
 impl Default for ParticleSystemUniforms {
     fn default() -> Self {
@@ -456,6 +743,7 @@ impl Default for ParticleSystemUniforms {
             viewport_width: 0,
             viewport_height: 0,
             viewport_offset: 0,
+            viewport_offset_x: 0,
             level_width: 0,
             level_height: 0,
             terrain_buffer_offset: 0,
@@ -466,30 +754,62 @@ impl Default for ParticleSystemUniforms {
             elasticity: 0.0,
             max_particle_life: 1.0,
             num_particles: 0,
+            deposition_amount: 0,
+            _padding: 0,
+            material_elasticity: [0.0; crate::material::NUM_MATERIALS],
+            types: [ParticleTypeParams::default(); NUM_PARTICLE_TYPES],
         }
     }
 }
 
 impl ParticleSystem {
+    /// GPU particle storage, laid out as `particle.wgsl.include`'s `Particle`.
+    pub fn particle_buffer(&self) -> &SizedBuffer {
+        &self.particle_buffer
+    }
+
+    /// Particles across every emitter's region.
+    pub fn num_particles(&self) -> u32 {
+        self.uniform_values.num_particles
+    }
+
+    fn exhaust(&mut self) -> &mut Emitter {
+        &mut self.emitters[EmitterKind::Exhaust as usize]
+    }
+
+    /// Set the exhaust nozzle's speed range.
     pub fn set_nozzle_speed(&mut self, speed_min: f32, speed_max: f32) {
-        self.emitter.set_nozzle_speed(speed_min, speed_max);
+        self.exhaust().set_nozzle_speed(speed_min, speed_max);
     }
 
-    /// Emit a radial burst of particles (e.g. ship explosion).
+    /// Set the exhaust nozzle's particle lifetime range.
+    pub fn set_nozzle_ttl(&mut self, ttl_min: f32, ttl_max: f32) {
+        self.exhaust().set_nozzle_ttl(ttl_min, ttl_max);
+    }
+
+    /// Set the exhaust nozzle's cone width.
+    pub fn set_nozzle_spread(&mut self, spread: f32) {
+        self.exhaust().set_nozzle_spread(spread);
+    }
+
+    /// Emit a radial burst of `particle_type` particles (e.g. ship
+    /// explosion debris). Bursts share the exhaust emitter's region.
     #[allow(clippy::too_many_arguments)]
     pub fn emit_burst(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
+        particle_type: ParticleType,
         center: [f32; 2],
         base_velocity: [f32; 2],
         burst_count: u32,
         speed: f32,
         ttl: f32,
     ) {
-        self.emitter.emit_burst(
+        self.exhaust().emit_burst(
             encoder,
             belt,
+            particle_type,
             center,
             base_velocity,
             burst_count,
@@ -498,13 +818,22 @@ impl ParticleSystem {
         );
     }
 
-    pub fn update_state(&mut self, dt: f32, viewport_offset: i32, motion: Option<EmitterMotion>) {
-        if let Some(motion) = motion {
-            self.emitter.emit_for_period(dt, motion);
+    /// `viewport_offset` is the world cell at the bottom-left of the viewport.
+    /// Each emitter in `emissions` fires along its motion this frame; the
+    /// rest stay idle.
+    pub fn update_state(
+        &mut self,
+        dt: f32,
+        viewport_offset: [i32; 2],
+        emissions: &[(EmitterKind, EmitterMotion)],
+    ) {
+        for &(kind, motion) in emissions {
+            self.emitters[kind as usize].emit_for_period(dt, motion);
         }
 
         self.uniform_values.dt = dt;
-        self.uniform_values.viewport_offset = viewport_offset;
+        self.uniform_values.viewport_offset_x = viewport_offset[0];
+        self.uniform_values.viewport_offset = viewport_offset[1];
         /*
         let system_params = &game_params.particle_system_params;
         let compute_uniforms = ComputeUniforms {
@@ -527,9 +856,11 @@ impl ParticleSystem {
     fn init_update_particles_pipeline(
         device: &wgpu::Device,
         uniform_buffer: &SizedBuffer,
+        particle_buffer: &SizedBuffer,
+        terrain_buffer: &SizedBuffer,
         density_buffer: &SizedBuffer,
-        emitter: &Emitter,
-        level_manager: &crate::level_manager::LevelManager,
+        material_buffer: &SizedBuffer,
+        num_particles: u32,
     ) -> (u32, wgpu::ComputePipeline, wgpu::BindGroup) {
         let compute_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -551,7 +882,7 @@ impl ParticleSystem {
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Storage { read_only: false },
                         has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(emitter.particle_buffer.size as _),
+                        min_binding_size: wgpu::BufferSize::new(particle_buffer.size as _),
                     },
                     count: None,
                 },
@@ -562,9 +893,7 @@ impl ParticleSystem {
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Storage { read_only: false },
                         has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(
-                            level_manager.terrain_buffer().buffer.size as _,
-                        ),
+                        min_binding_size: wgpu::BufferSize::new(terrain_buffer.size as _),
                     },
                     count: None,
                 },
@@ -579,6 +908,17 @@ impl ParticleSystem {
                     },
                     count: None,
                 },
+                // Terrain material buffer
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(material_buffer.size as _),
+                    },
+                    count: None,
+                },
             ],
             label: None,
         });
@@ -619,17 +959,13 @@ impl ParticleSystem {
                 // Particles
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: emitter.particle_buffer.buffer.as_entire_binding(),
+                    resource: particle_buffer.buffer.as_entire_binding(),
                 },
                 // Terrain Buffer
                 wgpu::BindGroupEntry {
                     binding: 2,
                     resource: wgpu::BindingResource::Buffer(
-                        level_manager
-                            .terrain_buffer()
-                            .buffer
-                            .buffer
-                            .as_entire_buffer_binding(),
+                        terrain_buffer.buffer.as_entire_buffer_binding(),
                     ),
                 },
                 // Particle density buffer
@@ -637,10 +973,14 @@ impl ParticleSystem {
                     binding: 3,
                     resource: density_buffer.buffer.as_entire_binding(),
                 },
+                // Terrain material buffer
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: material_buffer.buffer.as_entire_binding(),
+                },
             ],
         });
 
-        let num_particles = emitter.num_particles();
         let update_particles_work_groups = num_particles.div_ceil(PARTICLE_WORKGROUP_SIZE);
         (
             update_particles_work_groups,
@@ -714,11 +1054,16 @@ impl ParticleSystem {
         init_encoder: &mut wgpu::CommandEncoder,
         level_manager: &super::level_manager::LevelManager,
     ) -> Self {
+        let exhaust = EmitterParams::exhaust(game_params, 0);
+        let gun = EmitterParams::gun(game_params, exhaust.region_end());
+        let num_particles = gun.region_end();
+
         let uniform_values = ParticleSystemUniforms {
             dt: 0.0,
-            viewport_width: game_params.level_width,
+            viewport_width: game_params.viewport_width,
             viewport_height: game_params.viewport_height,
             viewport_offset: 0,
+            viewport_offset_x: 0,
             level_width: game_params.level_width,
             level_height: game_params.level_height,
             terrain_buffer_offset: level_manager.terrain_buffer().shape.start,
@@ -728,9 +1073,13 @@ impl ParticleSystem {
             gravity: game_params.particle_system_params.gravity,
             elasticity: game_params.particle_system_params.elasticity,
             max_particle_life: game_params.particle_system_params.max_particle_life,
-            num_particles: (game_params.particle_system_params.emission_rate
-                * game_params.particle_system_params.max_particle_life)
-                .ceil() as u32,
+            num_particles,
+            deposition_amount: game_params.particle_system_params.deposition_amount,
+            _padding: 0,
+            material_elasticity: crate::material::elasticities(
+                game_params.particle_system_params.elasticity,
+            ),
+            types: ParticleType::ALL.map(|particle_type| particle_type.params(game_params)),
         };
         let uniform_buffer = crate::buffer_util::make_uniform_buffer::<ParticleSystemUniforms>(
             device,
@@ -738,14 +1087,16 @@ impl ParticleSystem {
             &uniform_values,
         );
 
+        // A viewport-sized layer per particle type.
         let density_buffer = buffer_util::make_buffer(
             device,
             game_params.viewport_width as usize,
-            game_params.viewport_height as usize,
+            game_params.viewport_height as usize * NUM_PARTICLE_TYPES,
             "Density buffer",
         );
 
-        let emitter = Emitter::new(device, game_params);
+        let particle_buffer = Emitter::create_particle_buffer(device, num_particles);
+        let emitters = [exhaust, gun].map(|params| Emitter::new(device, &particle_buffer, params));
 
         let renderer = ParticleRenderer::init(device, game_params, &density_buffer, init_encoder);
 
@@ -758,13 +1109,16 @@ impl ParticleSystem {
             ParticleSystem::init_update_particles_pipeline(
                 device,
                 &uniform_buffer,
+                &particle_buffer,
+                &level_manager.terrain_buffer().buffer,
                 &density_buffer,
-                &emitter,
-                level_manager,
+                &level_manager.terrain_buffer().materials,
+                num_particles,
             );
 
         ParticleSystem {
-            emitter,
+            particle_buffer,
+            emitters,
             uniform_values,
             uniform_buffer,
 
@@ -786,7 +1140,9 @@ impl ParticleSystem {
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
     ) {
-        self.emitter.run_compute(encoder, belt);
+        for emitter in &mut self.emitters {
+            emitter.run_compute(encoder, belt);
+        }
 
         // Clear density buffer.
         // See https://docs.rs/wgpu/latest/wgpu/struct.CommandEncoder.html#method.clear_buffer
@@ -853,6 +1209,8 @@ struct ParticleRendererUniforms {
     pub height: u32,
     pub density_scale: f32,
     pub density_exponent: f32,
+    pub num_layers: u32,
+    pub _padding: [u32; 3],
 }
 
 struct ParticleRenderer {
@@ -872,18 +1230,20 @@ impl ParticleRenderer {
             source: wgpu::ShaderSource::Wgsl(crate::include_shader!("render_particles.wgsl")),
         });
 
-        let cm_texture = crate::color_maps::create_color_map(
-            256,
-            device,
-            super::color_maps::get_color_map_from_index(game_params.visual_params.color_map as _),
-            init_encoder,
-        );
+        // A color map row per density layer, in `ParticleType` order.
+        let color_maps = ParticleType::ALL.map(|particle_type| {
+            super::color_maps::get_color_map_from_index(particle_type.color_map(game_params))
+        });
+        let cm_texture =
+            crate::color_maps::create_color_map(256, device, &color_maps, init_encoder);
 
         let fragment_uniforms = ParticleRendererUniforms {
             width: game_params.viewport_width,
             height: game_params.viewport_height,
             density_scale: game_params.visual_params.density_scale,
             density_exponent: game_params.visual_params.density_exponent,
+            num_layers: NUM_PARTICLE_TYPES as u32,
+            _padding: [0; 3],
         };
         let uniform_buffer = crate::buffer_util::make_uniform_buffer::<ParticleRendererUniforms>(
             device,
@@ -1062,7 +1422,9 @@ mod tests {
         dt: f32,
         motion: EmitterMotion,
     ) -> (Vec<Particle>, u32) {
-        let mut emitter = Emitter::new(device, game_params);
+        let params = EmitterParams::exhaust(game_params, 0);
+        let particle_buffer = Emitter::create_particle_buffer(device, params.num_particles);
+        let mut emitter = Emitter::new(device, &particle_buffer, params);
         emitter.emit_for_period(dt, motion);
         let num_emitted = emitter
             .emit_params
@@ -1072,7 +1434,7 @@ mod tests {
 
         let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Particle readback staging"),
-            size: emitter.particle_buffer.size,
+            size: particle_buffer.size,
             usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -1083,11 +1445,11 @@ mod tests {
         });
         emitter.run_compute(&mut encoder, &mut belt);
         encoder.copy_buffer_to_buffer(
-            &emitter.particle_buffer.buffer,
+            &particle_buffer.buffer,
             0,
             &staging_buffer,
             0,
-            emitter.particle_buffer.size,
+            particle_buffer.size,
         );
         belt.finish();
         queue.submit(Some(encoder.finish()));
@@ -1105,6 +1467,30 @@ mod tests {
         (particles, num_emitted)
     }
 
+    #[test]
+    fn subframe_offset_packs_as_f16_beside_the_kind() {
+        assert_eq!(f32_to_f16_bits(0.0), 0);
+        assert_eq!(f32_to_f16_bits(1.0), 0x3c00);
+        assert_eq!(f32_to_f16_bits(-0.25), 0xb400);
+        assert_eq!(f32_to_f16_bits(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16_bits(1e6), 0x7c00);
+        // Smallest subnormal, and a tie rounding to the even neighbor.
+        assert_eq!(f32_to_f16_bits(2f32.powi(-24)), 1);
+        assert_eq!(f32_to_f16_bits(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16_bits(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+        for bits in [0u16, 1, 0x3ff, 0x400, 0x3c00, 0xb400, 0x7bff] {
+            assert_eq!(f32_to_f16_bits(f16_bits_to_f32(bits)), bits);
+        }
+
+        let mut p = Particle::new([1.0, 2.0], [3.0, 4.0], 1.5, ParticleType::Smoke);
+        assert_eq!(p.subframe_and_kind, ParticleType::Smoke.id());
+        p.set_subframe_dt_offset(-1.0 / 60.0);
+        assert_eq!(p.kind(), ParticleType::Smoke.id());
+        assert!((p.subframe_dt_offset() + 1.0 / 60.0).abs() < 1e-5);
+        p.set_subframe_dt_offset(0.0);
+        assert_eq!(p.subframe_and_kind, ParticleType::Smoke.id());
+    }
+
     #[test]
     fn test_emitter_compute_headless() {
         let Some((device, queue)) = gpu::try_create_headless_device() else {
@@ -1205,6 +1591,11 @@ mod tests {
                       + blob(40.0, 24.0,  40.0, 8.0); // dim lower-right spread
                 v as u32
             })
+            // Every other particle type's layer stays empty.
+            .chain(std::iter::repeat_n(
+                0,
+                (TEST_W * TEST_H) as usize * (NUM_PARTICLE_TYPES - 1),
+            ))
             .collect();
         let density_size = std::mem::size_of_val(density_data.as_slice()) as u64;
         let density_buffer = crate::buffer_util::SizedBuffer {
@@ -1254,7 +1645,9 @@ mod tests {
         game_params.particle_system_params.emission_rate = 100.0;
         game_params.particle_system_params.max_particle_life = 1.0;
 
-        let mut emitter = Emitter::new(&device, &game_params);
+        let params = EmitterParams::exhaust(&game_params, 0);
+        let particle_buffer = Emitter::create_particle_buffer(&device, params.num_particles);
+        let mut emitter = Emitter::new(&device, &particle_buffer, params);
 
         let center = [120.0f32, 67.0];
         let base_vel = [5.0f32, -3.0];
@@ -1264,7 +1657,7 @@ mod tests {
 
         let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Burst readback staging"),
-            size: emitter.particle_buffer.size,
+            size: particle_buffer.size,
             usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -1276,6 +1669,7 @@ mod tests {
         emitter.emit_burst(
             &mut encoder,
             &mut belt,
+            ParticleType::Debris,
             center,
             base_vel,
             burst_count,
@@ -1284,11 +1678,11 @@ mod tests {
         );
 
         encoder.copy_buffer_to_buffer(
-            &emitter.particle_buffer.buffer,
+            &particle_buffer.buffer,
             0,
             &staging_buffer,
             0,
-            emitter.particle_buffer.size,
+            particle_buffer.size,
         );
         belt.finish();
         queue.submit(Some(encoder.finish()));
@@ -1322,9 +1716,11 @@ mod tests {
                 ttl
             );
             assert_eq!(
-                p.subframe_dt_offset, 0.0,
+                p.subframe_dt_offset(),
+                0.0,
                 "Burst particles should use the neutral subframe_dt_offset"
             );
+            assert_eq!(p.kind(), ParticleType::Debris.id());
             // Velocity should include base_vel + radial component.
             let radial_vx = p.velocity[0] - base_vel[0];
             let radial_vy = p.velocity[1] - base_vel[1];
@@ -1339,4 +1735,444 @@ mod tests {
             );
         }
     }
+
+    /// The gun fires a narrow stream along the ship's heading into its own
+    /// region, leaving the exhaust region before it untouched.
+    #[test]
+    fn test_gun_emits_into_its_own_region() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping test_gun_emits_into_its_own_region");
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.particle_system_params.emission_rate = 100.0;
+        game_params.particle_system_params.max_particle_life = 1.0;
+        let exhaust = EmitterParams::exhaust(&game_params, 0);
+        let gun = EmitterParams::gun(&game_params, exhaust.region_end());
+        let gun_range = gun.region_start as usize..gun.region_end() as usize;
+        let particle_buffer = Emitter::create_particle_buffer(&device, gun.region_end());
+        let mut emitter = Emitter::new(&device, &particle_buffer, gun);
+
+        let position = [100.0f32, 200.0];
+        emitter.emit_for_period(
+            0.05,
+            EmitterMotion {
+                position_start: position,
+                position_end: position,
+                ..Default::default()
+            },
+        );
+
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Gun readback staging"),
+            size: particle_buffer.size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        emitter.run_compute(&mut encoder, &mut belt);
+        encoder.copy_buffer_to_buffer(
+            &particle_buffer.buffer,
+            0,
+            &staging_buffer,
+            0,
+            particle_buffer.size,
+        );
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        let buffer_slice = staging_buffer.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let particles: Vec<Particle> =
+            bytemuck::cast_slice(&buffer_slice.get_mapped_range()).to_vec();
+
+        assert!(particles[..gun_range.start].iter().all(|p| p.ttl == 0.0));
+        let live: Vec<_> = particles[gun_range]
+            .iter()
+            .filter(|p| p.ttl > 0.0)
+            .collect();
+        assert!(!live.is_empty(), "gun emitted nothing");
+        let gun_params = &game_params.gun_params;
+        for p in live {
+            assert_eq!(p.kind(), ParticleType::Gun.id());
+            assert!((p.ttl - gun_params.particle_life).abs() < 1e-5);
+            // Heading 0 fires along +x, within the narrow cone.
+            let [vx, vy] = p.velocity;
+            assert!((vx.hypot(vy) - gun_params.emission_speed).abs() < 1e-2);
+            assert!(vy.atan2(vx).abs() <= gun_params.spread / 2.0 + 1e-4);
+            // Leaves from a one-cell nozzle (jittered a little past its edge).
+            assert!((p.position[1] - position[1]).abs() <= 1.0);
+        }
+    }
+
+    /// Buffers read back after a particle update dispatch.
+    struct UpdateReadback {
+        particles: Vec<Particle>,
+        terrain: Vec<i32>,
+        materials: Vec<u32>,
+        density: Vec<u32>,
+    }
+
+    /// Run one dispatch of the real particle update pipeline over the given
+    /// state and read everything it writes back.
+    fn run_update_and_read_back(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        uniforms: &ParticleSystemUniforms,
+        particles: &[Particle],
+        terrain: &[i32],
+        materials: &[u32],
+    ) -> UpdateReadback {
+        use wgpu::util::DeviceExt;
+
+        let storage = |label: &str, contents: &[u8]| SizedBuffer {
+            buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            }),
+            size: contents.len() as _,
+        };
+        let density = vec![
+            0u32;
+            (uniforms.viewport_width * uniforms.viewport_height) as usize
+                * NUM_PARTICLE_TYPES
+        ];
+        let uniform_buffer =
+            buffer_util::make_uniform_buffer(device, "Test particle uniforms", uniforms);
+        let particle_buffer = storage("Test particles", bytemuck::cast_slice(particles));
+        let terrain_buffer = storage("Test terrain", bytemuck::cast_slice(terrain));
+        let density_buffer = storage("Test density", bytemuck::cast_slice(&density));
+        let material_buffer = storage("Test materials", bytemuck::cast_slice(materials));
+
+        let (work_groups, pipeline, bind_group) = ParticleSystem::init_update_particles_pipeline(
+            device,
+            &uniform_buffer,
+            &particle_buffer,
+            &terrain_buffer,
+            &density_buffer,
+            &material_buffer,
+            uniforms.num_particles,
+        );
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
+            cpass.set_pipeline(&pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups(work_groups, 1, 1);
+        }
+        let staging: Vec<_> = [
+            &particle_buffer,
+            &terrain_buffer,
+            &material_buffer,
+            &density_buffer,
+        ]
+        .into_iter()
+        .map(|source| {
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Particle update readback staging"),
+                size: source.size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            encoder.copy_buffer_to_buffer(&source.buffer, 0, &staging, 0, source.size);
+            staging
+        })
+        .collect();
+        queue.submit(Some(encoder.finish()));
+
+        for buffer in &staging {
+            buffer.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        }
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let read = |buffer: &wgpu::Buffer| buffer.slice(..).get_mapped_range().to_vec();
+        UpdateReadback {
+            particles: bytemuck::cast_slice(&read(&staging[0])).to_vec(),
+            terrain: bytemuck::cast_slice(&read(&staging[1])).to_vec(),
+            materials: bytemuck::cast_slice(&read(&staging[2])).to_vec(),
+            density: bytemuck::cast_slice(&read(&staging[3])).to_vec(),
+        }
+    }
+
+    /// Random particles over random terrain, stepped by both the shader and
+    /// `particles_cpu`. Every solid cell has far more health than a frame of
+    /// hits can remove (so crystal never shatters either), so bounces don't
+    /// depend on the order the GPU applies its atomics and the two results
+    /// must agree.
+    #[test]
+    fn test_particle_update_matches_cpu_reference() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping test_particle_update_matches_cpu_reference"
+            );
+            return;
+        };
+
+        // Terrain wider than the view, as in a horizontally scrolling level.
+        const LEVEL_W: u32 = 96;
+        const VIEWPORT_W: u32 = 64;
+        const VIEWPORT_H: u32 = 48;
+        const TERRAIN_H: u32 = 96;
+        const NUM_PARTICLES: u32 = 4000;
+
+        for seed in 0..8u64 {
+            let mut rng = fastrand::Rng::with_seed(seed);
+            let uniforms = ParticleSystemUniforms {
+                dt: 1.0 / 60.0,
+                viewport_width: VIEWPORT_W,
+                viewport_height: VIEWPORT_H,
+                viewport_offset: 120 + rng.i32(0..=(TERRAIN_H - VIEWPORT_H) as i32),
+                viewport_offset_x: rng.i32(0..=(LEVEL_W - VIEWPORT_W) as i32),
+                level_width: LEVEL_W,
+                level_height: TERRAIN_H / 2,
+                terrain_buffer_offset: 120,
+                terrain_buffer_height: TERRAIN_H,
+                damage_rate: rng.f32() * 2.0,
+                gravity: -50.0 * rng.f32(),
+                elasticity: rng.f32(),
+                max_particle_life: 2.0,
+                num_particles: NUM_PARTICLES,
+                material_elasticity: std::array::from_fn(|_| rng.f32()),
+                types: std::array::from_fn(|_| ParticleTypeParams {
+                    gravity_scale: rng.f32() * 3.0 - 1.0,
+                    drag: rng.f32() * 2.0,
+                    damage_scale: rng.f32() * 4.0,
+                    elasticity_scale: rng.f32(),
+                    heat_scale: 0.5 + rng.f32(),
+                    _padding: [0; 3],
+                }),
+                ..Default::default()
+            };
+            let terrain: Vec<i32> = (0..LEVEL_W * TERRAIN_H)
+                .map(|_| match rng.u8(0..4) {
+                    0 => 1 << 24,
+                    1 => -rng.i32(0..100),
+                    _ => 0,
+                })
+                .collect();
+            let materials: Vec<u32> = (0..LEVEL_W * TERRAIN_H)
+                .map(|_| rng.u32(0..crate::material::NUM_MATERIALS as u32))
+                .collect();
+            let mut particles: Vec<Particle> = (0..NUM_PARTICLES)
+                .map(|_| Particle {
+                    // Some start just off the buffer to exercise the kill path.
+                    position: [
+                        rng.f32() * (LEVEL_W + 4) as f32 - 2.0,
+                        120.0 + rng.f32() * (TERRAIN_H + 4) as f32 - 2.0,
+                    ],
+                    velocity: [(rng.f32() - 0.5) * 1200.0, (rng.f32() - 0.5) * 1200.0],
+                    ttl: rng.f32() * 2.5 - 0.25,
+                    // One past the last type, to exercise the clamp.
+                    subframe_and_kind: pack_subframe_and_kind(
+                        -rng.f32() / 60.0,
+                        rng.u32(0..=NUM_PARTICLE_TYPES as u32),
+                    ),
+                })
+                .collect();
+
+            let gpu = run_update_and_read_back(
+                &device, &queue, &uniforms, &particles, &terrain, &materials,
+            );
+
+            let mut cpu_terrain = terrain.clone();
+            let mut cpu_materials = materials.clone();
+            let mut cpu_density = vec![0; gpu.density.len()];
+            crate::particles_cpu::update_particles(
+                &uniforms,
+                &mut particles,
+                &mut cpu_terrain,
+                &mut cpu_materials,
+                &mut cpu_density,
+            );
+
+            for (i, (gpu, cpu)) in gpu.particles.iter().zip(&particles).enumerate() {
+                let close = |a: f32, b: f32| (a - b).abs() <= 1e-3 * b.abs().max(1.0);
+                assert!(
+                    close(gpu.position[0], cpu.position[0])
+                        && close(gpu.position[1], cpu.position[1])
+                        && close(gpu.velocity[0], cpu.velocity[0])
+                        && close(gpu.velocity[1], cpu.velocity[1])
+                        && close(gpu.ttl, cpu.ttl)
+                        && gpu.subframe_and_kind == cpu.subframe_and_kind,
+                    "seed {seed}, particle {i}: gpu {gpu:?} != cpu {cpu:?}"
+                );
+            }
+            assert!(gpu.terrain == cpu_terrain, "seed {seed}: terrain differs");
+            assert!(
+                gpu.materials == cpu_materials,
+                "seed {seed}: materials differ"
+            );
+            assert!(gpu.density == cpu_density, "seed {seed}: density differs");
+        }
+    }
+
+    /// Particles that expire this frame deposit dirt on the GPU exactly as on
+    /// the CPU. Each particle falls straight down its own column, so no two
+    /// touch the same cell and the result is independent of dispatch order.
+    #[test]
+    fn test_particle_deposition_matches_cpu_reference() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping test_particle_deposition_matches_cpu_reference"
+            );
+            return;
+        };
+
+        const W: u32 = 64;
+        const H: u32 = 32;
+        let mut rng = fastrand::Rng::with_seed(6);
+        let uniforms = ParticleSystemUniforms {
+            dt: 1.0 / 60.0,
+            viewport_width: W,
+            viewport_height: H,
+            level_width: W,
+            level_height: H,
+            terrain_buffer_height: H,
+            damage_rate: 0.5,
+            gravity: -40.0,
+            elasticity: 0.5,
+            max_particle_life: 2.0,
+            num_particles: W,
+            deposition_amount: 75,
+            material_elasticity: [0.5; crate::material::NUM_MATERIALS],
+            ..Default::default()
+        };
+        let terrain: Vec<i32> = (0..W * H)
+            .map(|_| match rng.u8(0..3) {
+                0 => 100,
+                1 => -rng.i32(0..1000),
+                _ => 0,
+            })
+            .collect();
+        let materials: Vec<u32> = (0..W * H)
+            .map(|_| rng.u32(0..crate::material::NUM_MATERIALS as u32))
+            .collect();
+        let mut particles: Vec<Particle> = (0..W)
+            .map(|x| {
+                Particle::new(
+                    [x as f32 + rng.f32(), 2.0 + rng.f32() * (H - 4) as f32],
+                    [0.0, -rng.f32() * 240.0],
+                    // Most, but not all, expire this frame.
+                    rng.f32() / 30.0,
+                    ParticleType::Exhaust,
+                )
+            })
+            .collect();
+
+        let gpu =
+            run_update_and_read_back(&device, &queue, &uniforms, &particles, &terrain, &materials);
+        let mut cpu_terrain = terrain.clone();
+        let mut cpu_materials = materials.clone();
+        let mut cpu_density = vec![0; gpu.density.len()];
+        crate::particles_cpu::update_particles(
+            &uniforms,
+            &mut particles,
+            &mut cpu_terrain,
+            &mut cpu_materials,
+            &mut cpu_density,
+        );
+
+        assert!(particles.iter().any(|p| p.ttl <= 0.0));
+        assert!(particles.iter().any(|p| p.ttl > 0.0));
+        assert!(gpu.terrain == cpu_terrain, "terrain differs");
+        assert!(gpu.materials == cpu_materials, "materials differ");
+    }
+
+    /// Deposits land in the composite terrain buffer and survive the
+    /// `decompose_tiles`/`compose_tiles` round trip through the level tiles.
+    #[test]
+    fn test_deposits_survive_tile_round_trip() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!("No GPU adapter available — skipping test_deposits_survive_tile_round_trip");
+            return;
+        };
+
+        let mut game_params = crate::game_params::GameParams::default();
+        game_params.viewport_width = 64;
+        game_params.viewport_height = 32;
+        game_params.level_width = 64;
+        game_params.level_height = 64;
+        game_params.particle_system_params.emission_rate = 1000.0;
+        game_params.particle_system_params.max_particle_life = 1.0;
+        game_params.particle_system_params.deposition_amount = 50;
+
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 4096);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let level_manager = crate::level_manager::LevelManager::init(
+            &device,
+            &game_params,
+            1,
+            0,
+            &mut encoder,
+            &mut belt,
+        );
+        let mut particle_system =
+            ParticleSystem::new(&device, &game_params, &mut encoder, &level_manager);
+
+        // A burst that expires on its first update, in the cleared launch area.
+        let center = [32.5f32, 8.5];
+        particle_system.emit_burst(
+            &mut encoder,
+            &mut belt,
+            ParticleType::Exhaust,
+            center,
+            [0.0, 0.0],
+            16,
+            1.0,
+            0.001,
+        );
+        particle_system.update_state(1.0 / 60.0, [0, 0], &[]);
+        level_manager.compose_tiles(&mut encoder);
+        particle_system.run_compute(&level_manager, &mut encoder, &mut belt);
+        level_manager.decompose_tiles(&mut encoder);
+
+        // Wipe the composite so the deposits can only come back from the tiles.
+        let composite = level_manager.terrain_buffer();
+        encoder.clear_buffer(&composite.buffer.buffer, 0, None);
+        encoder.clear_buffer(&composite.materials.buffer, 0, None);
+        level_manager.compose_tiles(&mut encoder);
+
+        let staging: Vec<_> = [&composite.buffer, &composite.materials]
+            .into_iter()
+            .map(|source| {
+                let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Composite readback staging"),
+                    size: source.size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                encoder.copy_buffer_to_buffer(&source.buffer, 0, &staging, 0, source.size);
+                staging
+            })
+            .collect();
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        for buffer in &staging {
+            buffer.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        }
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let terrain: Vec<i32> =
+            bytemuck::cast_slice(&staging[0].slice(..).get_mapped_range()).to_vec();
+        let materials: Vec<u32> =
+            bytemuck::cast_slice(&staging[1].slice(..).get_mapped_range()).to_vec();
+
+        // The composite starts at row 0 here, so it is indexed in global rows.
+        assert_eq!(composite.shape.start, 0);
+        let index = center[1] as usize * 64 + center[0] as usize;
+        assert_eq!(terrain[index], 16 * 50);
+        assert_eq!(materials[index], crate::material::Material::Dirt.id());
+    }
 }
@@ -0,0 +1,617 @@
+//! CPU reference implementation of the particle update in `particles.wgsl`.
+//!
+//! Mirrors the shader step for step: the same integer-cell walk, the same
+//! `try_erode` damage, material and bounce rules, per-type gravity and drag,
+//! deposition and density accumulation. It
+//! runs particles one at a time in index order, which is one valid ordering of
+//! the shader's atomics; results only differ from the GPU when several
+//! particles damage the same cell in one frame and the order matters.
+//!
+//! Used to test the shader against, and by anything that needs the particle
+//! step without a GPU.
+
+use crate::material::{Material, NUM_MATERIALS};
+use crate::particles::{Particle, ParticleSystemUniforms, NUM_PARTICLE_TYPES};
+
+/// Matches `DENSITY_HEAT_SCALE` in `particles.wgsl`.
+const DENSITY_HEAT_SCALE: f32 = 256.0;
+
+/// Advance `particles` by one update, eroding `terrain` and accumulating into
+/// `density`, exactly as one dispatch of `particles.wgsl` would.
+///
+/// `terrain` is the composited terrain buffer: `level_width` wide and
+/// `terrain_buffer_height` rows tall, starting at global row
+/// `terrain_buffer_offset`; `materials` holds the material ID of each cell in
+/// the same layout. `density` holds a `viewport_width` x `viewport_height`
+/// layer for each particle type present, and is only added to, never
+/// cleared.
+pub fn update_particles(
+    uniforms: &ParticleSystemUniforms,
+    particles: &mut [Particle],
+    terrain: &mut [i32],
+    materials: &mut [u32],
+    density: &mut [u32],
+) {
+    let num_particles = (uniforms.num_particles as usize).min(particles.len());
+    for particle in &mut particles[..num_particles] {
+        update_particle(uniforms, particle, terrain, materials, density);
+    }
+}
+
+fn update_particle(
+    u: &ParticleSystemUniforms,
+    particle: &mut Particle,
+    terrain: &mut [i32],
+    materials: &mut [u32],
+    density: &mut [u32],
+) {
+    if particle.ttl <= 0.0 {
+        return;
+    }
+    let kind = (particle.kind() as usize).min(NUM_PARTICLE_TYPES - 1);
+    let type_params = u.types[kind];
+    let damage_scale = type_params.damage_scale;
+
+    let current_cell = to_cell(particle.position);
+    let mut terrain_cell = [current_cell[0], current_cell[1] - u.terrain_buffer_offset];
+    if !on_terrain_buffer(u, terrain_cell) {
+        particle.ttl = 0.0;
+        return;
+    }
+
+    let dt = (u.dt + particle.subframe_dt_offset()).max(0.0);
+    particle.set_subframe_dt_offset(0.0);
+
+    let signed_delta = [particle.velocity[0] * dt, particle.velocity[1] * dt];
+    let end_pos = [
+        particle.position[0] + signed_delta[0],
+        particle.position[1] + signed_delta[1],
+    ];
+
+    let delta = [signed_delta[0].abs(), signed_delta[1].abs()];
+    let mut step = [copysign(signed_delta[0]), copysign(signed_delta[1])];
+
+    let end_cell = to_cell(end_pos);
+    let end_cell = [end_cell[0], end_cell[1] - u.terrain_buffer_offset];
+    let delta_i = [end_cell[0] - terrain_cell[0], end_cell[1] - terrain_cell[1]];
+
+    let offset = u.terrain_buffer_offset as f32;
+    let start_remainder = [
+        (0.5 - (particle.position[0] - terrain_cell[0] as f32)) * step[0] as f32,
+        (0.5 - ((particle.position[1] - offset) - terrain_cell[1] as f32)) * step[1] as f32,
+    ];
+    let mut end_remainder = [
+        end_pos[0] - end_cell[0] as f32,
+        (end_pos[1] - offset) - end_cell[1] as f32,
+    ];
+
+    // 'Bresenham' error value.
+    let mut error = delta[0] * start_remainder[1] - delta[1] * start_remainder[0];
+    let mut vel_out = particle.velocity;
+    let speed = particle.velocity[0].hypot(particle.velocity[1]);
+
+    for _ in 0..delta_i[0].abs() + delta_i[1].abs() {
+        let error_horizontal = error - delta[1];
+        let error_vertical = error + delta[0];
+        // Axis 0 is a horizontal step, 1 a vertical one.
+        let axis = if error_vertical > -error_horizontal {
+            error = error_horizontal;
+            0
+        } else {
+            error = error_vertical;
+            1
+        };
+        terrain_cell[axis] += step[axis];
+        let bounce = !on_terrain_buffer(u, terrain_cell)
+            || try_erode(u, terrain, materials, terrain_cell, speed, damage_scale);
+        if bounce {
+            let elasticity =
+                bounce_elasticity(u, materials, terrain_cell) * type_params.elasticity_scale;
+            terrain_cell[axis] -= step[axis];
+            step[axis] = -step[axis];
+            vel_out[axis] = -(vel_out[axis] * elasticity);
+            end_remainder[axis] = 1.0 - end_remainder[axis];
+        }
+    }
+    vel_out[1] += u.gravity * type_params.gravity_scale * dt;
+    let keep = (1.0 - type_params.drag * dt).max(0.0);
+    vel_out = [vel_out[0] * keep, vel_out[1] * keep];
+
+    let global_output_pos = [
+        terrain_cell[0] as f32 + end_remainder[0],
+        (terrain_cell[1] + u.terrain_buffer_offset) as f32 + end_remainder[1],
+    ];
+    particle.position = global_output_pos;
+    particle.velocity = vel_out;
+    particle.ttl -= dt;
+    if particle.ttl <= 0.0 && u.deposition_amount > 0 {
+        deposit(u, terrain, materials, terrain_cell);
+    }
+
+    let heat = (particle.ttl / u.max_particle_life * type_params.heat_scale).clamp(0.0, 1.0);
+    increment_cell(u, density, to_cell(global_output_pos), heat, kind);
+}
+
+/// WGSL's `vec2<i32>(v)`: truncate toward zero.
+fn to_cell(pos: [f32; 2]) -> [i32; 2] {
+    [pos[0] as i32, pos[1] as i32]
+}
+
+fn copysign(v: f32) -> i32 {
+    if v >= 0.0 {
+        1
+    } else {
+        -1
+    }
+}
+
+fn on_terrain_buffer(u: &ParticleSystemUniforms, cell: [i32; 2]) -> bool {
+    cell[0] >= 0
+        && cell[0] < u.level_width as i32
+        && cell[1] >= 0
+        && cell[1] < u.terrain_buffer_height as i32
+}
+
+fn buffer_index(u: &ParticleSystemUniforms, cell: [i32; 2]) -> usize {
+    cell[1] as usize * u.level_width as usize + cell[0] as usize
+}
+
+/// Returns true if the particle bounces, i.e. the cell had health left before
+/// this hit. The damage is applied either way, except to metal.
+fn try_erode(
+    u: &ParticleSystemUniforms,
+    terrain: &mut [i32],
+    materials: &[u32],
+    cell: [i32; 2],
+    speed: f32,
+    damage_scale: f32,
+) -> bool {
+    let index = buffer_index(u, cell);
+    let material = Material::from_id(materials[index]);
+    if material == Some(Material::Metal) {
+        return terrain[index] > 0;
+    }
+    let damage = (u.damage_rate * damage_scale * speed) as i32;
+    let old = terrain[index];
+    terrain[index] = old.wrapping_sub(damage);
+    if material == Some(Material::Crystal) && old > 0 && old <= damage {
+        shatter(u, terrain, materials, cell);
+    }
+    old > 0
+}
+
+/// Break any crystal cells 4-connected to `cell`.
+fn shatter(u: &ParticleSystemUniforms, terrain: &mut [i32], materials: &[u32], cell: [i32; 2]) {
+    for [dx, dy] in [[1, 0], [-1, 0], [0, 1], [0, -1]] {
+        let neighbor = [cell[0] + dx, cell[1] + dy];
+        if on_terrain_buffer(u, neighbor) {
+            let index = buffer_index(u, neighbor);
+            if materials[index] == Material::Crystal.id() {
+                terrain[index] = terrain[index].min(0);
+            }
+        }
+    }
+}
+
+/// Add the deposition amount to `cell`. An empty cell becomes loose dirt.
+fn deposit(u: &ParticleSystemUniforms, terrain: &mut [i32], materials: &mut [u32], cell: [i32; 2]) {
+    let index = buffer_index(u, cell);
+    if terrain[index] <= 0 {
+        terrain[index] = 0;
+        materials[index] = Material::Dirt.id();
+    }
+    terrain[index] = terrain[index].wrapping_add(u.deposition_amount);
+}
+
+fn bounce_elasticity(u: &ParticleSystemUniforms, materials: &[u32], cell: [i32; 2]) -> f32 {
+    if !on_terrain_buffer(u, cell) {
+        return u.elasticity;
+    }
+    let material = (materials[buffer_index(u, cell)] as usize).min(NUM_MATERIALS - 1);
+    u.material_elasticity[material]
+}
+
+fn increment_cell(
+    u: &ParticleSystemUniforms,
+    density: &mut [u32],
+    global_cell: [i32; 2],
+    heat: f32,
+    layer: usize,
+) {
+    let x = global_cell[0] - u.viewport_offset_x;
+    let y = global_cell[1] - u.viewport_offset;
+    if x < 0 || x >= u.viewport_width as i32 || y < 0 || y >= u.viewport_height as i32 {
+        return;
+    }
+    let layer_size = u.viewport_width as usize * u.viewport_height as usize;
+    let index = layer * layer_size + y as usize * u.viewport_width as usize + x as usize;
+    let contribution = ((heat * DENSITY_HEAT_SCALE) as u32).max(1);
+    density[index] = density[index].wrapping_add(contribution);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::particles::{ParticleType, ParticleTypeParams};
+
+    const W: u32 = 8;
+    const H: u32 = 8;
+
+    fn uniforms() -> ParticleSystemUniforms {
+        ParticleSystemUniforms {
+            dt: 1.0,
+            viewport_width: W,
+            viewport_height: H,
+            viewport_offset: 0,
+            level_width: W,
+            level_height: H,
+            terrain_buffer_offset: 0,
+            terrain_buffer_height: H,
+            damage_rate: 1.0,
+            gravity: 0.0,
+            elasticity: 1.0,
+            max_particle_life: 10.0,
+            num_particles: 1,
+            material_elasticity: [1.0; NUM_MATERIALS],
+            ..Default::default()
+        }
+    }
+
+    fn particle(position: [f32; 2], velocity: [f32; 2]) -> Particle {
+        Particle::new(position, velocity, 5.0, ParticleType::Exhaust)
+    }
+
+    fn step(u: &ParticleSystemUniforms, p: &mut Particle, terrain: &mut [i32]) -> Vec<u32> {
+        step_with_materials(u, p, terrain, &mut vec![0; terrain.len()])
+    }
+
+    fn step_with_materials(
+        u: &ParticleSystemUniforms,
+        p: &mut Particle,
+        terrain: &mut [i32],
+        materials: &mut [u32],
+    ) -> Vec<u32> {
+        let mut density = vec![0; (u.viewport_width * u.viewport_height) as usize];
+        update_particles(u, std::slice::from_mut(p), terrain, materials, &mut density);
+        density
+    }
+
+    fn index(x: u32, y: u32) -> usize {
+        (y * W + x) as usize
+    }
+
+    #[test]
+    fn moves_freely_through_empty_terrain() {
+        let u = uniforms();
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = particle([1.5, 1.5], [2.0, 3.0]);
+        let density = step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [3.5, 4.5]);
+        assert_eq!(p.velocity, [2.0, 3.0]);
+        assert_eq!(p.ttl, 4.0);
+        // Empty cells still take damage: erosion is unconditional.
+        assert!(terrain[index(2, 2)] < 0);
+        // heat = 4 / 10 of the scale.
+        assert_eq!(density[index(3, 4)], 102);
+    }
+
+    #[test]
+    fn bounces_off_solid_cell_with_elasticity() {
+        let mut u = uniforms();
+        u.material_elasticity[Material::Rock as usize] = 0.5;
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(3, 1)] = 1000;
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        // Reflected back into the starting cell, mirrored within it.
+        assert_eq!(p.position, [2.5, 1.5]);
+        assert_eq!(p.velocity, [-0.5, 0.0]);
+        assert_eq!(terrain[index(3, 1)], 999);
+    }
+
+    #[test]
+    fn bounces_vertically_and_keeps_horizontal_motion() {
+        let u = uniforms();
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(2, 3)] = 1000;
+        let mut p = particle([2.5, 2.5], [0.0, 1.0]);
+        step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [2.5, 2.5]);
+        assert_eq!(p.velocity, [0.0, -1.0]);
+    }
+
+    #[test]
+    fn erosion_damage_scales_with_speed() {
+        let mut u = uniforms();
+        u.damage_rate = 2.5;
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(3, 1)] = 1000;
+        let mut p = particle([2.5, 1.5], [4.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        // i32(2.5 * 4.0) per hit, truncated like the shader.
+        assert_eq!(terrain[index(3, 1)], 990);
+
+        u.damage_rate = 0.3;
+        terrain[index(3, 1)] = 1000;
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        // 0.3 truncates to no damage, but the cell still bounces.
+        assert_eq!(terrain[index(3, 1)], 1000);
+        assert_eq!(p.velocity, [-1.0, 0.0]);
+    }
+
+    #[test]
+    fn particle_type_sets_damage_bounce_gravity_drag_and_density_layer() {
+        let mut u = uniforms();
+        u.num_particles = 2;
+        u.damage_rate = 2.0;
+        u.gravity = -1.0;
+        u.types[ParticleType::Debris as usize] = ParticleTypeParams {
+            gravity_scale: 2.0,
+            drag: 0.5,
+            damage_scale: 3.0,
+            elasticity_scale: 0.5,
+            heat_scale: 2.0,
+            _padding: [0; 3],
+        };
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(3, 1)] = 1000;
+        terrain[index(3, 5)] = 1000;
+        let mut particles = [
+            particle([2.5, 1.5], [1.0, 0.0]),
+            Particle::new([2.5, 5.5], [1.0, 0.0], 5.0, ParticleType::Debris),
+        ];
+        let mut materials = vec![0; terrain.len()];
+        let mut density = vec![0; NUM_PARTICLE_TYPES * (W * H) as usize];
+        update_particles(
+            &u,
+            &mut particles,
+            &mut terrain,
+            &mut materials,
+            &mut density,
+        );
+        assert_eq!(terrain[index(3, 1)], 998);
+        assert_eq!(terrain[index(3, 5)], 994);
+        assert_eq!(particles[0].velocity, [-1.0, -1.0]);
+        // Half the bounce, double the gravity, then half the velocity lost
+        // to drag.
+        assert_eq!(particles[1].velocity, [-0.25, -1.0]);
+        // Heat 4 / 10 in the exhaust layer, doubled in the debris layer.
+        let layer = (W * H) as usize;
+        assert_eq!(density[index(2, 1)], 102);
+        assert_eq!(density[2 * layer + index(2, 5)], 204);
+        assert_eq!(density[index(2, 5)], 0);
+    }
+
+    #[test]
+    fn drag_stops_rather_than_reverses() {
+        let mut u = uniforms();
+        u.types[ParticleType::Smoke as usize].drag = 4.0;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = Particle::new([2.5, 2.5], [1.0, 0.0], 5.0, ParticleType::Smoke);
+        let mut density = vec![0; NUM_PARTICLE_TYPES * (W * H) as usize];
+        update_particles(
+            &u,
+            std::slice::from_mut(&mut p),
+            &mut terrain,
+            &mut vec![0; (W * H) as usize],
+            &mut density,
+        );
+        assert_eq!(p.position, [3.5, 2.5]);
+        assert_eq!(p.velocity, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn hit_that_depletes_a_cell_still_bounces() {
+        let mut u = uniforms();
+        u.damage_rate = 10.0;
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(3, 1)] = 5;
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        assert_eq!(p.velocity, [-1.0, 0.0]);
+        assert_eq!(terrain[index(3, 1)], -5);
+
+        // The next particle finds the cell already empty and passes through.
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [3.5, 1.5]);
+        assert_eq!(p.velocity, [1.0, 0.0]);
+    }
+
+    #[test]
+    fn bounces_off_buffer_edges_without_eroding() {
+        let u = uniforms();
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = particle([0.5, 4.5], [-2.0, 0.0]);
+        step(&u, &mut p, &mut terrain);
+        // Mirrored about the left edge of the buffer.
+        assert_eq!(p.position, [1.5, 4.5]);
+        assert_eq!(p.velocity, [2.0, 0.0]);
+        assert!(terrain.iter().all(|&t| t == 0));
+    }
+
+    #[test]
+    fn particles_off_the_buffer_die() {
+        let mut u = uniforms();
+        u.terrain_buffer_offset = 10;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = particle([2.5, 4.5], [0.0, 0.0]);
+        let density = step(&u, &mut p, &mut terrain);
+        assert_eq!(p.ttl, 0.0);
+        assert!(density.iter().all(|&d| d == 0));
+    }
+
+    #[test]
+    fn dead_particles_are_untouched() {
+        let u = uniforms();
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = particle([2.5, 2.5], [1.0, 1.0]);
+        p.ttl = 0.0;
+        p.set_subframe_dt_offset(0.25);
+        let before = p;
+        let density = step(&u, &mut p, &mut terrain);
+        assert_eq!(bytemuck::bytes_of(&p), bytemuck::bytes_of(&before));
+        assert!(density.iter().all(|&d| d == 0));
+    }
+
+    #[test]
+    fn subframe_offset_applies_once_and_resets() {
+        let mut u = uniforms();
+        u.dt = 0.5;
+        u.gravity = -2.0;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut p = particle([2.5, 5.5], [2.0, 0.0]);
+        p.set_subframe_dt_offset(-0.25);
+        step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [3.0, 5.5]);
+        assert_eq!(p.velocity, [2.0, -0.5]);
+        assert_eq!(p.ttl, 4.75);
+        assert_eq!(p.subframe_dt_offset(), 0.0);
+
+        // A negative dt clamps to zero rather than running time backwards.
+        p.set_subframe_dt_offset(-1.0);
+        step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [3.0, 5.5]);
+        assert_eq!(p.ttl, 4.75);
+    }
+
+    #[test]
+    fn terrain_buffer_offset_shifts_rows() {
+        let mut u = uniforms();
+        u.terrain_buffer_offset = 100;
+        u.viewport_offset = 100;
+        let mut terrain = vec![0; (W * H) as usize];
+        terrain[index(2, 3)] = 1000;
+        let mut p = particle([2.5, 102.5], [0.0, 1.0]);
+        let density = step(&u, &mut p, &mut terrain);
+        assert_eq!(p.position, [2.5, 102.5]);
+        assert_eq!(terrain[index(2, 3)], 999);
+        assert!(density[index(2, 2)] > 0);
+    }
+
+    #[test]
+    fn wide_terrain_uses_level_stride_and_horizontal_view_offset() {
+        let mut u = uniforms();
+        u.level_width = 2 * W;
+        u.viewport_offset_x = W as i32;
+        let mut terrain = vec![0; (2 * W * H) as usize];
+        terrain[3 * 2 * W as usize + 12] = 1000;
+        // Moving right into a cell past the viewport width.
+        let mut p = particle([11.5, 3.5], [1.0, 0.0]);
+        let density = step(&u, &mut p, &mut terrain);
+        assert_eq!(terrain[3 * 2 * W as usize + 12], 999);
+        assert_eq!(p.position, [11.5, 3.5]);
+        // Counted in the view, shifted left by the horizontal offset.
+        assert!(density[index(11 - W, 3)] > 0);
+    }
+
+    #[test]
+    fn bounce_uses_the_elasticity_of_the_cell_hit() {
+        let mut u = uniforms();
+        u.elasticity = 0.25;
+        u.material_elasticity[Material::Dirt as usize] = 0.5;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut materials = vec![Material::Rock.id(); terrain.len()];
+        terrain[index(3, 1)] = 1000;
+        materials[index(3, 1)] = Material::Dirt.id();
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(p.velocity, [-0.5, 0.0]);
+
+        // The buffer edge has no material and uses the base elasticity.
+        let mut p = particle([0.5, 4.5], [-2.0, 0.0]);
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(p.velocity, [0.5, 0.0]);
+    }
+
+    #[test]
+    fn metal_bounces_without_taking_damage() {
+        let mut u = uniforms();
+        u.damage_rate = 1000.0;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut materials = vec![Material::Rock.id(); terrain.len()];
+        terrain[index(3, 1)] = 5;
+        materials[index(3, 1)] = Material::Metal.id();
+        for _ in 0..3 {
+            let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+            step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+            assert_eq!(p.velocity, [-1.0, 0.0]);
+        }
+        assert_eq!(terrain[index(3, 1)], 5);
+
+        // Metal that was carved out at generation is passable.
+        terrain[index(3, 1)] = 0;
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(p.position, [3.5, 1.5]);
+    }
+
+    #[test]
+    fn breaking_crystal_shatters_adjacent_crystal() {
+        let mut u = uniforms();
+        u.damage_rate = 10.0;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut materials = vec![Material::Rock.id(); terrain.len()];
+        for (x, y, material) in [
+            (3, 1, Material::Crystal),
+            (4, 1, Material::Crystal),
+            (3, 2, Material::Crystal),
+            (5, 1, Material::Crystal),
+            (3, 0, Material::Rock),
+        ] {
+            terrain[index(x, y)] = 10;
+            materials[index(x, y)] = material.id();
+        }
+
+        // A hit that leaves the crystal standing does not shatter it.
+        terrain[index(3, 1)] = 20;
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(terrain[index(4, 1)], 10);
+
+        let mut p = particle([2.5, 1.5], [1.0, 0.0]);
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(p.velocity, [-1.0, 0.0]);
+        assert_eq!(terrain[index(3, 1)], 0);
+        assert_eq!(terrain[index(4, 1)], 0);
+        assert_eq!(terrain[index(3, 2)], 0);
+        // Only direct neighbors shatter, and only crystal ones.
+        assert_eq!(terrain[index(5, 1)], 10);
+        assert_eq!(terrain[index(3, 0)], 10);
+    }
+
+    #[test]
+    fn expiring_particle_deposits_loose_dirt() {
+        let mut u = uniforms();
+        u.deposition_amount = 40;
+        let mut terrain = vec![0; (W * H) as usize];
+        let mut materials = vec![Material::Metal.id(); terrain.len()];
+        terrain[index(3, 2)] = -500;
+
+        let mut p = particle([2.5, 2.5], [1.0, 0.0]);
+        p.ttl = 0.5;
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert!(p.ttl <= 0.0);
+        // Piles up from empty, however eroded the cell was.
+        assert_eq!(terrain[index(3, 2)], 40);
+        assert_eq!(materials[index(3, 2)], Material::Dirt.id());
+
+        // A second particle dying on the pile adds to it.
+        let mut p = particle([3.5, 2.5], [0.0, 0.0]);
+        p.ttl = 0.5;
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        assert_eq!(terrain[index(3, 2)], 80);
+
+        // Deposition is off at 0, and living particles never deposit.
+        u.deposition_amount = 0;
+        let mut p = particle([5.5, 5.5], [0.0, 0.0]);
+        p.ttl = 0.5;
+        step_with_materials(&u, &mut p, &mut terrain, &mut materials);
+        u.deposition_amount = 40;
+        let mut q = particle([6.5, 6.5], [0.0, 0.0]);
+        step_with_materials(&u, &mut q, &mut terrain, &mut materials);
+        assert_eq!(terrain[index(5, 5)], 0);
+        assert_eq!(terrain[index(6, 6)], 0);
+    }
+}
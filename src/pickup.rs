@@ -0,0 +1,269 @@
+//! Collectible pickups: placement in open space on generated levels,
+//! collection by overlap with the ship's hull, and the nozzle upgrades they
+//! grant.
+//!
+//! Pure CPU state carried in `Play`, like `turret`. The game applies
+//! `NozzleUpgrades` to the GPU emitter through the `ParticleSystem` nozzle
+//! setters each frame.
+
+use crate::entity_renderer::EntityBox;
+use crate::game_params::{GameParams, PickupParams};
+use crate::ship::ShipState;
+
+/// Pickups within this distance of the ship's hull are collected.
+const PICKUP_RADIUS: f32 = 3.0;
+/// Empty cells a pickup site needs on every side.
+const PICKUP_CLEARANCE: u32 = 6;
+/// Pickup sites are tested on a grid this many cells apart.
+const SITE_STRIDE: usize = 3;
+/// Minimum distance between pickups in one level, in cells.
+const MIN_PICKUP_SPACING: f32 = 40.0;
+/// Salt so pickup placement draws from a different stream than the level's
+/// other users of the per-level seed.
+const PLACEMENT_SALT: u64 = 0x7069_636b_7570_7321;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Adds `time_bonus_seconds` to the countdown.
+    TimeBonus,
+    /// Adds `fuel_refill` to the tank.
+    Fuel,
+    /// Faster exhaust.
+    NozzleSpeed,
+    /// Longer-lived exhaust, reaching further.
+    NozzleLife,
+    /// A wider exhaust cone.
+    NozzleSpread,
+}
+
+impl PickupKind {
+    /// Time bonuses and fuel are each as common as all the upgrades
+    /// together.
+    fn pick(rng: &mut fastrand::Rng) -> Self {
+        match rng.u32(0..9) {
+            0..=2 => PickupKind::TimeBonus,
+            3..=5 => PickupKind::Fuel,
+            6 => PickupKind::NozzleSpeed,
+            7 => PickupKind::NozzleLife,
+            _ => PickupKind::NozzleSpread,
+        }
+    }
+}
+
+/// Pick up to `count` pickup sites in the open space of a finished level,
+/// spread apart, each with a kind. Sites are `[x, y]` in level cells.
+/// Depends only on the level and `seed`.
+pub fn place_pickups(
+    level: &[i32],
+    width: u32,
+    height: u32,
+    count: u32,
+    seed: u64,
+) -> Vec<([u32; 2], PickupKind)> {
+    let c = PICKUP_CLEARANCE;
+    if width <= 2 * c || height <= 2 * c {
+        return Vec::new();
+    }
+    let open = |x: u32, y: u32| {
+        (y - c..=y + c).all(|y| (x - c..=x + c).all(|x| level[(y * width + x) as usize] <= 0))
+    };
+    let candidates: Vec<[u32; 2]> = (c..height - c)
+        .step_by(SITE_STRIDE)
+        .flat_map(|y| (c..width - c).step_by(SITE_STRIDE).map(move |x| [x, y]))
+        .filter(|&[x, y]| open(x, y))
+        .collect();
+    let sites = crate::turret::pick_spread_out(
+        candidates,
+        count,
+        MIN_PICKUP_SPACING,
+        seed ^ PLACEMENT_SALT,
+    );
+    let mut rng = fastrand::Rng::with_seed(seed ^ PLACEMENT_SALT.rotate_left(32));
+    sites
+        .into_iter()
+        .map(|site| (site, PickupKind::pick(&mut rng)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pickup {
+    pub position: [f32; 2],
+    pub kind: PickupKind,
+}
+
+/// Nozzle upgrades collected so far, each capped at `max_upgrades`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NozzleUpgrades {
+    pub speed: u32,
+    pub life: u32,
+    pub spread: u32,
+}
+
+impl NozzleUpgrades {
+    /// Count an upgrade pickup. Returns false if `kind` is not an upgrade or
+    /// that upgrade is already maxed out.
+    pub fn apply(&mut self, params: &PickupParams, kind: PickupKind) -> bool {
+        let level = match kind {
+            PickupKind::NozzleSpeed => &mut self.speed,
+            PickupKind::NozzleLife => &mut self.life,
+            PickupKind::NozzleSpread => &mut self.spread,
+            PickupKind::TimeBonus | PickupKind::Fuel => return false,
+        };
+        if *level >= params.max_upgrades {
+            return false;
+        }
+        *level += 1;
+        true
+    }
+
+    /// Multiplier on the base exhaust speed.
+    pub fn speed_scale(&self, params: &PickupParams) -> f32 {
+        1.0 + params.speed_step * self.speed as f32
+    }
+
+    /// Multiplier on the base particle life.
+    pub fn life_scale(&self, params: &PickupParams) -> f32 {
+        1.0 + params.life_step * self.life as f32
+    }
+
+    /// Multiplier on the base nozzle spread.
+    pub fn spread_scale(&self, params: &PickupParams) -> f32 {
+        1.0 + params.spread_step * self.spread as f32
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Pickups {
+    pub pickups: Vec<Pickup>,
+    /// Levels below this index have had their pickups spawned.
+    spawned_levels: u32,
+}
+
+impl Pickups {
+    /// The next level whose pickups should spawn, up to
+    /// `play::spawn_horizon_level`.
+    pub fn next_level_to_spawn(&self, params: &GameParams, viewport_offset: i32) -> Option<u32> {
+        if params.pickup_params.pickups_per_level == 0 {
+            return None;
+        }
+        let reached = crate::play::spawn_horizon_level(params, viewport_offset);
+        (self.spawned_levels <= reached).then_some(self.spawned_levels)
+    }
+
+    /// Add the pickups at `sites` (level cells, see `place_pickups`) for
+    /// `level_index`.
+    pub fn spawn_level(
+        &mut self,
+        params: &GameParams,
+        level_index: u32,
+        sites: &[([u32; 2], PickupKind)],
+    ) {
+        let base_y = (level_index * params.level_height) as f32;
+        self.pickups
+            .extend(sites.iter().map(|&([x, y], kind)| Pickup {
+                position: [x as f32 + 0.5, base_y + y as f32 + 0.5],
+                kind,
+            }));
+        self.spawned_levels = level_index + 1;
+    }
+
+    /// Remove and return the pickups touching `ship`'s hull.
+    pub fn collect(&mut self, ship: &ShipState) -> Vec<PickupKind> {
+        let mut collected = Vec::new();
+        self.pickups.retain(|pickup| {
+            let touching = ship.hull_overlaps_circle(pickup.position, PICKUP_RADIUS);
+            if touching {
+                collected.push(pickup.kind);
+            }
+            !touching
+        });
+        collected
+    }
+
+    /// Boxes to draw with `EntityRenderer`: a diamond per pickup, colored
+    /// by kind, around a bright core.
+    pub fn render_boxes(&self) -> Vec<EntityBox> {
+        let mut boxes = Vec::with_capacity(self.pickups.len() * 2);
+        for pickup in &self.pickups {
+            let color = match pickup.kind {
+                PickupKind::TimeBonus => [0.3, 1.4, 0.5, 1.0],
+                PickupKind::Fuel => [1.6, 0.5, 0.15, 1.0],
+                PickupKind::NozzleSpeed => [1.5, 1.1, 0.2, 1.0],
+                PickupKind::NozzleLife => [0.3, 0.7, 1.6, 1.0],
+                PickupKind::NozzleSpread => [1.4, 0.4, 1.0, 1.0],
+            };
+            let diamond = std::f32::consts::FRAC_PI_4;
+            boxes.push(EntityBox::new(
+                pickup.position,
+                [PICKUP_RADIUS * 0.7; 2],
+                diamond,
+                color,
+            ));
+            boxes.push(EntityBox::new(
+                pickup.position,
+                [0.75; 2],
+                diamond,
+                [2.0, 2.0, 2.0, 1.0],
+            ));
+        }
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pickups_spawn_in_open_space() {
+        let (width, height): (u32, u32) = (90, 60);
+        // Solid everywhere except a pocket around (45, 30).
+        let level: Vec<i32> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if x.abs_diff(45) <= 8 && y.abs_diff(30) <= 8 {
+                    0
+                } else {
+                    100
+                }
+            })
+            .collect();
+        let sites = place_pickups(&level, width, height, 5, 11);
+        assert_eq!(sites.len(), 1, "one pocket fits one pickup");
+        let ([x, y], _) = sites[0];
+        assert!(x.abs_diff(45) <= 2 && y.abs_diff(30) <= 2);
+        assert_eq!(sites, place_pickups(&level, width, height, 5, 11));
+    }
+
+    #[test]
+    fn flying_through_a_pickup_collects_it() {
+        let params = GameParams::default();
+        let mut pickups = Pickups::default();
+        pickups.spawn_level(&params, 0, &[([100, 60], PickupKind::NozzleSpeed)]);
+        let mut ship = ShipState {
+            position: [100.5, 40.5],
+            ..Default::default()
+        };
+        assert!(pickups.collect(&ship).is_empty());
+        // Pointing up, the nose reaches 12 cells above the center.
+        ship.position[1] = 47.0;
+        assert_eq!(pickups.collect(&ship), [PickupKind::NozzleSpeed]);
+        assert!(pickups.pickups.is_empty());
+    }
+
+    #[test]
+    fn upgrades_stop_at_the_cap() {
+        let params = PickupParams {
+            max_upgrades: 2,
+            ..Default::default()
+        };
+        let mut upgrades = NozzleUpgrades::default();
+        assert!(upgrades.apply(&params, PickupKind::NozzleLife));
+        assert!(upgrades.apply(&params, PickupKind::NozzleLife));
+        assert!(!upgrades.apply(&params, PickupKind::NozzleLife));
+        assert!(!upgrades.apply(&params, PickupKind::TimeBonus));
+        assert_eq!(upgrades.life, 2);
+        assert_eq!(upgrades.life_scale(&params), params.max_life_scale());
+        assert_eq!(upgrades.speed_scale(&params), 1.0);
+    }
+}
@@ -0,0 +1,1232 @@
+//! In-game session state and the pure-CPU gameplay step.
+//!
+//! Everything here is independent of wgpu: terrain collision is reported back
+//! through `Play::resolve_collision_result`, whether it came from the GPU
+//! collision pass in the game or the CPU terrain model in `sim`.
+
+use std::time::Duration;
+
+use crate::bomb;
+use crate::collision;
+use crate::enemy;
+use crate::game_params;
+use crate::input::InputState;
+use crate::level_manager::LevelMaker;
+use crate::pickup;
+use crate::scoring;
+use crate::ship;
+use crate::terrain_query::{Region, TerrainQueryId, TerrainSnapshot};
+use crate::turret;
+
+/// Shortest signed angular distance from `current` to `target`, in [-π, π].
+pub(crate) fn angle_diff(target: f32, current: f32) -> f32 {
+    let d = glam::Vec2::from_angle(target - current);
+    d.y.atan2(d.x) // equivalent to wrapping (target-current) to [-π, π]
+}
+
+/// Pick a run seed for a session with no configured seed. Seeded from the
+/// wall clock because `fastrand`'s global RNG can be deterministic on WASM.
+pub fn fresh_run_seed() -> u32 {
+    let nanos = web_time::SystemTime::now()
+        .duration_since(web_time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+    fastrand::Rng::with_seed(nanos).u32(..)
+}
+
+/// How long a ship may coast on an empty tank, hoping to drift into a fuel
+/// pickup, before it counts as stranded.
+const STRANDED_TIME: Duration = Duration::from_secs(3);
+
+/// Highest level index whose enemies should exist at `viewport_offset`:
+/// levels spawn once the camera is within a viewport of them, so their
+/// enemies are in place before they scroll into view.
+pub fn spawn_horizon_level(params: &game_params::GameParams, viewport_offset: i32) -> u32 {
+    let lookahead = viewport_offset + 2 * params.viewport_height as i32;
+    lookahead.max(0) as u32 / params.level_height
+}
+
+/// How the player died — drives the GAME OVER / TIMES UP overlay text and
+/// makes future causes (hazards, etc.) easy to add.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathCause {
+    Collided,
+    FellOff,
+    TimeExpired,
+    /// Hit by a turret projectile.
+    Shot,
+    /// Touched an enemy ship.
+    Rammed,
+    /// Ran the tank dry and found no fuel in time.
+    OutOfFuel,
+}
+
+/// In-game session data, shared across Playing / Paused / GameOver.
+///
+/// Lives inside the relevant `AppState` variants of the game binary, and is
+/// driven directly by the headless simulator. Methods on `Play` are pure
+/// game logic — they take `&GameParams`/`&InputState` rather than touching any
+/// GPU state, so the simulation step is testable without wgpu.
+#[derive(Debug, Default)]
+pub struct Play {
+    /// Run seed the level terrain was generated from. Shown on game over so a
+    /// run can be replayed with `--seed`.
+    pub seed: u32,
+    /// The roster ship this run flies: its handling, hull and exhaust.
+    pub ship: game_params::RosterShip,
+    pub ship_state: ship::ShipState,
+    pub prev_ship_state: ship::ShipState,
+    /// World row at the bottom of the viewport.
+    pub viewport_offset: i32,
+    /// World column at the left of the viewport. Always 0 unless the level is
+    /// wider than the viewport.
+    pub viewport_offset_x: i32,
+    pub progress_height: i32,
+    pub score: i32,
+    pub current_level_index: i32,
+    pub timer_elapsed: Duration,
+    pub timer_budget: Duration,
+    /// Every frame's motion, waiting on its terrain collision result.
+    pub collisions: collision::CollisionTracker,
+    pub turrets: turret::Turrets,
+    pub enemies: enemy::Enemies,
+    pub bombs: bomb::Bombs,
+    /// Terrain query feeding `turrets`, `enemies`, `bombs` and bounces that
+    /// has not come back yet.
+    pub terrain_query: Option<TerrainQueryId>,
+    /// Latest terrain around the ship (see `watch_region`), for keeping
+    /// bounces out of solid cells.
+    pub terrain: Option<TerrainSnapshot>,
+    pub pickups: pickup::Pickups,
+    pub nozzle: pickup::NozzleUpgrades,
+    /// Latest pickup and the `timer_elapsed` it was collected at, for HUD
+    /// feedback.
+    pub last_pickup: Option<(pickup::PickupKind, Duration)>,
+    /// Fuel left in the tank. Thrust stops when it runs out.
+    pub fuel: f32,
+    /// `timer_elapsed` when the tank last ran dry, while it stays empty.
+    pub fuel_empty_since: Option<Duration>,
+    /// Hull integrity left. Terrain hits below the safe impact speed bounce
+    /// and cost hull; the ship dies once a hit would use up the rest.
+    pub hull: f32,
+}
+
+/// Left edge of a viewport centered on `ship_x`, kept inside the level.
+fn horizontal_camera_offset(params: &game_params::GameParams, ship_x: f32) -> i32 {
+    let max_offset = params.level_width.saturating_sub(params.viewport_width) as i32;
+    (ship_x.floor() as i32 - (params.viewport_width / 2) as i32).clamp(0, max_offset)
+}
+
+impl Play {
+    /// A fresh run flying ship `ship` of `params.ships()`. Panics if `ship`
+    /// is past the end of the roster; replays check recorded ships first.
+    pub fn new(params: &game_params::GameParams, ship: usize) -> Self {
+        let ship = params.ships().swap_remove(ship);
+        let ship_state = ship::ShipState::init(
+            &ship.ship_params,
+            [
+                (params.level_width / 2) as f32 + 0.5,
+                (params.viewport_height / 2) as f32 + 0.5,
+            ],
+        );
+        let mut play = Self {
+            seed: params.level_params.seed.unwrap_or_else(fresh_run_seed),
+            ship_state,
+            prev_ship_state: ship_state,
+            timer_budget: scoring::level_time_limit_duration(params),
+            fuel: ship.ship_params.fuel_capacity,
+            hull: ship.ship_params.hull_points,
+            ship,
+            bombs: bomb::Bombs::new(params),
+            ..Default::default()
+        };
+        play.viewport_offset_x = horizontal_camera_offset(params, ship_state.position[0]);
+        play
+    }
+
+    /// World cell at the bottom-left of the viewport, as `[x, y]`.
+    pub fn viewport_offset(&self) -> [i32; 2] {
+        [self.viewport_offset_x, self.viewport_offset]
+    }
+
+    pub fn level_timer_remaining(&self, params: &game_params::GameParams) -> Duration {
+        self.timer_budget
+            .max(scoring::level_time_limit_duration(params))
+            .saturating_sub(self.timer_elapsed)
+    }
+
+    pub fn commit_progress_height(&mut self, params: &game_params::GameParams, height: f32) {
+        let confirmed_height = height.floor() as i32;
+        self.progress_height = std::cmp::max(confirmed_height, self.progress_height);
+
+        let next_level_index =
+            scoring::level_index_for_progress(self.progress_height, params.level_height);
+        if next_level_index > self.current_level_index {
+            let levels_crossed = next_level_index - self.current_level_index;
+            let time_award = scoring::level_time_award(params, levels_crossed);
+            self.timer_budget = self.timer_budget.saturating_add(time_award);
+            self.current_level_index = next_level_index;
+            log::info!(
+                "Entered level {} with {:.1}s added to time bank",
+                self.current_level_index + 1,
+                time_award.as_secs_f32()
+            );
+        }
+
+        self.score = scoring::height_score(self.progress_height);
+    }
+
+    /// The part of `thrust` the engine can deliver: all of it while there is
+    /// fuel, none once the tank is empty.
+    pub fn available_thrust(&self, thrust: f32) -> f32 {
+        if self.fuel > 0.0 {
+            thrust
+        } else {
+            0.0
+        }
+    }
+
+    /// Fraction of a full tank left, in [0, 1].
+    pub fn fuel_fraction(&self) -> f32 {
+        (self.fuel / self.ship.ship_params.fuel_capacity).clamp(0.0, 1.0)
+    }
+
+    /// Fraction of full hull integrity left, in [0, 1]. Zero when hull
+    /// integrity is disabled.
+    pub fn hull_fraction(&self) -> f32 {
+        let hull_points = self.ship.ship_params.hull_points;
+        if hull_points > 0.0 {
+            (self.hull / hull_points).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn update_camera(&mut self, params: &game_params::GameParams) {
+        let live_height = self.ship_state.position[1].floor() as i32;
+        let camera_height = std::cmp::max(live_height, self.progress_height);
+        self.viewport_offset = camera_height - (params.viewport_height / 2) as i32;
+        self.viewport_offset_x = horizontal_camera_offset(params, self.ship_state.position[0]);
+    }
+
+    /// Advance ship physics one frame. Returns the death cause if the ship
+    /// flew off the playfield this step.
+    fn update_ship(
+        &mut self,
+        params: &game_params::GameParams,
+        input: &InputState,
+        dt: f32,
+    ) -> Option<DeathCause> {
+        let gravity = params.particle_system_params.gravity;
+
+        let rotate = if let Some(target) = input.target_heading {
+            // Bang-bang controller: rotate at full speed toward target heading,
+            // stop when within one frame's worth of rotation to avoid oscillation.
+            let current = self.ship_state.orientation;
+            let error = angle_diff(target, current);
+            let dead_zone = self.ship_state.rotation_rate * dt;
+            if error.abs() <= dead_zone {
+                0.0
+            } else {
+                error.signum()
+            }
+        } else {
+            input.rotate
+        };
+        let step = ship::ShipStep {
+            dt,
+            thrust: self.available_thrust(input.thrust),
+            rotate,
+        };
+        self.fuel = (self.fuel - self.ship.ship_params.fuel_burn_rate * step.thrust * dt).max(0.0);
+        let prev_ship = self.ship_state;
+        self.ship_state.apply(&step, gravity);
+
+        if self.off_playfield(params) {
+            return Some(DeathCause::FellOff);
+        }
+        self.collisions.record(prev_ship, self.ship_state, step);
+        None
+    }
+
+    /// Whether the ship has flown off the playfield and is lost.
+    fn off_playfield(&self, params: &game_params::GameParams) -> bool {
+        // Off the horizontal edges.
+        let [x, y] = self.ship_state.position;
+        if x < 0.0 || x >= params.level_width as f32 {
+            return true;
+        }
+
+        // More than one viewport-height below the bottom of the visible
+        // window. At default gravity and viewport height, that is ~2.8 s of
+        // free-fall from rest from the viewport bottom — enough to recover
+        // from a stall, but not from a sustained fall.
+        let bottom = self.viewport_offset as f32;
+        let viewport_h = params.viewport_height as f32;
+        y < bottom - viewport_h
+    }
+
+    /// Spawn turrets, enemy ships and pickups for levels the camera is
+    /// approaching, placing them on `level_maker`'s terrain. Call before
+    /// `update` each frame.
+    pub fn spawn_entities(
+        &mut self,
+        params: &game_params::GameParams,
+        level_maker: &mut LevelMaker,
+    ) {
+        while let Some(level_index) = self
+            .turrets
+            .next_level_to_spawn(params, self.viewport_offset)
+        {
+            let sites = level_maker.turret_sites(level_index, &params.turret_params);
+            self.turrets.spawn_level(params, level_index, &sites);
+        }
+        while let Some(level_index) = self
+            .enemies
+            .next_level_to_spawn(params, self.viewport_offset)
+        {
+            let sites = level_maker.enemy_sites(level_index, &params.enemy_params);
+            self.enemies.spawn_level(params, level_index, &sites);
+        }
+        while let Some(level_index) = self
+            .pickups
+            .next_level_to_spawn(params, self.viewport_offset)
+        {
+            let sites = level_maker.pickup_sites(level_index, &params.pickup_params);
+            self.pickups.spawn_level(params, level_index, &sites);
+        }
+    }
+
+    /// Collect pickups the ship touches: time bonuses extend the countdown,
+    /// fuel tops up the tank, upgrades go to `nozzle`.
+    fn collect_pickups(&mut self, params: &game_params::GameParams) {
+        for kind in self.pickups.collect(&self.ship_state) {
+            match kind {
+                pickup::PickupKind::TimeBonus => {
+                    let bonus = Duration::from_secs_f32(params.pickup_params.time_bonus_seconds);
+                    self.timer_budget = self.timer_budget.saturating_add(bonus);
+                }
+                pickup::PickupKind::Fuel => {
+                    self.fuel = (self.fuel + params.pickup_params.fuel_refill)
+                        .min(self.ship.ship_params.fuel_capacity);
+                }
+                _ => {
+                    self.nozzle.apply(&params.pickup_params, kind);
+                }
+            }
+            log::info!("Collected {kind:?}");
+            self.last_pickup = Some((kind, self.timer_elapsed));
+        }
+    }
+
+    /// Whether any turret, projectile, enemy ship or bomb needs terrain
+    /// updates.
+    pub fn needs_terrain(&self) -> bool {
+        !self.turrets.is_idle() || !self.enemies.is_empty() || !self.bombs.is_empty()
+    }
+
+    /// Whether the ship survives slow hits by bouncing, and so wants
+    /// `terrain` kept current to place its bounces.
+    pub fn can_bounce(&self) -> bool {
+        self.ship.ship_params.hull_points > 0.0
+    }
+
+    /// The area whose terrain enemies near the ship care about: the
+    /// viewport, extended vertically by the turrets' range or the enemy
+    /// ships' sight, whichever is further.
+    pub fn watch_region(&self, params: &game_params::GameParams) -> Region {
+        let margin = params
+            .turret_params
+            .range
+            .max(params.enemy_params.sight_range)
+            .ceil() as i32;
+        Region {
+            x: 0,
+            y: self.viewport_offset - margin,
+            width: params.level_width,
+            height: params.viewport_height + 2 * margin as u32,
+        }
+    }
+
+    /// Hand current terrain health (see `watch_region`) to the turrets,
+    /// enemy ships and bombs, and keep it for placing bounces.
+    pub fn observe_terrain(&mut self, snapshot: TerrainSnapshot) {
+        self.enemies.observe_terrain(snapshot.clone());
+        self.bombs.observe_terrain(snapshot.clone());
+        self.turrets.observe_terrain(snapshot.clone());
+        self.terrain = Some(snapshot);
+    }
+
+    /// Advance one frame of gameplay. Returns the death cause if any check
+    /// killed the ship this step (timer expiry, out-of-bounds, turret fire,
+    /// enemy contact, an empty tank).
+    pub fn update(
+        &mut self,
+        params: &game_params::GameParams,
+        input: &InputState,
+        game_dt: f32,
+        game_dt_duration: Duration,
+    ) -> Option<DeathCause> {
+        self.timer_elapsed = self.timer_elapsed.saturating_add(game_dt_duration);
+        let timer_budget = self
+            .timer_budget
+            .max(scoring::level_time_limit_duration(params));
+        if self.timer_elapsed >= timer_budget {
+            log::info!(
+                "Level timer expired on level {}",
+                self.current_level_index + 1,
+            );
+            return Some(DeathCause::TimeExpired);
+        }
+
+        if game_dt <= 0.0 {
+            return None;
+        }
+
+        self.prev_ship_state = self.ship_state;
+        if let Some(cause) = self.update_ship(params, input, game_dt) {
+            return Some(cause);
+        }
+        self.update_camera(params);
+        self.collect_pickups(params);
+        self.bombs
+            .update(params, input.bomb, &self.ship_state, game_dt);
+        if self
+            .turrets
+            .update(&params.turret_params, &self.ship_state, game_dt)
+        {
+            log::info!(
+                "Ship shot down at ({:.0}, {:.0})",
+                self.ship_state.position[0],
+                self.ship_state.position[1]
+            );
+            return Some(DeathCause::Shot);
+        }
+        if self.enemies.update(params, &self.ship_state, game_dt) {
+            log::info!(
+                "Ship rammed at ({:.0}, {:.0})",
+                self.ship_state.position[0],
+                self.ship_state.position[1]
+            );
+            return Some(DeathCause::Rammed);
+        }
+        if self.fuel > 0.0 {
+            self.fuel_empty_since = None;
+        } else {
+            let empty_since = *self.fuel_empty_since.get_or_insert(self.timer_elapsed);
+            if self.timer_elapsed.saturating_sub(empty_since) >= STRANDED_TIME {
+                log::info!(
+                    "Ship stranded without fuel at ({:.0}, {:.0})",
+                    self.ship_state.position[0],
+                    self.ship_state.position[1]
+                );
+                return Some(DeathCause::OutOfFuel);
+            }
+        }
+        None
+    }
+
+    /// Apply a GPU collision-detection readback to current play state. Results
+    /// must arrive in the order their segments were dispatched; each applies
+    /// to the oldest segment in flight.
+    /// A hit slower than `max_safe_impact_speed` bounces the ship off the
+    /// contact normal and costs hull, then flies the rest of that frame and
+    /// every frame since from the bounce, so where the ship ends up does not
+    /// depend on how long the result took; the re-flown ship can still fall
+    /// off, be shot or be rammed. Anything harder, or a hit the remaining
+    /// hull can't absorb, returns `Some(DeathCause::Collided)`.
+    pub fn resolve_collision_result(
+        &mut self,
+        params: &game_params::GameParams,
+        result: collision::CollisionResult,
+    ) -> Option<DeathCause> {
+        let segment = self.collisions.resolve_next()?;
+        if !result.hit {
+            self.commit_progress_height(params, segment.next_ship.position[1]);
+            return None;
+        }
+        let mut impact_ship = segment.ship_at(result.impact_t);
+        self.commit_progress_height(params, impact_ship.position[1]);
+        // Later segments were swept from a state the hit has invalidated.
+        let later = self.collisions.drain();
+
+        let velocity = impact_ship.velocity;
+        let normal = contact_normal(result.normal, velocity);
+        let approach = velocity[0] * normal[0] + velocity[1] * normal[1];
+        let impact_speed = (-approach).max(0.0);
+        let ship_params = &self.ship.ship_params;
+        let damage = ship_params.hull_damage_per_speed * impact_speed;
+        if ship_params.hull_points > 0.0
+            && impact_speed <= ship_params.max_safe_impact_speed
+            && self.hull > damage
+        {
+            self.hull -= damage;
+            if approach < 0.0 {
+                let scale = (1.0 + ship_params.bounce_restitution) * approach;
+                impact_ship.velocity = [
+                    velocity[0] - scale * normal[0],
+                    velocity[1] - scale * normal[1],
+                ];
+            }
+            let separation = self.bounce_separation(&impact_ship, normal);
+            impact_ship.position[0] += normal[0] * separation;
+            impact_ship.position[1] += normal[1] * separation;
+            log::info!(
+                "Ship bounced off terrain at ({:.0}, {:.0}) speed={:.1} hull={:.1}",
+                impact_ship.position[0],
+                impact_ship.position[1],
+                impact_speed,
+                self.hull
+            );
+            let rest_of_frame = ship::ShipStep {
+                dt: segment.step.dt * (1.0 - result.impact_t),
+                ..segment.step
+            };
+            self.refly_from(
+                params,
+                impact_ship,
+                std::iter::once(rest_of_frame).chain(later.iter().map(|segment| segment.step)),
+            );
+            return self.check_reflown_ship(params);
+        }
+
+        self.prev_ship_state = segment.prev_ship;
+        self.ship_state = impact_ship;
+        log::info!(
+            "Ship collided with terrain at ({:.0}, {:.0}) t={:.3}",
+            impact_ship.position[0],
+            impact_ship.position[1],
+            result.impact_t
+        );
+        Some(DeathCause::Collided)
+    }
+
+    /// How far to push `ship` off a surface along `normal`: the first of
+    /// `BOUNCE_SEPARATION` and its halves that leaves the hull clear of the
+    /// latest terrain, so a bounce in a narrow gap doesn't land in the far
+    /// wall. No push at all when none fits; without terrain, the full push.
+    fn bounce_separation(&self, ship: &ship::ShipState, normal: [f32; 2]) -> f32 {
+        let Some(terrain) = &self.terrain else {
+            return BOUNCE_SEPARATION;
+        };
+        let clear = |separation: f32| {
+            let mut pushed = *ship;
+            pushed.position[0] += normal[0] * separation;
+            pushed.position[1] += normal[1] * separation;
+            !pushed
+                .hull_samples()
+                .iter()
+                .any(|&[x, y]| terrain.is_solid(x.floor() as i32, y.floor() as i32))
+        };
+        std::iter::successors(Some(BOUNCE_SEPARATION), |separation| Some(separation / 2.0))
+            .take(BOUNCE_SEPARATION_TRIES)
+            .find(|&separation| clear(separation))
+            .unwrap_or(0.0)
+    }
+
+    /// Fly `steps` again starting from `ship`, queueing each for collision
+    /// like a fresh frame.
+    fn refly_from(
+        &mut self,
+        params: &game_params::GameParams,
+        mut ship: ship::ShipState,
+        steps: impl IntoIterator<Item = ship::ShipStep>,
+    ) {
+        let mut prev_ship = ship;
+        for step in steps.into_iter().filter(|step| step.dt > 0.0) {
+            prev_ship = ship;
+            ship.apply(&step, params.particle_system_params.gravity);
+            self.collisions.record(prev_ship, ship, step);
+        }
+        self.prev_ship_state = prev_ship;
+        self.ship_state = ship;
+    }
+
+    /// Rerun `update`'s checks against where `refly_from` left the ship:
+    /// the frames it replaced ran them on the pre-bounce path. Only the
+    /// final pose is checked; pickups and projectiles the ship only passed
+    /// through on the way are missed.
+    fn check_reflown_ship(&mut self, params: &game_params::GameParams) -> Option<DeathCause> {
+        if self.off_playfield(params) {
+            return Some(DeathCause::FellOff);
+        }
+        self.update_camera(params);
+        self.collect_pickups(params);
+        if self.turrets.hit(&self.ship_state) {
+            log::info!(
+                "Ship shot down after bouncing at ({:.0}, {:.0})",
+                self.ship_state.position[0],
+                self.ship_state.position[1]
+            );
+            return Some(DeathCause::Shot);
+        }
+        if self.enemies.touching(&self.ship_state) {
+            log::info!(
+                "Ship rammed after bouncing at ({:.0}, {:.0})",
+                self.ship_state.position[0],
+                self.ship_state.position[1]
+            );
+            return Some(DeathCause::Rammed);
+        }
+        None
+    }
+}
+
+/// How far a bounce pushes the ship off the surface, in cells, so the next
+/// sweep doesn't start inside the cell it just hit.
+const BOUNCE_SEPARATION: f32 = 1.0;
+
+/// Pushes tried, halving from `BOUNCE_SEPARATION`, before giving up on one.
+const BOUNCE_SEPARATION_TRIES: usize = 4;
+
+/// Unit normal pointing out of the terrain at a contact. Readbacks without a
+/// normal (zero vector) fall back to straight back along the velocity.
+fn contact_normal(normal: [f32; 2], velocity: [f32; 2]) -> [f32; 2] {
+    let unit = |v: [f32; 2]| {
+        let len = (v[0] * v[0] + v[1] * v[1]).sqrt();
+        (len > 1e-6).then(|| [v[0] / len, v[1] / len])
+    };
+    unit(normal)
+        .or_else(|| unit([-velocity[0], -velocity[1]]))
+        .unwrap_or([0.0, 1.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{angle_diff, Play, BOUNCE_SEPARATION};
+    use crate::ship::ShipStep;
+    use crate::terrain_query::{Region, TerrainSnapshot};
+    use std::{f32::consts::PI, time::Duration};
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn same_angle_is_zero() {
+        assert!(approx(angle_diff(1.0, 1.0), 0.0));
+    }
+
+    #[test]
+    fn small_positive_difference() {
+        assert!(approx(angle_diff(1.0, 0.5), 0.5));
+    }
+
+    #[test]
+    fn small_negative_difference() {
+        assert!(approx(angle_diff(0.5, 1.0), -0.5));
+    }
+
+    #[test]
+    fn wraps_across_pi_boundary() {
+        // From just below π to just above π should be a small positive step,
+        // not a large negative one.
+        let diff = angle_diff(PI - 0.1, -(PI - 0.1));
+        assert!(diff.abs() < 0.3, "should wrap short way, got {}", diff);
+    }
+
+    #[test]
+    fn wraps_negative_direction() {
+        // From just above -π to just below -π.
+        let diff = angle_diff(-(PI - 0.1), PI - 0.1);
+        assert!(diff.abs() < 0.3, "should wrap short way, got {}", diff);
+    }
+
+    #[test]
+    fn opposite_directions_magnitude_is_pi() {
+        let diff = angle_diff(0.0, PI);
+        assert!(
+            approx(diff.abs(), PI),
+            "opposite angles should differ by π, got {}",
+            diff
+        );
+    }
+
+    #[test]
+    fn result_always_in_minus_pi_to_pi() {
+        for i in 0..100 {
+            let target = (i as f32) * 0.13 - 3.0;
+            let current = (i as f32) * 0.07 - 5.0;
+            let diff = angle_diff(target, current);
+            assert!(
+                diff >= -PI - 1e-5 && diff <= PI + 1e-5,
+                "angle_diff({}, {}) = {} out of range",
+                target,
+                current,
+                diff
+            );
+        }
+    }
+
+    #[test]
+    fn level_transition_banks_time_and_keeps_score_height_based() {
+        let mut params = crate::game_params::GameParams::default();
+        params.level_height = 100;
+        params.level_params.level_time_limit_seconds = 60.0;
+
+        let mut play = Play::new(&params, 0);
+        play.timer_elapsed = Duration::from_secs(40);
+        play.commit_progress_height(&params, 100.0);
+
+        assert_eq!(play.current_level_index, 1);
+        assert_eq!(play.level_timer_remaining(&params), Duration::from_secs(80));
+        assert_eq!(play.score, 100);
+    }
+
+    #[test]
+    fn crossing_multiple_levels_adds_one_time_award_per_level() {
+        let mut params = crate::game_params::GameParams::default();
+        params.level_height = 100;
+        params.level_params.level_time_limit_seconds = 60.0;
+
+        let mut play = Play::new(&params, 0);
+        play.timer_elapsed = Duration::from_secs(40);
+        play.commit_progress_height(&params, 250.0);
+
+        assert_eq!(play.current_level_index, 2);
+        assert_eq!(
+            play.level_timer_remaining(&params),
+            Duration::from_secs(140)
+        );
+        assert_eq!(play.score, 250);
+    }
+
+    #[test]
+    fn camera_follows_ship_sideways_within_wide_levels() {
+        let mut params = crate::game_params::GameParams::default();
+        params.viewport_width = 100;
+        params.level_width = 400;
+
+        let mut play = Play::new(&params, 0);
+        // Spawns in the middle of the level, centered on screen.
+        assert_eq!(play.viewport_offset_x, 150);
+
+        play.ship_state.position[0] = 320.5;
+        play.update_camera(&params);
+        assert_eq!(play.viewport_offset_x, 270);
+
+        // Stops at the level edges.
+        play.ship_state.position[0] = 390.0;
+        play.update_camera(&params);
+        assert_eq!(play.viewport_offset_x, 300);
+        play.ship_state.position[0] = 10.0;
+        play.update_camera(&params);
+        assert_eq!(play.viewport_offset_x, 0);
+    }
+
+    #[test]
+    fn camera_stays_put_when_level_fits_the_viewport() {
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        play.ship_state.position[0] = params.level_width as f32 - 1.0;
+        play.update_camera(&params);
+        assert_eq!(play.viewport_offset_x, 0);
+    }
+
+    #[test]
+    fn collision_hit_rewinds_to_the_frame_it_happened_in() {
+        let mut params = crate::game_params::GameParams::default();
+        // Without hull integrity every hit is fatal.
+        params.ship_params.hull_points = 0.0;
+        let mut play = Play::new(&params, 0);
+        let ship = |y: f32| crate::ship::ShipState {
+            position: [10.0, y],
+            ..Default::default()
+        };
+        play.collisions
+            .record(ship(20.0), ship(30.0), ShipStep::default());
+        play.collisions
+            .record(ship(30.0), ship(40.0), ShipStep::default());
+        play.collisions
+            .record(ship(40.0), ship(50.0), ShipStep::default());
+        play.ship_state = ship(50.0);
+        while play.collisions.dispatch_next().is_some() {}
+
+        let clear = crate::collision::CollisionResult::default();
+        assert_eq!(play.resolve_collision_result(&params, clear), None);
+        let death = play.resolve_collision_result(
+            &params,
+            crate::collision::CollisionResult {
+                hit: true,
+                normal: [0.0, -1.0],
+                impact_t: 0.5,
+            },
+        );
+
+        assert_eq!(death, Some(super::DeathCause::Collided));
+        assert!(approx(play.ship_state.position[1], 35.0));
+        assert!(approx(play.prev_ship_state.position[1], 30.0));
+        // Later frames were after the crash and are dropped.
+        assert_eq!(play.collisions.num_in_flight(), 0);
+        assert_eq!(play.resolve_collision_result(&params, clear), None);
+    }
+
+    /// A play with one in-flight segment climbing at `speed` into a ceiling.
+    fn play_climbing_into_ceiling(params: &crate::game_params::GameParams, speed: f32) -> Play {
+        let mut play = Play::new(params, 0);
+        let ship = |y: f32| crate::ship::ShipState {
+            position: [10.0, y],
+            velocity: [0.0, speed],
+            ..Default::default()
+        };
+        play.collisions
+            .record(ship(20.0), ship(30.0), ShipStep::default());
+        play.collisions
+            .record(ship(30.0), ship(40.0), ShipStep::default());
+        play.ship_state = ship(40.0);
+        while play.collisions.dispatch_next().is_some() {}
+        play
+    }
+
+    fn ceiling_hit() -> crate::collision::CollisionResult {
+        crate::collision::CollisionResult {
+            hit: true,
+            normal: [0.0, -1.0],
+            impact_t: 0.5,
+        }
+    }
+
+    #[test]
+    fn slow_hit_bounces_and_costs_hull() {
+        let params = crate::game_params::GameParams::default();
+        let ship_params = &params.ship_params;
+        let speed = ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+
+        assert_eq!(play.resolve_collision_result(&params, ceiling_hit()), None);
+
+        let expected_hull = ship_params.hull_points - ship_params.hull_damage_per_speed * speed;
+        assert!(approx(play.hull, expected_hull), "hull {}", play.hull);
+        assert!(play.hull_fraction() < 1.0);
+        // Reflected downward, scaled by restitution, and nudged off the ceiling.
+        assert!(approx(
+            play.ship_state.velocity[1],
+            -ship_params.bounce_restitution * speed
+        ));
+        assert!(play.ship_state.position[1] < 25.0);
+        assert_eq!(play.prev_ship_state.position, play.ship_state.position);
+        // Later frames were swept from the pre-bounce path.
+        assert_eq!(play.collisions.num_in_flight(), 0);
+    }
+
+    #[test]
+    fn bounce_collects_pickups_where_the_ship_lands() {
+        use crate::pickup::PickupKind;
+
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+        play.pickups
+            .spawn_level(&params, 0, &[([10, 24], PickupKind::TimeBonus)]);
+        let budget = play.timer_budget;
+
+        assert_eq!(play.resolve_collision_result(&params, ceiling_hit()), None);
+        assert!(play.pickups.pickups.is_empty());
+        assert!(play.timer_budget > budget);
+        let live_height = play.ship_state.position[1].floor() as i32;
+        assert_eq!(
+            play.viewport_offset,
+            live_height.max(play.progress_height) - (params.viewport_height / 2) as i32
+        );
+    }
+
+    #[test]
+    fn bounce_below_the_viewport_falls_off() {
+        use super::DeathCause;
+
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+        play.viewport_offset = 25 + params.viewport_height as i32 + 1;
+
+        assert_eq!(
+            play.resolve_collision_result(&params, ceiling_hit()),
+            Some(DeathCause::FellOff)
+        );
+    }
+
+    #[test]
+    fn bounce_in_a_narrow_gap_stays_out_of_the_far_wall() {
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+        // The ceiling hit lands at y = 25.6; put a floor just under the
+        // hull there, closer than a full push.
+        let hit = crate::collision::CollisionResult {
+            impact_t: 0.56,
+            ..ceiling_hit()
+        };
+        let impact = crate::ship::ShipState {
+            position: [10.0, 25.6],
+            ..Default::default()
+        };
+        let lowest = impact
+            .hull_samples()
+            .iter()
+            .map(|p| p[1])
+            .fold(f32::INFINITY, f32::min);
+        assert!(lowest.fract() >= 0.5);
+        let floor_top = lowest.floor() as i32;
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 40,
+            height: 60,
+        };
+        play.observe_terrain(TerrainSnapshot {
+            region,
+            health: (0..region.width * region.height)
+                .map(|i| {
+                    if ((i / region.width) as i32) < floor_top {
+                        100
+                    } else {
+                        0
+                    }
+                })
+                .collect(),
+        });
+
+        assert_eq!(play.resolve_collision_result(&params, hit), None);
+
+        let pushed = play.ship_state.position[1];
+        assert!(
+            pushed < 25.6 && pushed > 25.6 - BOUNCE_SEPARATION,
+            "{pushed}"
+        );
+        assert!(play
+            .ship_state
+            .hull_samples()
+            .iter()
+            .all(|p| (p[1].floor() as i32) >= floor_top));
+    }
+
+    #[test]
+    fn bounce_drops_the_readbacks_in_flight_behind_it() {
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+        assert_eq!(play.collisions.num_in_flight(), 2);
+        assert!(!play.collisions.take_dropped_in_flight());
+
+        assert_eq!(play.resolve_collision_result(&params, ceiling_hit()), None);
+        // The second segment's readback is still coming and must be ignored.
+        assert!(play.collisions.take_dropped_in_flight());
+        assert!(!play.collisions.take_dropped_in_flight());
+    }
+
+    #[test]
+    fn bounce_lands_in_the_same_place_however_late_the_result_arrives() {
+        use crate::input::InputState;
+
+        let params = crate::game_params::GameParams::default();
+        let input = InputState {
+            thrust: 1.0,
+            rotate: 0.5,
+            ..Default::default()
+        };
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        let fly = |play: &mut Play| play.update(&params, &input, dt.as_secs_f32(), dt);
+        let hit = crate::collision::CollisionResult {
+            impact_t: 0.25,
+            ..ceiling_hit()
+        };
+
+        // The hit comes back before the next frame...
+        let mut prompt = Play::new(&params, 0);
+        assert_eq!(fly(&mut prompt), None);
+        prompt.collisions.dispatch_next();
+        assert_eq!(prompt.resolve_collision_result(&params, hit), None);
+        for _ in 0..3 {
+            assert_eq!(fly(&mut prompt), None);
+        }
+
+        // ...or three frames late, with those frames already flown.
+        let mut late = Play::new(&params, 0);
+        for _ in 0..4 {
+            assert_eq!(fly(&mut late), None);
+        }
+        while late.collisions.dispatch_next().is_some() {}
+        assert_eq!(late.resolve_collision_result(&params, hit), None);
+
+        assert!(late.hull < params.ship_params.hull_points);
+        assert_eq!(late.hull, prompt.hull);
+        assert_eq!(late.ship_state.position, prompt.ship_state.position);
+        assert_eq!(late.ship_state.velocity, prompt.ship_state.velocity);
+        assert_eq!(late.ship_state.orientation, prompt.ship_state.orientation);
+        assert_eq!(
+            late.prev_ship_state.position,
+            prompt.prev_ship_state.position
+        );
+        // The frames flown again are swept again.
+        assert_eq!(late.collisions.num_in_flight(), 0);
+        assert!(late.collisions.next_queued().is_some());
+    }
+
+    #[test]
+    fn hard_hit_is_fatal() {
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed * 1.5;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+
+        assert_eq!(
+            play.resolve_collision_result(&params, ceiling_hit()),
+            Some(super::DeathCause::Collided)
+        );
+        assert!(approx(play.hull, params.ship_params.hull_points));
+    }
+
+    #[test]
+    fn hit_the_hull_cannot_absorb_is_fatal() {
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+        play.hull = params.ship_params.hull_damage_per_speed * speed;
+
+        assert_eq!(
+            play.resolve_collision_result(&params, ceiling_hit()),
+            Some(super::DeathCause::Collided)
+        );
+    }
+
+    #[test]
+    fn glancing_hit_keeps_tangential_speed() {
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        let ship = |x: f32, y: f32| crate::ship::ShipState {
+            position: [x, y],
+            velocity: [30.0, 10.0],
+            ..Default::default()
+        };
+        play.collisions
+            .record(ship(10.0, 20.0), ship(13.0, 21.0), ShipStep::default());
+        while play.collisions.dispatch_next().is_some() {}
+
+        assert_eq!(play.resolve_collision_result(&params, ceiling_hit()), None);
+        assert!(approx(play.ship_state.velocity[0], 30.0));
+        assert!(play.ship_state.velocity[1] < 0.0);
+    }
+
+    #[test]
+    fn hit_without_a_normal_bounces_back_along_the_velocity() {
+        let params = crate::game_params::GameParams::default();
+        let speed = params.ship_params.max_safe_impact_speed / 2.0;
+        let mut play = play_climbing_into_ceiling(&params, speed);
+
+        let hit = crate::collision::CollisionResult {
+            normal: [0.0, 0.0],
+            ..ceiling_hit()
+        };
+        assert_eq!(play.resolve_collision_result(&params, hit), None);
+        assert!(play.ship_state.velocity[1] < 0.0);
+    }
+
+    #[test]
+    fn turret_fire_kills_the_ship() {
+        use super::DeathCause;
+        use crate::input::InputState;
+
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        let [x, y] = play.ship_state.position;
+        play.turrets
+            .spawn_level(&params, 0, &[[x as u32 + 20, y as u32]]);
+
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        let death = (0..600).find_map(|_| {
+            // Hold the ship still in front of the turret.
+            play.ship_state.position = [x, y];
+            play.ship_state.velocity = [0.0, 0.0];
+            play.update(&params, &InputState::default(), dt.as_secs_f32(), dt)
+        });
+        assert_eq!(death, Some(DeathCause::Shot));
+    }
+
+    #[test]
+    fn time_pickups_extend_the_countdown() {
+        use crate::input::InputState;
+        use crate::pickup::PickupKind;
+
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        let [x, y] = play.ship_state.position;
+        play.pickups
+            .spawn_level(&params, 0, &[([x as u32, y as u32], PickupKind::TimeBonus)]);
+        let budget = play.timer_budget;
+
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        play.update(&params, &InputState::default(), dt.as_secs_f32(), dt);
+        assert!(play.pickups.pickups.is_empty());
+        assert_eq!(
+            play.timer_budget - budget,
+            Duration::from_secs_f32(params.pickup_params.time_bonus_seconds)
+        );
+        assert_eq!(play.last_pickup, Some((PickupKind::TimeBonus, dt)));
+    }
+
+    #[test]
+    fn chosen_roster_ship_sets_handling_hull_and_tanks() {
+        use crate::game_params::{GameParams, RosterShip, ShipHull};
+
+        let mut params = GameParams::default();
+        let mut hauler = RosterShip {
+            name: "HAULER".to_string(),
+            ship_params: params.ship_params,
+            ..Default::default()
+        };
+        hauler.ship_params.hull = ShipHull::Hauler;
+        hauler.ship_params.acceleration *= 2.0;
+        hauler.ship_params.fuel_capacity *= 3.0;
+        params.ship_roster = vec![RosterShip::default(), hauler.clone()];
+
+        let play = Play::new(&params, 1);
+        assert_eq!(play.ship.name, "HAULER");
+        assert_eq!(play.ship_state.hull, ShipHull::Hauler);
+        assert_eq!(
+            play.ship_state.acceleration,
+            hauler.ship_params.acceleration
+        );
+        assert_eq!(play.fuel, hauler.ship_params.fuel_capacity);
+        assert_eq!(play.fuel_fraction(), 1.0);
+    }
+
+    #[test]
+    fn an_empty_tank_cuts_thrust_and_strands_the_ship() {
+        use super::DeathCause;
+        use crate::input::InputState;
+
+        let mut params = crate::game_params::GameParams::default();
+        params.ship_params.fuel_capacity = 1.0;
+        params.ship_params.fuel_burn_rate = 10.0;
+        let mut play = Play::new(&params, 0);
+        let full_thrust = InputState {
+            thrust: 1.0,
+            ..Default::default()
+        };
+
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        // 0.1 s of full thrust empties the tank.
+        let frames_to_empty = (1..100).find(|_| {
+            play.update(&params, &full_thrust, dt.as_secs_f32(), dt);
+            play.fuel == 0.0
+        });
+        assert!(matches!(frames_to_empty, Some(6..=7)));
+        assert_eq!(play.available_thrust(1.0), 0.0);
+
+        // With the tank empty, only gravity acts on the ship.
+        let velocity = play.ship_state.velocity;
+        play.update(&params, &full_thrust, dt.as_secs_f32(), dt);
+        let gravity = params.particle_system_params.gravity;
+        assert_eq!(play.ship_state.velocity[0], velocity[0]);
+        assert!(approx(
+            play.ship_state.velocity[1],
+            velocity[1] + gravity * dt.as_secs_f32()
+        ));
+
+        let death = (0..600).find_map(|_| {
+            // Hold the ship in place so it cannot fall off the playfield.
+            play.ship_state.velocity = [0.0, 0.0];
+            play.update(&params, &full_thrust, dt.as_secs_f32(), dt)
+        });
+        assert_eq!(death, Some(DeathCause::OutOfFuel));
+    }
+
+    #[test]
+    fn fuel_pickups_refill_up_to_capacity() {
+        use crate::input::InputState;
+        use crate::pickup::PickupKind;
+
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        let [x, y] = play.ship_state.position;
+        play.pickups.spawn_level(
+            &params,
+            0,
+            &[
+                ([x as u32, y as u32], PickupKind::Fuel),
+                ([x as u32 + 1, y as u32], PickupKind::Fuel),
+            ],
+        );
+        play.fuel = 0.0;
+        play.fuel_empty_since = Some(Duration::ZERO);
+
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        play.update(&params, &InputState::default(), dt.as_secs_f32(), dt);
+        assert!(play.pickups.pickups.is_empty());
+        assert_eq!(
+            play.fuel,
+            (2.0 * params.pickup_params.fuel_refill).min(params.ship_params.fuel_capacity)
+        );
+        assert_eq!(play.fuel_empty_since, None);
+
+        play.fuel = params.ship_params.fuel_capacity - 1.0;
+        play.pickups
+            .spawn_level(&params, 0, &[([x as u32, y as u32], PickupKind::Fuel)]);
+        play.update(&params, &InputState::default(), dt.as_secs_f32(), dt);
+        assert_eq!(play.fuel, params.ship_params.fuel_capacity);
+    }
+
+    #[test]
+    fn enemy_contact_kills_the_ship() {
+        use super::DeathCause;
+        use crate::input::InputState;
+
+        let params = crate::game_params::GameParams::default();
+        let mut play = Play::new(&params, 0);
+        let [x, y] = play.ship_state.position;
+        play.enemies
+            .spawn_level(&params, 0, &[[x as u32 + 30, y as u32]]);
+        let region = play.watch_region(&params);
+        play.observe_terrain(crate::terrain_query::TerrainSnapshot {
+            region,
+            health: vec![0; (region.width * region.height) as usize],
+        });
+
+        let dt = Duration::from_secs_f32(1.0 / 60.0);
+        let death = (0..600).find_map(|_| {
+            play.ship_state.position = [x, y];
+            play.ship_state.velocity = [0.0, 0.0];
+            play.update(&params, &InputState::default(), dt.as_secs_f32(), dt)
+        });
+        assert_eq!(death, Some(DeathCause::Rammed));
+    }
+
+    #[test]
+    fn replayed_recording_reproduces_play_session() {
+        use crate::input::InputState;
+        use crate::replay::{InputPlayback, InputRecording};
+
+        let params = crate::game_params::GameParams::default();
+        let mut live = Play::new(&params, 0);
+        let mut recording = InputRecording::new(live.seed, 0, &params);
+        for frame in 0..240 {
+            let input = InputState {
+                thrust: if frame % 50 < 35 { 1.0 } else { 0.0 },
+                rotate: if frame % 90 < 20 { 1.0 } else { 0.0 },
+                ..Default::default()
+            };
+            // Uneven frame times, as a real session would have.
+            let dt = Duration::from_micros(14_000 + (frame % 7) * 1_000);
+            recording.push(&input, dt);
+            live.update(&params, &input, dt.as_secs_f32(), dt);
+        }
+
+        let bytes = recording.to_bytes();
+        let mut playback = InputPlayback::new(InputRecording::from_bytes(&bytes).unwrap());
+        let mut replayed = Play::new(&params, playback.ship() as usize);
+        while let Some(frame) = playback.next_frame() {
+            replayed.update(
+                &params,
+                &frame.input,
+                frame.game_dt.as_secs_f32(),
+                frame.game_dt,
+            );
+        }
+
+        assert_eq!(playback.seed(), live.seed);
+        assert_eq!(replayed.ship_state.position, live.ship_state.position);
+        assert_eq!(replayed.ship_state.velocity, live.ship_state.velocity);
+        assert_eq!(replayed.ship_state.orientation, live.ship_state.orientation);
+        assert_eq!(replayed.score, live.score);
+        assert_eq!(replayed.timer_elapsed, live.timer_elapsed);
+    }
+}
@@ -0,0 +1,534 @@
+//! Input recording and replay for play sessions.
+//!
+//! `Play::update` is a function of `GameParams`, the per-frame `InputState`
+//! and the game dt, so recording those two per frame (plus the run seed that
+//! generated the terrain) is enough to re-drive a session. Terrain erosion and
+//! the ship-vs-terrain collision test still run on the GPU, so a replay follows
+//! the recorded inputs exactly but a collision can land a frame earlier or
+//! later than it did live.
+//!
+//! File layout (little-endian):
+//!
+//! ```text
+//! header: magic "SPRP" | version u16 | seed u32 | ship u32 | params hash u64
+//!         | frame count u32
+//! frame:  flags u32 | game dt nanos u32 | thrust f32 | rotate f32
+//!         [target heading f32] [pointer pressed 2×f32] [pointer released 2×f32]
+//! ```
+//!
+//! The optional trailing fields are present only when the matching `HAS_*`
+//! flag bit is set. Version 1 files have no ship field and flew ship 0.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::game_params::GameParams;
+use crate::input::{InputState, PointerPress};
+
+const MAGIC: &[u8; 4] = b"SPRP";
+pub const FORMAT_VERSION: u16 = 2;
+
+// One bit per boolean `InputState` field, in declaration order.
+const RESTART: u32 = 1 << 0;
+const TOUCH_STARTED: u32 = 1 << 1;
+const HELP: u32 = 1 << 2;
+const AUDIO_NEXT_TRACK: u32 = 1 << 3;
+const AUDIO_TOGGLE: u32 = 1 << 4;
+const PAUSE: u32 = 1 << 5;
+const FULLSCREEN: u32 = 1 << 6;
+const MENU_UP: u32 = 1 << 7;
+const MENU_DOWN: u32 = 1 << 8;
+const MENU_LEFT: u32 = 1 << 9;
+const MENU_RIGHT: u32 = 1 << 10;
+const MENU_CONFIRM: u32 = 1 << 11;
+const MENU_CANCEL: u32 = 1 << 12;
+const CAM_IN: u32 = 1 << 13;
+const CAM_OUT: u32 = 1 << 14;
+const CAM_UP: u32 = 1 << 15;
+const CAM_DOWN: u32 = 1 << 16;
+const CAM_LEFT: u32 = 1 << 17;
+const CAM_RIGHT: u32 = 1 << 18;
+const CAM_PERSPECTIVE: u32 = 1 << 19;
+const CAM_RESET: u32 = 1 << 20;
+// Presence bits for the optional trailing fields.
+const HAS_TARGET_HEADING: u32 = 1 << 21;
+const HAS_POINTER_PRESSED: u32 = 1 << 22;
+const HAS_POINTER_RELEASED: u32 = 1 << 23;
+// Input added since; recordings made before it never set these bits.
+const BOMB: u32 = 1 << 24;
+const FIRE: u32 = 1 << 25;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    NotARecording,
+    UnsupportedVersion(u16),
+    Truncated,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "{err}"),
+            ReplayError::NotARecording => write!(f, "not a Spout input recording"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "recording format version {version} is not supported (expected {FORMAT_VERSION})"
+            ),
+            ReplayError::Truncated => write!(f, "recording is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(value: std::io::Error) -> Self {
+        ReplayError::Io(value)
+    }
+}
+
+/// One simulation frame: the input snapshot `update_phase` consumed and the
+/// game dt it stepped by.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RecordedFrame {
+    pub input: InputState,
+    pub game_dt: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InputRecording {
+    /// Run seed the session's terrain was generated from.
+    pub seed: u32,
+    /// Index of the roster ship the session flew; see `GameParams::ships`.
+    pub ship: u32,
+    /// Fingerprint of the `GameParams` the session was recorded with; see
+    /// [`params_hash`].
+    pub params_hash: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// Stable fingerprint of a config, so a replay can warn when it is driven with
+/// different params than it was recorded with. FNV-1a over the TOML encoding,
+/// then the bytes of every authored level image the config places, since a
+/// native build reads those from disk and the TOML only names them. The
+/// configured seed is excluded: the recording carries the seed it used.
+pub fn params_hash(params: &GameParams) -> u64 {
+    hash_params_and_images(params, |image| {
+        crate::authored_level::image_bytes(image).ok()
+    })
+}
+
+fn hash_params_and_images<'a>(
+    params: &GameParams,
+    image_bytes: impl Fn(&str) -> Option<std::borrow::Cow<'a, [u8]>>,
+) -> u64 {
+    let mut params = params.clone();
+    params.level_params.seed = None;
+    let serialized = toml::to_string(&params).unwrap_or_default();
+    let mut hash = fnv1a(0xcbf2_9ce4_8422_2325, serialized.as_bytes());
+    for placement in &params.level_params.authored_levels {
+        if let Some(bytes) = image_bytes(&placement.image) {
+            hash = fnv1a(hash, &bytes);
+        }
+    }
+    hash
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+impl InputRecording {
+    pub fn new(seed: u32, ship: u32, params: &GameParams) -> Self {
+        Self {
+            seed,
+            ship,
+            params_hash: params_hash(params),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, input: &InputState, game_dt: Duration) {
+        self.frames.push(RecordedFrame {
+            input: *input,
+            game_dt,
+        });
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(26 + self.frames.len() * 16);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.ship.to_le_bytes());
+        out.extend_from_slice(&self.params_hash.to_le_bytes());
+        out.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        for frame in &self.frames {
+            encode_frame(frame, &mut out);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes };
+        if reader.take(4).map_err(|_| ReplayError::NotARecording)? != MAGIC {
+            return Err(ReplayError::NotARecording);
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if !(1..=FORMAT_VERSION).contains(&version) {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+        let seed = reader.u32()?;
+        let ship = if version >= 2 { reader.u32()? } else { 0 };
+        let params_hash = u64::from_le_bytes(reader.array()?);
+        let frame_count = reader.u32()?;
+        let frames = (0..frame_count)
+            .map(|_| decode_frame(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            seed,
+            ship,
+            params_hash,
+            frames,
+        })
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &std::path::Path) -> Result<Self, ReplayError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+/// Feeds a recording back one frame at a time.
+#[derive(Debug)]
+pub struct InputPlayback {
+    recording: InputRecording,
+    next_frame: usize,
+}
+
+impl InputPlayback {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            next_frame: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.recording.seed
+    }
+
+    pub fn ship(&self) -> u32 {
+        self.recording.ship
+    }
+
+    pub fn params_hash(&self) -> u64 {
+        self.recording.params_hash
+    }
+
+    /// Next recorded frame, or `None` once the recording is exhausted.
+    pub fn next_frame(&mut self) -> Option<RecordedFrame> {
+        let frame = self.recording.frames.get(self.next_frame).copied();
+        if frame.is_some() {
+            self.next_frame += 1;
+        }
+        frame
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next_frame >= self.recording.frames.len()
+    }
+}
+
+fn encode_frame(frame: &RecordedFrame, out: &mut Vec<u8>) {
+    let input = &frame.input;
+    let bits = [
+        (input.restart, RESTART),
+        (input.touch_started, TOUCH_STARTED),
+        (input.help, HELP),
+        (input.audio_next_track, AUDIO_NEXT_TRACK),
+        (input.audio_toggle, AUDIO_TOGGLE),
+        (input.pause, PAUSE),
+        (input.fullscreen, FULLSCREEN),
+        (input.menu_up, MENU_UP),
+        (input.menu_down, MENU_DOWN),
+        (input.menu_left, MENU_LEFT),
+        (input.menu_right, MENU_RIGHT),
+        (input.menu_confirm, MENU_CONFIRM),
+        (input.menu_cancel, MENU_CANCEL),
+        (input.cam_in, CAM_IN),
+        (input.cam_out, CAM_OUT),
+        (input.cam_up, CAM_UP),
+        (input.cam_down, CAM_DOWN),
+        (input.cam_left, CAM_LEFT),
+        (input.cam_right, CAM_RIGHT),
+        (input.cam_perspective, CAM_PERSPECTIVE),
+        (input.cam_reset, CAM_RESET),
+        (input.target_heading.is_some(), HAS_TARGET_HEADING),
+        (input.pointer_pressed.is_some(), HAS_POINTER_PRESSED),
+        (input.pointer_released.is_some(), HAS_POINTER_RELEASED),
+        (input.bomb, BOMB),
+        (input.fire, FIRE),
+    ];
+    let flags = bits
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0u32, |flags, (_, bit)| flags | bit);
+
+    // MAX_FRAME_DT keeps game dt far below u32::MAX nanoseconds (~4.3 s).
+    let dt_nanos = frame.game_dt.as_nanos().min(u32::MAX as u128) as u32;
+
+    out.extend_from_slice(&flags.to_le_bytes());
+    out.extend_from_slice(&dt_nanos.to_le_bytes());
+    out.extend_from_slice(&input.thrust.to_le_bytes());
+    out.extend_from_slice(&input.rotate.to_le_bytes());
+    if let Some(heading) = input.target_heading {
+        out.extend_from_slice(&heading.to_le_bytes());
+    }
+    for press in [input.pointer_pressed, input.pointer_released]
+        .into_iter()
+        .flatten()
+    {
+        out.extend_from_slice(&press.x.to_le_bytes());
+        out.extend_from_slice(&press.y.to_le_bytes());
+    }
+}
+
+fn decode_frame(reader: &mut Reader) -> Result<RecordedFrame, ReplayError> {
+    let flags = reader.u32()?;
+    let game_dt = Duration::from_nanos(reader.u32()? as u64);
+    let thrust = reader.f32()?;
+    let rotate = reader.f32()?;
+    let target_heading = if flags & HAS_TARGET_HEADING != 0 {
+        Some(reader.f32()?)
+    } else {
+        None
+    };
+    let mut pointer = |bit: u32| -> Result<Option<PointerPress>, ReplayError> {
+        if flags & bit == 0 {
+            return Ok(None);
+        }
+        Ok(Some(PointerPress {
+            x: reader.f32()?,
+            y: reader.f32()?,
+        }))
+    };
+    let pointer_pressed = pointer(HAS_POINTER_PRESSED)?;
+    let pointer_released = pointer(HAS_POINTER_RELEASED)?;
+
+    let set = |bit: u32| flags & bit != 0;
+    Ok(RecordedFrame {
+        input: InputState {
+            thrust,
+            rotate,
+            bomb: set(BOMB),
+            fire: set(FIRE),
+            target_heading,
+            restart: set(RESTART),
+            touch_started: set(TOUCH_STARTED),
+            pointer_pressed,
+            pointer_released,
+            // Only the editor reads held pointers; recordings cover play.
+            pointer_held: None,
+            help: set(HELP),
+            audio_next_track: set(AUDIO_NEXT_TRACK),
+            audio_toggle: set(AUDIO_TOGGLE),
+            pause: set(PAUSE),
+            fullscreen: set(FULLSCREEN),
+            menu_up: set(MENU_UP),
+            menu_down: set(MENU_DOWN),
+            menu_left: set(MENU_LEFT),
+            menu_right: set(MENU_RIGHT),
+            menu_confirm: set(MENU_CONFIRM),
+            menu_cancel: set(MENU_CANCEL),
+            cam_in: set(CAM_IN),
+            cam_out: set(CAM_OUT),
+            cam_up: set(CAM_UP),
+            cam_down: set(CAM_DOWN),
+            cam_left: set(CAM_LEFT),
+            cam_right: set(CAM_RIGHT),
+            cam_perspective: set(CAM_PERSPECTIVE),
+            cam_reset: set(CAM_RESET),
+        },
+        game_dt,
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < n {
+            return Err(ReplayError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, ReplayError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_recording() -> InputRecording {
+        let mut recording = InputRecording::new(1234, 2, &GameParams::default());
+        recording.push(&InputState::default(), Duration::from_micros(16_667));
+        recording.push(
+            &InputState {
+                thrust: 1.0,
+                rotate: -1.0,
+                bomb: true,
+                fire: true,
+                pause: true,
+                cam_reset: true,
+                ..Default::default()
+            },
+            Duration::from_millis(50),
+        );
+        recording.push(
+            &InputState {
+                target_heading: Some(1.25),
+                pointer_pressed: Some(PointerPress { x: 3.0, y: 4.0 }),
+                pointer_released: Some(PointerPress { x: 5.0, y: 6.0 }),
+                touch_started: true,
+                ..Default::default()
+            },
+            Duration::ZERO,
+        );
+        recording
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let recording = sample_recording();
+        let decoded = InputRecording::from_bytes(&recording.to_bytes()).unwrap();
+        assert_eq!(decoded.seed, 1234);
+        assert_eq!(decoded.ship, 2);
+        assert_eq!(decoded.params_hash, recording.params_hash);
+        assert_eq!(decoded.frames.len(), 3);
+        for (a, b) in recording.frames.iter().zip(&decoded.frames) {
+            assert_eq!(a.game_dt, b.game_dt);
+            // InputState has no PartialEq; Debug covers every field.
+            assert_eq!(format!("{:?}", a.input), format!("{:?}", b.input));
+        }
+    }
+
+    #[test]
+    fn frames_without_optional_fields_are_compact() {
+        let mut recording = InputRecording::new(0, 0, &GameParams::default());
+        let header_len = recording.to_bytes().len();
+        recording.push(&InputState::default(), Duration::from_millis(16));
+        assert_eq!(recording.to_bytes().len() - header_len, 16);
+    }
+
+    #[test]
+    fn version_1_recordings_fly_the_first_ship() {
+        let recording = sample_recording();
+        // Version 1 had no ship field after the seed.
+        let mut bytes = recording.to_bytes();
+        bytes[4..6].copy_from_slice(&1u16.to_le_bytes());
+        bytes.drain(10..14);
+
+        let decoded = InputRecording::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.seed, 1234);
+        assert_eq!(decoded.ship, 0);
+        assert_eq!(decoded.params_hash, recording.params_hash);
+        assert_eq!(decoded.frames.len(), 3);
+    }
+
+    #[test]
+    fn rejects_foreign_and_damaged_files() {
+        assert!(matches!(
+            InputRecording::from_bytes(b"PNG\0 not a recording"),
+            Err(ReplayError::NotARecording)
+        ));
+
+        let mut bytes = sample_recording().to_bytes();
+        bytes[4] = 99;
+        assert!(matches!(
+            InputRecording::from_bytes(&bytes),
+            Err(ReplayError::UnsupportedVersion(99))
+        ));
+
+        let bytes = sample_recording().to_bytes();
+        assert!(matches!(
+            InputRecording::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn playback_yields_frames_in_order_then_stops() {
+        let mut playback = InputPlayback::new(sample_recording());
+        assert_eq!(playback.seed(), 1234);
+        assert_eq!(
+            playback.next_frame().unwrap().game_dt,
+            Duration::from_micros(16_667)
+        );
+        assert!(playback.next_frame().unwrap().input.pause);
+        assert!(!playback.finished());
+        assert!(playback.next_frame().unwrap().input.touch_started);
+        assert!(playback.finished());
+        assert!(playback.next_frame().is_none());
+    }
+
+    #[test]
+    fn params_hash_tracks_config_changes() {
+        let params = GameParams::default();
+        let mut changed = params.clone();
+        changed.ship_params.max_speed += 1.0;
+        assert_eq!(params_hash(&params), params_hash(&params));
+        assert_ne!(params_hash(&params), params_hash(&changed));
+
+        let mut seeded = params.clone();
+        seeded.level_params.seed = Some(5);
+        assert_eq!(params_hash(&params), params_hash(&seeded));
+    }
+
+    #[test]
+    fn params_hash_covers_authored_level_pixels() {
+        let mut params = GameParams::default();
+        params
+            .level_params
+            .authored_levels
+            .push(crate::game_params::AuthoredLevelPlacement {
+                image: "painted.png".into(),
+                level: 1,
+                repeat_every: None,
+            });
+        let painted =
+            |pixels: &'static [u8]| move |_: &str| Some(std::borrow::Cow::Borrowed(pixels));
+        assert_eq!(
+            hash_params_and_images(&params, painted(b"one")),
+            hash_params_and_images(&params, painted(b"one"))
+        );
+        assert_ne!(
+            hash_params_and_images(&params, painted(b"one")),
+            hash_params_and_images(&params, painted(b"two"))
+        );
+    }
+}
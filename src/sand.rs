@@ -0,0 +1,457 @@
+//! Falling-sand pass: loose terrain (dirt) falls and slides into empty cells.
+//!
+//! Runs over the composite terrain buffer after the particle update, so
+//! anything eroded loose or deposited by particles settles before the
+//! composite is decomposed back into the level tiles. Each step moves a loose
+//! cell at most one cell; see `falling_sand.wgsl` for the block scheme that
+//! keeps the GPU pass deterministic. [`step`] is the CPU reference.
+
+use crate::buffer_util::SizedBuffer;
+use crate::material::Material;
+
+/// Must match `particle_workgroup_size` in build.rs.
+const SAND_WORKGROUP_SIZE: u32 = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct SandUniforms {
+    width: u32,
+    height: u32,
+    step: u32,
+    _pad: u32,
+}
+
+pub struct FallingSand {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: SizedBuffer,
+    /// Steps run so far; picks the shader's block grid offset.
+    step: u32,
+}
+
+impl FallingSand {
+    pub fn init(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Falling sand shader module"),
+            source: wgpu::ShaderSource::Wgsl(crate::include_shader!("falling_sand.wgsl")),
+        });
+
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Falling sand BGL"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Terrain buffer
+                storage_entry(1),
+                // Material buffer
+                storage_entry(2),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Falling sand pipeline layout"),
+            bind_group_layouts: &[Some(&bind_group_layout)],
+            immediate_size: 0,
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Falling sand pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let uniform_buffer = crate::buffer_util::make_default_uniform_buffer::<SandUniforms>(
+            device,
+            "Falling sand uniform buffer",
+        );
+
+        FallingSand {
+            pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            step: 0,
+        }
+    }
+
+    /// Run one step over `terrain`, which is `terrain_width` cells wide.
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+        terrain: &crate::level_manager::TerrainTile,
+        terrain_width: u32,
+    ) {
+        let uniforms = SandUniforms {
+            width: terrain_width,
+            height: terrain.shape.size() as u32,
+            step: self.step,
+            _pad: 0,
+        };
+        self.step = self.step.wrapping_add(1);
+
+        // safe: uniform_buffer.size is always > 0 (set at GPU buffer creation)
+        belt.write_buffer(
+            encoder,
+            &self.uniform_buffer.buffer,
+            0,
+            wgpu::BufferSize::new(self.uniform_buffer.size as _).unwrap(),
+        )
+        .copy_from_slice(bytemuck::bytes_of(&uniforms));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Falling sand BG"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: terrain.buffer.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: terrain.materials.buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let num_blocks = terrain_width.div_ceil(2) * uniforms.height.div_ceil(2);
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Falling sand"),
+            timestamp_writes: None,
+        });
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(num_blocks.div_ceil(SAND_WORKGROUP_SIZE), 1, 1);
+    }
+}
+
+/// One step of the automaton on the CPU, exactly as `falling_sand.wgsl` runs
+/// it. `terrain` and `materials` are `width` x `height`, row 0 at the bottom;
+/// `step` is the step counter the GPU pass would use.
+pub fn step(terrain: &mut [i32], materials: &mut [u32], width: u32, height: u32, step: u32) {
+    let offset_x = (step & 1) as i32;
+    let offset_y = ((step >> 1) & 1) as i32;
+    let blocks_x = (width as i32 + offset_x + 1) / 2;
+    let blocks_y = (height as i32 + offset_y + 1) / 2;
+    let mut grid = Grid {
+        terrain,
+        materials,
+        width: width as i32,
+        height: height as i32,
+    };
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let x0 = bx * 2 - offset_x;
+            let y0 = by * 2 - offset_y;
+            let bottom_left = grid.cell_index(x0, y0);
+            let bottom_right = grid.cell_index(x0 + 1, y0);
+            let top_left = grid.cell_index(x0, y0 + 1);
+            let top_right = grid.cell_index(x0 + 1, y0 + 1);
+
+            if grid.is_loose(top_left) && grid.is_empty(bottom_left) {
+                grid.swap(top_left, bottom_left);
+            }
+            if grid.is_loose(top_right) && grid.is_empty(bottom_right) {
+                grid.swap(top_right, bottom_right);
+            }
+            grid.try_slide(top_left, bottom_left, bottom_right);
+            grid.try_slide(top_right, bottom_right, bottom_left);
+        }
+    }
+}
+
+struct Grid<'a> {
+    terrain: &'a mut [i32],
+    materials: &'a mut [u32],
+    width: i32,
+    height: i32,
+}
+
+/// Cell index into a [`Grid`]; `None` is outside the buffer, which acts as a
+/// wall.
+type Cell = Option<usize>;
+
+impl Grid<'_> {
+    fn cell_index(&self, x: i32, y: i32) -> Cell {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    fn is_empty(&self, cell: Cell) -> bool {
+        cell.is_some_and(|i| self.terrain[i] <= 0)
+    }
+
+    fn is_loose(&self, cell: Cell) -> bool {
+        cell.is_some_and(|i| self.terrain[i] > 0 && self.materials[i] == Material::Dirt.id())
+    }
+
+    /// Only called with in-bounds cells: one side is always loose and the
+    /// other empty.
+    fn swap(&mut self, a: Cell, b: Cell) {
+        let (a, b) = (a.unwrap(), b.unwrap());
+        self.terrain.swap(a, b);
+        self.materials.swap(a, b);
+    }
+
+    fn try_slide(&mut self, top: Cell, below: Cell, diagonal: Cell) {
+        if self.is_loose(top) && !self.is_empty(below) && self.is_empty(diagonal) {
+            self.swap(top, diagonal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a picture of a grid, top row first: `D` is dirt, `#` rock, `M`
+    /// metal and `.` empty.
+    fn grid(picture: &str) -> (Vec<i32>, Vec<u32>, u32, u32) {
+        let rows: Vec<&str> = picture.split_whitespace().rev().collect();
+        let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+        let (mut terrain, mut materials) = (vec![], vec![]);
+        for row in rows {
+            for c in row.chars() {
+                let (health, material) = match c {
+                    'D' => (100, Material::Dirt),
+                    '#' => (100, Material::Rock),
+                    'M' => (100, Material::Metal),
+                    _ => (0, Material::Rock),
+                };
+                terrain.push(health);
+                materials.push(material.id());
+            }
+        }
+        (terrain, materials, width, height)
+    }
+
+    fn picture(terrain: &[i32], materials: &[u32], width: u32) -> String {
+        let rows: Vec<String> = terrain
+            .chunks(width as usize)
+            .zip(materials.chunks(width as usize))
+            .rev()
+            .map(|(health, material)| {
+                health
+                    .iter()
+                    .zip(material)
+                    .map(|(&h, &m)| match (h > 0, Material::from_id(m)) {
+                        (false, _) => '.',
+                        (true, Some(Material::Dirt)) => 'D',
+                        (true, Some(Material::Metal)) => 'M',
+                        (true, _) => '#',
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
+    }
+
+    /// Run steps `first..first + count` and return the picture after each.
+    fn run(start: &str, first: u32, count: u32) -> Vec<String> {
+        let (mut terrain, mut materials, width, height) = grid(start);
+        (first..first + count)
+            .map(|n| {
+                step(&mut terrain, &mut materials, width, height, n);
+                picture(&terrain, &materials, width)
+            })
+            .collect()
+    }
+
+    /// Row (counting from the bottom) of the only loose cell in a picture.
+    fn loose_row(picture: &str) -> usize {
+        picture
+            .lines()
+            .rev()
+            .position(|row| row.contains('D'))
+            .unwrap()
+    }
+
+    #[test]
+    fn loose_cell_falls_one_cell_at_a_time_and_lands() {
+        let steps = run(".D.. .... .... .... ####", 0, 12);
+        let mut row = 4;
+        for picture in &steps {
+            let next = loose_row(picture);
+            assert!(
+                next == row || next + 1 == row,
+                "jumped from {row} to {next}"
+            );
+            row = next;
+        }
+        assert_eq!(steps.last().unwrap(), "....\n....\n....\n.D..\n####");
+    }
+
+    #[test]
+    fn only_loose_material_moves() {
+        let start = "#M.. .... ....";
+        for picture in run(start, 0, 8) {
+            assert_eq!(picture, start.replace(' ', "\n"));
+        }
+    }
+
+    #[test]
+    fn loose_cells_slide_off_piles() {
+        // Sits on rock, so slides diagonally off it and keeps falling.
+        let steps = run("D... #... ....", 0, 8);
+        assert_eq!(steps.last().unwrap(), "....\n#...\n.D..");
+        // Either way.
+        let steps = run("...D ...# ....", 0, 8);
+        assert_eq!(steps.last().unwrap(), "....\n...#\n..D.");
+    }
+
+    #[test]
+    fn buffer_edges_act_as_walls() {
+        // Odd sizes leave cells on the edges that only some block offsets
+        // reach; they still fall, and nothing falls or slides out.
+        let steps = run("D.D .D. ...", 0, 12);
+        assert_eq!(steps.last().unwrap(), "...\n...\nDDD");
+        let steps = run("D D D", 0, 12);
+        assert_eq!(steps.last().unwrap(), "D\nD\nD");
+    }
+
+    #[test]
+    fn stacks_settle_and_conserve_cells() {
+        let start = "DDDD DDDD .... .... .... #..#";
+        let (mut terrain, mut materials, width, height) = grid(start);
+        let total: i32 = terrain.iter().sum();
+        for n in 0..40 {
+            step(&mut terrain, &mut materials, width, height, n);
+            assert_eq!(terrain.iter().sum::<i32>(), total);
+        }
+        let settled = picture(&terrain, &materials, width);
+        assert_eq!(settled, "....\n....\n....\nD..D\nDDDD\n#DD#");
+        for n in 40..48 {
+            step(&mut terrain, &mut materials, width, height, n);
+        }
+        assert_eq!(picture(&terrain, &materials, width), settled);
+    }
+
+    #[test]
+    fn same_start_and_step_count_give_same_grid() {
+        let start = "D.DD.D ..DD.. D....D ..##.. ......";
+        assert_eq!(run(start, 0, 12), run(start, 0, 12));
+        // The step counter matters: it picks the block offset.
+        assert_ne!(run(".D.. ....", 0, 1), run(".D.. ....", 2, 1));
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod gpu_tests {
+    use super::*;
+    use crate::gpu_test_utils as gpu;
+    use wgpu::util::DeviceExt;
+
+    /// Random grids stepped by the shader and by [`step`] stay identical.
+    #[test]
+    fn test_falling_sand_matches_cpu_reference() {
+        let Some((device, queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping test_falling_sand_matches_cpu_reference"
+            );
+            return;
+        };
+
+        // Odd sizes so every block grid offset hangs off some edge.
+        const W: u32 = 37;
+        const H: u32 = 29;
+        const STEPS: u32 = 25;
+        let mut rng = fastrand::Rng::with_seed(7);
+        let terrain: Vec<i32> = (0..W * H)
+            .map(|_| {
+                if rng.bool() {
+                    rng.i32(1..1000)
+                } else {
+                    -rng.i32(0..1000)
+                }
+            })
+            .collect();
+        let materials: Vec<u32> = (0..W * H)
+            .map(|_| rng.u32(0..crate::material::NUM_MATERIALS as u32))
+            .collect();
+
+        let storage = |label: &str, contents: &[u8]| SizedBuffer {
+            buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            }),
+            size: contents.len() as _,
+        };
+        let tile = crate::level_manager::TerrainTile {
+            shape: crate::level_manager::Interval {
+                start: 100,
+                end: 100 + H as i32,
+            },
+            buffer: storage("Test terrain", bytemuck::cast_slice(&terrain)),
+            materials: storage("Test materials", bytemuck::cast_slice(&materials)),
+        };
+
+        let mut sand = FallingSand::init(&device);
+        let mut belt = wgpu::util::StagingBelt::new(device.clone(), 256);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        for _ in 0..STEPS {
+            sand.run(&device, &mut encoder, &mut belt, &tile, W);
+        }
+        let staging: Vec<_> = [&tile.buffer, &tile.materials]
+            .into_iter()
+            .map(|source| {
+                let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Falling sand readback staging"),
+                    size: source.size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                encoder.copy_buffer_to_buffer(&source.buffer, 0, &staging, 0, source.size);
+                staging
+            })
+            .collect();
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+
+        for buffer in &staging {
+            buffer.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        }
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let gpu_terrain: Vec<i32> =
+            bytemuck::cast_slice(&staging[0].slice(..).get_mapped_range()).to_vec();
+        let gpu_materials: Vec<u32> =
+            bytemuck::cast_slice(&staging[1].slice(..).get_mapped_range()).to_vec();
+
+        let (mut cpu_terrain, mut cpu_materials) = (terrain.clone(), materials.clone());
+        for n in 0..STEPS {
+            step(&mut cpu_terrain, &mut cpu_materials, W, H, n);
+        }
+        assert_ne!(cpu_terrain, terrain, "nothing moved; the test is vacuous");
+        assert!(gpu_terrain == cpu_terrain, "terrain differs");
+        assert!(gpu_materials == cpu_materials, "materials differ");
+    }
+}
@@ -0,0 +1,464 @@
+use spout::game_params::GameParams;
+use spout::input::{InputFrame, PointerPress};
+use spout::level_editor::{Brush, EditorLevel, BRUSH_RADII};
+use spout::material::Material;
+use spout::text::TextRenderer;
+use spout::ui::{self, RectStyle, UiButton, UiRect, UiRenderer};
+
+const BUTTON_PAD_X: f32 = 4.0;
+const BUTTON_PAD_Y: f32 = 4.0;
+const BUTTON_LABEL_H: f32 = 12.0;
+const BUTTON_MARGIN: f32 = 4.0;
+/// Camera speed while an arrow key or the UP / DN button is held.
+const SCROLL_SPEED: f32 = 240.0;
+/// How long status messages (saved, load failed, ...) stay on screen.
+const STATUS_SECONDS: f32 = 2.0;
+/// Where SAVE writes and LOAD reads the level image, relative to the working
+/// directory.
+#[cfg(not(target_arch = "wasm32"))]
+const LEVEL_FILE: &str = "edited_level.png";
+
+/// Level editor: paints level 0 with the pointer, test-flies it, and saves or
+/// loads it as an authored level image.
+#[derive(Debug)]
+pub struct EditorScreen {
+    level: EditorLevel,
+    /// Camera offset in world cells; rounded for rendering.
+    scroll: [f32; 2],
+    material: Material,
+    erasing: bool,
+    brush_size: usize,
+    pressed_button: Option<ButtonAction>,
+    /// Last painted world point while a stroke is in progress.
+    stroke: Option<[f32; 2]>,
+    /// Whether `level` changed since the terrain was last uploaded. Entering
+    /// the editor builds the terrain from `level`, so it starts clean.
+    dirty: bool,
+    status: Option<(&'static str, f32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorAction {
+    TestFly,
+    Exit,
+}
+
+pub struct EditorUiRenderContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub title_ui_view: &'a wgpu::TextureView,
+    pub ui: &'a UiRenderer,
+    pub params: &'a GameParams,
+    pub text: &'a TextRenderer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonAction {
+    Material,
+    Erase,
+    Size,
+    ScrollUp,
+    Fly,
+    Save,
+    Load,
+    Exit,
+    ScrollDown,
+}
+
+impl EditorScreen {
+    pub fn new(params: &GameParams) -> Self {
+        Self {
+            level: EditorLevel::empty(
+                params.level_width,
+                params.level_height,
+                params.level_params.starting_terrain_health,
+            ),
+            scroll: [0.0, 0.0],
+            material: Material::Rock,
+            erasing: false,
+            brush_size: 1,
+            pressed_button: None,
+            stroke: None,
+            dirty: false,
+            status: None,
+        }
+    }
+
+    pub fn level(&self) -> &EditorLevel {
+        &self.level
+    }
+
+    pub fn viewport_offset(&self) -> [i32; 2] {
+        [self.scroll[0].round() as i32, self.scroll[1].round() as i32]
+    }
+
+    /// The edited level if it changed since the last call, for re-uploading
+    /// the terrain.
+    pub fn take_changes(&mut self) -> Option<&EditorLevel> {
+        std::mem::take(&mut self.dirty).then_some(&self.level)
+    }
+
+    pub fn update(
+        &mut self,
+        input: InputFrame,
+        params: &GameParams,
+        text: &TextRenderer,
+        surface_size: (u32, u32),
+        wall_dt: f32,
+    ) -> Option<EditorAction> {
+        if let Some((_, remaining)) = &mut self.status {
+            *remaining -= wall_dt;
+            if *remaining <= 0.0 {
+                self.status = None;
+            }
+        }
+
+        if input.menu_cancel_pressed() {
+            return Some(EditorAction::Exit);
+        }
+
+        let mut scroll = [0.0, 0.0];
+        if input.current.menu_left {
+            scroll[0] -= 1.0;
+        }
+        if input.current.menu_right {
+            scroll[0] += 1.0;
+        }
+        if input.current.menu_up {
+            scroll[1] += 1.0;
+        }
+        if input.current.menu_down {
+            scroll[1] -= 1.0;
+        }
+        if input.pointer_held().is_some() {
+            match self.pressed_button {
+                Some(ButtonAction::ScrollUp) => scroll[1] += 1.0,
+                Some(ButtonAction::ScrollDown) => scroll[1] -= 1.0,
+                _ => {}
+            }
+        }
+        self.scroll_by(
+            [
+                scroll[0] * SCROLL_SPEED * wall_dt,
+                scroll[1] * SCROLL_SPEED * wall_dt,
+            ],
+            params,
+        );
+
+        if let Some(point) = input.pointer_pressed() {
+            self.pressed_button = self.button_at(point, params, text, surface_size);
+            if self.pressed_button.is_none() {
+                if let Some(world) = self.world_point(point, params, surface_size) {
+                    self.dirty |= self.level.stamp(world, self.brush_radius(), self.brush());
+                    self.stroke = Some(world);
+                }
+            }
+        }
+
+        if let (Some(from), Some(point)) = (self.stroke, input.pointer_held()) {
+            if let Some(to) = self.world_point(point, params, surface_size) {
+                self.dirty |= self
+                    .level
+                    .stroke(from, to, self.brush_radius(), self.brush());
+                self.stroke = Some(to);
+            }
+        }
+
+        if let Some(point) = input.pointer_released() {
+            self.stroke = None;
+            let pressed_button = self.pressed_button.take();
+            let released_button = self.button_at(point, params, text, surface_size);
+            if let (Some(pressed), Some(released)) = (pressed_button, released_button) {
+                if pressed == released {
+                    return self.activate_button(released, params);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn prepare_ui(&self, ctx: EditorUiRenderContext<'_>) {
+        {
+            let _pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("editor_ui_clear"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: ctx.title_ui_view,
+                    resolve_target: None,
+                    depth_slice: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                ..Default::default()
+            });
+        }
+
+        let button_color = [0.7, 0.78, 0.78, 1.0];
+        let accent_color = [0.9, 0.72, 0.48, 1.0];
+        let buttons = self.buttons(ctx.params, ctx.text);
+        let rects: Vec<(UiRect, RectStyle)> = buttons
+            .iter()
+            .map(|button| (button.rect, self.button_style(button.action)))
+            .collect();
+        ctx.ui
+            .draw_rects(ctx.device, ctx.encoder, ctx.title_ui_view, &rects);
+
+        let mut texts: Vec<(&str, f32, f32, f32, [f32; 4])> = Vec::new();
+        for button in &buttons {
+            let scale = 1.0;
+            let label_x =
+                button.rect.x + (button.rect.w - ctx.text.text_width(button.label, scale)) / 2.0;
+            let label_y = button.rect.y + (button.rect.h - BUTTON_LABEL_H) / 2.0;
+            let color = if self.button_highlighted(button.action) {
+                accent_color
+            } else {
+                button_color
+            };
+            texts.push((button.label, label_x, label_y, scale, color));
+        }
+
+        if let Some((message, _)) = self.status {
+            let x = (ctx.text.surface_width - ctx.text.text_width(message, 1.0)) / 2.0;
+            let y = ctx.params.viewport_height as f32 / 2.0 - BUTTON_LABEL_H / 2.0;
+            texts.push((message, x, y, 1.0, accent_color));
+        }
+
+        ctx.text
+            .draw(ctx.device, ctx.encoder, ctx.title_ui_view, &texts);
+    }
+
+    fn brush(&self) -> Brush {
+        if self.erasing {
+            Brush::Erase
+        } else {
+            Brush::Paint(self.material)
+        }
+    }
+
+    fn brush_radius(&self) -> u32 {
+        BRUSH_RADII[self.brush_size]
+    }
+
+    fn scroll_by(&mut self, delta: [f32; 2], params: &GameParams) {
+        let max_x = params.level_width.saturating_sub(params.viewport_width) as f32;
+        let max_y = params.level_height.saturating_sub(params.viewport_height) as f32;
+        self.scroll = [
+            (self.scroll[0] + delta[0]).clamp(0.0, max_x),
+            (self.scroll[1] + delta[1]).clamp(0.0, max_y),
+        ];
+    }
+
+    /// World cell under a surface point. Game-space UI points run y-down;
+    /// world rows run y-up from the bottom of the viewport.
+    fn world_point(
+        &self,
+        point: PointerPress,
+        params: &GameParams,
+        surface_size: (u32, u32),
+    ) -> Option<[f32; 2]> {
+        let (game_x, game_y) = ui::surface_to_game_point(
+            point,
+            params.viewport_width,
+            params.viewport_height,
+            surface_size.0,
+            surface_size.1,
+        )?;
+        let [offset_x, offset_y] = self.viewport_offset();
+        Some([
+            offset_x as f32 + game_x,
+            offset_y as f32 + params.viewport_height as f32 - game_y,
+        ])
+    }
+
+    fn buttons(&self, params: &GameParams, text: &TextRenderer) -> Vec<UiButton<ButtonAction>> {
+        let material_label = match self.material {
+            Material::Rock => "ROCK",
+            Material::Dirt => "DIRT",
+            Material::Metal => "METAL",
+            Material::Crystal => "CRYS",
+        };
+        let size_label = ["S2", "S4", "S8", "S16"][self.brush_size];
+        let (_, button_h) = Self::button_size("", text);
+        let mut buttons = Self::button_row(
+            &[
+                (ButtonAction::Material, material_label),
+                (ButtonAction::Erase, "ERASE"),
+                (ButtonAction::Size, size_label),
+                (ButtonAction::ScrollUp, "UP"),
+            ],
+            BUTTON_MARGIN,
+            params,
+            text,
+        );
+        buttons.extend(Self::button_row(
+            &[
+                (ButtonAction::Fly, "FLY"),
+                (ButtonAction::Save, "SAVE"),
+                (ButtonAction::Load, "LOAD"),
+                (ButtonAction::Exit, "X"),
+                (ButtonAction::ScrollDown, "DN"),
+            ],
+            params.viewport_height as f32 - button_h - BUTTON_MARGIN,
+            params,
+            text,
+        ));
+        buttons
+    }
+
+    /// Lay out `labels` across the full viewport width at height `y`, with the
+    /// leftover width shared out as equal gaps.
+    fn button_row(
+        labels: &[(ButtonAction, &'static str)],
+        y: f32,
+        params: &GameParams,
+        text: &TextRenderer,
+    ) -> Vec<UiButton<ButtonAction>> {
+        let sizes: Vec<(f32, f32)> = labels
+            .iter()
+            .map(|(_, label)| Self::button_size(label, text))
+            .collect();
+        let buttons_w: f32 = sizes.iter().map(|(w, _)| w).sum();
+        let gap = ((params.viewport_width as f32 - buttons_w - BUTTON_MARGIN * 2.0)
+            / (labels.len() - 1) as f32)
+            .floor()
+            .max(1.0);
+        let mut x = BUTTON_MARGIN;
+        labels
+            .iter()
+            .zip(sizes)
+            .map(|(&(action, label), (w, h))| {
+                let button = UiButton {
+                    action,
+                    label,
+                    rect: UiRect { x, y, w, h },
+                };
+                x += w + gap;
+                button
+            })
+            .collect()
+    }
+
+    fn button_at(
+        &self,
+        point: PointerPress,
+        params: &GameParams,
+        text: &TextRenderer,
+        surface_size: (u32, u32),
+    ) -> Option<ButtonAction> {
+        let (game_x, game_y) = ui::surface_to_game_point(
+            point,
+            params.viewport_width,
+            params.viewport_height,
+            surface_size.0,
+            surface_size.1,
+        )?;
+        self.buttons(params, text)
+            .iter()
+            .find(|button| button.rect.contains(game_x, game_y))
+            .map(|button| button.action)
+    }
+
+    fn activate_button(
+        &mut self,
+        action: ButtonAction,
+        params: &GameParams,
+    ) -> Option<EditorAction> {
+        match action {
+            ButtonAction::Material => {
+                let next = (self.material.id() as usize + 1) % Material::ALL.len();
+                self.material = Material::ALL[next];
+                self.erasing = false;
+            }
+            ButtonAction::Erase => self.erasing = !self.erasing,
+            ButtonAction::Size => self.brush_size = (self.brush_size + 1) % BRUSH_RADII.len(),
+            ButtonAction::ScrollUp | ButtonAction::ScrollDown => {}
+            ButtonAction::Fly => return Some(EditorAction::TestFly),
+            ButtonAction::Save => self.save(),
+            ButtonAction::Load => self.load(params),
+            ButtonAction::Exit => return Some(EditorAction::Exit),
+        }
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&mut self) {
+        match std::fs::write(LEVEL_FILE, self.level.to_png()) {
+            Ok(()) => {
+                log::info!("Saved edited level to {LEVEL_FILE}");
+                self.show_status("SAVED");
+            }
+            Err(err) => {
+                log::warn!("Failed to save edited level to {LEVEL_FILE}: {err}");
+                self.show_status("SAVE FAILED");
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load(&mut self, params: &GameParams) {
+        let loaded = std::fs::read(LEVEL_FILE)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| {
+                EditorLevel::from_png(
+                    &bytes,
+                    params.level_width,
+                    params.level_height,
+                    params.level_params.starting_terrain_health,
+                )
+                .map_err(|err| err.to_string())
+            });
+        match loaded {
+            Ok(level) => {
+                log::info!("Loaded edited level from {LEVEL_FILE}");
+                self.level = level;
+                self.dirty = true;
+                self.show_status("LOADED");
+            }
+            Err(err) => {
+                log::warn!("Failed to load edited level from {LEVEL_FILE}: {err}");
+                self.show_status("LOAD FAILED");
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save(&mut self) {
+        log::warn!("Saving edited levels is not supported on the web");
+        self.show_status("NO FILES ON WEB");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load(&mut self, _params: &GameParams) {
+        log::warn!("Loading edited levels is not supported on the web");
+        self.show_status("NO FILES ON WEB");
+    }
+
+    fn show_status(&mut self, message: &'static str) {
+        self.status = Some((message, STATUS_SECONDS));
+    }
+
+    fn button_style(&self, action: ButtonAction) -> RectStyle {
+        ui::button_style(
+            self.pressed_button == Some(action),
+            self.button_selected(action),
+        )
+    }
+
+    fn button_highlighted(&self, action: ButtonAction) -> bool {
+        self.pressed_button == Some(action) || self.button_selected(action)
+    }
+
+    /// Toggle buttons stay highlighted while their mode is on.
+    fn button_selected(&self, action: ButtonAction) -> bool {
+        action == ButtonAction::Erase && self.erasing
+    }
+
+    fn button_size(label: &str, text: &TextRenderer) -> (f32, f32) {
+        let w = (text.text_width(label, 1.0) + BUTTON_PAD_X * 2.0).round();
+        let h = (BUTTON_LABEL_H + BUTTON_PAD_Y * 2.0).round();
+        (w, h)
+    }
+}
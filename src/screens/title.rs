@@ -13,6 +13,9 @@ const BUTTON_SIDE_MARGIN: f32 = 14.0;
 #[derive(Debug)]
 pub struct TitleScreen {
     instructions_open: bool,
+    ship_select_open: bool,
+    /// Roster index of the ship shown on the ship select.
+    selected_ship: usize,
     focused_button: ButtonAction,
     pressed_button: Option<ButtonAction>,
     focus_visible: bool,
@@ -21,8 +24,17 @@ pub struct TitleScreen {
 
 impl Default for TitleScreen {
     fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl TitleScreen {
+    /// A title screen whose ship select starts on roster ship `selected_ship`.
+    pub fn new(selected_ship: usize) -> Self {
         Self {
             instructions_open: false,
+            ship_select_open: false,
+            selected_ship,
             focused_button: ButtonAction::Play,
             pressed_button: None,
             focus_visible: true,
@@ -33,7 +45,11 @@ impl Default for TitleScreen {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TitleAction {
-    StartGame,
+    /// Fly roster ship `ship`.
+    StartGame {
+        ship: usize,
+    },
+    OpenEditor,
     ToggleMusic,
 }
 
@@ -56,8 +72,13 @@ pub struct TitleUiRenderContext<'a> {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ButtonAction {
     Play,
+    Edit,
     Menu,
     Music,
+    PrevShip,
+    NextShip,
+    Fly,
+    Back,
 }
 
 impl TitleScreen {
@@ -105,7 +126,7 @@ impl TitleScreen {
             );
             if let (Some(pressed), Some(released)) = (pressed_button, released_button) {
                 if pressed == released {
-                    return self.activate_button(released);
+                    return self.activate_button(released, params);
                 }
             }
             return None;
@@ -125,6 +146,13 @@ impl TitleScreen {
             return None;
         }
 
+        if input.menu_cancel_pressed() && self.ship_select_open {
+            self.focus_visible = true;
+            self.keyboard_focus_engaged = true;
+            self.close_ship_select();
+            return None;
+        }
+
         if self.move_focus_from_input(input, &buttons) {
             return None;
         }
@@ -132,14 +160,14 @@ impl TitleScreen {
         if input.menu_confirm_pressed() {
             self.focus_visible = true;
             self.keyboard_focus_engaged = true;
-            return self.activate_button(self.focused_button);
+            return self.activate_button(self.focused_button, params);
         }
 
         None
     }
 
     pub fn prepare_ui(&self, ctx: TitleUiRenderContext<'_>) {
-        let clear_color = if self.instructions_open {
+        let clear_color = if self.instructions_open || self.ship_select_open {
             wgpu::Color {
                 r: 0.02,
                 g: 0.05,
@@ -178,6 +206,12 @@ impl TitleScreen {
         ctx.ui
             .draw_rects(ctx.device, ctx.encoder, ctx.title_ui_view, &rects);
 
+        let ship_lines = if self.ship_select_open {
+            self.ship_lines(ctx.params, text_color, accent_color)
+        } else {
+            Vec::new()
+        };
+
         let mut texts: Vec<(&str, f32, f32, f32, [f32; 4])> = Vec::new();
         for button in &buttons {
             let scale = 1.0;
@@ -216,10 +250,40 @@ impl TitleScreen {
             }));
         }
 
+        texts.extend(ship_lines.iter().map(|(line, y, color)| {
+            let scale = 1.0;
+            let x = (ctx.text.surface_width - ctx.text.text_width(line, scale)) / 2.0;
+            (line.as_str(), x, *y, scale, *color)
+        }));
+
         ctx.text
             .draw(ctx.device, ctx.encoder, ctx.title_ui_view, &texts);
     }
 
+    /// The selected ship's name and the stats it trades off, one line each.
+    fn ship_lines(
+        &self,
+        params: &GameParams,
+        text_color: [f32; 4],
+        accent_color: [f32; 4],
+    ) -> Vec<(String, f32, [f32; 4])> {
+        let ships = params.ships();
+        let Some(ship) = ships.get(self.selected_ship) else {
+            return Vec::new();
+        };
+        let stats = &ship.ship_params;
+        vec![
+            (ship.name.clone(), 24.0, accent_color),
+            (
+                format!("THRUST {:.0}", stats.acceleration),
+                46.0,
+                text_color,
+            ),
+            (format!("TURN {:.1}", stats.rotation_rate), 64.0, text_color),
+            (format!("SPEED {:.0}", stats.max_speed), 82.0, text_color),
+        ]
+    }
+
     fn buttons(
         &self,
         params: &GameParams,
@@ -259,11 +323,66 @@ impl TitleScreen {
             ];
         }
 
+        if self.ship_select_open {
+            let (arrow_w, button_h) = Self::button_size("<", text);
+            let (fly_w, _) = Self::button_size("FLY", text);
+            let (close_w, _) = Self::button_size("X", text);
+            let y = params.viewport_height as f32 - button_h - BUTTON_BOTTOM_MARGIN;
+            let start_x = (params.viewport_width as f32 - fly_w) / 2.0 - arrow_w - BUTTON_GAP;
+            return vec![
+                UiButton {
+                    action: ButtonAction::PrevShip,
+                    label: "<",
+                    rect: UiRect {
+                        x: start_x,
+                        y,
+                        w: arrow_w,
+                        h: button_h,
+                    },
+                },
+                UiButton {
+                    action: ButtonAction::Fly,
+                    label: "FLY",
+                    rect: UiRect {
+                        x: start_x + arrow_w + BUTTON_GAP,
+                        y,
+                        w: fly_w,
+                        h: button_h,
+                    },
+                },
+                UiButton {
+                    action: ButtonAction::NextShip,
+                    label: ">",
+                    rect: UiRect {
+                        x: start_x + arrow_w + fly_w + BUTTON_GAP * 2.0,
+                        y,
+                        w: arrow_w,
+                        h: button_h,
+                    },
+                },
+                UiButton {
+                    action: ButtonAction::Back,
+                    label: "X",
+                    rect: UiRect {
+                        x: params.viewport_width as f32 - close_w - BUTTON_SIDE_MARGIN,
+                        y,
+                        w: close_w,
+                        h: button_h,
+                    },
+                },
+            ];
+        }
+
         let (play_w, button_h) = Self::button_size("PLAY", text);
+        let (edit_w, _) = Self::button_size("EDIT", text);
         let (menu_w, _) = Self::button_size("MENU", text);
         let y = params.viewport_height as f32 - button_h - BUTTON_BOTTOM_MARGIN;
-        let row_w = play_w + menu_w + BUTTON_GAP;
-        let start_x = (params.viewport_width as f32 - row_w) / 2.0;
+        let buttons_w = play_w + edit_w + menu_w;
+        // Narrow viewports squeeze the gaps rather than the buttons.
+        let gap = ((params.viewport_width as f32 - buttons_w - BUTTON_SIDE_MARGIN * 2.0) / 2.0)
+            .floor()
+            .clamp(1.0, BUTTON_GAP);
+        let start_x = (params.viewport_width as f32 - buttons_w - gap * 2.0) / 2.0;
         vec![
             UiButton {
                 action: ButtonAction::Play,
@@ -275,11 +394,21 @@ impl TitleScreen {
                     h: button_h,
                 },
             },
+            UiButton {
+                action: ButtonAction::Edit,
+                label: "EDIT",
+                rect: UiRect {
+                    x: start_x + play_w + gap,
+                    y,
+                    w: edit_w,
+                    h: button_h,
+                },
+            },
             UiButton {
                 action: ButtonAction::Menu,
                 label: "MENU",
                 rect: UiRect {
-                    x: start_x + play_w + BUTTON_GAP,
+                    x: start_x + play_w + edit_w + gap * 2.0,
                     y,
                     w: menu_w,
                     h: button_h,
@@ -310,19 +439,59 @@ impl TitleScreen {
             .map(|button| button.action)
     }
 
-    fn activate_button(&mut self, action: ButtonAction) -> Option<TitleAction> {
+    fn activate_button(
+        &mut self,
+        action: ButtonAction,
+        params: &GameParams,
+    ) -> Option<TitleAction> {
+        let num_ships = params.ships().len();
         match action {
-            ButtonAction::Play => Some(TitleAction::StartGame),
+            // With a single ship there is nothing to choose.
+            ButtonAction::Play if num_ships <= 1 => Some(TitleAction::StartGame { ship: 0 }),
+            ButtonAction::Play => {
+                self.open_ship_select(num_ships);
+                None
+            }
+            ButtonAction::Edit => Some(TitleAction::OpenEditor),
             ButtonAction::Menu => {
                 self.toggle_menu();
                 None
             }
             ButtonAction::Music => Some(TitleAction::ToggleMusic),
+            ButtonAction::PrevShip => {
+                self.selected_ship = (self.selected_ship + num_ships - 1) % num_ships;
+                None
+            }
+            ButtonAction::NextShip => {
+                self.selected_ship = (self.selected_ship + 1) % num_ships;
+                None
+            }
+            ButtonAction::Fly => Some(TitleAction::StartGame {
+                ship: self.selected_ship,
+            }),
+            ButtonAction::Back => {
+                self.close_ship_select();
+                None
+            }
         }
     }
 
+    fn open_ship_select(&mut self, num_ships: usize) {
+        self.pressed_button = None;
+        self.ship_select_open = true;
+        self.selected_ship %= num_ships;
+        self.focused_button = ButtonAction::Fly;
+    }
+
+    fn close_ship_select(&mut self) {
+        self.pressed_button = None;
+        self.ship_select_open = false;
+        self.focused_button = ButtonAction::Play;
+    }
+
     fn toggle_menu(&mut self) {
         self.pressed_button = None;
+        self.ship_select_open = false;
         if self.instructions_open {
             self.close_menu();
         } else {
@@ -386,21 +555,7 @@ impl TitleScreen {
     fn button_style(&self, action: ButtonAction, focus_visible: bool) -> RectStyle {
         let pressed = self.pressed_button == Some(action);
         let focused = focus_visible && action == self.focused_button;
-        RectStyle {
-            fill_color: if pressed {
-                [0.12, 0.16, 0.16, 0.9]
-            } else if focused {
-                [0.08, 0.12, 0.13, 0.78]
-            } else {
-                [0.02, 0.05, 0.07, 0.68]
-            },
-            outline_color: if pressed || focused {
-                [0.9, 0.72, 0.48, 1.0]
-            } else {
-                [0.45, 0.57, 0.58, 0.92]
-            },
-            outline_px: if pressed || focused { 2.0 } else { 1.0 },
-        }
+        ui::button_style(pressed, focused)
     }
 
     fn button_highlighted(&self, action: ButtonAction, focus_visible: bool) -> bool {
@@ -36,6 +36,7 @@ struct Uniforms {
     viewport_height: f32,
     viewport_offset: f32,
     tile_size: f32,
+    viewport_offset_x: f32,
 };
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
@@ -48,11 +49,12 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let px = in.tex_coord.x * uniforms.viewport_width;
     let py = in.tex_coord.y * uniforms.viewport_height;
 
-    // World Y (scrolls with viewport).
+    // World position (scrolls with viewport).
+    let world_x = uniforms.viewport_offset_x + px;
     let world_y = uniforms.viewport_offset + py;
 
     // Tile UV — the Repeat sampler handles wrapping automatically.
-    let tile_u = px / uniforms.tile_size;
+    let tile_u = world_x / uniforms.tile_size;
     let tile_v = world_y / uniforms.tile_size;
 
     return textureSampleLevel(tile_texture, tile_sampler, vec2<f32>(tile_u, tile_v), 0.0);
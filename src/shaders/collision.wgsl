@@ -1,8 +1,12 @@
+{% include "ship_hull.wgsl.include" %}
 // Ship-terrain collision detection with contact normal.
 //
-// Parallelizes over sampled hull points. Each workgroup lane Bresenham-walks
-// one sampled point from the previous position to the current position, then
-// a workgroup reduction picks the earliest hit.
+// Parallelizes over points sampled along the selected hull's outline. Each
+// workgroup lane Bresenham-walks its share of the points from the previous
+// pose to the current one, then a workgroup reduction picks the earliest hit.
+// The walk follows the ship's turn in `num_substeps` straight pieces, each
+// short enough (about a cell) that a point cannot skip over a cell by
+// cutting across its arc.
 //
 // Result buffer layout:
 //   [0] = hit (0 or 1)
@@ -17,14 +21,18 @@ struct CollisionUniforms {
     // Previous ship position.
     prev_ship_x: f32,
     prev_ship_y: f32,
-    // Ship orientation (current and previous).
+    // Ship orientation (current and previous). Unwrapped: the turn between
+    // them is the shorter way round.
     ship_orientation: f32,
     prev_ship_orientation: f32,
     // Terrain buffer.
     terrain_buffer_offset: i32,
     terrain_width: u32,
     terrain_buffer_height: u32,
-    _pad: u32,
+    // Equal pieces the segment is swept in; at least 1.
+    num_substeps: u32,
+    // Index into the hull tables.
+    hull: u32,
 };
 
 @group(0) @binding(0)
@@ -43,21 +51,6 @@ struct HitInfo {
 
 const WORKGROUP_SIZE: u32 = 32u;
 
-// Ship hull vertices in local space (matches ship.wgsl outline_vertices).
-// Must stay in sync with the 4-vertex perimeter in ship.wgsl.
-const HULL_VERTS: array<vec2<f32>, 4> = array<vec2<f32>, 4>(
-    vec2<f32>( 12.0,  0.0),  // nose
-    vec2<f32>( -8.0,  9.0),  // left wing
-    vec2<f32>( -5.0,  0.0),  // tail notch
-    vec2<f32>( -8.0, -9.0),  // right wing
-);
-
-const NUM_HULL_VERTS: u32 = 4u;
-// Sample hull edges every ~2 world units so no 1-cell gap escapes detection.
-// Values are floor(edge length / 2.0), matching the original serial sampler.
-const EDGE_STEPS: array<u32, 4> = array<u32, 4>(10u, 4u, 4u, 10u);
-const NUM_COLLISION_SAMPLES: u32 = 28u;
-
 var<workgroup> sample_t: array<f32, 32>;
 var<workgroup> sample_normal: array<vec2<f32>, 32>;
 
@@ -158,38 +151,57 @@ fn bresenham_check(start: vec2<f32>, end: vec2<f32>) -> HitInfo {
     return no_hit();
 }
 
+// World position of a local-space point `t` of the way along the segment.
+fn world_point(local_pos: vec2<f32>, t: f32) -> vec2<f32> {
+    let orientation = mix(uniforms.prev_ship_orientation, uniforms.ship_orientation, t);
+    let position = mix(
+        vec2<f32>(uniforms.prev_ship_x, uniforms.prev_ship_y),
+        vec2<f32>(uniforms.ship_x, uniforms.ship_y),
+        t,
+    );
+    return rotate(local_pos, orientation) + position;
+}
+
 // Sweep a single local-space point from its previous world position to its
-// current world position, returning hit info if it collides.
+// current world position, piece by piece, returning hit info if it collides.
 fn check_point(local_pos: vec2<f32>) -> HitInfo {
-    let prev_world = rotate(local_pos, uniforms.prev_ship_orientation)
-        + vec2<f32>(uniforms.prev_ship_x, uniforms.prev_ship_y);
-    let curr_world = rotate(local_pos, uniforms.ship_orientation)
-        + vec2<f32>(uniforms.ship_x, uniforms.ship_y);
-    return bresenham_check(prev_world, curr_world);
+    let num_substeps = max(uniforms.num_substeps, 1u);
+    var start = world_point(local_pos, 0.0);
+    for (var i = 0u; i < num_substeps; i = i + 1u) {
+        let end = world_point(local_pos, f32(i + 1u) / f32(num_substeps));
+        let hit = bresenham_check(start, end);
+        if (hit.t <= 1.0) {
+            return HitInfo(hit.normal, (f32(i) + hit.t) / f32(num_substeps));
+        }
+        start = end;
+    }
+    return no_hit();
 }
 
 fn edge_sample(edge_index: u32, step_index: u32) -> vec2<f32> {
-    let a = HULL_VERTS[edge_index];
-    let b = HULL_VERTS[(edge_index + 1u) % NUM_HULL_VERTS];
-    let t = f32(step_index) / f32(EDGE_STEPS[edge_index]);
+    let a = hull_vertex(uniforms.hull, edge_index);
+    let b = hull_vertex(uniforms.hull, edge_index + 1u);
+    let steps = HULL_EDGE_STEPS[HULL_FIRST_VERT[uniforms.hull] + edge_index];
+    let t = f32(step_index) / f32(steps);
     return a + (b - a) * t;
 }
 
 fn sample_point(sample_index: u32) -> vec2<f32> {
-    if (sample_index < NUM_HULL_VERTS) {
-        return HULL_VERTS[sample_index];
+    let num_verts = HULL_NUM_VERTS[uniforms.hull];
+    if (sample_index < num_verts) {
+        return hull_vertex(uniforms.hull, sample_index);
     }
 
-    var remaining = sample_index - NUM_HULL_VERTS;
-    for (var edge = 0u; edge < NUM_HULL_VERTS; edge = edge + 1u) {
-        let interior_samples = EDGE_STEPS[edge] - 1u;
+    var remaining = sample_index - num_verts;
+    for (var edge = 0u; edge < num_verts; edge = edge + 1u) {
+        let interior_samples = HULL_EDGE_STEPS[HULL_FIRST_VERT[uniforms.hull] + edge] - 1u;
         if (remaining < interior_samples) {
             return edge_sample(edge, remaining + 1u);
         }
         remaining = remaining - interior_samples;
     }
 
-    return HULL_VERTS[0];
+    return hull_vertex(uniforms.hull, 0u);
 }
 
 @compute @workgroup_size(32)
@@ -197,8 +209,14 @@ fn main(@builtin(local_invocation_id) local_id: vec3<u32>) {
     let lane = local_id.x;
     var lane_hit = no_hit();
 
-    if (lane < NUM_COLLISION_SAMPLES) {
-        lane_hit = check_point(sample_point(lane));
+    // Bigger hulls have more samples than lanes; each lane keeps the earliest
+    // hit among its share.
+    let num_samples = HULL_NUM_SAMPLES[uniforms.hull];
+    for (var i = lane; i < num_samples; i = i + WORKGROUP_SIZE) {
+        let hit = check_point(sample_point(i));
+        if (hit.t < lane_hit.t) {
+            lane_hit = hit;
+        }
     }
 
     sample_t[lane] = lane_hit.t;
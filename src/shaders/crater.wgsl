@@ -0,0 +1,61 @@
+{% include "terrain_material.wgsl.include" %}
+
+// Blast a crater into the composite terrain buffer: every cell whose center
+// lies within `radius` of the crater center loses health, `damage` at the
+// center falling off linearly to nothing at the rim. Metal is untouched and
+// health never drops below zero. One thread per cell of the crater's
+// bounding box. Mirrored by `crater::carve` on the CPU.
+
+struct UniformData {
+    // Crater center in world cells.
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    damage: i32,
+    // World row of the composite buffer's first row.
+    terrain_buffer_offset: i32,
+    terrain_width: u32,
+    terrain_buffer_height: u32,
+    // Side of the square of cells the dispatch covers.
+    box_size: u32,
+};
+@group(0) @binding(0)
+var<uniform> uniforms: UniformData;
+
+@group(0) @binding(1)
+var<storage, read_write> terrain_buffer: array<i32>;
+
+@group(0) @binding(2)
+var<storage, read> material_buffer: array<u32>;
+
+@compute @workgroup_size({{ particle_workgroup_size }})
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+  let cell = global_id[0];
+  if (cell >= uniforms.box_size * uniforms.box_size) {
+    return;
+  }
+  let reach = i32(ceil(uniforms.radius));
+  let x = i32(floor(uniforms.center_x)) - reach + i32(cell % uniforms.box_size);
+  let y = i32(floor(uniforms.center_y)) - reach + i32(cell / uniforms.box_size);
+  let row = y - uniforms.terrain_buffer_offset;
+  if (x < 0 || x >= i32(uniforms.terrain_width) || row < 0
+      || row >= i32(uniforms.terrain_buffer_height)) {
+    return;
+  }
+
+  let distance = length(vec2<f32>(f32(x) + 0.5, f32(y) + 0.5)
+      - vec2<f32>(uniforms.center_x, uniforms.center_y));
+  if (distance > uniforms.radius) {
+    return;
+  }
+  let index = row * i32(uniforms.terrain_width) + x;
+  if (material_buffer[index] == MATERIAL_METAL) {
+    return;
+  }
+  let health = terrain_buffer[index];
+  if (health <= 0) {
+    return;
+  }
+  let damage = i32(f32(uniforms.damage) * (1.0 - distance / uniforms.radius));
+  terrain_buffer[index] = max(health - damage, 0);
+}
@@ -17,16 +17,23 @@ struct EmitterMotion {
     _p1: u32,
 };
 
-// Size 16, Alignment 4
+// Size 32, Alignment 4
 struct NozzleParams {
     speed_min: f32,
     speed_max: f32,
     ttl_min: f32,
     ttl_max: f32,
+    // Width of the exhaust cone in radians.
+    spread: f32,
+    // Width of the nozzle mouth particles leave from.
+    width: f32,
+    _p1: u32,
+    _p2: u32,
 };
 
-// Size 16 + 40 + 4 + 32 = 84
-// Alignment 8 -> No padding.
+// Size 16 + 48 + 32 + 12 = 108, padded to 112 on the Rust side.
+// Indices are within this emitter's region of the particle buffer, which
+// starts at `region_start`.
 struct EmitData {
     start_index: u32,
     num_emitted: u32,
@@ -36,6 +43,9 @@ struct EmitData {
     motion: EmitterMotion,
     nozzle: NozzleParams,
     num_particles: u32,
+    region_start: u32,
+    // The PARTICLE_* type this emitter fills its region with.
+    particle_type: u32,
 };
 
 @group(0) @binding(0)
@@ -77,8 +87,8 @@ fn get_emit_index(global_id: u32, total_particles: u32) -> u32 {
 
 // The x shape of the wing
 fn nozzle_shape(interp: f32) -> vec2<f32> {
-  let rocket_width: f32 = 8.0;
-  return vec2<f32>(0.0, mix(-rocket_width / 2.0, rocket_width / 2.0, interp));
+  let width = emit_data.nozzle.width;
+  return vec2<f32>(0.0, mix(-width / 2.0, width / 2.0, interp));
 }
 
 @compute @workgroup_size({{ particle_workgroup_size }})
@@ -90,7 +100,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
         return;
     }
 
-    let particle = &(particle_buffer[gid]);
+    let particle = &(particle_buffer[emit_data.region_start + gid]);
 
     let emit_index = get_emit_index(gid, emit_data.num_particles);
     if (emit_index >= emit_data.num_emitted) {
@@ -122,7 +132,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
 
     // Do all of the math as if the ship were at the origin oriented down the X axis, and then transform at the end.
     let tentacle_frequency = 25.0;
-    let local_emit_angle = noise2d(vec2<f32>(x_interp * tentacle_frequency, interp_time)) - .5;
+    let local_emit_angle = (noise2d(vec2<f32>(x_interp * tentacle_frequency, interp_time)) - .5) * emit_data.nozzle.spread;
     let unit_emit_rotation = vec2<f32>(cos(local_emit_angle), sin(local_emit_angle)); 
 
     // let speed_noise_magnitude = 0.0;
@@ -145,5 +155,5 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
     (*particle).position = ship_position + local_rotate_global * local_emit_position; 
     (*particle).velocity = local_rotate_global * local_emit_velocity;
     (*particle).ttl = mix(emit_data.nozzle.ttl_min, emit_data.nozzle.ttl_max, .5); 
-    (*particle).subframe_dt_offset = -pass_time;
+    (*particle).subframe_and_kind = pack_subframe_and_kind(-pass_time, emit_data.particle_type);
 }
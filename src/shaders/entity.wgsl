@@ -0,0 +1,54 @@
+// Instanced oriented boxes for small world entities (turrets, projectiles).
+// Each instance is a rectangle of `half_size` around `center`, rotated by
+// `angle`, filled with a flat HDR color.
+
+struct Uniforms {
+    viewport_width: u32,
+    viewport_height: u32,
+    viewport_offset: i32,
+    viewport_offset_x: i32,
+};
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) center: vec2<f32>,
+    @location(1) half_size: vec2<f32>,
+    @location(2) angle: f32,
+    @location(3) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+fn rotate2d(angle: f32) -> mat2x2<f32> {
+    return mat2x2<f32>(vec2<f32>(cos(angle), sin(angle)),
+                       vec2<f32>(-sin(angle), cos(angle)));
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    // Triangle strip corners: (-1,-1), (1,-1), (-1,1), (1,1).
+    let corner = vec2<f32>(
+        f32(input.vertex_index & 1u) * 2.0 - 1.0,
+        f32(input.vertex_index >> 1u) * 2.0 - 1.0,
+    );
+    let world_pos = rotate2d(input.angle) * (corner * input.half_size) + input.center;
+    let offset = vec2<f32>(f32(uniforms.viewport_offset_x), f32(uniforms.viewport_offset));
+    let size = vec2<f32>(f32(uniforms.viewport_width), f32(uniforms.viewport_height));
+    let view_pos = world_pos - offset;
+    let ndc = 2.0 * vec2<f32>(view_pos.x, size.y - view_pos.y) / size - vec2<f32>(1.0, 1.0);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.color = input.color;
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return input.color;
+}
@@ -0,0 +1,68 @@
+{% include "particle.wgsl.include" %}
+
+// Particle-vs-entity hit counting.
+//
+// Each lane tests one live particle against every target circle. Hits gather
+// in workgroup-local counters first, then each workgroup adds its totals to
+// the result buffer, so the global atomics see one add per target per group.
+//
+// Smoke is harmless and never counts as a hit.
+//
+// Result buffer layout: counts[i] = particles inside target i.
+
+struct HitTarget {
+  center: vec2<f32>,
+  radius: f32,
+  // Caller-side tag; unused here.
+  id: u32,
+};
+
+struct EntityHitUniforms {
+  num_particles: u32,
+  num_targets: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: EntityHitUniforms;
+
+@group(0) @binding(1)
+var<storage, read> particles: array<Particle>;
+
+@group(0) @binding(2)
+var<storage, read> targets: array<HitTarget, {{ max_hit_targets }}>;
+
+@group(0) @binding(3)
+var<storage, read_write> counts: array<atomic<u32>, {{ max_hit_targets }}>;
+
+var<workgroup> local_counts: array<atomic<u32>, {{ max_hit_targets }}>;
+
+@compute @workgroup_size({{ particle_workgroup_size }})
+fn main(
+  @builtin(global_invocation_id) global_id: vec3<u32>,
+  @builtin(local_invocation_index) local_index: u32,
+) {
+  if (local_index < uniforms.num_targets) {
+    atomicStore(&local_counts[local_index], 0u);
+  }
+  workgroupBarrier();
+
+  let index = global_id.x;
+  if (index < uniforms.num_particles && particles[index].ttl > 0.0 && particle_kind(particles[index]) != PARTICLE_SMOKE) {
+    let position = particles[index].position;
+    for (var i = 0u; i < uniforms.num_targets; i = i + 1u) {
+      let offset = position - targets[i].center;
+      let radius = targets[i].radius;
+      if (dot(offset, offset) <= radius * radius) {
+        atomicAdd(&local_counts[i], 1u);
+      }
+    }
+  }
+  workgroupBarrier();
+
+  if (local_index < uniforms.num_targets) {
+    let local_hits = atomicLoad(&local_counts[local_index]);
+    if (local_hits > 0u) {
+      atomicAdd(&counts[local_index], local_hits);
+    }
+  }
+}
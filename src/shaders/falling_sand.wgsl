@@ -0,0 +1,96 @@
+{% include "terrain_material.wgsl.include" %}
+
+// One step of the falling-sand automaton over the composite terrain buffer.
+// Loose (dirt) cells fall into empty cells below them, or slide diagonally
+// down when the cell below is occupied.
+//
+// The grid is split into 2x2 blocks (a Margolus neighborhood) and each thread
+// owns one block, so no two threads touch the same cell and the result does
+// not depend on scheduling. The block grid cycles through all four one-cell
+// offsets so every cell takes every position in a block, letting cells cross
+// block boundaries and slide either way. Blocks hanging off the buffer edge
+// treat the missing cells as walls. Mirrored by `sand::step` on the CPU.
+
+struct UniformData {
+    // Composite terrain buffer shape, in cells.
+    width: u32,
+    height: u32,
+    // Step counter; picks the block grid offset.
+    step: u32,
+    _pad: u32,
+};
+@group(0) @binding(0)
+var<uniform> uniforms: UniformData;
+
+@group(0) @binding(1)
+var<storage, read_write> terrain_buffer: array<i32>;
+
+@group(0) @binding(2)
+var<storage, read_write> material_buffer: array<u32>;
+
+// Index of cell (x, y), or -1 outside the buffer.
+fn cell_index(x: i32, y: i32) -> i32 {
+  if (x < 0 || y < 0 || x >= i32(uniforms.width) || y >= i32(uniforms.height)) {
+    return -1;
+  }
+  return y * i32(uniforms.width) + x;
+}
+
+// Cells outside the buffer are never empty and never loose, so nothing moves
+// into or out of them.
+fn is_empty(index: i32) -> bool {
+  return index >= 0 && terrain_buffer[index] <= 0;
+}
+
+fn is_loose(index: i32) -> bool {
+  return index >= 0 && terrain_buffer[index] > 0 && material_buffer[index] == MATERIAL_DIRT;
+}
+
+fn swap_cells(a: i32, b: i32) {
+  let health = terrain_buffer[a];
+  terrain_buffer[a] = terrain_buffer[b];
+  terrain_buffer[b] = health;
+  let material = material_buffer[a];
+  material_buffer[a] = material_buffer[b];
+  material_buffer[b] = material;
+}
+
+// Move `top` diagonally down into `diagonal` if it is loose, the cell below it
+// is occupied, and the diagonal is empty.
+fn try_slide(top: i32, below: i32, diagonal: i32) {
+  if (is_loose(top) && !is_empty(below) && is_empty(diagonal)) {
+    swap_cells(top, diagonal);
+  }
+}
+
+@compute @workgroup_size({{ particle_workgroup_size }})
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+  let offset_x = uniforms.step & 1u;
+  let offset_y = (uniforms.step >> 1u) & 1u;
+  // Shifted grids start one cell before the buffer, so they need one more
+  // block to reach the far edge.
+  let blocks_x = (uniforms.width + offset_x + 1u) / 2u;
+  let blocks_y = (uniforms.height + offset_y + 1u) / 2u;
+  let block = global_id[0];
+  if (block >= blocks_x * blocks_y) {
+    return;
+  }
+  let x0 = i32((block % blocks_x) * 2u) - i32(offset_x);
+  let y0 = i32((block / blocks_x) * 2u) - i32(offset_y);
+
+  // +y is up, so row y0 is the bottom of the block.
+  let bottom_left = cell_index(x0, y0);
+  let bottom_right = cell_index(x0 + 1, y0);
+  let top_left = cell_index(x0, y0 + 1);
+  let top_right = cell_index(x0 + 1, y0 + 1);
+
+  if (is_loose(top_left) && is_empty(bottom_left)) {
+    swap_cells(top_left, bottom_left);
+  }
+  if (is_loose(top_right) && is_empty(bottom_right)) {
+    swap_cells(top_right, bottom_right);
+  }
+  // At most one of these can fire, so their order doesn't matter.
+  try_slide(top_left, bottom_left, bottom_right);
+  try_slide(top_right, bottom_right, bottom_left);
+}
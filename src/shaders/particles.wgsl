@@ -1,16 +1,37 @@
 {% include "particle.wgsl.include" %}
+{% include "terrain_material.wgsl.include" %}
 
 // This shader module handles particle update and aggregation.
 // It could possibly be combined with the emitter module... but for now it takes the particle_buffer, updates it, then aggregates particle densities into the density_buffer.
 
+// How one particle type moves, erodes and glows.
+struct ParticleTypeParams {
+    // Scales `gravity`; negative values make the particle rise.
+    gravity_scale: f32,
+    // Fraction of velocity lost per second.
+    drag: f32,
+    // Scales `damage_rate`.
+    damage_scale: f32,
+    // Scales the terrain's bounce elasticity.
+    elasticity_scale: f32,
+    // Scales the heat the particle adds to the density buffer.
+    heat_scale: f32,
+    _p0: u32,
+    _p1: u32,
+    _p2: u32,
+};
+
 // IN:
 struct UniformData {
     dt: f32,
+    // Shape of the density buffer, which covers the viewport.
     viewport_width: u32,
     viewport_height: u32,
+    // World cell at the bottom-left of the viewport.
     viewport_offset: i32,
+    viewport_offset_x: i32,
 
-    // level_width and viewport_width should be the same.
+    // Terrain buffer row stride; at least viewport_width.
     level_width: u32,
     level_height: u32,
 
@@ -22,6 +43,15 @@ struct UniformData {
     elasticity: f32,
     max_particle_life: f32,
     num_particles: u32,
+    // Terrain health a particle adds to its cell when its ttl runs out.
+    // 0 disables deposition.
+    deposition_amount: i32,
+    // Bounce elasticity per terrain material, indexed by material ID.
+    // `elasticity` is used for bounces off the edge of the terrain buffer.
+    material_elasticity: vec4<f32>,
+    // Indexed by particle type; each type also owns a layer of the density
+    // buffer.
+    types: array<ParticleTypeParams, NUM_PARTICLE_TYPES>,
 };
 @group(0) @binding(0)
 var<uniform> uniforms: UniformData;
@@ -34,21 +64,25 @@ var<storage, read_write> particle_buffer: array<Particle>;
 @group(0) @binding(2)
 var<storage, read_write> terrain_buffer: array<atomic<i32>>;
 
-// OUTPUT:
+// OUTPUT: one viewport-sized layer per particle type.
 @group(0) @binding(3)
 var<storage, read_write> density_buffer: array<atomic<u32>>;
 
+// IN OUT: material ID per terrain cell, same layout as terrain_buffer.
+@group(0) @binding(4)
+var<storage, read_write> material_buffer: array<u32>;
+
 // Takes cell in the global frame. heat is in [0, 1] where 1 = freshly emitted.
 // We scale by 256 so the u32 density buffer can accumulate fractional heat values.
 const DENSITY_HEAT_SCALE: u32 = 256u;
 
-fn increment_cell(global_cell: vec2<i32>, heat: f32) {
-  var cell = global_cell;
-  cell.y = cell.y - i32(uniforms.viewport_offset);
+fn increment_cell(global_cell: vec2<i32>, heat: f32, layer: u32) {
+  let cell = global_cell - vec2<i32>(uniforms.viewport_offset_x, uniforms.viewport_offset);
   if (cell.x < 0 || cell.x >= i32(uniforms.viewport_width) || cell.y < 0 || cell.y >= i32(uniforms.viewport_height)) {
     return;
   }
-  let index = cell.y * i32(uniforms.viewport_width) + cell.x;
+  let layer_size = uniforms.viewport_width * uniforms.viewport_height;
+  let index = layer * layer_size + u32(cell.y) * uniforms.viewport_width + u32(cell.x);
 
   let contribution = max(u32(heat * f32(DENSITY_HEAT_SCALE)), 1u);
   atomicAdd(&density_buffer[index], contribution);
@@ -67,20 +101,63 @@ fn terrain_buffer_to_global(cell: vec2<i32>) -> vec2<i32> {
 }
 
 fn on_terrain_buffer(terrain_cell: vec2<i32>) -> bool {
-  return terrain_cell.x >= i32(0) && terrain_cell.x < i32(uniforms.viewport_width) && terrain_cell.y >= 0 && terrain_cell.y < i32(uniforms.terrain_buffer_height);
+  return terrain_cell.x >= i32(0) && terrain_cell.x < i32(uniforms.level_width) && terrain_cell.y >= 0 && terrain_cell.y < i32(uniforms.terrain_buffer_height);
 }
 
 fn get_buffer_offset(cell: vec2<i32>) -> u32 {
   return u32(cell.y) * uniforms.level_width + u32(cell.x);
 }
 
+// Breaks any crystal cells 4-connected to `terrain_cell`.
+fn shatter(terrain_cell: vec2<i32>) {
+  let neighbors = array<vec2<i32>, 4>(vec2<i32>(1, 0), vec2<i32>(-1, 0), vec2<i32>(0, 1), vec2<i32>(0, -1));
+  for (var i = 0; i < 4; i = i + 1) {
+    let neighbor = terrain_cell + neighbors[i];
+    if (on_terrain_buffer(neighbor)) {
+      let offset = get_buffer_offset(neighbor);
+      if (material_buffer[offset] == MATERIAL_CRYSTAL) {
+        atomicMin(&terrain_buffer[offset], 0);
+      }
+    }
+  }
+}
+
 // Returns true if bounce occurred.
-fn try_erode(terrain_cell: vec2<i32>, speed: f32) -> bool {
-  let dmg_amt = i32(uniforms.damage_rate * speed);
-  let actual_value = atomicAdd(&terrain_buffer[get_buffer_offset(terrain_cell)], -dmg_amt);
+fn try_erode(terrain_cell: vec2<i32>, speed: f32, damage_scale: f32) -> bool {
+  let offset = get_buffer_offset(terrain_cell);
+  let material = material_buffer[offset];
+  if (material == MATERIAL_METAL) {
+    return atomicLoad(&terrain_buffer[offset]) > 0;
+  }
+  let dmg_amt = i32(uniforms.damage_rate * damage_scale * speed);
+  let actual_value = atomicAdd(&terrain_buffer[offset], -dmg_amt);
+  if (material == MATERIAL_CRYSTAL && actual_value > 0 && actual_value <= dmg_amt) {
+    shatter(terrain_cell);
+  }
   return actual_value > 0;
 }
 
+// Adds the deposition amount to `terrain_cell`. An empty cell becomes loose
+// dirt; a solid one just gets tougher.
+fn deposit(terrain_cell: vec2<i32>) {
+  let offset = get_buffer_offset(terrain_cell);
+  // Eroded cells can be far below zero; start the pile from empty.
+  let old = atomicMax(&terrain_buffer[offset], 0);
+  if (old <= 0) {
+    material_buffer[offset] = MATERIAL_DIRT;
+  }
+  atomicAdd(&terrain_buffer[offset], uniforms.deposition_amount);
+}
+
+// Elasticity of a bounce off `terrain_cell`, which may be off the buffer.
+fn bounce_elasticity(terrain_cell: vec2<i32>) -> f32 {
+  if (!on_terrain_buffer(terrain_cell)) {
+    return uniforms.elasticity;
+  }
+  let material = min(material_buffer[get_buffer_offset(terrain_cell)], NUM_MATERIALS - 1u);
+  return uniforms.material_elasticity[material];
+}
+
 fn norm(vel: vec2<f32>) -> f32{
   return sqrt(vel.x*vel.x + vel.y * vel.y);
 }
@@ -109,6 +186,9 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
   if ((*particle).ttl <= 0.0) {
    return;
   } 
+  let kind = min(particle_kind(*particle), NUM_PARTICLE_TYPES - 1u);
+  let type_params = uniforms.types[kind];
+  let damage_scale = type_params.damage_scale;
 
   let current_cell = vec2<i32>((*particle).position);
   var terrain_cell = global_to_terrain_buffer(current_cell);
@@ -121,8 +201,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
   // New particles can be emitted partway through the frame.
   // `subframe_dt_offset` adjusts the uniform frame dt once, then resets to the
   // neutral offset for later updates.
-  let dt = max(uniforms.dt + (*particle).subframe_dt_offset, 0.0);
-  (*particle).subframe_dt_offset = 0.0;
+  let dt = max(uniforms.dt + particle_subframe_dt_offset(*particle), 0.0);
+  (*particle).subframe_and_kind = pack_subframe_and_kind(0.0, particle_kind(*particle));
 
   // TODO collisions 
   let signed_delta = (*particle).velocity * dt;
@@ -156,12 +236,13 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
       error = error_horizontal;
       terrain_cell.x = terrain_cell.x + step.x;
       // Check cell
-      let bounce = !on_terrain_buffer(terrain_cell) || try_erode(terrain_cell, speed);
+      let bounce = !on_terrain_buffer(terrain_cell) || try_erode(terrain_cell, speed, damage_scale);
       if (bounce) {
         // Bounce horizontally
+        let elasticity = bounce_elasticity(terrain_cell) * type_params.elasticity_scale;
         terrain_cell.x = terrain_cell.x - step.x;
         step.x = -1 * step.x;
-        vel_out.x = -(vel_out.x * uniforms.elasticity);
+        vel_out.x = -(vel_out.x * elasticity);
         end_remainder.x = 1.0 - end_remainder.x;
       }
     } else {
@@ -169,26 +250,31 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(num_workgr
       error = error_vertical;
       terrain_cell.y = terrain_cell.y + step.y;
       // Check cell
-      let bounce = !on_terrain_buffer(terrain_cell) || try_erode(terrain_cell, speed);
+      let bounce = !on_terrain_buffer(terrain_cell) || try_erode(terrain_cell, speed, damage_scale);
       if (bounce) {
         // Bounce vertically 
+        let elasticity = bounce_elasticity(terrain_cell) * type_params.elasticity_scale;
         terrain_cell.y = terrain_cell.y - step.y;
         step.y = -1 * step.y;
-        vel_out.y = -(vel_out.y * uniforms.elasticity);
+        vel_out.y = -(vel_out.y * elasticity);
         end_remainder.y = 1.0 - end_remainder.y;
       }
     }
     num_cells = num_cells - 1;
   }
-  vel_out.y = vel_out.y + uniforms.gravity * dt;
+  vel_out.y = vel_out.y + uniforms.gravity * type_params.gravity_scale * dt;
+  vel_out = vel_out * max(1.0 - type_params.drag * dt, 0.0);
 
   let global_output_pos = vec2<f32>(terrain_buffer_to_global(terrain_cell)) + end_remainder;
   (*particle).position = global_output_pos;
   (*particle).velocity = vel_out;
   (*particle).ttl = (*particle).ttl - dt;
+  if ((*particle).ttl <= 0.0 && uniforms.deposition_amount > 0) {
+    deposit(terrain_cell);
+  }
 
   // Draw particle to density buffer. Heat = ttl/max_life: new particles are
   // bright (hot), old particles are dim (cool).
-  let heat = clamp((*particle).ttl / uniforms.max_particle_life, 0.0, 1.0);
-  increment_cell(vec2<i32>(global_output_pos), heat);
+  let heat = clamp((*particle).ttl / uniforms.max_particle_life * type_params.heat_scale, 0.0, 1.0);
+  increment_cell(vec2<i32>(global_output_pos), heat, kind);
 }
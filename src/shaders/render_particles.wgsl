@@ -36,6 +36,8 @@ struct ViewData {
     density_scale: f32,
     // Sigmoid exponent: >1 = steeper curve, <1 = gentler.
     density_exponent: f32,
+    // Density layers, one per particle type, each with its own color map row.
+    num_layers: u32,
 };
 @group(0) @binding(0)
 var<uniform> view_data: ViewData;
@@ -49,15 +51,16 @@ var color_map: texture_2d<f32>;
 @group(0) @binding(3)
 var color_map_sampler: sampler;
 
-fn get_cell(tex_coord: vec2<f32>) -> u32 {
+fn get_cell(tex_coord: vec2<f32>, layer: u32) -> u32 {
     let cell_f: vec2<f32> = tex_coord * vec2<f32>(f32(view_data.width), f32(view_data.height));
-    return density_buffer[i32(cell_f.y) * i32(view_data.width) + i32(cell_f.x)];
+    let layer_size = view_data.width * view_data.height;
+    return density_buffer[layer * layer_size + u32(cell_f.y) * view_data.width + u32(cell_f.x)];
 }
 
 // Returns the effective density (heat-weighted particle count).
 // The compute shader writes heat * 256 per particle, so we divide back out.
-fn read_density(tex_coord: vec2<f32>) -> f32 {
-  let raw = get_cell(tex_coord);
+fn read_density(tex_coord: vec2<f32>, layer: u32) -> f32 {
+  let raw = get_cell(tex_coord, layer);
   return f32(raw) / 256.0;
 }
 
@@ -68,10 +71,24 @@ fn sigmoid(x: f32, exponent: f32) -> f32 {
   return xe / sqrt(1.0 + xe * xe);
 }
 
+// Layers mix by coverage: the color is each layer's color map sample
+// weighted by its coverage, and the alpha is the strongest coverage.
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-  let count = read_density(in.tex_coord);
-  let rescaled = sigmoid(count / view_data.density_scale, view_data.density_exponent);
-  let sample = textureSample(color_map, color_map_sampler, vec2<f32>(rescaled, 0.0));
-  return vec4<f32>(sample.xyz, rescaled);
+  var color = vec3<f32>(0.0);
+  var total = 0.0;
+  var alpha = 0.0;
+  for (var layer = 0u; layer < view_data.num_layers; layer = layer + 1u) {
+    let count = read_density(in.tex_coord, layer);
+    let rescaled = sigmoid(count / view_data.density_scale, view_data.density_exponent);
+    let row = (f32(layer) + 0.5) / f32(view_data.num_layers);
+    let sample = textureSample(color_map, color_map_sampler, vec2<f32>(rescaled, row));
+    color = color + sample.xyz * rescaled;
+    total = total + rescaled;
+    alpha = max(alpha, rescaled);
+  }
+  if (total <= 0.0) {
+    return vec4<f32>(0.0);
+  }
+  return vec4<f32>(color / total, alpha);
 }
\ No newline at end of file
@@ -1,29 +1,8 @@
+{% include "ship_hull.wgsl.include" %}
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
 };
 
-// Two triangles forming a chevron with a concave tail notch:
-//   nose(12,0) → left-wing(-8,9) → notch(-5,0)
-//   nose(12,0) → notch(-5,0)     → right-wing(-8,-9)
-var<private> ship_vertices: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
-    vec2<f32>( 12.0,  0.0),   // nose
-    vec2<f32>( -8.0,  9.0),   // left wing
-    vec2<f32>( -5.0,  0.0),   // tail notch
-    vec2<f32>( 12.0,  0.0),   // nose (repeated)
-    vec2<f32>( -5.0,  0.0),   // tail notch (repeated)
-    vec2<f32>( -8.0, -9.0),   // right wing
-);
-
-// Closed perimeter for the wireframe outline (LineStrip, 5 points).
-var<private> outline_vertices: array<vec2<f32>, 5> = array<vec2<f32>, 5>(
-    vec2<f32>( 12.0,  0.0),   // nose
-    vec2<f32>( -8.0,  9.0),   // left wing
-    vec2<f32>( -5.0,  0.0),   // tail notch
-    vec2<f32>( -8.0, -9.0),   // right wing
-    vec2<f32>( 12.0,  0.0),   // nose (close loop)
-);
-
-
 struct Uniforms {
     position: vec2<f32>,
     orientation: f32,
@@ -31,12 +10,20 @@ struct Uniforms {
     viewport_width: u32,
     viewport_height: u32,
     viewport_offset: i32,
+    viewport_offset_x: i32,
+    // Index into the hull tables.
+    hull: u32,
 };
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
-fn world_to_ndc(world_coord: vec2<f32>, viewport_width: f32, viewport_height: f32, viewport_offset: f32) -> vec2<f32> {
-  return 2.0 * vec2<f32>(world_coord.x, viewport_height - (world_coord.y - viewport_offset)) / vec2<f32>(viewport_width, viewport_height) - vec2<f32>(1.0, 1.0);
+// `viewport_offset` is the world position of the viewport's bottom-left corner.
+fn world_to_ndc(world_coord: vec2<f32>, viewport_width: f32, viewport_height: f32, viewport_offset: vec2<f32>) -> vec2<f32> {
+  return 2.0 * vec2<f32>(world_coord.x - viewport_offset.x, viewport_height - (world_coord.y - viewport_offset.y)) / vec2<f32>(viewport_width, viewport_height) - vec2<f32>(1.0, 1.0);
+}
+
+fn viewport_offset() -> vec2<f32> {
+  return vec2<f32>(f32(uniforms.viewport_offset_x), f32(uniforms.viewport_offset));
 }
 
 fn rotate2d(orientation: f32) -> mat2x2<f32> {
@@ -44,11 +31,19 @@ fn rotate2d(orientation: f32) -> mat2x2<f32> {
                        vec2<f32>(-sin(orientation),cos(orientation)));
 }
 
+// The hull is filled as a fan of triangles from its first vertex (the nose):
+// triangle `i` is vertices 0, i + 1, i + 2.
+fn fill_vertex(vertex_index: u32) -> vec2<f32> {
+    let corner = vertex_index % 3u;
+    let i = select(vertex_index / 3u + corner, 0u, corner == 0u);
+    return hull_vertex(uniforms.hull, i);
+}
+
 @vertex
 fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     let rot = rotate2d(uniforms.orientation);
-    let world_pos = rot * ship_vertices[vertex_index] + uniforms.position;
-    let viewport_pos = world_to_ndc(world_pos, f32(uniforms.viewport_width), f32(uniforms.viewport_height), f32(uniforms.viewport_offset));
+    let world_pos = rot * fill_vertex(vertex_index) + uniforms.position;
+    let viewport_pos = world_to_ndc(world_pos, f32(uniforms.viewport_width), f32(uniforms.viewport_height), viewport_offset());
     var out: VertexOutput;
     out.position = vec4<f32>(viewport_pos.x, viewport_pos.y, 0.0, 1.0);
     return out;
@@ -60,11 +55,12 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(0.25, 0.42, 0.75, 0.5);
 }
 
+// Closed perimeter for the wireframe outline: a LineStrip back to the nose.
 @vertex
 fn vs_outline(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     let rot = rotate2d(uniforms.orientation);
-    let world_pos = rot * outline_vertices[vertex_index] + uniforms.position;
-    let viewport_pos = world_to_ndc(world_pos, f32(uniforms.viewport_width), f32(uniforms.viewport_height), f32(uniforms.viewport_offset));
+    let world_pos = rot * hull_vertex(uniforms.hull, vertex_index) + uniforms.position;
+    let viewport_pos = world_to_ndc(world_pos, f32(uniforms.viewport_width), f32(uniforms.viewport_height), viewport_offset());
     var out: VertexOutput;
     out.position = vec4<f32>(viewport_pos.x, viewport_pos.y, 0.0, 1.0);
     return out;
@@ -1,3 +1,5 @@
+{% include "terrain_material.wgsl.include" %}
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coord: vec2<f32>,
@@ -34,22 +36,42 @@ struct Uniforms {
     viewport_height: u32,
     viewport_offset: u32,
     terrain_buffer_offset: u32,
+    viewport_offset_x: i32,
+    // Terrain buffer row stride; at least viewport_width.
+    level_width: u32,
+    _padding: vec2<u32>,
+    // Per-material colors indexed by material ID.
+    edge_colors: array<vec4<f32>, NUM_MATERIALS>,
+    fill_colors: array<vec4<f32>, NUM_MATERIALS>,
 };
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
 
 @group(0) @binding(1) var<storage, read> terrain_buffer: array<i32>;
 
-// Reads the terrain cell at integer grid position (tx, ty).
-// Returns 0 (empty) for out-of-bounds coordinates.
-fn get_cell_xy(tx: i32, ty: i32) -> i32 {
-    if tx < 0 || tx >= i32(uniforms.viewport_width) {
-        return 0;
+@group(0) @binding(2) var<storage, read> material_buffer: array<u32>;
+
+// Index into the terrain buffer of viewport grid position (tx, ty), or -1 if
+// it is out of bounds.
+fn cell_offset_xy(tx: i32, ty: i32) -> i32 {
+    let absolute_x = uniforms.viewport_offset_x + tx;
+    if absolute_x < 0 || absolute_x >= i32(uniforms.level_width) {
+        return -1;
     }
     let absolute_height = i32(uniforms.viewport_offset) + ty;
     let row_in_terrain_buffer = absolute_height - i32(uniforms.terrain_buffer_offset);
-    let cells_per_row = i32(uniforms.viewport_width);
-    let cell_offset = row_in_terrain_buffer * cells_per_row + tx;
+    let cells_per_row = i32(uniforms.level_width);
+    let cell_offset = row_in_terrain_buffer * cells_per_row + absolute_x;
     if cell_offset < 0 || u32(cell_offset) >= arrayLength(&terrain_buffer) {
+        return -1;
+    }
+    return cell_offset;
+}
+
+// Reads the terrain cell at integer grid position (tx, ty).
+// Returns 0 (empty) for out-of-bounds coordinates.
+fn get_cell_xy(tx: i32, ty: i32) -> i32 {
+    let cell_offset = cell_offset_xy(tx, ty);
+    if cell_offset < 0 {
         return 0;
     }
     return terrain_buffer[cell_offset];
@@ -76,11 +98,14 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let e = get_cell_xy(cell.x + 1, cell.y);
     let w = get_cell_xy(cell.x - 1, cell.y);
 
+    // Solid cells are always in bounds.
+    let material = min(material_buffer[cell_offset_xy(cell.x, cell.y)], NUM_MATERIALS - 1u);
+
     if n <= 0 || s <= 0 || e <= 0 || w <= 0 {
-        // HDR red glow — values > 1.0 drive the bloom pass.
-        return vec4<f32>(1.2, 0.15, 0.08, 1.0);
+        // HDR glow — values > 1.0 drive the bloom pass.
+        return vec4<f32>(uniforms.edge_colors[material].rgb, 1.0);
     }
 
     let p = f32(val) / f32(max_val);
-    return vec4<f32>(0.196, 0.196, 0.196, p);
+    return vec4<f32>(uniforms.fill_colors[material].rgb, p);
 }
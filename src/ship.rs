@@ -1,7 +1,17 @@
 //! Ship state (position, velocity, orientation), physics update, and GPU rendering
 //! (filled triangle + wireframe outline).
 
-use crate::{buffer_util::SizedBuffer, game_params};
+use crate::{buffer_util::SizedBuffer, game_params, ship_geometry};
+
+/// The controls one `ShipState::update` applied. Kept with each frame's
+/// collision segment so the frame can be flown again from a bounced pose.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ShipStep {
+    pub dt: f32,
+    /// Thrust actually applied, after the fuel limit.
+    pub thrust: f32,
+    pub rotate: f32,
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct ShipState {
@@ -14,6 +24,7 @@ pub struct ShipState {
     pub acceleration: f32,
     pub rotation_rate: f32,
     pub max_speed: f32,
+    pub hull: game_params::ShipHull,
 }
 impl Default for ShipState {
     fn default() -> Self {
@@ -24,6 +35,7 @@ impl Default for ShipState {
             acceleration: 10.0,
             rotation_rate: 15.0,
             max_speed: 10000.0,
+            hull: game_params::ShipHull::default(),
         }
     }
 }
@@ -35,6 +47,7 @@ impl ShipState {
             acceleration: ship_params.acceleration,
             rotation_rate: ship_params.rotation_rate,
             max_speed: ship_params.max_speed,
+            hull: ship_params.hull,
             ..Default::default()
         }
     }
@@ -65,13 +78,79 @@ impl ShipState {
         self.orientation += dt * rotate * self.rotation_rate;
     }
 
+    /// `update` with the controls of `step`.
+    pub fn apply(&mut self, step: &ShipStep, gravity: f32) {
+        self.update(step.dt, step.thrust, step.rotate, gravity);
+    }
+
+    pub fn hull_shape(&self) -> &'static ship_geometry::HullShape {
+        self.hull.shape()
+    }
+
+    /// A point in ship-local space placed at the ship's position and
+    /// orientation.
+    fn local_to_world(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let (sin, cos) = self.orientation.sin_cos();
+        [
+            self.position[0] + cos * x - sin * y,
+            self.position[1] + sin * x + cos * y,
+        ]
+    }
+
+    /// The hull outline placed at the ship's position and orientation.
+    pub fn hull(&self) -> Vec<[f32; 2]> {
+        let vertices = self.hull_shape().vertices;
+        vertices.iter().map(|&v| self.local_to_world(v)).collect()
+    }
+
+    /// The hull's collision sample points (see
+    /// `HullShape::collision_samples`) placed at the ship's position and
+    /// orientation.
+    pub fn hull_samples(&self) -> Vec<[f32; 2]> {
+        let samples = self.hull_shape().collision_samples();
+        samples
+            .into_iter()
+            .map(|v| self.local_to_world(v))
+            .collect()
+    }
+
+    /// Whether a circle touches the hull: its center is inside the outline,
+    /// or within `radius` of one of its edges.
+    pub fn hull_overlaps_circle(&self, center: [f32; 2], radius: f32) -> bool {
+        let hull = self.hull();
+        let p = glam::Vec2::from(center);
+        let near_edge = (0..hull.len()).any(|i| {
+            let a = glam::Vec2::from(hull[i]);
+            let b = glam::Vec2::from(hull[(i + 1) % hull.len()]);
+            let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+            p.distance(a + (b - a) * t) <= radius
+        });
+        // Even-odd crossing test; the outline is concave at the tail notch.
+        let inside = (0..hull.len()).fold(false, |inside, i| {
+            let [ax, ay] = hull[i];
+            let [bx, by] = hull[(i + 1) % hull.len()];
+            let crosses = (ay > p.y) != (by > p.y) && p.x < ax + (p.y - ay) / (by - ay) * (bx - ax);
+            inside ^ crosses
+        });
+        near_edge || inside
+    }
+
+    /// Exhaust mount position, and the heading exhaust leaves along:
+    /// straight back.
     pub fn get_emitter_state(&self) -> ([f32; 2], f32) {
-        let emitter_offset = 8.0;
-        let emitter_orientation = self.orientation + std::f32::consts::PI;
-        let (mut y, mut x) = emitter_orientation.sin_cos();
-        x = x * emitter_offset + self.position[0];
-        y = y * emitter_offset + self.position[1];
-        ([x, y], emitter_orientation)
+        (
+            self.local_to_world(self.hull_shape().emitter),
+            self.orientation + std::f32::consts::PI,
+        )
+    }
+
+    /// Muzzle position just past the nose, and the heading the gun fires
+    /// along.
+    pub fn get_gun_state(&self) -> ([f32; 2], f32) {
+        (
+            self.local_to_world(self.hull_shape().muzzle),
+            self.orientation,
+        )
     }
 }
 
@@ -83,6 +162,9 @@ struct ShipRendererUniforms {
     pub viewport_width: u32,
     pub viewport_height: u32,
     pub viewport_offset: i32,
+    pub viewport_offset_x: i32,
+    /// Index into the hull tables; see `ship_geometry::HULLS`.
+    pub hull: u32,
 }
 
 pub struct ShipRenderer {
@@ -105,6 +187,8 @@ impl ShipRenderer {
             viewport_width: 0,
             viewport_height: 0,
             viewport_offset: 0,
+            viewport_offset_x: 0,
+            hull: 0,
         };
         let uniform_buffer = crate::buffer_util::make_uniform_buffer::<ShipRendererUniforms>(
             device,
@@ -217,7 +301,7 @@ impl ShipRenderer {
         &mut self,
         state: &ShipState,
         game_params: &game_params::GameParams,
-        viewport_offset: i32,
+        viewport_offset: [i32; 2],
         output_texture_view: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
         belt: &mut wgpu::util::StagingBelt,
@@ -228,7 +312,9 @@ impl ShipRenderer {
             orientation: state.orientation,
             viewport_width: game_params.viewport_width,
             viewport_height: game_params.viewport_height,
-            viewport_offset,
+            viewport_offset: viewport_offset[1],
+            viewport_offset_x: viewport_offset[0],
+            hull: state.hull as u32,
         };
         belt.write_buffer(
             encoder,
@@ -257,10 +343,12 @@ impl ShipRenderer {
         });
         rpass.set_pipeline(&self.render_pipeline);
         rpass.set_bind_group(0, &self.render_bind_group, &[]);
-        rpass.draw(0..6_u32, 0..1);
+        // A fan of triangles from the nose, then the outline closed back to it.
+        let num_vertices = state.hull_shape().vertices.len() as u32;
+        rpass.draw(0..3 * (num_vertices - 2), 0..1);
 
         rpass.set_pipeline(&self.outline_pipeline);
-        rpass.draw(0..5_u32, 0..1);
+        rpass.draw(0..num_vertices + 1, 0..1);
     }
 }
 
@@ -268,6 +356,57 @@ impl ShipRenderer {
 mod unit_tests {
     use super::*;
 
+    #[test]
+    fn hull_overlap_follows_position_and_orientation() {
+        let ship = ShipState {
+            position: [100.0, 50.0],
+            orientation: 0.0,
+            ..Default::default()
+        };
+        assert!(ship.hull_overlaps_circle([100.0, 50.0], 0.5));
+        assert!(
+            ship.hull_overlaps_circle([113.5, 50.0], 2.0),
+            "just past the nose"
+        );
+        assert!(!ship.hull_overlaps_circle([115.0, 50.0], 2.0));
+        // The tail notch is open: a point behind it but between the wings
+        // is outside the hull.
+        assert!(!ship.hull_overlaps_circle([93.0, 50.0], 0.5));
+
+        // Pointing up, the nose is above the center.
+        let up = ShipState {
+            orientation: std::f32::consts::FRAC_PI_2,
+            ..ship
+        };
+        assert!(up.hull_overlaps_circle([100.0, 63.0], 1.5));
+        assert!(!up.hull_overlaps_circle([113.5, 50.0], 1.0));
+    }
+
+    #[test]
+    fn hull_selects_outline_and_mounts() {
+        let dart = ShipState {
+            position: [100.0, 50.0],
+            orientation: 0.0,
+            ..Default::default()
+        };
+        let hauler = ShipState {
+            hull: game_params::ShipHull::Hauler,
+            ..dart
+        };
+        let scout = ShipState {
+            hull: game_params::ShipHull::Scout,
+            ..dart
+        };
+        // Out past the dart's left wingtip, inside the hauler's.
+        assert!(!dart.hull_overlaps_circle([92.0, 61.0], 0.5));
+        assert!(hauler.hull_overlaps_circle([92.0, 61.0], 0.5));
+        // The scout's nose stops short of the dart's.
+        assert!(dart.hull_overlaps_circle([110.0, 50.0], 0.5));
+        assert!(!scout.hull_overlaps_circle([110.0, 50.0], 0.5));
+        assert_eq!(scout.get_emitter_state().0, [95.0, 50.0]);
+        assert_eq!(scout.get_gun_state().0, [109.0, 50.0]);
+    }
+
     #[test]
     fn stationary_ship_stays_put() {
         let mut s = ShipState::default();
@@ -422,7 +561,7 @@ mod tests {
         renderer.render(
             &state,
             &game_params,
-            0,
+            [0, 0],
             &target.view,
             &mut encoder,
             &mut belt,
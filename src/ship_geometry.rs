@@ -0,0 +1,166 @@
+//! Ship hull shapes: the outline, exhaust mount and gun muzzle of each hull,
+//! in ship-local space with the nose along +x.
+//!
+//! This is the single definition of the ship's shape. `build.rs` includes
+//! this file directly and renders `HULLS` into the shader templates, where
+//! `ship.wgsl` draws the outline and `collision.wgsl` sweeps points sampled
+//! along it. It must stay free of dependencies on the rest of the crate.
+
+/// One selectable ship shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HullShape {
+    /// Name `ShipParams::hull` selects the hull by in config.
+    pub name: &'static str,
+    /// Closed outline, counter-clockwise. Filled as a fan of triangles from
+    /// the first vertex, so every vertex must be visible from it.
+    pub vertices: &'static [[f32; 2]],
+    /// Where the exhaust leaves the ship, pointing straight back.
+    pub emitter: [f32; 2],
+    /// Where gun rounds leave the ship, just past the nose.
+    pub muzzle: [f32; 2],
+}
+
+/// Largest spacing, in world units, of collision samples along hull edges.
+/// Under one cell, so a 1-cell-thick wall can't fit between two samples.
+pub const EDGE_SAMPLE_SPACING: f32 = 0.9;
+
+/// The original chevron.
+pub const DART: HullShape = HullShape {
+    name: "dart",
+    vertices: &[
+        [12.0, 0.0],  // nose
+        [-8.0, 9.0],  // left wing
+        [-5.0, 0.0],  // tail notch
+        [-8.0, -9.0], // right wing
+    ],
+    emitter: [-8.0, 0.0],
+    muzzle: [13.0, 0.0],
+};
+
+/// Broad, blunt wings: a much bigger target.
+pub const HAULER: HullShape = HullShape {
+    name: "hauler",
+    vertices: &[
+        [10.0, 0.0],   // nose
+        [-9.0, 13.0],  // left wing
+        [-5.0, 0.0],   // tail notch
+        [-9.0, -13.0], // right wing
+    ],
+    emitter: [-8.0, 0.0],
+    muzzle: [11.0, 0.0],
+};
+
+/// A small chevron that fits through tighter gaps.
+pub const SCOUT: HullShape = HullShape {
+    name: "scout",
+    vertices: &[
+        [8.0, 0.0],   // nose
+        [-6.0, 6.0],  // left wing
+        [-3.0, 0.0],  // tail notch
+        [-6.0, -6.0], // right wing
+    ],
+    emitter: [-5.0, 0.0],
+    muzzle: [9.0, 0.0],
+};
+
+/// Every hull, in `game_params::ShipHull` order: the hull index the shaders
+/// take is the variant's discriminant.
+pub const HULLS: [HullShape; 3] = [DART, HAULER, SCOUT];
+
+impl HullShape {
+    /// Collision samples along each edge, edge `i` running from vertex `i`
+    /// to the next: its length over `EDGE_SAMPLE_SPACING`, rounded up, so
+    /// samples are never further apart than that.
+    pub fn edge_steps(&self) -> Vec<u32> {
+        let n = self.vertices.len();
+        (0..n)
+            .map(|i| {
+                let [ax, ay] = self.vertices[i];
+                let [bx, by] = self.vertices[(i + 1) % n];
+                (((bx - ax).hypot(by - ay) / EDGE_SAMPLE_SPACING).ceil() as u32).max(1)
+            })
+            .collect()
+    }
+
+    /// Points the collision sweep follows: every vertex, then the interior
+    /// samples of each edge in turn.
+    pub fn num_collision_samples(&self) -> u32 {
+        self.vertices.len() as u32 + self.edge_steps().iter().map(|steps| steps - 1).sum::<u32>()
+    }
+
+    /// The collision sample points themselves, in the same order as
+    /// `collision.wgsl`'s `sample_point`.
+    pub fn collision_samples(&self) -> Vec<[f32; 2]> {
+        let n = self.vertices.len();
+        let interior = self
+            .edge_steps()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, steps)| {
+                let [ax, ay] = self.vertices[i];
+                let [bx, by] = self.vertices[(i + 1) % n];
+                (1..steps).map(move |step| {
+                    let t = step as f32 / steps as f32;
+                    [ax + (bx - ax) * t, ay + (by - ay) * t]
+                })
+            });
+        self.vertices.iter().copied().chain(interior).collect()
+    }
+
+    /// Distance from the ship's center to the farthest vertex.
+    pub fn radius(&self) -> f32 {
+        self.vertices
+            .iter()
+            .map(|[x, y]| x.hypot(*y))
+            .fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hulls_fill_as_counter_clockwise_fans() {
+        for hull in HULLS {
+            let [ox, oy] = hull.vertices[0];
+            for pair in hull.vertices[1..].windows(2) {
+                let ([ax, ay], [bx, by]) = (pair[0], pair[1]);
+                let cross = (ax - ox) * (by - oy) - (ay - oy) * (bx - ox);
+                assert!(cross > 0.0, "{} fan triangle is not CCW", hull.name);
+            }
+        }
+    }
+
+    #[test]
+    fn samples_are_under_a_cell_apart_on_every_hull() {
+        for hull in HULLS {
+            let n = hull.vertices.len();
+            let samples = hull.collision_samples();
+            assert_eq!(samples.len(), hull.num_collision_samples() as usize);
+            // Interior samples follow the vertices, edge by edge.
+            let mut interior = samples[n..].iter();
+            for (i, steps) in hull.edge_steps().into_iter().enumerate() {
+                let edge: Vec<[f32; 2]> = std::iter::once(hull.vertices[i])
+                    .chain(interior.by_ref().take(steps as usize - 1).copied())
+                    .chain(std::iter::once(hull.vertices[(i + 1) % n]))
+                    .collect();
+                for pair in edge.windows(2) {
+                    let gap = (pair[1][0] - pair[0][0]).hypot(pair[1][1] - pair[0][1]);
+                    assert!(gap < 1.0, "{} edge {i} has a {gap} gap", hull.name);
+                }
+            }
+            assert!(interior.next().is_none());
+        }
+    }
+
+    #[test]
+    fn dart_samples_and_radius() {
+        assert_eq!(DART.edge_steps(), [25, 11, 11, 25]);
+        assert_eq!(DART.num_collision_samples(), 72);
+        assert!(
+            (DART.radius() - 145f32.sqrt()).abs() < 1e-6,
+            "wingtip is farthest"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Headless simulation: run `Play` against a CPU terrain model, no window or GPU.
+//!
+//! Terrain comes straight from `LevelMaker`, so it is exactly what a seeded run
+//! would generate, but nothing erodes it: the exhaust particle system only
+//! exists on the GPU. Collision uses the CPU `check_collision` test, swept one
+//! unit at a time along each frame's motion and resolved the same frame
+//! (the game resolves a frame later, when the GPU readback lands); contact
+//! normals come from the open neighbours of the cells the hull lands in, so
+//! bounces follow the surface like the GPU pass's do. Turrets
+//! see the same uneroded terrain, so none are ever destroyed, and with no
+//! particles enemy ships only die by crashing. Bombs fall and detonate, but
+//! their craters are not carved either. Treat results
+//! as a balance signal, not a frame-exact prediction of a live run.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::collision::{CollisionResult, CollisionSegment};
+use crate::game_params::GameParams;
+use crate::input::InputState;
+use crate::level_manager::LevelMaker;
+use crate::play::{DeathCause, Play};
+use crate::replay::RecordedFrame;
+use crate::scoring;
+use crate::ship::ShipState;
+use crate::terrain_query::Region;
+
+/// Final state of a headless run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimOutcome {
+    pub seed: u32,
+    /// Frames actually simulated; fewer than requested if the ship died or
+    /// the input ran out.
+    pub frames: u32,
+    pub game_time: Duration,
+    pub score: i32,
+    /// 1-based, as shown on the HUD.
+    pub level: i32,
+    pub death: Option<DeathCause>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError(String);
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Parse a scripted input stream. Each non-empty line is
+/// `<frames> <thrust> <rotate>`: hold that input for that many frames at the
+/// config's fixed frame rate. `#` starts a comment.
+///
+/// ```text
+/// # Climb, then bank left while coasting.
+/// 90 1.0 0.0
+/// 20 0.0 1.0
+/// ```
+pub fn parse_input_script(
+    script: &str,
+    params: &GameParams,
+) -> Result<Vec<RecordedFrame>, ScriptError> {
+    let game_dt = Duration::from_secs_f64(1.0 / params.fps);
+    let mut frames = Vec::new();
+    for (line_index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: &str| ScriptError(format!("line {}: {message}", line_index + 1));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [count, thrust, rotate] = fields[..] else {
+            return Err(error("expected `<frames> <thrust> <rotate>`"));
+        };
+        let count: u32 = count
+            .parse()
+            .map_err(|_| error("frame count must be a non-negative integer"))?;
+        let thrust: f32 = thrust
+            .parse()
+            .ok()
+            .filter(|t| (0.0..=1.0).contains(t))
+            .ok_or_else(|| error("thrust must be a number in [0, 1]"))?;
+        let rotate: f32 = rotate
+            .parse()
+            .ok()
+            .filter(|r| (-1.0..=1.0).contains(r))
+            .ok_or_else(|| error("rotate must be a number in [-1, 1]"))?;
+        let input = InputState {
+            thrust,
+            rotate,
+            ..Default::default()
+        };
+        frames.extend((0..count).map(|_| RecordedFrame { input, game_dt }));
+    }
+    Ok(frames)
+}
+
+/// Run up to `max_frames` of a play session seeded with `seed` and flying
+/// roster ship `ship`, feeding `inputs` one frame at a time.
+pub fn run<I>(params: &GameParams, seed: u32, ship: usize, inputs: I, max_frames: u32) -> SimOutcome
+where
+    I: IntoIterator<Item = RecordedFrame>,
+{
+    let mut play = Play::new(params, ship);
+    play.seed = seed;
+    let mut terrain = LevelMaker::init(
+        params.level_width,
+        params.level_height,
+        &params.level_params,
+        seed,
+    );
+
+    let mut frames = 0;
+    let mut game_time = Duration::ZERO;
+    let mut death = None;
+    for frame in inputs.into_iter().take(max_frames as usize) {
+        frames += 1;
+        game_time += frame.game_dt;
+        play.spawn_entities(params, &mut terrain);
+        if play.needs_terrain() {
+            play.observe_terrain(terrain.terrain_snapshot(play.watch_region(params)));
+        }
+        death = play
+            .update(
+                params,
+                &frame.input,
+                frame.game_dt.as_secs_f32(),
+                frame.game_dt,
+            )
+            .or_else(|| resolve_collision(&mut play, params, &mut terrain));
+        play.bombs.take_detonations();
+        if death.is_some() {
+            break;
+        }
+    }
+
+    SimOutcome {
+        seed,
+        frames,
+        game_time,
+        score: play.score,
+        level: play.current_level_index + 1,
+        death,
+    }
+}
+
+/// CPU stand-in for the GPU collision pass: sweep each queued frame segment
+/// through the generated terrain and report it back through the same `Play`
+/// path.
+fn resolve_collision(
+    play: &mut Play,
+    params: &GameParams,
+    terrain: &mut LevelMaker,
+) -> Option<DeathCause> {
+    while let Some(segment) = play.collisions.dispatch_next() {
+        let death = resolve_segment(play, params, terrain, segment);
+        if death.is_some() {
+            return death;
+        }
+    }
+    None
+}
+
+fn resolve_segment(
+    play: &mut Play,
+    params: &GameParams,
+    terrain: &mut LevelMaker,
+    segment: CollisionSegment,
+) -> Option<DeathCause> {
+    let top = segment.prev_ship.position[1].max(segment.next_ship.position[1]);
+    terrain.finish_through_level(
+        scoring::level_index_for_progress(top.ceil() as i32, params.level_height) + 1,
+    );
+
+    let impact_t = segment.first_impact(|ship| terrain.check_ship_collision(ship));
+    let normal = impact_t.map_or([0.0, 0.0], |t| {
+        let ship = segment.ship_at(t);
+        let motion = [
+            segment.next_ship.position[0] - segment.prev_ship.position[0],
+            segment.next_ship.position[1] - segment.prev_ship.position[1],
+        ];
+        let snapshot = terrain.terrain_snapshot(hull_region(&ship));
+        let normal = contact_normal(&ship, motion, |x, y| snapshot.is_solid(x, y));
+        // The border cell around the hull covers a bounce's push.
+        play.terrain = Some(snapshot);
+        normal
+    });
+
+    play.resolve_collision_result(
+        params,
+        CollisionResult {
+            hit: impact_t.is_some(),
+            normal,
+            impact_t: impact_t.unwrap_or(1.0),
+        },
+    )
+}
+
+/// Cells under `ship`'s hull samples, with a one-cell border for their
+/// neighbours.
+fn hull_region(ship: &ShipState) -> Region {
+    let samples = ship.hull_samples();
+    let cells = |axis: usize| samples.iter().map(move |p| p[axis].floor() as i32);
+    let (x, y) = (cells(0).min().unwrap() - 1, cells(1).min().unwrap() - 1);
+    Region {
+        x,
+        y,
+        width: (cells(0).max().unwrap() + 2 - x) as u32,
+        height: (cells(1).max().unwrap() + 2 - y) as u32,
+    }
+}
+
+/// Axis-aligned contact normal for `ship` touching terrain. The GPU pass
+/// takes the face of the solid cell its Bresenham walk steps into; this
+/// approximates that after the fact, since `first_impact` only knows the
+/// hull overlaps: every open 4-neighbour of a solid cell under a hull
+/// sample votes for its direction, and the dominant axis wins. The two can
+/// disagree at corners. A hull buried with no open neighbour falls back to
+/// straight back against `motion`, like the GPU's start-in-solid case.
+fn contact_normal(
+    ship: &ShipState,
+    motion: [f32; 2],
+    is_solid: impl Fn(i32, i32) -> bool,
+) -> [f32; 2] {
+    let mut open = [0.0f32; 2];
+    for [x, y] in ship.hull_samples() {
+        let (x, y) = (x.floor() as i32, y.floor() as i32);
+        if !is_solid(x, y) {
+            continue;
+        }
+        for [dx, dy] in [[1, 0], [-1, 0], [0, 1], [0, -1]] {
+            if !is_solid(x + dx, y + dy) {
+                open[0] += dx as f32;
+                open[1] += dy as f32;
+            }
+        }
+    }
+    let axis = |v: [f32; 2]| {
+        if v[0].abs() > v[1].abs() {
+            Some([v[0].signum(), 0.0])
+        } else if v[1] != 0.0 {
+            Some([0.0, v[1].signum()])
+        } else {
+            None
+        }
+    };
+    axis(open)
+        .or_else(|| axis([-motion[0], -motion[1]]))
+        .unwrap_or([0.0, 1.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> GameParams {
+        crate::game_params::parse_embedded_config().unwrap()
+    }
+
+    fn hold(params: &GameParams, thrust: f32, rotate: f32) -> Vec<RecordedFrame> {
+        parse_input_script(&format!("100000 {thrust} {rotate}"), params).unwrap()
+    }
+
+    #[test]
+    fn script_expands_to_fixed_rate_frames() {
+        let params = params();
+        let frames =
+            parse_input_script("# comment\n\n2 1.0 0.0\n1 0 -1 # bank right\n", &params).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].input.thrust, 1.0);
+        assert_eq!(frames[2].input.rotate, -1.0);
+        assert_eq!(frames[2].game_dt, Duration::from_secs_f64(1.0 / params.fps));
+    }
+
+    #[test]
+    fn bad_script_lines_report_line_numbers() {
+        let params = params();
+        let err = parse_input_script("1 1 0\n1 2 0\n", &params).unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"), "{err}");
+        assert!(parse_input_script("1 1\n", &params).is_err());
+        assert!(parse_input_script("-1 1 0\n", &params).is_err());
+    }
+
+    #[test]
+    fn coasting_ship_falls_off_the_playfield() {
+        let params = params();
+        let outcome = run(&params, 1, 0, hold(&params, 0.0, 0.0), 10_000);
+        assert_eq!(outcome.death, Some(DeathCause::FellOff));
+        assert_eq!(outcome.level, 1);
+    }
+
+    #[test]
+    fn climbing_ship_hits_terrain_and_scores_height() {
+        let params = params();
+        let outcome = run(&params, 1, 0, hold(&params, 1.0, 0.0), 10_000);
+        assert_eq!(outcome.death, Some(DeathCause::Collided));
+        assert!(outcome.score > (params.viewport_height / 2) as i32);
+    }
+
+    #[test]
+    fn runs_stop_at_max_frames_or_end_of_input() {
+        let params = params();
+        let outcome = run(&params, 1, 0, hold(&params, 0.0, 0.0), 5);
+        assert_eq!(outcome.frames, 5);
+        assert_eq!(outcome.death, None);
+
+        let outcome = run(&params, 1, 0, Vec::new(), 100);
+        assert_eq!(outcome.frames, 0);
+    }
+
+    #[test]
+    fn contact_normals_point_out_of_the_surface_hit() {
+        let ship = |position| ShipState {
+            position,
+            ..Default::default()
+        };
+        // Flying right into a wall filling x >= 20: pushed back left, even
+        // with the ship also climbing.
+        let wall = |x: i32, _: i32| x >= 20;
+        assert_eq!(
+            contact_normal(&ship([18.5, 50.5]), [3.0, 1.0], wall),
+            [-1.0, 0.0]
+        );
+        // Falling onto a floor filling y < 40: pushed up, even sliding
+        // sideways faster than it falls.
+        let floor = |_: i32, y: i32| y < 40;
+        assert_eq!(
+            contact_normal(&ship([30.5, 41.5]), [5.0, -1.0], floor),
+            [0.0, 1.0]
+        );
+        // Buried: straight back against the motion.
+        assert_eq!(
+            contact_normal(&ship([30.5, 30.5]), [0.0, 2.0], |_, _| true),
+            [0.0, -1.0]
+        );
+    }
+
+    #[test]
+    fn same_seed_and_input_give_same_outcome() {
+        let params = params();
+        let script = parse_input_script("40 1 0\n10 1 1\n30 1 0\n20 0 -1\n", &params).unwrap();
+        let a = run(&params, 77, 0, script.iter().copied().cycle(), 3_000);
+        let b = run(&params, 77, 0, script.iter().copied().cycle(), 3_000);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,323 @@
+//! Async queries for current (post-erosion) terrain health.
+//!
+//! The composite tile on the GPU is the only up-to-date copy of the terrain
+//! near the viewport; the CPU level data only catches up when a tile is
+//! evicted. A query copies the requested rows out of the composite after the
+//! frame's compute passes and maps them back like
+//! `CollisionDetector::start_readback`/`poll_result`, so results arrive a
+//! frame or so after the request. Driven through `LevelManager`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use crate::level_manager::{Interval, TerrainTile};
+
+/// A rectangle of world cells: `x..x + width` by `y..y + height`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as i32
+            && y < self.y + self.height as i32
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TerrainQueryId(u64);
+
+/// Terrain health over a [`Region`] as of the frame the query was dispatched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainSnapshot {
+    pub region: Region,
+    /// Row-major, `region.width` per row, starting at `region.y`.
+    pub health: Vec<i32>,
+}
+
+impl TerrainSnapshot {
+    /// Health at world cell (x, y), or 0 outside the region.
+    pub fn health_at(&self, x: i32, y: i32) -> i32 {
+        if !self.region.contains(x, y) {
+            return 0;
+        }
+        let (dx, dy) = (x - self.region.x, y - self.region.y);
+        self.health[(dy as u32 * self.region.width + dx as u32) as usize]
+    }
+
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        self.health_at(x, y) > 0
+    }
+}
+
+/// Why a query finished without a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerrainQueryError {
+    /// The staging buffer holding the composite rows failed to map.
+    MapFailed(String),
+}
+
+impl fmt::Display for TerrainQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainQueryError::MapFailed(message) => {
+                write!(f, "terrain query readback failed to map: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TerrainQueryError {}
+
+/// Composite rows copied out for one query.
+struct Readback {
+    rows: Interval,
+    staging: wgpu::Buffer,
+    /// Whether `map_async` has been initiated.
+    mapping_started: bool,
+    /// Set by the `map_async` callback when the mapping completes or fails.
+    map_result: Arc<OnceLock<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+struct PendingQuery {
+    id: TerrainQueryId,
+    region: Region,
+    dispatched: bool,
+    /// `None` once dispatched if the region missed the composite entirely.
+    readback: Option<Readback>,
+}
+
+impl PendingQuery {
+    /// How the readback went, or `None` while it is still in flight.
+    fn outcome(&self) -> Option<Result<(), TerrainQueryError>> {
+        match &self.readback {
+            Some(readback) => readback.map_result.get().map(|result| {
+                result
+                    .clone()
+                    .map_err(|err| TerrainQueryError::MapFailed(err.to_string()))
+            }),
+            None => self.dispatched.then_some(Ok(())),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TerrainQueries {
+    next_id: u64,
+    pending: Vec<PendingQuery>,
+    finished: HashMap<TerrainQueryId, Result<TerrainSnapshot, TerrainQueryError>>,
+}
+
+impl TerrainQueries {
+    pub fn request(&mut self, region: Region) -> TerrainQueryId {
+        let id = TerrainQueryId(self.next_id);
+        self.next_id += 1;
+        self.pending.push(PendingQuery {
+            id,
+            region,
+            dispatched: false,
+            readback: None,
+        });
+        id
+    }
+
+    /// Queue copies of the composite rows each new query needs. Call after
+    /// the frame's last pass that writes the composite.
+    pub fn dispatch(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        composite: &TerrainTile,
+        level_width: u32,
+    ) {
+        let bytes_per_row = level_width as u64 * std::mem::size_of::<i32>() as u64;
+        for query in self.pending.iter_mut().filter(|q| !q.dispatched) {
+            query.dispatched = true;
+            let rows = composite.shape.intersection(&Interval {
+                start: query.region.y,
+                end: query.region.y + query.region.height as i32,
+            });
+            if rows.empty() {
+                continue;
+            }
+            let size = rows.size() as u64 * bytes_per_row;
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Terrain query staging"),
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let offset = (rows.start - composite.shape.start) as u64 * bytes_per_row;
+            encoder.copy_buffer_to_buffer(&composite.buffer.buffer, offset, &staging, 0, size);
+            query.readback = Some(Readback {
+                rows,
+                staging,
+                mapping_started: false,
+                map_result: Arc::new(OnceLock::new()),
+            });
+        }
+    }
+
+    /// Initiate async mapping of dispatched queries. Call after
+    /// `queue.submit()`.
+    pub fn start_readback(&mut self) {
+        for readback in self.pending.iter_mut().filter_map(|q| q.readback.as_mut()) {
+            if readback.mapping_started {
+                continue;
+            }
+            let map_result = Arc::clone(&readback.map_result);
+            readback
+                .staging
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = map_result.set(result);
+                });
+            readback.mapping_started = true;
+        }
+    }
+
+    /// Build snapshots for every query whose readback has landed. Cells in
+    /// rows the composite didn't cover come from `fallback`; cells outside
+    /// `0..level_width` are 0. A readback that failed to map finishes its
+    /// query with the error instead. Non-blocking; the caller drives wgpu
+    /// callbacks with `device.poll`.
+    pub fn finish(&mut self, level_width: u32, fallback: impl Fn(i32, i32) -> i32) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            let Some(outcome) = self.pending[i].outcome() else {
+                i += 1;
+                continue;
+            };
+            let query = self.pending.swap_remove(i);
+            if let Err(err) = outcome {
+                self.finished.insert(query.id, Err(err));
+                continue;
+            }
+            let region = query.region;
+            let mapped = query
+                .readback
+                .as_ref()
+                .map(|r| (r.rows, r.staging.slice(..).get_mapped_range()));
+            let mut health = Vec::with_capacity((region.width * region.height) as usize);
+            for y in region.y..region.y + region.height as i32 {
+                for x in region.x..region.x + region.width as i32 {
+                    health.push(match &mapped {
+                        _ if x < 0 || x >= level_width as i32 => 0,
+                        Some((rows, data)) if y >= rows.start && y < rows.end => {
+                            let data: &[i32] = bytemuck::cast_slice(data);
+                            data[((y - rows.start) as u32 * level_width + x as u32) as usize]
+                        }
+                        _ => fallback(x, y),
+                    });
+                }
+            }
+            drop(mapped);
+            if let Some(readback) = &query.readback {
+                readback.staging.unmap();
+            }
+            self.finished
+                .insert(query.id, Ok(TerrainSnapshot { region, health }));
+        }
+    }
+
+    /// Take the result of a query, if it has finished.
+    pub fn take(
+        &mut self,
+        id: TerrainQueryId,
+    ) -> Option<Result<TerrainSnapshot, TerrainQueryError>> {
+        self.finished.remove(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu_test_utils as gpu;
+
+    #[test]
+    fn snapshot_health_is_zero_outside_its_region() {
+        let snapshot = TerrainSnapshot {
+            region: Region {
+                x: 10,
+                y: 20,
+                width: 2,
+                height: 2,
+            },
+            health: vec![1, 2, 3, -4],
+        };
+        assert_eq!(snapshot.health_at(10, 20), 1);
+        assert_eq!(snapshot.health_at(11, 20), 2);
+        assert_eq!(snapshot.health_at(10, 21), 3);
+        assert!(!snapshot.is_solid(11, 21));
+        assert_eq!(snapshot.health_at(9, 20), 0);
+        assert_eq!(snapshot.health_at(12, 20), 0);
+        assert_eq!(snapshot.health_at(10, 22), 0);
+    }
+
+    #[test]
+    fn queries_get_distinct_ids_and_wait_for_dispatch() {
+        let mut queries = TerrainQueries::default();
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        let a = queries.request(region);
+        let b = queries.request(region);
+        assert_ne!(a, b);
+        queries.finish(8, |_, _| 1);
+        assert_eq!(queries.take(a), None);
+    }
+
+    #[test]
+    fn failed_readbacks_finish_their_query_with_an_error() {
+        let Some((device, _queue)) = gpu::try_create_headless_device() else {
+            eprintln!(
+                "No GPU adapter available — skipping failed_readbacks_finish_their_query_with_an_error"
+            );
+            return;
+        };
+        let mut queries = TerrainQueries::default();
+        let id = queries.request(Region {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // Mapping a destroyed buffer fails.
+        staging.destroy();
+        queries.pending[0].dispatched = true;
+        queries.pending[0].readback = Some(Readback {
+            rows: Interval { start: 0, end: 1 },
+            staging,
+            mapping_started: false,
+            map_result: Arc::new(OnceLock::new()),
+        });
+
+        let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        queries.start_readback();
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        assert!(pollster::block_on(scope.pop()).is_some());
+
+        queries.finish(1, |_, _| 1);
+        assert!(queries.pending.is_empty());
+        assert!(matches!(
+            queries.take(id),
+            Some(Err(TerrainQueryError::MapFailed(_)))
+        ));
+    }
+}
@@ -0,0 +1,394 @@
+//! Enemy turrets: placement on generated terrain, aiming and firing at the
+//! ship, and destruction when the terrain under them erodes.
+//!
+//! Pure CPU state carried in `Play`. The current terrain only exists on the
+//! GPU, so turrets learn about erosion from `TerrainSnapshot`s handed to
+//! `Turrets::observe_terrain`; the same snapshot stops projectiles at walls.
+
+use crate::entity_renderer::EntityBox;
+use crate::game_params::{GameParams, TurretParams};
+use crate::ship::ShipState;
+use crate::terrain_query::TerrainSnapshot;
+
+/// Turret body size, sitting on top of its anchor cell.
+const TURRET_HALF_WIDTH: f32 = 3.0;
+const TURRET_HEIGHT: f32 = 4.0;
+const BARREL_LENGTH: f32 = 6.0;
+/// Empty cells a surface needs above it to host a turret.
+const TURRET_CLEARANCE: u32 = 8;
+/// Minimum distance between turrets in one level, in cells.
+const MIN_TURRET_SPACING: f32 = 32.0;
+/// Projectiles are drawn as squares this far across from their center, and
+/// hit the ship's hull within it.
+const PROJECTILE_RADIUS: f32 = 1.0;
+/// Salt so turret placement draws from a different stream than the level's
+/// generator, which uses the same per-level seed.
+const PLACEMENT_SALT: u64 = 0x7475_7272_6574_7321;
+
+/// Pick up to `count` turret sites on the surfaces of a finished level:
+/// solid cells with open space above them, spread apart. Sites are `[x, y]`
+/// in level cells. Depends only on the level and `seed`.
+pub fn place_turrets(
+    level: &[i32],
+    width: u32,
+    height: u32,
+    count: u32,
+    seed: u64,
+) -> Vec<[u32; 2]> {
+    let solid = |x: u32, y: u32| level[(y * width + x) as usize] > 0;
+    let candidates: Vec<[u32; 2]> = (0..height.saturating_sub(TURRET_CLEARANCE))
+        .flat_map(|y| (0..width).map(move |x| [x, y]))
+        .filter(|&[x, y]| solid(x, y) && (1..=TURRET_CLEARANCE).all(|dy| !solid(x, y + dy)))
+        .collect();
+    pick_spread_out(candidates, count, MIN_TURRET_SPACING, seed ^ PLACEMENT_SALT)
+}
+
+/// Up to `count` of `candidates` in a shuffled order drawn from `seed`,
+/// skipping any closer than `min_spacing` to one already picked.
+pub(crate) fn pick_spread_out(
+    mut candidates: Vec<[u32; 2]>,
+    count: u32,
+    min_spacing: f32,
+    seed: u64,
+) -> Vec<[u32; 2]> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    rng.shuffle(&mut candidates);
+
+    let mut sites: Vec<[u32; 2]> = Vec::new();
+    for candidate in candidates {
+        if sites.len() as u32 >= count {
+            break;
+        }
+        let far_enough = sites.iter().all(|site| {
+            let dx = site[0] as f32 - candidate[0] as f32;
+            let dy = site[1] as f32 - candidate[1] as f32;
+            dx.hypot(dy) >= min_spacing
+        });
+        if far_enough {
+            sites.push(candidate);
+        }
+    }
+    sites
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Turret {
+    /// World cell the turret sits on. Eroding it destroys the turret.
+    pub anchor: [i32; 2],
+    /// Barrel direction in radians.
+    pub aim: f32,
+    /// Seconds of the ship being in range before the next shot.
+    pub reload: f32,
+}
+
+impl Turret {
+    /// Barrel pivot: the middle of the body on top of the anchor cell.
+    pub fn position(&self) -> [f32; 2] {
+        [
+            self.anchor[0] as f32 + 0.5,
+            self.anchor[1] as f32 + 1.0 + TURRET_HEIGHT / 2.0,
+        ]
+    }
+
+    fn muzzle(&self) -> [f32; 2] {
+        let [x, y] = self.position();
+        let (sin, cos) = self.aim.sin_cos();
+        [x + cos * BARREL_LENGTH, y + sin * BARREL_LENGTH]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projectile {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Seconds left before it fizzles out.
+    pub life: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct Turrets {
+    pub turrets: Vec<Turret>,
+    pub projectiles: Vec<Projectile>,
+    /// Levels below this index have had their turrets spawned.
+    spawned_levels: u32,
+    /// Latest terrain around the ship, for stopping projectiles at walls.
+    terrain: Option<TerrainSnapshot>,
+}
+
+impl Turrets {
+    /// The next level whose turrets should spawn, up to
+    /// `play::spawn_horizon_level`.
+    pub fn next_level_to_spawn(&self, params: &GameParams, viewport_offset: i32) -> Option<u32> {
+        if params.turret_params.turrets_per_level == 0 {
+            return None;
+        }
+        let reached = crate::play::spawn_horizon_level(params, viewport_offset);
+        (self.spawned_levels <= reached).then_some(self.spawned_levels)
+    }
+
+    /// Add the turrets at `sites` (level cells, see `place_turrets`) for
+    /// `level_index`.
+    pub fn spawn_level(&mut self, params: &GameParams, level_index: u32, sites: &[[u32; 2]]) {
+        let base_y = (level_index * params.level_height) as i32;
+        self.turrets.extend(sites.iter().map(|&[x, y]| Turret {
+            anchor: [x as i32, base_y + y as i32],
+            aim: std::f32::consts::FRAC_PI_2,
+            reload: params.turret_params.fire_interval,
+        }));
+        self.spawned_levels = level_index + 1;
+    }
+
+    /// Whether nothing needs terrain updates.
+    pub fn is_idle(&self) -> bool {
+        self.turrets.is_empty() && self.projectiles.is_empty()
+    }
+
+    /// Take in current terrain health: turrets whose anchor cell inside the
+    /// snapshot has eroded away are destroyed. Returns how many were.
+    pub fn observe_terrain(&mut self, snapshot: TerrainSnapshot) -> usize {
+        let region = snapshot.region;
+        let before = self.turrets.len();
+        self.turrets.retain(|turret| {
+            let [x, y] = turret.anchor;
+            let survives = !region.contains(x, y) || snapshot.is_solid(x, y);
+            if !survives {
+                log::info!("Turret at ({x}, {y}) destroyed");
+            }
+            survives
+        });
+        self.terrain = Some(snapshot);
+        before - self.turrets.len()
+    }
+
+    /// Aim, fire and move projectiles for one step. Returns whether a
+    /// projectile hit `ship`'s hull.
+    pub fn update(&mut self, params: &TurretParams, ship: &ShipState, dt: f32) -> bool {
+        let ship_position = ship.position;
+        for turret in &mut self.turrets {
+            let [x, y] = turret.position();
+            let (dx, dy) = (ship_position[0] - x, ship_position[1] - y);
+            if dx.hypot(dy) > params.range {
+                continue;
+            }
+            turret.aim = dy.atan2(dx);
+            turret.reload -= dt;
+            if turret.reload <= 0.0 {
+                turret.reload += params.fire_interval;
+                let (sin, cos) = turret.aim.sin_cos();
+                self.projectiles.push(Projectile {
+                    position: turret.muzzle(),
+                    velocity: [cos * params.projectile_speed, sin * params.projectile_speed],
+                    life: params.projectile_life,
+                });
+            }
+        }
+
+        for projectile in &mut self.projectiles {
+            projectile.position[0] += projectile.velocity[0] * dt;
+            projectile.position[1] += projectile.velocity[1] * dt;
+            projectile.life -= dt;
+        }
+        let hit = self.hit(ship);
+        let terrain = self.terrain.as_ref();
+        self.projectiles.retain(|projectile| {
+            let [x, y] = projectile.position;
+            let in_wall =
+                terrain.is_some_and(|terrain| terrain.is_solid(x.floor() as i32, y.floor() as i32));
+            projectile.life > 0.0 && !in_wall
+        });
+        hit
+    }
+
+    /// Remove the projectiles overlapping `ship`'s hull where they are now.
+    /// Returns whether there were any.
+    pub fn hit(&mut self, ship: &ShipState) -> bool {
+        let before = self.projectiles.len();
+        self.projectiles.retain(|projectile| {
+            !ship.hull_overlaps_circle(projectile.position, PROJECTILE_RADIUS)
+        });
+        self.projectiles.len() < before
+    }
+
+    /// Boxes to draw with `EntityRenderer`: each turret's body and barrel,
+    /// then the projectiles.
+    pub fn render_boxes(&self) -> Vec<EntityBox> {
+        let body_color = [0.55, 0.12, 0.1, 1.0];
+        let barrel_color = [1.2, 0.35, 0.2, 1.0];
+        let projectile_color = [2.0, 0.8, 0.3, 1.0];
+        let mut boxes = Vec::with_capacity(self.turrets.len() * 2 + self.projectiles.len());
+        for turret in &self.turrets {
+            let [x, y] = turret.position();
+            boxes.push(EntityBox::new(
+                [x, y],
+                [TURRET_HALF_WIDTH, TURRET_HEIGHT / 2.0],
+                0.0,
+                body_color,
+            ));
+            let (sin, cos) = turret.aim.sin_cos();
+            let half = BARREL_LENGTH / 2.0;
+            boxes.push(EntityBox::new(
+                [x + cos * half, y + sin * half],
+                [half, 0.75],
+                turret.aim,
+                barrel_color,
+            ));
+        }
+        boxes.extend(self.projectiles.iter().map(|p| {
+            EntityBox::new(
+                p.position,
+                [PROJECTILE_RADIUS, PROJECTILE_RADIUS],
+                0.0,
+                projectile_color,
+            )
+        }));
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_query::Region;
+
+    fn params() -> GameParams {
+        GameParams {
+            viewport_height: 100,
+            level_width: 100,
+            level_height: 300,
+            ..Default::default()
+        }
+    }
+
+    fn ship_at(position: [f32; 2]) -> ShipState {
+        ShipState {
+            position,
+            ..Default::default()
+        }
+    }
+
+    fn turret_at(anchor: [i32; 2], params: &GameParams) -> Turrets {
+        Turrets {
+            turrets: vec![Turret {
+                anchor,
+                aim: 0.0,
+                reload: params.turret_params.fire_interval,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn turrets_sit_on_surfaces_with_room_above() {
+        let (width, height) = (120, 60);
+        // Floor along row 9, plus one block at the top too close to the
+        // ceiling to host a turret.
+        let mut level = vec![0; (width * height) as usize];
+        for x in 0..width {
+            level[(9 * width + x) as usize] = 100;
+        }
+        level[((height - 3) * width + 5) as usize] = 100;
+
+        let sites = place_turrets(&level, width, height, 10, 7);
+        assert!(!sites.is_empty());
+        assert!(sites.len() <= 4, "spacing limits a 120-wide floor");
+        for &[x, y] in &sites {
+            assert_eq!(y, 9, "site {x},{y} is not on the floor");
+        }
+        assert_eq!(sites, place_turrets(&level, width, height, 10, 7));
+        assert!(place_turrets(&level, width, height, 0, 7).is_empty());
+    }
+
+    #[test]
+    fn levels_spawn_a_viewport_ahead_of_the_camera() {
+        let params = params();
+        let mut turrets = Turrets::default();
+        assert_eq!(turrets.next_level_to_spawn(&params, 0), Some(0));
+        turrets.spawn_level(&params, 0, &[]);
+        assert_eq!(turrets.next_level_to_spawn(&params, 0), None);
+        // Level 1 starts at row 300; the lookahead reaches it at offset 100.
+        assert_eq!(turrets.next_level_to_spawn(&params, 99), None);
+        assert_eq!(turrets.next_level_to_spawn(&params, 100), Some(1));
+        turrets.spawn_level(&params, 1, &[[10, 20]]);
+        assert_eq!(turrets.turrets[0].anchor, [10, 320]);
+    }
+
+    #[test]
+    fn turrets_fire_at_a_ship_in_range() {
+        let params = params();
+        let mut turrets = turret_at([50, 0], &params);
+        let far = [50.5, 200.0];
+        assert!(!turrets.update(&params.turret_params, &ship_at(far), 10.0));
+        assert!(turrets.projectiles.is_empty());
+
+        let near = [80.5, 3.0];
+        let dt = params.turret_params.fire_interval;
+        assert!(!turrets.update(&params.turret_params, &ship_at(near), dt));
+        assert_eq!(turrets.projectiles.len(), 1);
+        assert!(
+            turrets.turrets[0].aim.abs() < 1e-3,
+            "aims right at the ship"
+        );
+
+        // The projectile flies straight into the ship.
+        let mut hit = false;
+        for _ in 0..200 {
+            hit |= turrets.update(&params.turret_params, &ship_at(near), 1.0 / 60.0);
+        }
+        assert!(hit);
+    }
+
+    #[test]
+    fn projectiles_stop_at_walls() {
+        let params = params();
+        let mut turrets = turret_at([10, 0], &params);
+        let dt = params.turret_params.fire_interval;
+        let ship = [60.5, 3.0];
+        turrets.update(&params.turret_params, &ship_at(ship), dt);
+        assert_eq!(turrets.projectiles.len(), 1);
+
+        // A wall column at x = 30 between the turret and the ship.
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 20,
+        };
+        let health = (0..100 * 20)
+            .map(|i| if i % 100 == 30 || i == 10 { 100 } else { 0 })
+            .collect();
+        assert_eq!(
+            turrets.observe_terrain(TerrainSnapshot { region, health }),
+            0
+        );
+        let mut hit = false;
+        for _ in 0..200 {
+            hit |= turrets.update(&params.turret_params, &ship_at(ship), 1.0 / 60.0);
+        }
+        assert!(!hit);
+    }
+
+    #[test]
+    fn eroding_the_anchor_destroys_the_turret() {
+        let params = params();
+        let mut turrets = turret_at([5, 5], &params);
+        turrets.turrets.push(Turret {
+            anchor: [5, 500],
+            ..turrets.turrets[0]
+        });
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        // Empty terrain: the first turret's anchor is gone; the second one
+        // is outside the snapshot and stays.
+        let destroyed = turrets.observe_terrain(TerrainSnapshot {
+            region,
+            health: vec![0; 100],
+        });
+        assert_eq!(destroyed, 1);
+        assert_eq!(turrets.turrets.len(), 1);
+        assert_eq!(turrets.turrets[0].anchor, [5, 500]);
+    }
+}
@@ -46,6 +46,26 @@ pub struct RectStyle {
     pub outline_px: f32,
 }
 
+/// Shared look for menu buttons: `pressed` while the pointer is down on the
+/// button, `focused` when it has keyboard focus or is otherwise selected.
+pub fn button_style(pressed: bool, focused: bool) -> RectStyle {
+    RectStyle {
+        fill_color: if pressed {
+            [0.12, 0.16, 0.16, 0.9]
+        } else if focused {
+            [0.08, 0.12, 0.13, 0.78]
+        } else {
+            [0.02, 0.05, 0.07, 0.68]
+        },
+        outline_color: if pressed || focused {
+            [0.9, 0.72, 0.48, 1.0]
+        } else {
+            [0.45, 0.57, 0.58, 0.92]
+        },
+        outline_px: if pressed || focused { 2.0 } else { 1.0 },
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct ScreenUniform {